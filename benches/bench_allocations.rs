@@ -0,0 +1,71 @@
+//! Reports the number of heap allocations made while decoding the network data of a replay.
+//! Criterion only measures time, so this is a small standalone harness that swaps in a counting
+//! allocator.
+
+use boxcars::ParserBuilder;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn count<F: FnOnce()>(f: F) -> (usize, usize) {
+    let allocs = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = BYTES.load(Ordering::Relaxed);
+    f();
+    (
+        ALLOCATIONS.load(Ordering::Relaxed) - allocs,
+        BYTES.load(Ordering::Relaxed) - bytes,
+    )
+}
+
+fn main() {
+    let data = include_bytes!("../assets/replays/good/3381.replay");
+
+    let (header_allocs, header_bytes) = count(|| {
+        let replay = ParserBuilder::new(&data[..])
+            .never_check_crc()
+            .never_parse_network_data()
+            .parse()
+            .unwrap();
+        drop(replay);
+    });
+
+    let mut frames = 0;
+    let (allocs, bytes) = count(|| {
+        let replay = ParserBuilder::new(&data[..])
+            .never_check_crc()
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+        frames = replay.network_frames.as_ref().unwrap().frames.len();
+        drop(replay);
+    });
+
+    let allocs = allocs - header_allocs;
+    let bytes = bytes - header_bytes;
+    println!("bench_network_allocations: {} frames", frames);
+    println!(
+        "  allocations: {} ({:.2} per frame)",
+        allocs,
+        allocs as f64 / frames as f64
+    );
+    println!("  allocated bytes: {}", bytes);
+}
@@ -1,6 +1,5 @@
 #[macro_use]
 extern crate criterion;
-use serde_json;
 
 use boxcars::crc::calc_crc;
 use boxcars::*;
@@ -16,6 +15,15 @@ fn bench_crc(c: &mut Criterion) {
     });
 }
 
+fn bench_crc_unaligned(c: &mut Criterion) {
+    c.bench_function("bench_crc_unaligned", |b| {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        b.iter(|| {
+            black_box(calc_crc(&data[1..data.len() - 2]));
+        })
+    });
+}
+
 fn bench_parse_crc_body(c: &mut Criterion) {
     c.bench_function("bench_parse_crc_body", |b| {
         let data = include_bytes!("../assets/replays/good/3381.replay");
@@ -46,6 +54,21 @@ fn bench_parse_no_crc_body(c: &mut Criterion) {
     });
 }
 
+fn bench_parse_rigid_bodies(c: &mut Criterion) {
+    c.bench_function("bench_parse_rigid_bodies", |b| {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        b.iter(|| {
+            black_box(
+                ParserBuilder::new(data)
+                    .on_error_check_crc()
+                    .must_parse_network_data()
+                    .parse()
+                    .is_ok(),
+            )
+        });
+    });
+}
+
 fn bench_parse_no_crc_no_body(c: &mut Criterion) {
     c.bench_function("bench_parse_no_crc_no_body", |b| {
         let data = include_bytes!("../assets/replays/good/3381.replay");
@@ -87,8 +110,10 @@ fn bench_parse_no_crc_json(c: &mut Criterion) {
 criterion_group!(
     benches,
     bench_crc,
+    bench_crc_unaligned,
     bench_parse_crc_body,
     bench_parse_no_crc_body,
+    bench_parse_rigid_bodies,
     bench_parse_no_crc_no_body,
     bench_parse_crc_json,
     bench_parse_no_crc_json
@@ -105,7 +105,7 @@ fn main() {
         .build(&mut file)
         .unwrap();
 
-    write!(&mut file, ";\n").unwrap();
+    writeln!(&mut file, ";").unwrap();
 
 
     write!(&mut file, "pub (crate) static ATTRIBUTES: phf::Map<&'static str, AttributeTag> = ").unwrap();
@@ -164,12 +164,15 @@ fn main() {
         .entry("TAGame.Car_TA:AttachedPickup", "AttributeTag::Flagged")
         .entry("TAGame.Car_TA:ClubColors", "AttributeTag::ClubColors")
         .entry("TAGame.Car_TA:ReplicatedDemolish", "AttributeTag::Demolish")
+        .entry("TAGame.Car_TA:ReplicatedDemolishExtended", "AttributeTag::DemolishExtended")
+        .entry("TAGame.Car_TA:ReplicatedDemolishGoalExplosion", "AttributeTag::DemolishExtended")
         .entry("TAGame.Car_TA:TeamPaint", "AttributeTag::TeamPaint")
         .entry("TAGame.CarComponent_Boost_TA:bNoBoost", "AttributeTag::Boolean")
         .entry("TAGame.CarComponent_Boost_TA:BoostModifier", "AttributeTag::Float")
         .entry("TAGame.CarComponent_Boost_TA:bUnlimitedBoost", "AttributeTag::Boolean")
         .entry("TAGame.CarComponent_Boost_TA:RechargeDelay", "AttributeTag::Float")
         .entry("TAGame.CarComponent_Boost_TA:RechargeRate", "AttributeTag::Float")
+        .entry("TAGame.CarComponent_Boost_TA:ReplicatedBoost", "AttributeTag::ReplicatedBoost")
         .entry("TAGame.CarComponent_Boost_TA:ReplicatedBoostAmount", "AttributeTag::Byte")
         .entry("TAGame.CarComponent_Boost_TA:UnlimitedBoostRefCount", "AttributeTag::Int")
         .entry("TAGame.CarComponent_Dodge_TA:DodgeTorque", "AttributeTag::Location")
@@ -273,6 +276,7 @@ fn main() {
         .entry("TAGame.Vehicle_TA:ReplicatedThrottle", "AttributeTag::Byte")
         .entry("TAGame.VehiclePickup_TA:bNoPickup", "AttributeTag::Boolean")
         .entry("TAGame.VehiclePickup_TA:ReplicatedPickupData", "AttributeTag::Pickup")
+        .entry("TAGame.VehiclePickup_TA:NewReplicatedPickupData", "AttributeTag::PickupNew")
         .entry("TAGame.Ball_Haunted_TA:LastTeamTouch", "AttributeTag::Byte")
         .entry("TAGame.Ball_Haunted_TA:TotalActiveBeams", "AttributeTag::Byte")
         .entry("TAGame.Ball_Haunted_TA:DeactivatedGoalIndex", "AttributeTag::Byte")
@@ -282,7 +286,7 @@ fn main() {
         .build(&mut file)
         .unwrap();
 
-    write!(&mut file, ";\n").unwrap();
+    writeln!(&mut file, ";").unwrap();
 
 
     write!(&mut file, "pub static OBJECT_CLASSES: phf::Map<&'static str, &'static str> = ").unwrap();
@@ -350,7 +354,7 @@ fn main() {
         .build(&mut file)
         .unwrap();
 
-    write!(&mut file, ";\n").unwrap();
+    writeln!(&mut file, ";").unwrap();
 
 
     write!(&mut file, "pub static PARENT_CLASSES: phf::Map<&'static str, &'static str> = ").unwrap();
@@ -412,5 +416,5 @@ fn main() {
         .build(&mut file)
         .unwrap();
 
-    write!(&mut file, ";\n").unwrap();
+    writeln!(&mut file, ";").unwrap();
 }
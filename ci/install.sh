@@ -1,6 +1,11 @@
 set -ex
 
 main() {
+    # Native jobs build with cargo
+    if [ ! -z $NATIVE ]; then
+        return
+    fi
+
     local target=
     if [ $TRAVIS_OS_NAME = linux ]; then
         target=x86_64-unknown-linux-musl
@@ -4,13 +4,18 @@ set -ex
 
 # TODO This is the "test phase", tweak it as you see fit
 main() {
-    cross build --all --target $TARGET
+    local cargo=cross
+    if [ ! -z $NATIVE ]; then
+        cargo=cargo
+    fi
+
+    $cargo build --all --target $TARGET
 
     if [ ! -z $DISABLE_TESTS ]; then
         return
     fi
 
-    cross test --all --target $TARGET
+    $cargo test --all --target $TARGET
 }
 
 # we don't run the "test phase" when doing deploys
@@ -0,0 +1,89 @@
+#![no_main]
+extern crate boxcars;
+#[macro_use]
+extern crate libfuzzer_sys;
+
+use boxcars::{ActorId, Attribute, FrameBuilder, ParserBuilder, ReplayBuilder, Trajectory};
+
+/// An attribute that is decoded as the given attribute's type
+fn attribute_name(attr: &Attribute) -> Option<&'static str> {
+    let name = match attr {
+        Attribute::AppliedDamage(..) => "TAGame.Ball_Breakout_TA:AppliedDamage",
+        Attribute::Boolean(_) => "Engine.Actor:bHidden",
+        Attribute::Byte(_) => "TAGame.Ball_Haunted_TA:LastTeamTouch",
+        Attribute::CamSettings(_) => "TAGame.PRI_TA:CameraSettings",
+        Attribute::ClubColors(_) => "TAGame.Team_TA:ClubColors",
+        Attribute::DamageState(..) => "TAGame.BreakOutActor_Platform_TA:DamageState",
+        Attribute::Demolish(_) => "TAGame.Car_TA:ReplicatedDemolish",
+        Attribute::DemolishExtended(_) => "TAGame.Car_TA:ReplicatedDemolishGoalExplosion",
+        Attribute::Enum(_) => "Engine.Actor:RemoteRole",
+        Attribute::Explosion(_) => "TAGame.Ball_TA:ReplicatedExplosionData",
+        Attribute::ExtendedExplosion(..) => "TAGame.Ball_TA:ReplicatedExplosionDataExtended",
+        Attribute::Flagged(..) => "TAGame.CrowdManager_TA:ReplicatedGlobalOneShotSound",
+        Attribute::FlaggedByte(..) => "TAGame.PRI_TA:SkillTier",
+        Attribute::Float(_) => "TAGame.Car_TA:AddedBallForceMultiplier",
+        Attribute::GameMode(..) => "TAGame.GameEvent_TA:GameMode",
+        Attribute::Int(_) => "TAGame.GameEvent_TA:BotSkill",
+        Attribute::Loadout(_) => "TAGame.PRI_TA:ClientLoadout",
+        Attribute::LoadoutOnline(_) => "TAGame.PRI_TA:ClientLoadoutOnline",
+        Attribute::LoadoutsOnline(_) => "TAGame.PRI_TA:ClientLoadoutsOnline",
+        Attribute::Location(_) => "TAGame.CarComponent_Dodge_TA:DodgeTorque",
+        Attribute::MusicStinger(_) => "TAGame.GameEvent_Soccar_TA:ReplicatedMusicStinger",
+        Attribute::PartyLeader(_) => "TAGame.PRI_TA:PartyLeader",
+        Attribute::Pickup(_) => "TAGame.VehiclePickup_TA:ReplicatedPickupData",
+        Attribute::PickupNew(_) => "TAGame.VehiclePickup_TA:NewReplicatedPickupData",
+        Attribute::PlayerHistoryKey(_) => "TAGame.PRI_TA:PlayerHistoryKey",
+        Attribute::PrivateMatch(_) => "TAGame.GameEvent_SoccarPrivate_TA:MatchSettings",
+        Attribute::QWord(_) => "ProjectX.GRI_X:GameServerID",
+        Attribute::ReplicatedBoost(_) => "TAGame.CarComponent_Boost_TA:ReplicatedBoost",
+        Attribute::Reservation(_) => "ProjectX.GRI_X:Reservations",
+        Attribute::RigidBody(_) => "TAGame.RBActor_TA:ReplicatedRBState",
+        Attribute::StatEvent(..) => "TAGame.GameEvent_Soccar_TA:ReplicatedStatEvent",
+        Attribute::String(_) => "Engine.PlayerReplicationInfo:PlayerName",
+        Attribute::TeamLoadout(_) => "TAGame.PRI_TA:ClientLoadouts",
+        Attribute::TeamPaint(_) => "TAGame.Car_TA:TeamPaint",
+        Attribute::Title(..) => "TAGame.PRI_TA:PrimaryTitle",
+        Attribute::UniqueId(_) => "Engine.PlayerReplicationInfo:UniqueId",
+        Attribute::Welded(_) => "TAGame.RBActor_TA:WeldedInfo",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+fuzz_target!(|attributes: Vec<Attribute>| {
+    let mut frame = FrameBuilder::new(1.0, 0.03).spawn(
+        ActorId(0),
+        "Archetypes.Ball.Ball_Default",
+        Trajectory {
+            location: None,
+            rotation: None,
+        },
+    );
+
+    let mut expected = Vec::new();
+    for attr in attributes {
+        if let Some(name) = attribute_name(&attr) {
+            expected.push(format!("{:?}", attr));
+            frame = frame.update(ActorId(0), name, attr);
+        }
+    }
+
+    // Attributes that can't be encoded for the replay's version are rejected by the builder, but
+    // everything that is written must be parsed back into the same attributes
+    if let Ok(data) = ReplayBuilder::new().frame(frame).build() {
+        let replay = ParserBuilder::new(&data[..])
+            .always_check_crc()
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let frames = replay.network_frames.unwrap().frames;
+        let actual: Vec<_> = frames[0]
+            .updated_actors
+            .iter()
+            .map(|x| format!("{:?}", x.attribute))
+            .collect();
+        assert_eq!(actual, expected);
+    }
+});
@@ -0,0 +1,278 @@
+//! Metadata of the Rocket League arenas, keyed by the internal map name that a replay records in
+//! its "MapName" header property (eg: `EuroStadium_Night_P` is Mannfield (Night)).
+//!
+//! Map names are matched without regard to case, as replays are inconsistent about it (eg:
+//! `Stadium_P` and `Stadium_p`). Dimensions are in unreal units. The standard soccar dimensions
+//! are exact, while those of other layouts are approximate extents of their field.
+//!
+//! ```
+//! use boxcars::arenas::Layout;
+//!
+//! # let data = include_bytes!("../assets/replays/good/rumble.replay");
+//! let replay = boxcars::ParserBuilder::new(&data[..])
+//!     .never_parse_network_data()
+//!     .parse()
+//!     .unwrap();
+//!
+//! let arena = replay.arena().unwrap();
+//! assert_eq!(arena.name, "DFH Stadium (Stormy)");
+//! assert_eq!(arena.layout, Layout::Standard);
+//! ```
+
+use crate::network::Vector3f;
+
+/// The shape of an arena's field. Every arena of a layout has the same dimensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Layout {
+    /// The soccar field that most arenas share
+    Standard,
+
+    /// The shorter, narrower field of hoops with a hoop at each end
+    Hoops,
+
+    /// The hexagonal field of dropshot, which has a breakable floor instead of goals
+    Dropshot,
+
+    /// The longer field of throwback stadium, with its own boost pad layout
+    Throwback,
+}
+
+/// The extents of a field from its center, which is the center of the floor
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct FieldDimensions {
+    /// From the center to a side wall (along x)
+    pub half_width: f32,
+
+    /// From the center to a back wall (along y), excluding the goals
+    pub half_length: f32,
+
+    /// From the floor to the ceiling
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct GoalSize {
+    pub width: f32,
+    pub height: f32,
+
+    /// How far the goal extends past the back wall
+    pub depth: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct Arena {
+    /// The internal map name (eg: `EuroStadium_Night_P`)
+    pub map_name: &'static str,
+
+    /// The name of the arena that the game shows (eg: `Mannfield (Night)`)
+    pub name: &'static str,
+    pub layout: Layout,
+    pub dimensions: FieldDimensions,
+
+    /// The size of each goal. Hoops and dropshot don't have rectangular goals.
+    pub goal: Option<GoalSize>,
+}
+
+impl Arena {
+    /// Scales a location so that the field spans -1 to 1 along x and y (the blue goal is at
+    /// negative y) and 0 to 1 along z, which makes locations comparable across layouts
+    pub fn normalize(&self, location: Vector3f) -> Vector3f {
+        Vector3f {
+            x: location.x / self.dimensions.half_width,
+            y: location.y / self.dimensions.half_length,
+            z: location.z / self.dimensions.height,
+        }
+    }
+
+    /// Returns true if the location is within the field, where the goals are outside the field
+    pub fn contains(&self, location: Vector3f) -> bool {
+        let dimensions = &self.dimensions;
+        location.x.abs() <= dimensions.half_width
+            && location.y.abs() <= dimensions.half_length
+            && location.z >= 0.0
+            && location.z <= dimensions.height
+    }
+
+    /// The center of the goal line of the team's goal: team 0 (blue) defends the goal at
+    /// negative y and team 1 (orange) defends the goal at positive y
+    pub fn goal_center(&self, team: u8) -> Option<Vector3f> {
+        let goal = self.goal?;
+        let y = match team {
+            0 => -self.dimensions.half_length,
+            1 => self.dimensions.half_length,
+            _ => return None,
+        };
+
+        Some(Vector3f {
+            x: 0.0,
+            y,
+            z: goal.height / 2.0,
+        })
+    }
+}
+
+const STANDARD_FIELD: FieldDimensions = FieldDimensions {
+    half_width: 4096.0,
+    half_length: 5120.0,
+    height: 2044.0,
+};
+
+const STANDARD_GOAL: GoalSize = GoalSize {
+    width: 1786.0,
+    height: 642.775,
+    depth: 880.0,
+};
+
+const HOOPS_FIELD: FieldDimensions = FieldDimensions {
+    half_width: 2966.0,
+    half_length: 3581.0,
+    height: 1820.0,
+};
+
+const DROPSHOT_FIELD: FieldDimensions = FieldDimensions {
+    half_width: 4555.0,
+    half_length: 5026.0,
+    height: 2020.0,
+};
+
+const THROWBACK_FIELD: FieldDimensions = FieldDimensions {
+    half_width: 4096.0,
+    half_length: 5888.0,
+    height: 2044.0,
+};
+
+const fn standard(map_name: &'static str, name: &'static str) -> Arena {
+    Arena {
+        map_name,
+        name,
+        layout: Layout::Standard,
+        dimensions: STANDARD_FIELD,
+        goal: Some(STANDARD_GOAL),
+    }
+}
+
+/// The known arenas
+pub static ARENAS: &[Arena] = &[
+    standard("Stadium_P", "DFH Stadium"),
+    standard("Stadium_Day_P", "DFH Stadium (Day)"),
+    standard("Stadium_Foggy_P", "DFH Stadium (Stormy)"),
+    standard("Stadium_Winter_P", "DFH Stadium (Snowy)"),
+    standard("Stadium_Race_Day_P", "DFH Stadium (Circuit)"),
+    standard("EuroStadium_P", "Mannfield"),
+    standard("EuroStadium_Night_P", "Mannfield (Night)"),
+    standard("EuroStadium_Rainy_P", "Mannfield (Stormy)"),
+    standard("EuroStadium_SnowNight_P", "Mannfield (Snowy)"),
+    standard("EuroStadium_Dusk_P", "Mannfield (Dusk)"),
+    standard("UtopiaStadium_P", "Utopia Coliseum"),
+    standard("UtopiaStadium_Dusk_P", "Utopia Coliseum (Dusk)"),
+    standard("UtopiaStadium_Snow_P", "Utopia Coliseum (Snowy)"),
+    standard("UtopiaStadium_Lux_P", "Utopia Coliseum (Gilded)"),
+    standard("Park_P", "Beckwith Park"),
+    standard("Park_Night_P", "Beckwith Park (Midnight)"),
+    standard("Park_Rainy_P", "Beckwith Park (Stormy)"),
+    standard("TrainStation_P", "Urban Central"),
+    standard("TrainStation_Night_P", "Urban Central (Night)"),
+    standard("TrainStation_Dawn_P", "Urban Central (Dawn)"),
+    standard("Haunted_TrainStation_P", "Urban Central (Haunted)"),
+    standard("cs_p", "Champions Field"),
+    standard("cs_day_p", "Champions Field (Day)"),
+    standard("cs_hw_p", "Rivals Arena"),
+    standard("wasteland_s_p", "Wasteland"),
+    standard("wasteland_Night_S_P", "Wasteland (Night)"),
+    standard("NeoTokyo_Standard_P", "Neo Tokyo"),
+    standard("arc_standard_p", "Starbase ARC"),
+    standard("Underwater_P", "AquaDome"),
+    standard("beach_P", "Salty Shores"),
+    standard("beach_night_p", "Salty Shores (Night)"),
+    standard("farm_p", "Farmstead"),
+    standard("farm_night_p", "Farmstead (Night)"),
+    standard("ChinaTown_P", "Forbidden Temple"),
+    standard("ChinaTown_Day_P", "Forbidden Temple (Day)"),
+    standard("music_p", "Neon Fields"),
+    standard("outlaw_p", "Deadeye Canyon"),
+    standard("woods_p", "Drift Woods"),
+    standard("woods_night_p", "Drift Woods (Night)"),
+    Arena {
+        map_name: "HoopsStadium_P",
+        name: "Dunk House",
+        layout: Layout::Hoops,
+        dimensions: HOOPS_FIELD,
+        goal: None,
+    },
+    Arena {
+        map_name: "HoopsStreet_P",
+        name: "The Block (Dusk)",
+        layout: Layout::Hoops,
+        dimensions: HOOPS_FIELD,
+        goal: None,
+    },
+    Arena {
+        map_name: "ShatterShot_P",
+        name: "Core 707",
+        layout: Layout::Dropshot,
+        dimensions: DROPSHOT_FIELD,
+        goal: None,
+    },
+    Arena {
+        map_name: "ThrowbackStadium_P",
+        name: "Throwback Stadium",
+        layout: Layout::Throwback,
+        dimensions: THROWBACK_FIELD,
+        goal: Some(STANDARD_GOAL),
+    },
+];
+
+/// Returns the arena of the internal map name, without regard to case
+pub fn arena(map_name: &str) -> Option<&'static Arena> {
+    ARENAS
+        .iter()
+        .find(|x| x.map_name.eq_ignore_ascii_case(map_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arena() {
+        let arena = arena("utopiastadium_p").unwrap();
+        assert_eq!(arena.name, "Utopia Coliseum");
+        assert_eq!(arena.goal_center(0).map(|x| x.y), Some(-5120.0));
+        assert!(super::arena("HoopsStadium_P")
+            .unwrap()
+            .goal_center(0)
+            .is_none());
+        assert!(super::arena("Labs_DoubleGoal_P").is_none());
+
+        let location = Vector3f {
+            x: 2048.0,
+            y: -5120.0,
+            z: 1022.0,
+        };
+        assert!(arena.contains(location));
+        assert_eq!(
+            arena.normalize(location),
+            Vector3f {
+                x: 0.5,
+                y: -1.0,
+                z: 0.5,
+            }
+        );
+
+        // Map names are unique
+        let mut names: Vec<_> = ARENAS
+            .iter()
+            .map(|x| x.map_name.to_ascii_lowercase())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), ARENAS.len());
+    }
+}
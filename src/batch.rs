@@ -0,0 +1,415 @@
+//! Parses many replays across a pool of threads.
+//!
+//! A replay borrows from the data it was parsed from, so each replay is mapped to an owned
+//! value (eg: the stats that a site stores) on the thread that parsed it. Memory is bounded by
+//! the number of threads: each thread holds a single replay's data at a time, and threads stop
+//! to wait once there is a result per thread that hasn't been received.
+
+use crate::models::Replay;
+use crate::parser::{CrcCheck, NetworkParse, ParserBuilder};
+use failure::Error;
+use std::collections::HashSet;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Options for parsing a batch of replays
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchOptions {
+    threads: Option<usize>,
+    crc_check: CrcCheck,
+    network_parse: NetworkParse,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        BatchOptions {
+            threads: None,
+            crc_check: CrcCheck::OnError,
+            network_parse: NetworkParse::IgnoreOnError,
+        }
+    }
+}
+
+impl BatchOptions {
+    pub fn new() -> Self {
+        BatchOptions::default()
+    }
+
+    /// The number of threads to parse with. Defaults to the available parallelism.
+    pub fn threads(mut self, threads: usize) -> BatchOptions {
+        self.threads = Some(threads.max(1));
+        self
+    }
+
+    pub fn with_crc_check(mut self, check: CrcCheck) -> BatchOptions {
+        self.crc_check = check;
+        self
+    }
+
+    pub fn with_network_parse(mut self, parse: NetworkParse) -> BatchOptions {
+        self.network_parse = parse;
+        self
+    }
+}
+
+/// The results of a batch in the order that the replays finish parsing. Dropping the results
+/// stops the threads once they finish the replays they are parsing.
+#[derive(Debug)]
+pub struct BatchResults<T> {
+    /// The paths that couldn't be discovered, which are yielded before the parsed replays
+    errors: std::vec::IntoIter<(PathBuf, Error)>,
+    receiver: Receiver<(PathBuf, Result<T, Error>)>,
+}
+
+impl<T> Iterator for BatchResults<T> {
+    type Item = (PathBuf, Result<T, Error>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.errors.next() {
+            Some((path, e)) => Some((path, Err(e))),
+            None => self.receiver.recv().ok(),
+        }
+    }
+}
+
+/// The replays of a directory (see `from_dir`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayDir {
+    root: PathBuf,
+    recursive: bool,
+}
+
+/// Discovers the replays of the directory: the files with a `.replay` extension (or `.replay.gz`
+/// with the `gzip` feature). Symbolic links are followed, and a directory that is linked more
+/// than once is only searched once.
+///
+/// ```
+/// use boxcars::batch::{self, BatchOptions};
+/// use boxcars::NetworkParse;
+///
+/// let options = BatchOptions::new().with_network_parse(NetworkParse::Never);
+/// let results = batch::from_dir("assets/replays")
+///     .recursive(true)
+///     .parse_all(&options, |_path, replay| replay.properties.len());
+///
+/// for (path, result) in results {
+///     match result {
+///         Ok(properties) => println!("{}: {} properties", path.display(), properties),
+///         Err(e) => println!("{}: {}", path.display(), e),
+///     }
+/// }
+/// ```
+pub fn from_dir<P: Into<PathBuf>>(path: P) -> ReplayDir {
+    ReplayDir {
+        root: path.into(),
+        recursive: false,
+    }
+}
+
+impl ReplayDir {
+    /// Whether to search subdirectories too. Defaults to false.
+    pub fn recursive(mut self, recursive: bool) -> ReplayDir {
+        self.recursive = recursive;
+        self
+    }
+
+    /// The replays of the directory sorted by path, and the paths that couldn't be read (eg: an
+    /// unreadable subdirectory or a broken symbolic link)
+    pub fn paths(&self) -> (Vec<PathBuf>, Vec<(PathBuf, Error)>) {
+        let mut paths = Vec::new();
+        let mut errors = Vec::new();
+        let mut visited = HashSet::new();
+        let mut dirs = vec![self.root.clone()];
+        while let Some(dir) = dirs.pop() {
+            // A directory that has already been searched through another link is skipped
+            let canonical = match fs::canonicalize(&dir) {
+                Ok(x) => x,
+                Err(e) => {
+                    errors.push((dir, e.into()));
+                    continue;
+                }
+            };
+
+            if !visited.insert(canonical) {
+                continue;
+            }
+
+            let entries = match fs::read_dir(&dir) {
+                Ok(x) => x,
+                Err(e) => {
+                    errors.push((dir, e.into()));
+                    continue;
+                }
+            };
+
+            for entry in entries {
+                let path = match entry {
+                    Ok(x) => x.path(),
+                    Err(e) => {
+                        errors.push((dir.clone(), e.into()));
+                        continue;
+                    }
+                };
+
+                // The metadata follows symbolic links to what they link to
+                match fs::metadata(&path) {
+                    Ok(x) if x.is_dir() => {
+                        if self.recursive {
+                            dirs.push(path);
+                        }
+                    }
+                    Ok(_) if is_replay(&path) => paths.push(path),
+                    Ok(_) => {}
+                    Err(e) if is_replay(&path) => errors.push((path, e.into())),
+                    Err(_) => {}
+                }
+            }
+        }
+
+        paths.sort();
+        (paths, errors)
+    }
+
+    /// Parses the replays of the directory across a pool of threads and maps each parsed replay
+    /// with `f` (see `parse_all`). The paths that couldn't be read are yielded as errors first.
+    pub fn parse_all<F, T>(&self, options: &BatchOptions, f: F) -> BatchResults<T>
+    where
+        F: Fn(&Path, Replay<'_>) -> T + Send + Sync + 'static,
+        T: Send + 'static,
+    {
+        let (paths, errors) = self.paths();
+        let mut results = parse_all(paths, options, f);
+        results.errors = errors.into_iter();
+        results
+    }
+}
+
+fn is_replay(path: &Path) -> bool {
+    let name = match path.file_name().and_then(|x| x.to_str()) {
+        Some(x) => x.to_ascii_lowercase(),
+        None => return false,
+    };
+
+    name.ends_with(".replay") || (cfg!(feature = "gzip") && name.ends_with(".replay.gz"))
+}
+
+/// Parses the replays at the given paths across a pool of threads and maps each parsed replay
+/// with `f`. A replay that can't be read or parsed, or that causes a panic, is yielded as an
+/// error without affecting the rest of the batch.
+///
+/// ```
+/// use boxcars::batch::{self, BatchOptions};
+///
+/// let paths = vec!["assets/replays/good/rumble.replay", "assets/replays/missing.replay"];
+/// let options = BatchOptions::new().threads(2);
+/// let mut results: Vec<_> = batch::parse_all(paths, &options, |_path, replay| {
+///     replay.game_type.into_owned()
+/// })
+/// .collect();
+///
+/// results.sort_by(|a, b| a.0.cmp(&b.0));
+/// assert_eq!(results[0].1.as_ref().unwrap(), "TAGame.Replay_Soccar_TA");
+/// assert!(results[1].1.is_err());
+/// ```
+pub fn parse_all<I, P, F, T>(paths: I, options: &BatchOptions, f: F) -> BatchResults<T>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<PathBuf>,
+    F: Fn(&Path, Replay<'_>) -> T + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    let paths: Vec<PathBuf> = paths.into_iter().map(Into::into).collect();
+    let threads = options
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |x| x.get()))
+        .min(paths.len())
+        .max(1);
+
+    let queue = Arc::new(Mutex::new(paths.into_iter()));
+    let f = Arc::new(f);
+    let (sender, receiver) = mpsc::sync_channel(threads);
+    for _ in 0..threads {
+        let queue = Arc::clone(&queue);
+        let f = Arc::clone(&f);
+        let sender = sender.clone();
+        let options = options.clone();
+        thread::spawn(move || loop {
+            // A panic while holding the lock can't leave the iterator in a bad state
+            let path = match queue.lock().unwrap_or_else(|e| e.into_inner()).next() {
+                Some(path) => path,
+                None => break,
+            };
+
+            let result = parse_file(&path, &options, &*f);
+            if sender.send((path, result)).is_err() {
+                break;
+            }
+        });
+    }
+
+    drop(sender);
+    BatchResults {
+        errors: Vec::new().into_iter(),
+        receiver,
+    }
+}
+
+fn parse_file<F, T>(path: &Path, options: &BatchOptions, f: &F) -> Result<T, Error>
+where
+    F: Fn(&Path, Replay<'_>) -> T,
+{
+    // A compressed replay is decompressed as it's read
+    #[cfg(feature = "gzip")]
+    let data = crate::compression::read(std::fs::File::open(path)?)?;
+    #[cfg(not(feature = "gzip"))]
+    let data = std::fs::read(path)?;
+
+    let parse = || {
+        let replay = ParserBuilder::new(&data)
+            .with_crc_check(options.crc_check)
+            .with_network_parse(options.network_parse)
+            .parse()?;
+        Ok(f(path, replay))
+    };
+
+    panic::catch_unwind(AssertUnwindSafe(parse)).unwrap_or_else(|e| {
+        let msg = e
+            .downcast_ref::<&str>()
+            .map(|x| String::from(*x))
+            .or_else(|| e.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(format_err!("Parsing panicked: {}", msg))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_all() {
+        let mut paths: Vec<PathBuf> = std::fs::read_dir("assets/replays/good")
+            .unwrap()
+            .map(|x| x.unwrap().path())
+            .collect();
+        let good = paths.len();
+        paths.push(PathBuf::from("assets/replays/bad/fuzz-corpus.replay"));
+        paths.push(PathBuf::from("assets/replays/does-not-exist.replay"));
+
+        let options = BatchOptions::new()
+            .threads(4)
+            .with_network_parse(NetworkParse::Never);
+        let results: Vec<_> =
+            parse_all(paths, &options, |_, replay| replay.properties.len()).collect();
+
+        assert_eq!(results.len(), good + 2);
+        let errors: Vec<_> = results.iter().filter(|x| x.1.is_err()).collect();
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_all_panic() {
+        let paths = vec![
+            "assets/replays/good/rumble.replay",
+            "assets/replays/good/3381.replay",
+        ];
+        let options = BatchOptions::new()
+            .threads(2)
+            .with_network_parse(NetworkParse::Never);
+        let results: Vec<_> = parse_all(paths, &options, |path, _| {
+            if path.ends_with("rumble.replay") {
+                panic!("bad replay");
+            }
+        })
+        .collect();
+
+        assert_eq!(results.len(), 2);
+        for (path, result) in results {
+            if path.ends_with("rumble.replay") {
+                let err = result.unwrap_err();
+                assert_eq!(err.to_string(), "Parsing panicked: bad replay");
+            } else {
+                assert!(result.is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_dir() {
+        let good = from_dir("assets/replays/good");
+        let (paths, errors) = good.paths();
+        assert!(errors.is_empty());
+        assert!(paths.windows(2).all(|x| x[0] < x[1]));
+        assert!(paths.iter().all(|x| x.extension().unwrap() == "replay"));
+
+        // The root only has directories
+        let root = from_dir("assets/replays");
+        assert!(root.paths().0.is_empty());
+        let (all, _) = root.recursive(true).paths();
+        assert!(all.len() > paths.len());
+        assert!(all.iter().any(|x| x.ends_with("bad/fuzz-corpus.replay")));
+
+        let options = BatchOptions::new().with_network_parse(NetworkParse::Never);
+        let results: Vec<_> = good.parse_all(&options, |_, _| ()).collect();
+        assert_eq!(results.len(), paths.len());
+        assert!(results.iter().all(|x| x.1.is_ok()));
+
+        let missing: Vec<_> = from_dir("assets/replays/missing")
+            .parse_all(&options, |_, _| ())
+            .collect();
+        assert_eq!(missing.len(), 1);
+        assert!(missing[0].1.is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_dir_links() {
+        use std::os::unix::fs::symlink;
+
+        let root = std::env::temp_dir().join(format!("boxcars-from-dir-{}", std::process::id()));
+        let nested = root.join("nested");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&nested).unwrap();
+
+        let replay = fs::canonicalize("assets/replays/good/small-frames.replay").unwrap();
+        fs::copy(&replay, nested.join("a.replay")).unwrap();
+        fs::write(root.join("notes.txt"), "not a replay").unwrap();
+        symlink(&replay, root.join("linked.replay")).unwrap();
+        symlink(root.join("missing.replay"), root.join("broken.replay")).unwrap();
+
+        // A link back to the root would loop forever if it were followed again
+        symlink(&root, nested.join("loop")).unwrap();
+
+        let (paths, errors) = from_dir(&root).recursive(true).paths();
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(
+            paths,
+            vec![root.join("linked.replay"), nested.join("a.replay")]
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, root.join("broken.replay"));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_parse_all_compressed() {
+        let paths = vec![
+            "assets/replays/good/small-frames.replay",
+            "assets/replays/compressed/small-frames.replay.gz",
+        ];
+        let options = BatchOptions::new().with_network_parse(NetworkParse::Never);
+        let results: Vec<_> =
+            parse_all(paths, &options, |_, replay| replay.properties.len()).collect();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].1.as_ref().unwrap(),
+            results[1].1.as_ref().unwrap()
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! The bit reader that the network data is decoded with, for those writing their own attribute
+//! decoders or researching the network data of a new patch.
+//!
+//! The network data is a stream of little endian bits: values are read least significant bit
+//! first and aren't aligned to byte boundaries. Bits are taken from each byte starting at its
+//! least significant bit, so a value that is read at a byte boundary has the same bytes as its
+//! little endian encoding (eg: `read_u32` of `[0x01, 0x02, 0x03, 0x04]` is `0x04030201`). Reads
+//! (and peeks) return `None` without advancing when there isn't enough data.
+//!
+//! Byte aligned data can be read after skipping to the next byte boundary with `align_to_byte`,
+//! or borrowed with `read_bytes_aligned`.
+//!
+//! ```
+//! use boxcars::bits::BitReader;
+//!
+//! let mut bits = BitReader::new(&[0b1010_1101, 0xff]);
+//! assert_eq!(bits.read_bit(), Some(true));
+//! assert_eq!(bits.peek_bits(3), Some(0b110));
+//! assert_eq!(bits.read_bits(3), Some(0b110));
+//! assert_eq!(bits.position(), 4);
+//! assert_eq!(bits.read_u16(), None);
+//! assert_eq!(bits.read_u8(), Some(0xfa));
+//! assert_eq!(bits.bits_remaining(), 4);
+//! ```
+
+use crate::network::attributes::read_text;
+use crate::network::{CompressedRotation, Rotation, Vector, Vector3f};
+use std::borrow::Cow;
+
+pub use crate::network::bit_reader::BitGet;
+
+/// Reads bits from a slice of data
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    bits: BitGet<'a>,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        BitReader {
+            data,
+            bits: BitGet::new(data),
+        }
+    }
+
+    /// A reader that starts at the given bit of the data, which is useful for resuming from a
+    /// `BitSpan`. Returns `None` when the position is past the end of the data.
+    pub fn at(data: &'a [u8], position: usize) -> Option<Self> {
+        let mut bits = BitGet::new(data.get(position / 8..)?);
+        if position % 8 != 0 {
+            bits.read_u32_bits((position % 8) as i32)?;
+        }
+        Some(BitReader { data, bits })
+    }
+
+    /// The number of bits that have been read
+    pub fn position(&self) -> usize {
+        self.data.len() * 8 - self.bits_remaining()
+    }
+
+    /// The number of bits left to read
+    pub fn bits_remaining(&self) -> usize {
+        self.bits.bits_remaining()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Reads the next bit without advancing the reader
+    pub fn peek_bit(&self) -> Option<bool> {
+        self.peek(|x| x.read_bit())
+    }
+
+    /// Reads the next given number of bits (up to 32) without advancing the reader, as
+    /// `read_bits` would read them
+    pub fn peek_bits(&self, bits: i32) -> Option<u32> {
+        self.peek(|x| x.read_bits(bits))
+    }
+
+    /// Calls the function with a copy of the reader so that it doesn't advance this reader
+    pub fn peek<T, F>(&self, f: F) -> Option<T>
+    where
+        F: FnOnce(&mut BitReader<'a>) -> Option<T>,
+    {
+        f(&mut BitReader::at(self.data, self.position())?)
+    }
+
+    pub fn read_bit(&mut self) -> Option<bool> {
+        self.bits.read_bit()
+    }
+
+    pub fn read_u8(&mut self) -> Option<u8> {
+        self.bits.read_u8()
+    }
+
+    pub fn read_i8(&mut self) -> Option<i8> {
+        self.bits.read_i8()
+    }
+
+    pub fn read_u16(&mut self) -> Option<u16> {
+        self.bits.read_u16()
+    }
+
+    pub fn read_u32(&mut self) -> Option<u32> {
+        self.bits.read_u32()
+    }
+
+    pub fn read_i32(&mut self) -> Option<i32> {
+        self.bits.read_i32()
+    }
+
+    pub fn read_u64(&mut self) -> Option<u64> {
+        self.bits.read_u64()
+    }
+
+    pub fn read_f32(&mut self) -> Option<f32> {
+        self.bits.read_f32()
+    }
+
+    /// Reads the given number of bits (up to 32) as an unsigned integer
+    pub fn read_bits(&mut self, bits: i32) -> Option<u32> {
+        self.bits.read_u32_bits(bits)
+    }
+
+    /// Reads a compressed integer that is less than or equal to `max`, which takes at most the
+    /// given number of bits (1 to 32). The most significant bit is only read if setting it
+    /// wouldn't exceed `max`, which is how Unreal serializes integers with a known upper bound
+    /// (eg: stream ids).
+    pub fn read_bits_max(&mut self, bits: i32, max: i32) -> Option<u32> {
+        self.bits.read_bits_max(bits, max)
+    }
+
+    /// Reads a bit and only when it is set, reads a value with the given function
+    pub fn if_get<T, F>(&mut self, f: F) -> Option<Option<T>>
+    where
+        F: FnOnce(&mut Self) -> Option<T>,
+    {
+        match self.read_bit()? {
+            true => f(self).map(Some),
+            false => Some(None),
+        }
+    }
+
+    /// Reads the given number of bytes, which are borrowed when the reader is at a byte boundary
+    /// and else are each read as eight bits
+    pub fn read_bytes(&mut self, bytes: i32) -> Option<Cow<'_, [u8]>> {
+        self.bits.read_bytes(bytes)
+    }
+
+    /// Returns true when the reader is at a byte boundary
+    pub fn is_aligned(&self) -> bool {
+        self.position() % 8 == 0
+    }
+
+    /// Skips the rest of the current byte so that the reader is at a byte boundary, and returns
+    /// the number of bits skipped. A reader that is already at a byte boundary doesn't move, and
+    /// neither does a reader that can't read the rest of its byte, so both skip zero bits.
+    ///
+    /// ```
+    /// use boxcars::bits::BitReader;
+    ///
+    /// let mut bits = BitReader::new(&[0xff, 0x2a]);
+    /// assert_eq!(bits.read_bits(3), Some(0b111));
+    /// assert_eq!(bits.align_to_byte(), 5);
+    /// assert_eq!(bits.align_to_byte(), 0);
+    /// assert_eq!(bits.read_u8(), Some(0x2a));
+    /// ```
+    pub fn align_to_byte(&mut self) -> usize {
+        let skipped = (8 - self.position() % 8) % 8;
+        self.bits
+            .read_u32_bits(skipped as i32)
+            .map_or(0, |_| skipped)
+    }
+
+    /// Skips to the next byte boundary (see `align_to_byte`) and borrows the given number of
+    /// bytes from there. Returns `None` without advancing when there aren't enough bytes after
+    /// the byte boundary.
+    ///
+    /// ```
+    /// use boxcars::bits::BitReader;
+    ///
+    /// let mut bits = BitReader::new(&[0x01, 0x02, 0x03]);
+    /// assert_eq!(bits.read_bit(), Some(true));
+    /// assert_eq!(bits.read_bytes_aligned(3), None);
+    /// assert_eq!(bits.read_bytes_aligned(2), Some(&[0x02, 0x03][..]));
+    /// assert!(bits.is_empty());
+    /// ```
+    pub fn read_bytes_aligned(&mut self, bytes: usize) -> Option<&'a [u8]> {
+        let start = (self.position() + 7) / 8;
+        let end = start.checked_add(bytes)?;
+        let res = self.data.get(start..end)?;
+        *self = BitReader::at(self.data, end * 8)?;
+        Some(res)
+    }
+
+    /// Reads a length prefixed string, where a negative length denotes a UTF-16 string
+    pub fn read_string(&mut self) -> Option<String> {
+        read_text(&mut self.bits, str::to_owned).ok()
+    }
+
+    /// Reads a serialized vector, whose components take a variable number of bits
+    pub fn read_vector(&mut self, net_version: i32) -> Option<Vector> {
+        Vector::decode(&mut self.bits, net_version)
+    }
+
+    /// Reads a serialized vector and decompresses it into floats
+    pub fn read_vector3f(&mut self, net_version: i32) -> Option<Vector3f> {
+        self.read_vector(net_version)
+            .map(|x| x.to_vector3f(net_version))
+    }
+
+    /// Reads a rotation of optional bytes, as used when an actor spawns
+    pub fn read_rotation(&mut self) -> Option<Rotation> {
+        Rotation::decode(&mut self.bits)
+    }
+
+    /// Reads a rotation of a rigid body, which is a compressed quaternion since net version 7
+    pub fn read_compressed_rotation(&mut self, net_version: i32) -> Option<CompressedRotation> {
+        CompressedRotation::decode(&mut self.bits, net_version)
+    }
+}
+
+impl<'a> std::fmt::Debug for BitReader<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BitReader")
+            .field("position", &self.position())
+            .field("bits_remaining", &self.bits_remaining())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_at() {
+        let data = [0xff, 0x00, 0x0f];
+        let mut bits = BitReader::at(&data, 4).unwrap();
+        assert_eq!(bits.position(), 4);
+        assert_eq!(bits.read_u8(), Some(0x0f));
+        assert_eq!(bits.peek_bit(), Some(false));
+        assert_eq!(bits.read_bits(12), Some(0xf0));
+        assert!(bits.is_empty());
+        assert!(BitReader::at(&data, 25).is_none());
+    }
+
+    #[test]
+    fn test_read_aligned() {
+        let data = [0b1000_0001, 0x34, 0x12, 0xff];
+        let mut bits = BitReader::new(&data);
+        assert!(bits.is_aligned());
+        assert_eq!(bits.read_bytes_aligned(0), Some(&[][..]));
+        assert_eq!(bits.peek_bits(8), Some(0x81));
+        assert_eq!(bits.read_bits(7), Some(0x01));
+        assert!(!bits.is_aligned());
+
+        // The last bit of the first byte is skipped
+        let mut aligned = BitReader::new(&data);
+        aligned.read_bits(7);
+        assert_eq!(aligned.align_to_byte(), 1);
+        assert_eq!(aligned.read_u16(), Some(0x1234));
+
+        assert_eq!(bits.read_bytes_aligned(4), None);
+        assert_eq!(bits.position(), 7);
+        assert_eq!(bits.read_bytes_aligned(2), Some(&[0x34, 0x12][..]));
+        assert_eq!(bits.position(), 24);
+        assert_eq!(bits.bits_remaining(), 8);
+        assert_eq!(bits.align_to_byte(), 0);
+        assert_eq!(bits.read_bytes_aligned(1), Some(&[0xff][..]));
+        assert_eq!(bits.read_bytes_aligned(1), None);
+        assert_eq!(bits.align_to_byte(), 0);
+    }
+
+    #[test]
+    fn test_read_string() {
+        let mut data = vec![4, 0, 0, 0];
+        data.extend_from_slice(b"abc\0");
+        data.extend_from_slice(&(-2i32).to_le_bytes());
+        data.extend_from_slice(&[b'a', 0, 0, 0]);
+        let mut bits = BitReader::new(&data);
+        assert_eq!(bits.read_string(), Some(String::from("abc")));
+        assert_eq!(bits.read_string(), Some(String::from("a")));
+        assert_eq!(bits.read_string(), None);
+    }
+
+    #[test]
+    fn test_read_bits_max() {
+        let mut bits = BitReader::new(&[0xff; 8]);
+        assert_eq!(bits.read_bits_max(0, 20), None);
+        assert_eq!(bits.read_bits_max(33, i32::MAX), None);
+        assert_eq!(bits.position(), 0);
+        assert_eq!(bits.read_bits_max(32, i32::MAX), Some(0x7fff_ffff));
+        assert_eq!(bits.read_bits_max(5, 20), Some(0xf));
+    }
+
+    #[test]
+    fn test_if_get() {
+        let mut bits = BitReader::new(&[0b0000_0010, 0b0000_0001]);
+        assert_eq!(bits.if_get(BitReader::read_u8), Some(None));
+        assert_eq!(bits.if_get(BitReader::read_u8), Some(Some(0x40)));
+        assert_eq!(bits.if_get(BitReader::read_u8), Some(None));
+    }
+}
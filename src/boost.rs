@@ -0,0 +1,296 @@
+//! The boost pads of the standard soccar layout, which the standard maps share, and the
+//! resolution of a replay's boost pad actors to them.
+//!
+//! A replay doesn't record where its boost pads are. Pads are actors of the level (eg:
+//! `Stadium_P.TheWorld:PersistentLevel.VehiclePickup_Boost_TA_27`) that spawn without a location,
+//! and the number that ends their name differs between maps. Instead, a pad is resolved to the
+//! standard pad nearest to the cars that pick it up.
+//!
+//! ```
+//! # let data = include_bytes!("../assets/replays/good/rumble.replay");
+//! let replay = boxcars::ParserBuilder::new(&data[..])
+//!     .must_parse_network_data()
+//!     .parse()
+//!     .unwrap();
+//!
+//! let pads = boxcars::boost::BoostPads::from_replay(&replay);
+//! for pickup in pads.pickups() {
+//!     if let Some(pad) = pickup.pad {
+//!         println!("{} picked up pad {} ({:?})", pickup.car, pad.id, pad.size);
+//!     }
+//! }
+//! ```
+
+use crate::models::Replay;
+use crate::network::attributes::{Pickup, PickupNew};
+use crate::network::{ActorId, Attribute, ObjectId, Vector3f};
+use fnv::FnvHashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum PadSize {
+    /// Fills a car's boost
+    Big,
+
+    /// Gives a car 12 boost
+    Small,
+}
+
+/// A boost pad of the standard soccar layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct BoostPad {
+    /// The index of the pad in `STANDARD_PADS`
+    pub id: usize,
+
+    /// The center of the pad in unreal units, where the blue goal is at negative y
+    pub location: Vector3f,
+    pub size: PadSize,
+}
+
+const fn pad(id: usize, x: f32, y: f32, size: PadSize) -> BoostPad {
+    let z = match size {
+        PadSize::Big => 73.0,
+        PadSize::Small => 70.0,
+    };
+
+    BoostPad {
+        id,
+        location: Vector3f { x, y, z },
+        size,
+    }
+}
+
+/// The boost pads shared by the standard soccar maps, ordered from the blue goal to the orange
+/// goal. Maps of other modes (eg: hoops, dropshot) and throwback stadium have other layouts.
+pub static STANDARD_PADS: [BoostPad; 34] = [
+    pad(0, 0.0, -4240.0, PadSize::Small),
+    pad(1, -1792.0, -4184.0, PadSize::Small),
+    pad(2, 1792.0, -4184.0, PadSize::Small),
+    pad(3, -3072.0, -4096.0, PadSize::Big),
+    pad(4, 3072.0, -4096.0, PadSize::Big),
+    pad(5, -940.0, -3308.0, PadSize::Small),
+    pad(6, 940.0, -3308.0, PadSize::Small),
+    pad(7, 0.0, -2816.0, PadSize::Small),
+    pad(8, -3584.0, -2484.0, PadSize::Small),
+    pad(9, 3584.0, -2484.0, PadSize::Small),
+    pad(10, -1788.0, -2300.0, PadSize::Small),
+    pad(11, 1788.0, -2300.0, PadSize::Small),
+    pad(12, -2048.0, -1036.0, PadSize::Small),
+    pad(13, 0.0, -1024.0, PadSize::Small),
+    pad(14, 2048.0, -1036.0, PadSize::Small),
+    pad(15, -3584.0, 0.0, PadSize::Big),
+    pad(16, -1024.0, 0.0, PadSize::Small),
+    pad(17, 1024.0, 0.0, PadSize::Small),
+    pad(18, 3584.0, 0.0, PadSize::Big),
+    pad(19, -2048.0, 1036.0, PadSize::Small),
+    pad(20, 0.0, 1024.0, PadSize::Small),
+    pad(21, 2048.0, 1036.0, PadSize::Small),
+    pad(22, -1788.0, 2300.0, PadSize::Small),
+    pad(23, 1788.0, 2300.0, PadSize::Small),
+    pad(24, -3584.0, 2484.0, PadSize::Small),
+    pad(25, 3584.0, 2484.0, PadSize::Small),
+    pad(26, 0.0, 2816.0, PadSize::Small),
+    pad(27, -940.0, 3308.0, PadSize::Small),
+    pad(28, 940.0, 3308.0, PadSize::Small),
+    pad(29, -3072.0, 4096.0, PadSize::Big),
+    pad(30, 3072.0, 4096.0, PadSize::Big),
+    pad(31, -1792.0, 4184.0, PadSize::Small),
+    pad(32, 1792.0, 4184.0, PadSize::Small),
+    pad(33, 0.0, 4240.0, PadSize::Small),
+];
+
+/// The farthest (along the ground) that a car picking up a pad is considered to be from the
+/// pad's center. It's larger than a pad's pickup radius, as a car's last known location lags
+/// behind where it is.
+const PICKUP_DISTANCE: f32 = 400.0;
+
+/// Returns the standard pad nearest to the location along the ground, if one is near enough to
+/// be picked up by a car at the location
+pub fn standard_pad_near(location: Vector3f) -> Option<&'static BoostPad> {
+    let distance = |pad: &BoostPad| {
+        let dx = pad.location.x - location.x;
+        let dy = pad.location.y - location.y;
+        dx * dx + dy * dy
+    };
+
+    STANDARD_PADS
+        .iter()
+        .map(|pad| (distance(pad), pad))
+        .filter(|(d, _)| *d <= PICKUP_DISTANCE * PICKUP_DISTANCE)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, pad)| pad)
+}
+
+fn is_boost_pad(object: &str) -> bool {
+    object.contains("VehiclePickup_Boost_TA")
+}
+
+/// A car picking up a boost pad
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct BoostPickup {
+    /// The index of the frame of the pickup
+    pub frame: usize,
+    pub time: f32,
+
+    /// The car that picked up the pad
+    pub car: ActorId,
+
+    /// The object of the pad's actor
+    pub object_id: ObjectId,
+
+    /// The standard pad that the pad's actor resolved to
+    pub pad: Option<&'static BoostPad>,
+}
+
+/// A replay's boost pad actors resolved to the standard pads. Each pad is resolved to the
+/// standard pad that the cars picking it up are most often nearest to, so a pad that is never
+/// picked up, or that is on a map without the standard layout, doesn't resolve.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BoostPads {
+    pads: FnvHashMap<ObjectId, &'static BoostPad>,
+    pickups: Vec<BoostPickup>,
+}
+
+impl BoostPads {
+    /// Resolves the boost pads of the replay's network frames, which is empty when the network
+    /// data wasn't decoded
+    pub fn from_replay(replay: &Replay<'_>) -> Self {
+        let frames = match replay.network_frames.as_ref() {
+            Some(network) => &network.frames,
+            None => return BoostPads::default(),
+        };
+
+        let net_version = replay.net_version.unwrap_or(0);
+        let mut pad_actors: FnvHashMap<ActorId, ObjectId> = FnvHashMap::default();
+        let mut locations: FnvHashMap<ActorId, Vector3f> = FnvHashMap::default();
+        let mut votes: FnvHashMap<(ObjectId, usize), usize> = FnvHashMap::default();
+        let mut pickups = Vec::new();
+
+        for (i, frame) in frames.iter().enumerate() {
+            for actor in &frame.new_actors {
+                locations.remove(&actor.actor_id);
+                let object = replay.objects.get(actor.object_id.0 as usize);
+                if object.is_some_and(|x| is_boost_pad(x)) {
+                    pad_actors.insert(actor.actor_id, actor.object_id);
+                } else {
+                    pad_actors.remove(&actor.actor_id);
+                }
+            }
+
+            for update in &frame.updated_actors {
+                let instigator = match update.attribute {
+                    Attribute::RigidBody(ref body) => {
+                        let location = body.location.to_vector3f(net_version);
+                        locations.insert(update.actor_id, location);
+                        continue;
+                    }
+                    Attribute::Pickup(Pickup {
+                        instigator_id: Some(car),
+                        picked_up: true,
+                    }) => car,
+                    Attribute::PickupNew(PickupNew {
+                        instigator_id: Some(car),
+                        ..
+                    }) => car,
+                    _ => continue,
+                };
+
+                let object_id = match pad_actors.get(&update.actor_id) {
+                    Some(object_id) => *object_id,
+                    None => continue,
+                };
+
+                let car = ActorId(instigator as i32);
+                let nearest = locations.get(&car).and_then(|x| standard_pad_near(*x));
+                if let Some(pad) = nearest {
+                    *votes.entry((object_id, pad.id)).or_insert(0) += 1;
+                }
+
+                pickups.push(BoostPickup {
+                    frame: i,
+                    time: frame.time,
+                    car,
+                    object_id,
+                    pad: None,
+                });
+            }
+        }
+
+        let mut best: FnvHashMap<ObjectId, (usize, usize)> = FnvHashMap::default();
+        for ((object_id, id), count) in votes {
+            let entry = best.entry(object_id).or_insert((count, id));
+            if (count, id) > *entry {
+                *entry = (count, id);
+            }
+        }
+
+        let pads: FnvHashMap<_, _> = best
+            .into_iter()
+            .map(|(object_id, (_, id))| (object_id, &STANDARD_PADS[id]))
+            .collect();
+
+        for pickup in &mut pickups {
+            pickup.pad = pads.get(&pickup.object_id).cloned();
+        }
+
+        BoostPads { pads, pickups }
+    }
+
+    /// The standard pad that the pad actor of the given object resolved to
+    pub fn pad(&self, object_id: ObjectId) -> Option<&'static BoostPad> {
+        self.pads.get(&object_id).cloned()
+    }
+
+    /// The objects of the pad actors that resolved, and the pads that they resolved to
+    pub fn pads(&self) -> impl Iterator<Item = (ObjectId, &'static BoostPad)> + '_ {
+        self.pads.iter().map(|(object_id, pad)| (*object_id, *pad))
+    }
+
+    /// Every boost pad pickup, in the order they are replicated
+    pub fn pickups(&self) -> &[BoostPickup] {
+        &self.pickups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParserBuilder;
+
+    #[test]
+    fn test_standard_pad_near() {
+        let near = |x, y| standard_pad_near(Vector3f { x, y, z: 17.0 }).map(|x| x.id);
+        assert_eq!(near(-3000.0, -4000.0), Some(3));
+        assert_eq!(near(10.0, 4200.0), Some(33));
+        assert_eq!(near(0.0, 0.0), None);
+        assert!(STANDARD_PADS.iter().enumerate().all(|(i, x)| x.id == i));
+        let big = STANDARD_PADS.iter().filter(|x| x.size == PadSize::Big);
+        assert_eq!(big.count(), 6);
+    }
+
+    #[test]
+    fn test_boost_pads() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let pads = BoostPads::from_replay(&replay);
+        assert!(!pads.pickups().is_empty());
+        assert!(pads
+            .pickups()
+            .iter()
+            .all(|x| x.pad == pads.pad(x.object_id)));
+
+        // Every pad of the standard map is picked up during the match
+        let mut ids: Vec<_> = pads.pads().map(|(_, pad)| pad.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), STANDARD_PADS.len());
+    }
+}
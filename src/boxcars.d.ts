@@ -0,0 +1,348 @@
+// TypeScript definitions for the JSON serialization of a boxcars replay.
+//
+// 64 bit integers are serialized as strings as they can't be represented by a JavaScript number.
+
+export interface Replay {
+  header_size: number;
+  header_crc: number;
+  major_version: number;
+  minor_version: number;
+  net_version: number | null;
+  game_type: string;
+  properties: { [key: string]: HeaderProp };
+  content_size: number;
+  content_crc: number;
+  network_frames: NetworkFrames | null;
+  levels: string[];
+  keyframes: KeyFrame[];
+  debug_info: DebugInfo[];
+  tick_marks: TickMark[];
+  packages: string[];
+  objects: string[];
+  names: string[];
+  class_indices: ClassIndex[];
+  net_cache: ClassNetCache[];
+}
+
+// Byte properties are serialized as 0 and QWord properties are serialized as strings
+export type HeaderProp =
+  | boolean
+  | number
+  | string
+  | { [key: string]: HeaderProp }[];
+
+export interface NetworkFrames {
+  frames: Frame[];
+}
+
+export interface KeyFrame {
+  time: number;
+  frame: number;
+  position: number;
+}
+
+export interface DebugInfo {
+  frame: number;
+  user: string;
+  text: string;
+}
+
+export interface TickMark {
+  description: string;
+  frame: number;
+}
+
+export interface ClassIndex {
+  class: string;
+  index: number;
+}
+
+export interface CacheProp {
+  object_ind: number;
+  stream_id: number;
+}
+
+export interface ClassNetCache {
+  object_ind: number;
+  parent_id: number;
+  cache_id: number;
+  properties: CacheProp[];
+}
+
+export interface Frame {
+  time: number;
+  delta: number;
+  new_actors: NewActor[];
+  deleted_actors: number[];
+  updated_actors: UpdatedAttribute[];
+}
+
+// The names are only serialized by a denormalized replay (see `Replay::denormalized`)
+export interface NewActor {
+  actor_id: number;
+  name_id: number | null;
+  object_id: number;
+  initial_trajectory: Trajectory;
+  object_name?: string | null;
+  name?: string | null;
+}
+
+export interface Trajectory {
+  location: Vector | null;
+  rotation: Rotation | null;
+}
+
+// The names are only serialized by a denormalized replay (see `Replay::denormalized`)
+export interface UpdatedAttribute {
+  actor_id: number;
+  stream_id: number;
+  attribute: Attribute;
+  object_name?: string | null;
+  attribute_name?: string | null;
+}
+
+export interface Vector {
+  bias: number;
+  dx: number;
+  dy: number;
+  dz: number;
+}
+
+export interface Rotation {
+  yaw: number | null;
+  pitch: number | null;
+  roll: number | null;
+}
+
+export type CompressedRotation =
+  | { Rotator: { pitch: number; yaw: number; roll: number } }
+  | { Quaternion: { largest: number; a: number; b: number; c: number } };
+
+export type Attribute =
+  | { Boolean: boolean }
+  | { Byte: number }
+  | { AppliedDamage: [number, Vector, number, number] }
+  | { DamageState: [number, boolean, number, Vector, boolean, boolean] }
+  | { CamSettings: CamSettings }
+  | { ClubColors: ClubColors }
+  | { Demolish: Demolish }
+  | { DemolishExtended: DemolishExtended }
+  | { Enum: number }
+  | { Explosion: Explosion }
+  | { ExtendedExplosion: [Explosion, boolean, number] }
+  | { FlaggedByte: [boolean, number] }
+  | { Flagged: [boolean, number] }
+  | { Float: number }
+  | { GameMode: [number, number] }
+  | { Int: number }
+  | { Int64: string }
+  | { Loadout: Loadout }
+  | { TeamLoadout: TeamLoadout }
+  | { Location: Vector }
+  | { MusicStinger: MusicStinger }
+  | { PlayerHistoryKey: number }
+  | { Pickup: Pickup }
+  | { PickupNew: PickupNew }
+  | { QWord: string }
+  | { Welded: Welded }
+  | { Title: [boolean, boolean, number, number, number, number, number, boolean] }
+  | { TeamPaint: TeamPaint }
+  | { RigidBody: RigidBody }
+  | { String: string }
+  | { UniqueId: UniqueId }
+  | { Reservation: Reservation }
+  | { PartyLeader: UniqueId | null }
+  | { PrivateMatch: PrivateMatchSettings }
+  | { LoadoutOnline: Product[][] }
+  | { LoadoutsOnline: LoadoutsOnline }
+  | { StatEvent: [boolean, number] }
+  | { ReplicatedBoost: ReplicatedBoost };
+
+export interface CamSettings {
+  fov: number;
+  height: number;
+  angle: number;
+  distance: number;
+  swiftness: number;
+  swivel: number;
+  transition: number | null;
+}
+
+export interface ClubColors {
+  blue_flag: boolean;
+  blue_color: number;
+  orange_flag: boolean;
+  orange_color: number;
+}
+
+export interface Demolish {
+  attacker_flag: boolean;
+  attacker_actor_id: number;
+  victim_flag: boolean;
+  victim_actor_id: number;
+  attack_velocity: Vector;
+  victim_velocity: Vector;
+}
+
+export interface DemolishExtended {
+  attacker_pri_flag: boolean;
+  attacker_pri_actor_id: number;
+  self_demo_flag: boolean;
+  self_demo_actor_id: number;
+  self_demolish: boolean;
+  goal_explosion_owner_flag: boolean;
+  goal_explosion_owner_actor_id: number;
+  attacker_flag: boolean;
+  attacker_actor_id: number;
+  victim_flag: boolean;
+  victim_actor_id: number;
+  attack_velocity: Vector;
+  victim_velocity: Vector;
+}
+
+export interface Explosion {
+  flag: boolean;
+  actor_id: number;
+  location: Vector;
+}
+
+export interface Loadout {
+  version: number;
+  body: number;
+  decal: number;
+  wheels: number;
+  rocket_trail: number;
+  antenna: number;
+  topper: number;
+  unknown1: number;
+  unknown2: number | null;
+  engine_audio: number | null;
+  trail: number | null;
+  goal_explosion: number | null;
+  banner: number | null;
+  unknown3: number | null;
+}
+
+export interface TeamLoadout {
+  blue: Loadout;
+  orange: Loadout;
+}
+
+export interface MusicStinger {
+  flag: boolean;
+  cue: number;
+  trigger: number;
+}
+
+export interface Pickup {
+  instigator_id: number | null;
+  picked_up: boolean;
+}
+
+export interface PickupNew {
+  instigator_id: number | null;
+  picked_up: number;
+}
+
+export interface ReplicatedBoost {
+  grant_count: number;
+  boost_amount: number;
+  component_state: [number, number];
+}
+
+export interface Welded {
+  active: boolean;
+  actor_id: number;
+  offset: Vector;
+  mass: number;
+  rotation: Rotation;
+}
+
+export interface TeamPaint {
+  team: number;
+  primary_color: number;
+  accent_color: number;
+  primary_finish: number;
+  accent_finish: number;
+}
+
+export interface RigidBody {
+  sleeping: boolean;
+  location: Vector;
+  rotation: CompressedRotation;
+  linear_velocity: Vector | null;
+  angular_velocity: Vector | null;
+}
+
+export interface UniqueId {
+  system_id: number;
+  remote_id: RemoteId;
+  local_id: number;
+}
+
+export type RemoteId =
+  | { PlayStation: Ps4Id }
+  | { PsyNet: PsyNetId }
+  | { SplitScreen: number }
+  | { Steam: string }
+  | { Switch: SwitchId }
+  | { Xbox: string };
+
+export interface Ps4Id {
+  online_id: string;
+  name: string;
+  unknown1: number[];
+}
+
+export interface PsyNetId {
+  online_id: string;
+  unknown1: number[];
+}
+
+export interface SwitchId {
+  online_id: string;
+  unknown1: number[];
+}
+
+export interface Reservation {
+  number: number;
+  unique_id: UniqueId;
+  name: string | null;
+  unknown1: boolean;
+  unknown2: boolean;
+  unknown3: number | null;
+}
+
+export interface PrivateMatchSettings {
+  mutators: string;
+  joinable_by: number;
+  max_players: number;
+  game_name: string;
+  password: string;
+  flag: boolean;
+}
+
+export interface Product {
+  unknown: boolean;
+  object_ind: number;
+  value: ProductValue;
+}
+
+export interface LoadoutsOnline {
+  blue: Product[][];
+  orange: Product[][];
+  unknown1: boolean;
+  unknown2: boolean;
+}
+
+export type ProductValue =
+  | "NoColor"
+  | "Absent"
+  | { OldColor: number }
+  | { NewColor: number }
+  | { OldPaint: number }
+  | { NewPaint: number }
+  | { Title: string }
+  | { SpecialEdition: number }
+  | { OldTeamEdition: number }
+  | { NewTeamEdition: number };
@@ -0,0 +1,918 @@
+//! # Building
+//!
+//! Constructs the bytes of a minimal, valid replay from a handful of header properties and frames.
+//! This is the inverse of parsing and is intended for deterministic test fixtures, so that
+//! downstream crates don't need to commit multi-megabyte replays to exercise their code.
+//!
+//! Only the information required to parse the replay is synthesized. Objects are registered in
+//! the order that they are first seen, and each class that is spawned receives a net cache entry
+//! containing exactly the attributes that were updated on its actors.
+
+use crate::crc::calc_crc;
+use crate::errors::BuildError;
+use crate::hashes::{ATTRIBUTES, OBJECT_CLASSES};
+use crate::header::is_legacy_platform;
+use crate::models::*;
+use crate::network::attributes::Attribute;
+use crate::network::{
+    self, attribute_tag, normalize_object, ActorId, Frame, NewActor, ObjectId, StreamId,
+    Trajectory, UpdatedAttribute,
+};
+use crate::version::ReplayVersion;
+use encoding_rs::WINDOWS_1252;
+use failure::Error;
+use fnv::FnvHashMap;
+use std::borrow::Cow;
+
+/// The actors spawned, updated, and deleted within a single frame of a `ReplayBuilder`. Within a
+/// frame, actors are spawned first, then updated, and then deleted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameBuilder<'a> {
+    time: f32,
+    delta: f32,
+    spawns: Vec<(ActorId, &'a str, Trajectory)>,
+    updates: Vec<(ActorId, &'a str, Attribute)>,
+    deletes: Vec<ActorId>,
+}
+
+impl<'a> FrameBuilder<'a> {
+    pub fn new(time: f32, delta: f32) -> Self {
+        FrameBuilder {
+            time,
+            delta,
+            spawns: Vec::new(),
+            updates: Vec::new(),
+            deletes: Vec::new(),
+        }
+    }
+
+    /// Spawns an actor of the given object (eg: "Archetypes.Ball.Ball_Default"). The parts of the
+    /// trajectory that the object doesn't encode on spawn are ignored.
+    pub fn spawn(mut self, actor_id: ActorId, object: &'a str, trajectory: Trajectory) -> Self {
+        self.spawns.push((actor_id, object, trajectory));
+        self
+    }
+
+    /// Updates an attribute (eg: "TAGame.RBActor_TA:ReplicatedRBState") on a spawned actor. The
+    /// attribute must be of the type that the attribute is decoded as.
+    pub fn update(mut self, actor_id: ActorId, attribute: &'a str, value: Attribute) -> Self {
+        self.updates.push((actor_id, attribute, value));
+        self
+    }
+
+    pub fn delete(mut self, actor_id: ActorId) -> Self {
+        self.deletes.push(actor_id);
+        self
+    }
+}
+
+/// Constructs a replay from header properties and frames, and emits the raw bytes that
+/// `ParserBuilder` will parse back.
+///
+/// ```
+/// use boxcars::{ActorId, Attribute, FrameBuilder, ParserBuilder, ReplayBuilder, Trajectory};
+///
+/// let data = ReplayBuilder::new()
+///     .frame(FrameBuilder::new(1.0, 0.03).spawn(
+///         ActorId(0),
+///         "Archetypes.Ball.Ball_Default",
+///         Trajectory { location: None, rotation: None },
+///     ))
+///     .frame(FrameBuilder::new(1.03, 0.03).update(
+///         ActorId(0),
+///         "Engine.Actor:bHidden",
+///         Attribute::Boolean(true),
+///     ))
+///     .build()
+///     .unwrap();
+///
+/// let replay = ParserBuilder::new(&data[..])
+///     .always_check_crc()
+///     .must_parse_network_data()
+///     .parse()
+///     .unwrap();
+/// assert_eq!(replay.network_frames.unwrap().frames.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayBuilder<'a> {
+    major_version: i32,
+    minor_version: i32,
+    net_version: i32,
+    game_type: &'a str,
+    properties: Vec<(&'a str, HeaderProp<'a>)>,
+    levels: Vec<&'a str>,
+    keyframes: Vec<KeyFrame>,
+    tick_marks: Vec<(&'a str, i32)>,
+    packages: Vec<&'a str>,
+    objects: Vec<&'a str>,
+    names: Vec<&'a str>,
+    frames: Vec<FrameBuilder<'a>>,
+}
+
+impl<'a> Default for ReplayBuilder<'a> {
+    fn default() -> Self {
+        ReplayBuilder::new()
+    }
+}
+
+impl<'a> ReplayBuilder<'a> {
+    /// A soccar replay of the most recent version that boxcars understands
+    pub fn new() -> Self {
+        ReplayBuilder {
+            major_version: 868,
+            minor_version: 24,
+            net_version: 10,
+            game_type: "TAGame.Replay_Soccar_TA",
+            properties: Vec::new(),
+            levels: Vec::new(),
+            keyframes: Vec::new(),
+            tick_marks: Vec::new(),
+            packages: Vec::new(),
+            objects: Vec::new(),
+            names: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Sets the version of the replay. The net version is only written for replays that are new
+    /// enough to contain one (a major version above 865 and a minor version above 17).
+    pub fn version(mut self, major: i32, minor: i32, net: i32) -> Self {
+        self.major_version = major;
+        self.minor_version = minor;
+        self.net_version = net;
+        self
+    }
+
+    pub fn game_type(mut self, game_type: &'a str) -> Self {
+        self.game_type = game_type;
+        self
+    }
+
+    /// Adds a header property. If "NumFrames" is not given, it is set to the number of frames.
+    pub fn property(mut self, key: &'a str, value: HeaderProp<'a>) -> Self {
+        self.properties.push((key, value));
+        self
+    }
+
+    pub fn level(mut self, level: &'a str) -> Self {
+        self.levels.push(level);
+        self
+    }
+
+    pub fn keyframe(mut self, keyframe: KeyFrame) -> Self {
+        self.keyframes.push(keyframe);
+        self
+    }
+
+    pub fn tick_mark(mut self, description: &'a str, frame: i32) -> Self {
+        self.tick_marks.push((description, frame));
+        self
+    }
+
+    pub fn package(mut self, package: &'a str) -> Self {
+        self.packages.push(package);
+        self
+    }
+
+    /// Registers an object ahead of the objects seen in the frames. Objects are assigned indices
+    /// in the order that they are registered, starting at zero, which allows attributes that
+    /// reference objects (such as products in an online loadout) to be constructed.
+    pub fn object(mut self, object: &'a str) -> Self {
+        self.objects.push(object);
+        self
+    }
+
+    pub fn name(mut self, name: &'a str) -> Self {
+        self.names.push(name);
+        self
+    }
+
+    pub fn frame(mut self, frame: FrameBuilder<'a>) -> Self {
+        self.frames.push(frame);
+        self
+    }
+
+    fn has_net_version(&self) -> bool {
+        self.major_version > 865 && self.minor_version > 17
+    }
+
+    fn replay_version(&self) -> ReplayVersion {
+        let net_version = if self.has_net_version() {
+            self.net_version
+        } else {
+            0
+        };
+
+        ReplayVersion::new(self.major_version, self.minor_version, net_version)
+    }
+
+    /// Emits the bytes of the replay
+    pub fn build(self) -> Result<Vec<u8>, Error> {
+        let version = self.replay_version();
+        let mut objects = ObjectTable::default();
+        for object in &self.objects {
+            objects.index(object);
+        }
+
+        // Classes with a net cache entry and the attributes (and their stream ids) on each
+        let mut classes: Vec<(ObjectId, Vec<CacheProp>)> = Vec::new();
+        let mut streams: FnvHashMap<(ObjectId, ObjectId), StreamId> = FnvHashMap::default();
+        let mut actors: FnvHashMap<ActorId, usize> = FnvHashMap::default();
+        let mut frames = Vec::with_capacity(self.frames.len());
+
+        for frame in &self.frames {
+            let mut new_actors = network::NewActors::new();
+            for (actor_id, object, trajectory) in &frame.spawns {
+                let object_id = objects.index(object);
+                let class = OBJECT_CLASSES
+                    .get(normalize_object(object))
+                    .cloned()
+                    .unwrap_or(*object);
+                let class_id = objects.index(class);
+                let class_ind = match classes.iter().position(|(id, _)| *id == class_id) {
+                    Some(ind) => ind,
+                    None => {
+                        classes.push((class_id, Vec::new()));
+                        classes.len() - 1
+                    }
+                };
+
+                actors.insert(*actor_id, class_ind);
+                new_actors.push(NewActor {
+                    actor_id: *actor_id,
+                    name_id: if version.at_least(868, 14, 0) {
+                        Some(0)
+                    } else {
+                        None
+                    },
+                    object_id,
+                    initial_trajectory: *trajectory,
+                });
+            }
+
+            let mut updated_actors = network::UpdatedActors::new();
+            for (actor_id, attribute, value) in &frame.updates {
+                let class_ind = *actors
+                    .get(actor_id)
+                    .ok_or(BuildError::UnknownActor(*actor_id))?;
+
+                let expected = ATTRIBUTES
+                    .get(*attribute)
+                    .ok_or_else(|| BuildError::UnknownAttribute(String::from(*attribute)))?;
+                let actual = attribute_tag(value);
+                if *expected != actual {
+                    return Err(BuildError::AttributeMismatch(
+                        String::from(*attribute),
+                        format!("{:?}", expected),
+                        format!("{:?}", actual),
+                    )
+                    .into());
+                }
+
+                // Stream ids are unique across classes so that a class inheriting the attributes
+                // of another class in the replay won't have its stream ids shadowed
+                let attribute_id = objects.index(attribute);
+                let (class_id, props) = &mut classes[class_ind];
+                let next_stream = StreamId(streams.len() as i32 + 1);
+                let stream_id = *streams.entry((*class_id, attribute_id)).or_insert_with(|| {
+                    props.push(CacheProp {
+                        object_ind: attribute_id.0,
+                        stream_id: next_stream.0,
+                    });
+                    next_stream
+                });
+
+                updated_actors.push(UpdatedAttribute {
+                    actor_id: *actor_id,
+                    stream_id,
+                    attribute: value.clone(),
+                });
+            }
+
+            for actor_id in &frame.deletes {
+                actors.remove(actor_id);
+            }
+
+            frames.push(Frame {
+                time: frame.time,
+                delta: frame.delta,
+                new_actors,
+                deleted_actors: frame.deletes.iter().copied().collect(),
+                updated_actors,
+            });
+        }
+
+        let net_cache: Vec<ClassNetCache> = classes
+            .iter()
+            .enumerate()
+            .map(|(i, (class_id, props))| ClassNetCache {
+                object_ind: class_id.0,
+                parent_id: 0,
+                cache_id: i as i32 + 1,
+                properties: props.clone(),
+            })
+            .collect();
+
+        let max_channels = self
+            .properties
+            .iter()
+            .find(|&&(key, _)| key == "MaxChannels")
+            .and_then(|(_, prop)| prop.as_i32());
+
+        let object_names: Vec<Cow<'_, str>> =
+            objects.names.iter().map(|x| Cow::Borrowed(*x)).collect();
+        let network_data =
+            network::encode(version, max_channels, &object_names, &net_cache, &frames)?;
+
+        let mut header = Vec::new();
+        write_i32(&mut header, self.major_version);
+        write_i32(&mut header, self.minor_version);
+        if self.has_net_version() {
+            write_i32(&mut header, self.net_version);
+        }
+        write_text(&mut header, self.game_type);
+
+        let mut properties = self.properties.clone();
+        if !properties.iter().any(|(key, _)| *key == "NumFrames") {
+            properties.push(("NumFrames", HeaderProp::Int(frames.len() as i32)));
+        }
+        write_rdict(&mut header, &properties);
+
+        let mut body = Vec::new();
+        write_list(&mut body, &self.levels, |buf, x| write_text(buf, x));
+        write_list(&mut body, &self.keyframes, |buf, x| {
+            write_f32(buf, x.time);
+            write_i32(buf, x.frame);
+            write_i32(buf, x.position);
+        });
+        write_i32(&mut body, network_data.len() as i32);
+        body.extend_from_slice(&network_data);
+
+        // debug info
+        write_i32(&mut body, 0);
+        write_list(&mut body, &self.tick_marks, |buf, (desc, frame)| {
+            write_text(buf, desc);
+            write_i32(buf, *frame);
+        });
+        write_list(&mut body, &self.packages, |buf, x| write_text(buf, x));
+        write_list(&mut body, &objects.names, |buf, x| write_text(buf, x));
+        write_list(&mut body, &self.names, |buf, x| write_text(buf, x));
+        write_list(&mut body, &classes, |buf, (class_id, _)| {
+            write_str(buf, objects.names[class_id.0 as usize]);
+            write_i32(buf, class_id.0);
+        });
+        write_list(&mut body, &net_cache, |buf, x| {
+            write_i32(buf, x.object_ind);
+            write_i32(buf, x.parent_id);
+            write_i32(buf, x.cache_id);
+            write_list(buf, &x.properties, |buf, prop| {
+                write_i32(buf, prop.object_ind);
+                write_i32(buf, prop.stream_id);
+            });
+        });
+
+        let mut result = Vec::with_capacity(header.len() + body.len() + 16);
+        write_section(&mut result, &header);
+        write_section(&mut result, &body);
+        Ok(result)
+    }
+}
+
+/// The objects of the replay in the order that they were first seen
+#[derive(Debug, Default)]
+struct ObjectTable<'a> {
+    names: Vec<&'a str>,
+    indices: FnvHashMap<&'a str, ObjectId>,
+}
+
+impl<'a> ObjectTable<'a> {
+    fn index(&mut self, name: &'a str) -> ObjectId {
+        let names = &mut self.names;
+        *self.indices.entry(name).or_insert_with(|| {
+            names.push(name);
+            ObjectId(names.len() as i32 - 1)
+        })
+    }
+}
+
+fn write_i32(buf: &mut Vec<u8>, value: i32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_f32(buf: &mut Vec<u8>, value: f32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Writes a section prefixed by its size and crc
+fn write_section(buf: &mut Vec<u8>, data: &[u8]) {
+    write_i32(buf, data.len() as i32);
+    write_i32(buf, calc_crc(data) as i32);
+    buf.extend_from_slice(data);
+}
+
+fn write_list<T, F>(buf: &mut Vec<u8>, items: &[T], mut f: F)
+where
+    F: FnMut(&mut Vec<u8>, &T),
+{
+    write_i32(buf, items.len() as i32);
+    for item in items {
+        f(buf, item);
+    }
+}
+
+/// Writes a null terminated UTF-8 string
+fn write_str(buf: &mut Vec<u8>, text: &str) {
+    write_i32(buf, text.len() as i32 + 1);
+    buf.extend_from_slice(text.as_bytes());
+    buf.push(0);
+}
+
+/// Writes a null terminated string as Windows-1252 when possible, else UTF-16
+fn write_text(buf: &mut Vec<u8>, text: &str) {
+    let (data, _, had_errors) = WINDOWS_1252.encode(text);
+    if !had_errors {
+        write_i32(buf, data.len() as i32 + 1);
+        buf.extend_from_slice(&data);
+        buf.push(0);
+    } else {
+        let units: Vec<u16> = text.encode_utf16().collect();
+        write_i32(buf, -(units.len() as i32 + 1));
+        for unit in units {
+            buf.extend_from_slice(&unit.to_le_bytes());
+        }
+        buf.extend_from_slice(&[0, 0]);
+    }
+}
+
+fn write_rdict(buf: &mut Vec<u8>, properties: &[(&str, HeaderProp<'_>)]) {
+    for (key, prop) in properties {
+        write_str(buf, key);
+
+        let mut value = Vec::new();
+        let kind = match prop {
+            HeaderProp::Array(arr) => {
+                write_i32(&mut value, arr.len() as i32);
+                for dict in arr {
+                    write_rdict(&mut value, dict);
+                }
+                "ArrayProperty"
+            }
+            HeaderProp::Bool(x) => {
+                value.push(*x as u8);
+                "BoolProperty"
+            }
+            HeaderProp::Byte { kind, value: val } => {
+                write_str(&mut value, kind);
+                if !is_legacy_platform(kind) {
+                    write_str(&mut value, val.unwrap_or(""));
+                }
+                "ByteProperty"
+            }
+            HeaderProp::Float(x) => {
+                write_f32(&mut value, *x);
+                "FloatProperty"
+            }
+            HeaderProp::Int(x) => {
+                write_i32(&mut value, *x);
+                "IntProperty"
+            }
+            HeaderProp::Name(x) => {
+                write_text(&mut value, x);
+                "NameProperty"
+            }
+            HeaderProp::QWord(x) => {
+                value.extend_from_slice(&x.to_le_bytes());
+                "QWordProperty"
+            }
+            HeaderProp::Str(x) => {
+                write_text(&mut value, x);
+                "StrProperty"
+            }
+        };
+
+        // The 64 bits preceding a property's value are discarded by the parser, but the original
+        // replays store the size of the value in the first 32 bits
+        write_str(buf, kind);
+        write_i32(buf, value.len() as i32);
+        write_i32(buf, 0);
+        buf.extend_from_slice(&value);
+    }
+
+    write_str(buf, "None");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::attributes::{RemoteId, RigidBody, UniqueId};
+    use crate::network::{CompressedRotation, Rotation, Vector};
+    use crate::ParserBuilder;
+
+    fn vector(x: i32, y: i32, z: i32) -> Vector {
+        let bias = 1 << 14;
+        Vector {
+            bias,
+            dx: x + bias,
+            dy: y + bias,
+            dz: z + bias,
+        }
+    }
+
+    fn parse(data: &[u8]) -> Replay<'_> {
+        ParserBuilder::new(data)
+            .always_check_crc()
+            .must_parse_network_data()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_build_empty_replay() {
+        let data = ReplayBuilder::new()
+            .property("TeamSize", HeaderProp::Int(3))
+            .build()
+            .unwrap();
+        let replay = parse(&data[..]);
+        assert_eq!(replay.major_version, 868);
+        assert_eq!(replay.minor_version, 24);
+        assert_eq!(replay.net_version, Some(10));
+        assert_eq!(replay.game_type, "TAGame.Replay_Soccar_TA");
+        assert_eq!(
+            replay.properties,
+            vec![
+                ("TeamSize", HeaderProp::Int(3)),
+                ("NumFrames", HeaderProp::Int(0))
+            ]
+        );
+        assert_eq!(replay.network_frames.unwrap().frames, Vec::new());
+    }
+
+    #[test]
+    fn test_build_header() {
+        let data = ReplayBuilder::new()
+            .version(868, 17, 0)
+            .game_type("TAGame.Replay_Soccar_TA")
+            .property("PlayerName", HeaderProp::Str(Cow::Borrowed("comagoosie")))
+            .property("Id", HeaderProp::Str(Cow::Borrowed("日本語")))
+            .property("MapName", HeaderProp::Name(Cow::Borrowed("stadium_p")))
+            .property("RecordFPS", HeaderProp::Float(30.0))
+            .property("bUnfairBots", HeaderProp::Bool(true))
+            .property("OnlineID", HeaderProp::QWord(76561198101748375))
+            .property(
+                "Platform",
+                HeaderProp::Byte {
+                    kind: "OnlinePlatform",
+                    value: Some("OnlinePlatform_Steam"),
+                },
+            )
+            .property(
+                "Goals",
+                HeaderProp::Array(vec![vec![
+                    ("PlayerName", HeaderProp::Str(Cow::Borrowed("Cakeboss"))),
+                    ("PlayerTeam", HeaderProp::Int(1)),
+                ]]),
+            )
+            .property("NumFrames", HeaderProp::Int(0))
+            .level("stadium_p")
+            .keyframe(KeyFrame {
+                time: 0.5,
+                frame: 10,
+                position: 20,
+            })
+            .tick_mark("Team1Goal", 396)
+            .package("ProjectX")
+            .name("None")
+            .build()
+            .unwrap();
+
+        let replay = parse(&data[..]);
+        assert_eq!(replay.minor_version, 17);
+        assert_eq!(replay.net_version, None);
+        assert_eq!(replay.properties.len(), 9);
+        assert_eq!(
+            replay.properties[1],
+            ("Id", HeaderProp::Str(Cow::Borrowed("日本語")))
+        );
+        assert_eq!(replay.properties[8], ("NumFrames", HeaderProp::Int(0)));
+        assert_eq!(replay.levels, vec!["stadium_p"]);
+        assert_eq!(
+            replay.keyframes,
+            vec![KeyFrame {
+                time: 0.5,
+                frame: 10,
+                position: 20,
+            }]
+        );
+        assert_eq!(
+            replay.tick_marks,
+            vec![TickMark {
+                description: Cow::Borrowed("Team1Goal"),
+                frame: 396,
+            }]
+        );
+        assert_eq!(replay.packages, vec!["ProjectX"]);
+        assert_eq!(replay.names, vec!["None"]);
+    }
+
+    #[test]
+    fn test_build_frames_round_trip() {
+        let rigid_body = RigidBody {
+            sleeping: false,
+            location: vector(100, -200, 93),
+            rotation: CompressedRotation::Quaternion {
+                largest: 3,
+                a: 10,
+                b: 20,
+                c: 30,
+            },
+            linear_velocity: Some(vector(0, 1, -1)),
+            angular_velocity: Some(vector(5, 5, 5)),
+        };
+
+        let unique_id = UniqueId {
+            system_id: 1,
+            remote_id: RemoteId::Steam(76561198101748375),
+            local_id: 0,
+        };
+
+        let ball_trajectory = Trajectory {
+            location: Some(vector(0, 0, 93)),
+            rotation: Some(Rotation {
+                yaw: Some(1),
+                pitch: None,
+                roll: Some(-1),
+            }),
+        };
+
+        let pri_trajectory = Trajectory {
+            location: Some(vector(0, 0, 0)),
+            rotation: None,
+        };
+
+        let data = ReplayBuilder::new()
+            .frame(
+                FrameBuilder::new(1.0, 0.03)
+                    .spawn(ActorId(0), "Archetypes.Ball.Ball_Default", ball_trajectory)
+                    .spawn(ActorId(5), "TAGame.Default__PRI_TA", pri_trajectory)
+                    .update(
+                        ActorId(5),
+                        "Engine.PlayerReplicationInfo:PlayerName",
+                        Attribute::String("comagoosie".into()),
+                    )
+                    .update(
+                        ActorId(5),
+                        "Engine.PlayerReplicationInfo:UniqueId",
+                        Attribute::UniqueId(unique_id.clone()),
+                    ),
+            )
+            .frame(
+                FrameBuilder::new(1.03, 0.03)
+                    .update(
+                        ActorId(0),
+                        "TAGame.RBActor_TA:ReplicatedRBState",
+                        Attribute::RigidBody(rigid_body),
+                    )
+                    .update(ActorId(0), "Engine.Actor:bHidden", Attribute::Boolean(true)),
+            )
+            .frame(FrameBuilder::new(1.06, 0.03).delete(ActorId(0)))
+            .build()
+            .unwrap();
+
+        let replay = parse(&data[..]);
+        assert_eq!(replay.properties, vec![("NumFrames", HeaderProp::Int(3))]);
+
+        let frames = replay.network_frames.unwrap().frames;
+        assert_eq!(frames.len(), 3);
+
+        assert_eq!(frames[0].time, 1.0);
+        assert_eq!(frames[0].delta, 0.03);
+        assert_eq!(frames[0].new_actors.len(), 2);
+        assert_eq!(frames[0].new_actors[0].actor_id, ActorId(0));
+        assert_eq!(frames[0].new_actors[0].initial_trajectory, ball_trajectory);
+        assert_eq!(frames[0].new_actors[1].actor_id, ActorId(5));
+        assert_eq!(frames[0].new_actors[1].initial_trajectory, pri_trajectory);
+        assert_eq!(
+            frames[0]
+                .updated_actors
+                .iter()
+                .map(|x| x.attribute.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                Attribute::String("comagoosie".into()),
+                Attribute::UniqueId(unique_id),
+            ]
+        );
+
+        assert_eq!(
+            frames[1]
+                .updated_actors
+                .iter()
+                .map(|x| (x.actor_id, x.attribute.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (ActorId(0), Attribute::RigidBody(rigid_body)),
+                (ActorId(0), Attribute::Boolean(true)),
+            ]
+        );
+
+        assert_eq!(frames[2].deleted_actors.as_slice(), &[ActorId(0)]);
+        assert!(frames[2].new_actors.is_empty());
+        assert!(frames[2].updated_actors.is_empty());
+    }
+
+    #[test]
+    fn test_build_is_deterministic() {
+        let build = || {
+            ReplayBuilder::new()
+                .frame(
+                    FrameBuilder::new(1.0, 0.03)
+                        .spawn(
+                            ActorId(0),
+                            "Archetypes.Ball.Ball_Default",
+                            Trajectory {
+                                location: None,
+                                rotation: None,
+                            },
+                        )
+                        .update(ActorId(0), "Engine.Actor:bHidden", Attribute::Boolean(true))
+                        .update(
+                            ActorId(0),
+                            "Engine.Actor:bBlockActors",
+                            Attribute::Boolean(false),
+                        ),
+                )
+                .build()
+                .unwrap()
+        };
+
+        assert_eq!(build(), build());
+    }
+
+    #[test]
+    fn test_build_update_unknown_actor() {
+        let err = ReplayBuilder::new()
+            .frame(FrameBuilder::new(1.0, 0.03).update(
+                ActorId(1),
+                "Engine.Actor:bHidden",
+                Attribute::Boolean(true),
+            ))
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<BuildError>().unwrap(),
+            BuildError::UnknownActor(ActorId(1))
+        );
+    }
+
+    #[test]
+    fn test_build_update_deleted_actor() {
+        let err = ReplayBuilder::new()
+            .frame(
+                FrameBuilder::new(1.0, 0.03)
+                    .spawn(
+                        ActorId(0),
+                        "Archetypes.Ball.Ball_Default",
+                        Trajectory {
+                            location: None,
+                            rotation: None,
+                        },
+                    )
+                    .delete(ActorId(0)),
+            )
+            .frame(FrameBuilder::new(1.03, 0.03).update(
+                ActorId(0),
+                "Engine.Actor:bHidden",
+                Attribute::Boolean(true),
+            ))
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<BuildError>().unwrap(),
+            BuildError::UnknownActor(ActorId(0))
+        );
+    }
+
+    #[test]
+    fn test_build_attribute_mismatch() {
+        let err = ReplayBuilder::new()
+            .frame(
+                FrameBuilder::new(1.0, 0.03)
+                    .spawn(
+                        ActorId(0),
+                        "Archetypes.Ball.Ball_Default",
+                        Trajectory {
+                            location: None,
+                            rotation: None,
+                        },
+                    )
+                    .update(ActorId(0), "Engine.Actor:bHidden", Attribute::Int(1)),
+            )
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<BuildError>().unwrap(),
+            BuildError::AttributeMismatch(
+                String::from("Engine.Actor:bHidden"),
+                String::from("Boolean"),
+                String::from("Int")
+            )
+        );
+    }
+
+    #[test]
+    fn test_build_unknown_attribute() {
+        let err = ReplayBuilder::new()
+            .frame(
+                FrameBuilder::new(1.0, 0.03)
+                    .spawn(
+                        ActorId(0),
+                        "Archetypes.Ball.Ball_Default",
+                        Trajectory {
+                            location: None,
+                            rotation: None,
+                        },
+                    )
+                    .update(ActorId(0), "Engine.Actor:bUnknown", Attribute::Int(1)),
+            )
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<BuildError>().unwrap(),
+            BuildError::UnknownAttribute(String::from("Engine.Actor:bUnknown"))
+        );
+    }
+
+    #[test]
+    fn test_build_actor_out_of_range() {
+        let err = ReplayBuilder::new()
+            .property("MaxChannels", HeaderProp::Int(8))
+            .frame(FrameBuilder::new(1.0, 0.03).spawn(
+                ActorId(8),
+                "Archetypes.Ball.Ball_Default",
+                Trajectory {
+                    location: None,
+                    rotation: None,
+                },
+            ))
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<BuildError>().unwrap(),
+            BuildError::ActorIdOutOfRange(ActorId(8), 8)
+        );
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_build_arbitrary_attributes_round_trip() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        // Deterministic pseudo random data (xorshift) for generating attributes
+        let mut seed = 0x2545_F491_4F6C_DD1Du64;
+        let data: Vec<u8> = (0..1 << 16)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                seed as u8
+            })
+            .collect();
+
+        let mut u = Unstructured::new(&data);
+        let mut round_trips = 0;
+        for _ in 0..500 {
+            let attr = Attribute::arbitrary(&mut u).unwrap();
+            let tag = attribute_tag(&attr);
+            let name = match ATTRIBUTES.entries().find(|(_, x)| **x == tag) {
+                Some((name, _)) => *name,
+                None => continue,
+            };
+
+            let frame = FrameBuilder::new(1.0, 0.03)
+                .spawn(
+                    ActorId(0),
+                    "Archetypes.Ball.Ball_Default",
+                    Trajectory {
+                        location: None,
+                        rotation: None,
+                    },
+                )
+                .update(ActorId(0), name, attr.clone());
+
+            // Not every generated attribute can be encoded for this replay's version
+            if let Ok(data) = ReplayBuilder::new().frame(frame).build() {
+                let replay = parse(&data[..]);
+                let frames = replay.network_frames.unwrap().frames;
+
+                // Compare debug output to treat NaN floats as equal
+                assert_eq!(
+                    format!("{:?}", frames[0].updated_actors[0].attribute),
+                    format!("{:?}", attr)
+                );
+                round_trips += 1;
+            }
+        }
+
+        assert!(round_trips > 100);
+    }
+}
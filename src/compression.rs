@@ -0,0 +1,286 @@
+//! Detects replays that are stored compressed (eg: `.replay.gz`), which is common in replay
+//! archives, and with the `gzip` feature decompresses them so that they can be parsed without a
+//! separate decompression step.
+//!
+//! A replay borrows from the data that it is parsed from, so a compressed replay is decompressed
+//! into a buffer that the replay then borrows. `read` decompresses as it reads, so the compressed
+//! file is never held in memory alongside the decompressed replay.
+//!
+//! ```
+//! # #[cfg(feature = "gzip")]
+//! # fn main() -> Result<(), failure::Error> {
+//! let file = std::fs::File::open("assets/replays/compressed/small-frames.replay.gz")?;
+//! let data = boxcars::compression::read(file)?;
+//! let replay = boxcars::ParserBuilder::new(&data).parse()?;
+//! assert_eq!(replay.game_type, "TAGame.Replay_Soccar_TA");
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "gzip"))]
+//! # fn main() {}
+//! ```
+
+#[cfg(feature = "gzip")]
+pub use crate::errors::DecompressError;
+#[cfg(feature = "gzip")]
+use miniz_oxide::inflate::stream::{inflate, InflateState};
+#[cfg(feature = "gzip")]
+use miniz_oxide::{DataFormat, MZError, MZFlush, MZStatus};
+#[cfg(feature = "gzip")]
+use std::borrow::Cow;
+#[cfg(feature = "gzip")]
+use std::io::{BufRead, BufReader, Cursor, Read};
+
+/// How a replay's data is compressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Compression {
+    Gzip,
+
+    /// Detected so that the error is clear, but not supported yet as there isn't a zstd decoder
+    /// among the dependencies
+    Zstd,
+}
+
+impl Compression {
+    /// Detects the compression of the data from its magic bytes. Uncompressed replays (and data
+    /// that isn't compressed with a known format) result in `None`.
+    ///
+    /// ```
+    /// use boxcars::compression::Compression;
+    ///
+    /// assert_eq!(Compression::detect(&[0x1f, 0x8b, 0x08, 0x00]), Some(Compression::Gzip));
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// assert_eq!(Compression::detect(&data[..]), None);
+    /// ```
+    pub fn detect(data: &[u8]) -> Option<Compression> {
+        if data.starts_with(&GZIP_MAGIC) {
+            Some(Compression::Gzip)
+        } else if data.starts_with(&ZSTD_MAGIC) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// The most bytes that a replay decompresses to. Replays are rarely larger than 10MB, so larger
+/// data is assumed to be a decompression bomb.
+#[cfg(feature = "gzip")]
+pub const MAX_DECOMPRESSED_SIZE: usize = 256 * 1024 * 1024;
+
+/// Decompresses the data if it is compressed, else the data is returned as is
+///
+/// ```
+/// # let data = include_bytes!("../assets/replays/good/small-frames.replay");
+/// # let compressed = include_bytes!("../assets/replays/compressed/small-frames.replay.gz");
+/// let decompressed = boxcars::compression::decompress(&compressed[..]).unwrap();
+/// assert_eq!(&decompressed[..], &data[..]);
+/// ```
+#[cfg(feature = "gzip")]
+pub fn decompress(data: &[u8]) -> Result<Cow<'_, [u8]>, DecompressError> {
+    match Compression::detect(data) {
+        None => Ok(Cow::Borrowed(data)),
+        Some(Compression::Gzip) => gunzip(data).map(Cow::Owned),
+        Some(x) => Err(DecompressError::Unsupported(x)),
+    }
+}
+
+/// Reads the replay from the reader, decompressing it as it is read if it is compressed
+#[cfg(feature = "gzip")]
+pub fn read<R: Read>(mut reader: R) -> Result<Vec<u8>, DecompressError> {
+    let mut magic = Vec::with_capacity(ZSTD_MAGIC.len());
+    (&mut reader)
+        .take(ZSTD_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+
+    let mut reader = Cursor::new(magic).chain(reader);
+    let compression = Compression::detect(reader.get_ref().0.get_ref());
+    match compression {
+        None => {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            Ok(data)
+        }
+        Some(Compression::Gzip) => gunzip(reader),
+        Some(x) => Err(DecompressError::Unsupported(x)),
+    }
+}
+
+// The flags of a gzip member's header
+#[cfg(feature = "gzip")]
+const FHCRC: u8 = 0x02;
+#[cfg(feature = "gzip")]
+const FEXTRA: u8 = 0x04;
+#[cfg(feature = "gzip")]
+const FNAME: u8 = 0x08;
+#[cfg(feature = "gzip")]
+const FCOMMENT: u8 = 0x10;
+
+/// Decompresses the first member of gzip data (RFC 1952) and verifies its trailer
+#[cfg(feature = "gzip")]
+fn gunzip<R: Read>(reader: R) -> Result<Vec<u8>, DecompressError> {
+    let mut reader = BufReader::new(reader);
+    let header = read_array::<_, 10>(&mut reader)?;
+    if header[..2] != GZIP_MAGIC {
+        return Err(DecompressError::InvalidGzip("magic bytes"));
+    } else if header[2] != 8 {
+        return Err(DecompressError::InvalidGzip("compression method"));
+    }
+
+    let flags = header[3];
+    if flags & FEXTRA != 0 {
+        let len = u16::from_le_bytes(read_array(&mut reader)?);
+        let skipped = std::io::copy(
+            &mut (&mut reader).take(u64::from(len)),
+            &mut std::io::sink(),
+        )?;
+        if skipped != u64::from(len) {
+            return Err(DecompressError::InvalidGzip("header"));
+        }
+    }
+
+    for flag in &[FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let mut text = Vec::new();
+            reader.read_until(0, &mut text)?;
+            if text.last() != Some(&0) {
+                return Err(DecompressError::InvalidGzip("header"));
+            }
+        }
+    }
+
+    if flags & FHCRC != 0 {
+        read_array::<_, 2>(&mut reader)?;
+    }
+
+    let mut state = InflateState::new_boxed(DataFormat::Raw);
+    let mut chunk = vec![0; 64 * 1024];
+    let mut data = Vec::new();
+    loop {
+        let input = reader.fill_buf()?;
+        if input.is_empty() {
+            return Err(DecompressError::InvalidGzip("truncated data"));
+        }
+
+        let result = inflate(&mut state, input, &mut chunk, MZFlush::None);
+        reader.consume(result.bytes_consumed);
+        if data.len() + result.bytes_written > MAX_DECOMPRESSED_SIZE {
+            return Err(DecompressError::TooLarge(MAX_DECOMPRESSED_SIZE));
+        }
+        data.extend_from_slice(&chunk[..result.bytes_written]);
+
+        match result.status {
+            Ok(MZStatus::StreamEnd) => break,
+            Ok(_) => {}
+
+            // The inflater needs more input, which is fine as long as it's making progress
+            Err(MZError::Buf) if result.bytes_consumed + result.bytes_written > 0 => {}
+            Err(_) => return Err(DecompressError::InvalidGzip("deflate data")),
+        }
+    }
+
+    let trailer = read_array::<_, 8>(&mut reader)?;
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+    if crc != crc32(&data) {
+        return Err(DecompressError::InvalidGzip("crc"));
+    } else if size != data.len() as u32 {
+        return Err(DecompressError::InvalidGzip("size"));
+    }
+
+    Ok(data)
+}
+
+#[cfg(feature = "gzip")]
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> Result<[u8; N], DecompressError> {
+    let mut result = [0; N];
+    reader
+        .read_exact(&mut result)
+        .map_err(|_| DecompressError::InvalidGzip("truncated data"))?;
+    Ok(result)
+}
+
+/// The reflected crc-32 of gzip, which unlike the crc of replays (see `crc::calc_crc`) is the
+/// common one
+#[cfg(feature = "gzip")]
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |acc, &x| {
+        (acc >> 8) ^ CRC32_TABLE[((acc as u8) ^ x) as usize]
+    })
+}
+
+#[cfg(feature = "gzip")]
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+#[cfg(feature = "gzip")]
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut x = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            x = if x & 1 != 0 {
+                (x >> 1) ^ 0xedb8_8320
+            } else {
+                x >> 1
+            };
+            bit += 1;
+        }
+        table[i] = x;
+        i += 1;
+    }
+    table
+}
+
+#[cfg(all(test, feature = "gzip"))]
+mod tests {
+    use super::*;
+
+    const DATA: &[u8] = include_bytes!("../assets/replays/good/small-frames.replay");
+    const COMPRESSED: &[u8] = include_bytes!("../assets/replays/compressed/small-frames.replay.gz");
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn test_decompress() {
+        assert_eq!(Compression::detect(COMPRESSED), Some(Compression::Gzip));
+        assert_eq!(&decompress(COMPRESSED).unwrap()[..], DATA);
+        assert!(matches!(decompress(DATA).unwrap(), Cow::Borrowed(_)));
+        assert_eq!(read(COMPRESSED).unwrap(), DATA);
+        assert_eq!(read(DATA).unwrap(), DATA);
+        assert_eq!(read(&[][..]).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_decompress_corrupt() {
+        let truncated = &COMPRESSED[..COMPRESSED.len() / 2];
+        assert!(matches!(
+            decompress(truncated),
+            Err(DecompressError::InvalidGzip(_))
+        ));
+
+        let mut corrupt = COMPRESSED.to_vec();
+        let crc = corrupt.len() - 8;
+        corrupt[crc] ^= 0xff;
+        assert!(matches!(
+            decompress(&corrupt),
+            Err(DecompressError::InvalidGzip("crc"))
+        ));
+    }
+
+    #[test]
+    fn test_decompress_zstd() {
+        let data = [0x28, 0xb5, 0x2f, 0xfd, 0x00];
+        assert!(matches!(
+            decompress(&data),
+            Err(DecompressError::Unsupported(Compression::Zstd))
+        ));
+    }
+}
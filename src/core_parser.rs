@@ -1,4 +1,6 @@
-use crate::errors::ParseError;
+use crate::errors::{LimitExceeded, LimitKind, ParseError};
+use crate::limits::Limits;
+use crate::models::{RawString, TextEncoding};
 use crate::parsing_utils::{decode_str, decode_utf16, decode_windows1252, le_i32};
 use std::borrow::Cow;
 
@@ -8,17 +10,33 @@ pub struct CoreParser<'a> {
 
     /// Current offset in regards to the whole view of the replay
     col: i32,
+
+    /// Bounds the size of strings and lists
+    pub limits: Limits,
+
+    /// Collects the bytes of each string parsed when set
+    pub raw_strings: Option<Vec<RawString<'a>>>,
 }
 
 impl<'a> CoreParser<'a> {
     pub fn new(data: &'a [u8]) -> Self {
-        CoreParser { data, col: 0 }
+        CoreParser {
+            data,
+            col: 0,
+            limits: Limits::default(),
+            raw_strings: None,
+        }
     }
 
     pub fn bytes_read(&self) -> i32 {
         self.col
     }
 
+    /// The data that has yet to be read
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
     /// Used for skipping some amount of data
     pub fn advance(&mut self, ind: usize) {
         self.col += ind as i32;
@@ -58,16 +76,23 @@ impl<'a> CoreParser<'a> {
         Ok(res)
     }
 
-    /// Repeatedly parse the same elements from replay until `size` elements parsed
-    pub fn repeat<F, T>(size: usize, mut f: F) -> Result<Vec<T>, ParseError>
+    /// Repeatedly parse the same elements from replay until `size` elements parsed, as long as
+    /// `size` is within the list limit
+    pub fn repeat<F, T, E>(size: usize, limits: &Limits, mut f: F) -> Result<Vec<T>, E>
     where
-        F: FnMut() -> Result<T, ParseError>,
+        F: FnMut() -> Result<T, E>,
+        E: From<ParseError>,
     {
-        if size > 25_000 {
-            return Err(ParseError::ListTooLarge(size));
+        if size > limits.max_list_len {
+            return Err(E::from(ParseError::LimitExceeded(LimitExceeded {
+                kind: LimitKind::ListLength,
+                limit: limits.max_list_len,
+                requested: size,
+            })));
         }
 
-        let mut res = Vec::with_capacity(size);
+        // A relaxed limit shouldn't let a corrupt size allocate everything up front
+        let mut res = Vec::with_capacity(size.min(Limits::default().max_list_len));
         for _ in 0..size {
             res.push(f()?);
         }
@@ -79,7 +104,8 @@ impl<'a> CoreParser<'a> {
         F: FnMut(&mut Self) -> Result<T, ParseError>,
     {
         let size = self.take(4, le_i32)?;
-        CoreParser::repeat(size as usize, || f(self))
+        let limits = self.limits;
+        CoreParser::repeat(size as usize, &limits, || f(self))
     }
 
     pub fn text_list(&mut self) -> Result<Vec<Cow<'a, str>>, ParseError> {
@@ -97,7 +123,7 @@ impl<'a> CoreParser<'a> {
         if size == 0x5_000_000 {
             size = 8;
         }
-        self.take_res(size, decode_str)
+        self.take_text(size, TextEncoding::Utf8, decode_str)
     }
 
     /// Parses either UTF-16 or Windows-1252 encoded strings
@@ -106,21 +132,53 @@ impl<'a> CoreParser<'a> {
         // else the string is windows 1252 encoded.
         let characters = self.take(4, le_i32)?;
 
-        // size.abs() will panic at min_value, so we eschew it for manual checking
+        // size.abs() will panic at min_value, so the unsigned magnitude is checked instead
         if characters == 0 {
             Err(ParseError::ZeroSize)
-        } else if characters > 10_000 || characters < -10_000 {
-            Err(ParseError::TextTooLarge(characters))
+        } else if characters.unsigned_abs() as usize > self.limits.max_string_len {
+            Err(ParseError::LimitExceeded(LimitExceeded {
+                kind: LimitKind::StringLength,
+                limit: self.limits.max_string_len,
+                requested: characters.unsigned_abs() as usize,
+            }))
         } else if characters < 0 {
             // We're dealing with UTF-16 and each character is two bytes, we
             // multiply the size by 2. The last two bytes included in the count are
             // null terminators
-            let size = characters * -2;
-            self.take_res(size as usize, |d| decode_utf16(d))
+            let size = characters.unsigned_abs() as usize * 2;
+            self.take_text(size, TextEncoding::Utf16, decode_utf16)
         } else {
-            self.take_res(characters as usize, |d| decode_windows1252(d))
+            self.take_text(
+                characters as usize,
+                TextEncoding::Windows1252,
+                decode_windows1252,
+            )
         }
     }
+
+    /// Decodes the string that follows its length prefix, and records its bytes if raw strings
+    /// are collected
+    fn take_text<F, T>(
+        &mut self,
+        size: usize,
+        encoding: TextEncoding,
+        f: F,
+    ) -> Result<T, ParseError>
+    where
+        F: FnMut(&'a [u8]) -> Result<T, ParseError>,
+    {
+        let offset = self.col - 4;
+        let bytes = self.view_data(size)?;
+        let res = self.take_res(size, f)?;
+        if let Some(raw) = self.raw_strings.as_mut() {
+            raw.push(RawString {
+                offset,
+                encoding,
+                bytes,
+            });
+        }
+        Ok(res)
+    }
 }
 
 #[cfg(test)]
@@ -193,4 +251,68 @@ mod tests {
         let res = parser.parse_text().unwrap();
         assert_eq!(res, "�\u{0}");
     }
+
+    #[test]
+    fn test_raw_strings() {
+        let data = include_bytes!("../assets/replays/partial/utf-16-text.replay");
+        let mut parser = CoreParser::new(&data[..]);
+        parser.raw_strings = Some(Vec::new());
+        let text = parser.parse_text().unwrap();
+
+        let invalid = [0xfd, 0xff, 0xff, 0xff, 0xd8, 0xd8, 0x00, 0x00, 0x00, 0x00];
+        let mut bad = CoreParser::new(&invalid[..]);
+        bad.raw_strings = Some(Vec::new());
+        bad.parse_text().unwrap();
+
+        let raw = parser.raw_strings.unwrap();
+        assert_eq!(raw.len(), 1);
+        assert_eq!(raw[0].offset, 0);
+        assert_eq!(raw[0].encoding, TextEncoding::Utf16);
+        assert_eq!(raw[0].bytes, &data[4..]);
+        assert_eq!(raw[0].text(), text);
+        assert!(raw[0].is_lossless());
+
+        // The unpaired surrogate is replaced when decoded, so its bytes are needed to write it
+        let raw = bad.raw_strings.unwrap();
+        assert_eq!(raw[0].bytes, &invalid[4..]);
+        assert!(!raw[0].is_lossless());
+
+        // Strings that fail to parse aren't retained
+        let mut parser = CoreParser::new(&[0, 0, 0, 0, 0]);
+        parser.raw_strings = Some(Vec::new());
+        assert!(parser.parse_str().is_err());
+        assert_eq!(parser.raw_strings, Some(Vec::new()));
+    }
+
+    #[test]
+    fn parse_utf16_surrogate_pairs() {
+        // "\u{1f680}x" is a surrogate pair followed by an ascii character
+        let data = [
+            0xfc, 0xff, 0xff, 0xff, 0x3d, 0xd8, 0x80, 0xde, 0x78, 0x00, 0x00, 0x00,
+        ];
+        let mut parser = CoreParser::new(&data[..]);
+        assert_eq!(parser.parse_text().unwrap(), "\u{1f680}x");
+
+        // A missing null terminator keeps the last character
+        let data = [0xfe, 0xff, 0xff, 0xff, 0x3d, 0xd8, 0x80, 0xde];
+        let mut parser = CoreParser::new(&data[..]);
+        assert_eq!(parser.parse_text().unwrap(), "\u{1f680}");
+
+        // A pair split by the end of the string decodes to the replacement character
+        let data = [0xfe, 0xff, 0xff, 0xff, 0x78, 0x00, 0x3d, 0xd8];
+        let mut parser = CoreParser::new(&data[..]);
+        assert_eq!(parser.parse_text().unwrap(), "x\u{fffd}");
+
+        assert_eq!(decode_utf16(&[0x78, 0x00, 0x79]).unwrap(), "x\u{fffd}");
+        assert_eq!(decode_utf16(&[0x00, 0x00]).unwrap(), "");
+        assert_eq!(decode_utf16(&[0x78]).unwrap_err(), ParseError::ZeroSize);
+    }
+
+    #[test]
+    fn parse_utf16_huge_length() {
+        let mut parser = CoreParser::new(&[0x00, 0x00, 0x00, 0x80, 0x78, 0x00]);
+        parser.limits.max_string_len = usize::MAX;
+        let err = parser.parse_text().unwrap_err();
+        assert!(matches!(err, ParseError::InsufficientData(_, 2)));
+    }
 }
@@ -19,12 +19,54 @@
 /// `CRCTablesSB8_DEPRECATED` from the unreal engine to glean any information on derivation or
 /// usage. They can be found in the `AHRUnrealEngine` Github repo. I've copied them and the usage
 /// in `MemCrc_DEPRECATED` faithfully, but no luck. This has been a teachable moment
+///
+/// On aarch64 hosts with the CRC extension (detected at runtime), the crc is computed with the
+/// architecture's crc32 instructions instead of the table, unless the `forbid-unsafe` feature is
+/// enabled. Those implement the reflected form of the same polynomial, so reflecting the bits of
+/// the input and of the register going in and out yields the non-reflected crc.
 pub fn calc_crc(data: &[u8]) -> u32 {
+    #[cfg(all(target_arch = "aarch64", not(feature = "forbid-unsafe")))]
+    {
+        if std::arch::is_aarch64_feature_detected!("crc") {
+            // The crc instructions were just detected
+            return unsafe { aarch64::calc_crc(data) };
+        }
+    }
+
+    table_crc(data)
+}
+
+fn table_crc(data: &[u8]) -> u32 {
     !data.iter().fold(!0xefcb_f201, |acc, &x| {
         (acc << 8) ^ (TABLE[((u32::from(x)) ^ (acc >> 24)) as usize])
     })
 }
 
+#[cfg(all(target_arch = "aarch64", not(feature = "forbid-unsafe")))]
+mod aarch64 {
+    use std::arch::aarch64::{__crc32b, __crc32d};
+
+    #[target_feature(enable = "crc")]
+    pub unsafe fn calc_crc(data: &[u8]) -> u32 {
+        let mut acc = (!0xefcb_f201u32).reverse_bits();
+        let mut chunks = data.chunks_exact(8);
+        for chunk in &mut chunks {
+            let mut word = [0; 8];
+            word.copy_from_slice(chunk);
+
+            // Reflects the bits of each byte while keeping the bytes in order
+            let word = u64::from_le_bytes(word).reverse_bits().swap_bytes();
+            acc = __crc32d(acc, word);
+        }
+
+        for &x in chunks.remainder() {
+            acc = __crc32b(acc, x.reverse_bits());
+        }
+
+        !acc.reverse_bits()
+    }
+}
+
 /// This is equal to the `CRCTable_Deprecated` found in the unreal project
 /// ([link](https://docs.unrealengine.com/latest/INT/API/Runtime/Core/Misc/FCrc/CRCTable_DEPRECATED/index.html)). From the docs:
 ///
@@ -291,7 +333,7 @@ const TABLE: [u32; 256] = [
 
 #[cfg(test)]
 mod tests {
-    use crate::crc::calc_crc;
+    use crate::crc::{calc_crc, table_crc};
 
     #[test]
     fn crc_rumble_test() {
@@ -303,4 +345,16 @@ mod tests {
     fn single_byte_test() {
         assert_eq!(calc_crc(&[0xa0]), 0x76cc8c81);
     }
+
+    #[test]
+    fn matches_table_test() {
+        // Covers each alignment and length of the bytes left over from whole words
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        for start in 0..8 {
+            for len in (0..64).chain(Some(data.len() - start)) {
+                let data = &data[start..start + len];
+                assert_eq!(calc_crc(data), table_crc(data));
+            }
+        }
+    }
 }
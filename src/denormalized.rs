@@ -0,0 +1,296 @@
+//! Serializes a replay with the names of objects and attributes written inline in the network
+//! frames. Frames normally refer to objects by their index in the replay's `objects`, and to
+//! attributes by a stream id that is only meaningful with the replay's net cache, so consumers
+//! of the serialized output have to resolve the names themselves. A denormalized replay is larger
+//! but self contained.
+
+use crate::models::*;
+use crate::network::{
+    object_attributes, ActorId, Attribute, Frame, ObjectId, StreamId, Trajectory,
+};
+use failure::Error;
+use fnv::FnvHashMap;
+use serde::ser::{SerializeSeq, Serializer};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+impl<'a> Replay<'a> {
+    /// Returns the replay for serializing with the names of objects and attributes inline in
+    /// the network frames. New actors include the name of their object and the actor's name
+    /// (see `NewActor::name`), while updated attributes include the name of their actor's object
+    /// and the name of the attribute (eg: `TAGame.Car_TA:TeamPaint`). Otherwise the replay is
+    /// serialized as is. Fails when the replay's net cache can't be resolved.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .must_parse_network_data()
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let json = serde_json::to_string(&replay.denormalized().unwrap()).unwrap();
+    /// assert!(json.len() > serde_json::to_string(&replay).unwrap().len());
+    /// ```
+    pub fn denormalized(&self) -> Result<Denormalized<'_, 'a>, Error> {
+        let attributes = object_attributes(&self.objects, &self.net_cache)?;
+        Ok(Denormalized {
+            replay: self,
+            attributes,
+        })
+    }
+}
+
+/// A replay that serializes with the names of objects and attributes inline in the network
+/// frames. See `Replay::denormalized`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Denormalized<'b, 'a> {
+    replay: &'b Replay<'a>,
+
+    /// The attribute object that each stream id of an object refers to
+    attributes: HashMap<ObjectId, HashMap<StreamId, ObjectId>>,
+}
+
+impl<'b, 'a> Denormalized<'b, 'a> {
+    fn object_name(&self, object_id: ObjectId) -> Option<&'b str> {
+        self.replay
+            .objects
+            .get(usize::from(object_id))
+            .map(|x| x.as_ref())
+    }
+}
+
+impl<'b, 'a> Serialize for Denormalized<'b, 'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let replay = self.replay;
+        DenormalizedReplay {
+            header_size: replay.header_size,
+            header_crc: replay.header_crc,
+            major_version: replay.major_version,
+            minor_version: replay.minor_version,
+            net_version: replay.net_version,
+            game_type: &replay.game_type,
+            properties: PairMap(&replay.properties),
+            content_size: replay.content_size,
+            content_crc: replay.content_crc,
+            network_frames: replay.network_frames.as_ref().map(|x| DenormalizedFrames {
+                frames: FrameSeq {
+                    replay: self,
+                    frames: &x.frames,
+                },
+            }),
+            levels: &replay.levels,
+            keyframes: &replay.keyframes,
+            debug_info: &replay.debug_info,
+            tick_marks: &replay.tick_marks,
+            packages: &replay.packages,
+            objects: &replay.objects,
+            names: &replay.names,
+            class_indices: &replay.class_indices,
+            net_cache: &replay.net_cache,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// The fields of `Replay`, with the network frames swapped out for their denormalized form
+#[derive(Serialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+struct DenormalizedReplay<'c, 'b, 'a> {
+    header_size: i32,
+    header_crc: u32,
+    major_version: i32,
+    minor_version: i32,
+    net_version: Option<i32>,
+    game_type: &'b Cow<'a, str>,
+    properties: PairMap<'b, &'a str, HeaderProp<'a>>,
+    content_size: i32,
+    content_crc: u32,
+    network_frames: Option<DenormalizedFrames<'c, 'b, 'a>>,
+    levels: &'b [Cow<'a, str>],
+    keyframes: &'b [KeyFrame],
+    debug_info: &'b [DebugInfo<'a>],
+    tick_marks: &'b [TickMark<'a>],
+    packages: &'b [Cow<'a, str>],
+    objects: &'b [Cow<'a, str>],
+    names: &'b [Cow<'a, str>],
+    class_indices: &'b [ClassIndex<'a>],
+    net_cache: &'b [ClassNetCache],
+}
+
+/// The network frames, which serialize like `NetworkFrames`
+#[derive(Serialize)]
+struct DenormalizedFrames<'c, 'b, 'a> {
+    frames: FrameSeq<'c, 'b, 'a>,
+}
+
+struct FrameSeq<'c, 'b, 'a> {
+    replay: &'c Denormalized<'b, 'a>,
+    frames: &'b [Frame],
+}
+
+impl<'c, 'b, 'a> Serialize for FrameSeq<'c, 'b, 'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // The object of each live actor, which is needed to name the actor's attributes. Like
+        // `FrameIndex`, spawns are applied first and deletions last.
+        let mut actors: FnvHashMap<ActorId, ObjectId> = FnvHashMap::default();
+        let denormalized = self.replay;
+        let mut seq = serializer.serialize_seq(Some(self.frames.len()))?;
+        for frame in self.frames {
+            for actor in &frame.new_actors {
+                actors.insert(actor.actor_id, actor.object_id);
+            }
+
+            let new_actors = frame
+                .new_actors
+                .iter()
+                .map(|actor| NamedActor {
+                    actor_id: actor.actor_id,
+                    name_id: actor.name_id,
+                    object_id: actor.object_id,
+                    initial_trajectory: &actor.initial_trajectory,
+                    object_name: denormalized.object_name(actor.object_id),
+                    name: actor.name(denormalized.replay),
+                })
+                .collect();
+
+            let updated_actors = frame
+                .updated_actors
+                .iter()
+                .map(|update| {
+                    let object_id = actors.get(&update.actor_id).copied();
+                    let attribute = object_id
+                        .and_then(|x| denormalized.attributes.get(&x))
+                        .and_then(|x| x.get(&update.stream_id));
+                    NamedAttribute {
+                        actor_id: update.actor_id,
+                        stream_id: update.stream_id,
+                        attribute: &update.attribute,
+                        object_name: object_id.and_then(|x| denormalized.object_name(x)),
+                        attribute_name: attribute.and_then(|x| denormalized.object_name(*x)),
+                    }
+                })
+                .collect();
+
+            seq.serialize_element(&NamedFrame {
+                time: frame.time,
+                delta: frame.delta,
+                new_actors,
+                deleted_actors: &frame.deleted_actors,
+                updated_actors,
+            })?;
+
+            for actor_id in &frame.deleted_actors {
+                actors.remove(actor_id);
+            }
+        }
+        seq.end()
+    }
+}
+
+/// The fields of `Frame` with its actors and attributes named
+#[derive(Serialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+struct NamedFrame<'b> {
+    time: f32,
+    delta: f32,
+    new_actors: Vec<NamedActor<'b>>,
+    deleted_actors: &'b [ActorId],
+    updated_actors: Vec<NamedAttribute<'b>>,
+}
+
+/// The fields of `NewActor` followed by the names of its object and of the actor
+#[derive(Serialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+struct NamedActor<'b> {
+    actor_id: ActorId,
+    name_id: Option<i32>,
+    object_id: ObjectId,
+    initial_trajectory: &'b Trajectory,
+    object_name: Option<&'b str>,
+    name: Option<&'b str>,
+}
+
+/// The fields of `UpdatedAttribute` followed by the names of its actor's object and of the
+/// attribute
+#[derive(Serialize)]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+struct NamedAttribute<'b> {
+    actor_id: ActorId,
+    stream_id: StreamId,
+    attribute: &'b Attribute,
+    object_name: Option<&'b str>,
+    attribute_name: Option<&'b str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ParserBuilder;
+    use serde_json::Value;
+
+    fn key(snake: &'static str, camel: &'static str) -> &'static str {
+        if cfg!(feature = "camel-case") {
+            camel
+        } else {
+            snake
+        }
+    }
+
+    #[test]
+    fn test_denormalized() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let expected = serde_json::to_value(&replay).unwrap();
+        let mut actual = serde_json::to_value(replay.denormalized().unwrap()).unwrap();
+        let objects = &replay.objects;
+
+        let frames = actual[key("network_frames", "networkFrames")]["frames"]
+            .as_array_mut()
+            .unwrap();
+        let mut named = 0;
+        for frame in frames {
+            for actor in frame[key("new_actors", "newActors")]
+                .as_array_mut()
+                .unwrap()
+            {
+                let actor = actor.as_object_mut().unwrap();
+                let object_id = actor[key("object_id", "objectId")].as_u64().unwrap();
+                let object_name = actor.remove(key("object_name", "objectName")).unwrap();
+                assert_eq!(object_name, objects[object_id as usize].as_ref());
+                assert_eq!(actor.remove("name"), Some(Value::Null));
+            }
+
+            for update in frame[key("updated_actors", "updatedActors")]
+                .as_array_mut()
+                .unwrap()
+            {
+                let update = update.as_object_mut().unwrap();
+                assert!(update
+                    .remove(key("object_name", "objectName"))
+                    .unwrap()
+                    .is_string());
+                let attribute = update
+                    .remove(key("attribute_name", "attributeName"))
+                    .unwrap();
+                if attribute == "TAGame.Car_TA:TeamPaint" {
+                    assert!(update["attribute"].get("TeamPaint").is_some());
+                    named += 1;
+                }
+            }
+        }
+
+        // Without the names, the replay is serialized as is
+        assert!(named > 0);
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,466 @@
+//! # Diff
+//!
+//! Semantic comparison between two parsed replays. Instead of comparing the raw bytes (where a
+//! single shifted bit cascades into meaningless noise) the replays are compared section by
+//! section so that the reported differences point at the models that changed. Floating point
+//! values are compared with a tolerance as the same replay decoded by two slightly different
+//! code paths can accumulate rounding differences.
+//!
+//! The main use cases are regression testing parser changes (decode the same replay with the
+//! before and after versions) and verifying that writing out a replay and reading it back yields
+//! the same data.
+
+use crate::models::*;
+use crate::network::attributes::Attribute;
+use crate::network::{Frame, UpdatedAttribute};
+use std::fmt::Debug;
+
+/// The default absolute tolerance used when comparing floating point values
+pub const DEFAULT_EPSILON: f32 = 1e-4;
+
+/// The section of the replay where a difference was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    Header,
+    Properties,
+    Levels,
+    KeyFrames,
+    NetworkFrames,
+    DebugInfo,
+    TickMarks,
+    Packages,
+    Objects,
+    Names,
+    ClassIndices,
+    NetCache,
+}
+
+/// A single difference between the left and right replays. The values are formatted with their
+/// `Debug` representation so that differences of all types can be reported uniformly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// The section of the replay the difference resides in
+    pub section: Section,
+
+    /// A path to the differing value, eg: `frames[10].updated_actors[2].attribute`
+    pub path: String,
+
+    /// The value from the left replay (or `None` if the value is missing from the left)
+    pub left: Option<String>,
+
+    /// The value from the right replay (or `None` if the value is missing from the right)
+    pub right: Option<String>,
+}
+
+/// All the differences found between two replays
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReplayDiff {
+    pub differences: Vec<Difference>,
+}
+
+impl ReplayDiff {
+    /// Returns true if the two replays are semantically equivalent
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Returns the differences found in a given section
+    pub fn in_section(&self, section: Section) -> impl Iterator<Item = &Difference> {
+        self.differences
+            .iter()
+            .filter(move |x| x.section == section)
+    }
+}
+
+/// Compares two replays with the default float tolerance
+pub fn diff(left: &Replay<'_>, right: &Replay<'_>) -> ReplayDiff {
+    diff_with_tolerance(left, right, DEFAULT_EPSILON)
+}
+
+/// Compares two replays where floating point values are considered equal if their absolute
+/// difference is within the given `epsilon`.
+pub fn diff_with_tolerance(left: &Replay<'_>, right: &Replay<'_>, epsilon: f32) -> ReplayDiff {
+    let mut differ = Differ {
+        epsilon,
+        differences: Vec::new(),
+    };
+
+    differ.value(
+        Section::Header,
+        "header_size",
+        &left.header_size,
+        &right.header_size,
+    );
+    differ.value(
+        Section::Header,
+        "header_crc",
+        &left.header_crc,
+        &right.header_crc,
+    );
+    differ.value(
+        Section::Header,
+        "major_version",
+        &left.major_version,
+        &right.major_version,
+    );
+    differ.value(
+        Section::Header,
+        "minor_version",
+        &left.minor_version,
+        &right.minor_version,
+    );
+    differ.value(
+        Section::Header,
+        "net_version",
+        &left.net_version,
+        &right.net_version,
+    );
+    differ.value(
+        Section::Header,
+        "game_type",
+        &left.game_type,
+        &right.game_type,
+    );
+    differ.value(
+        Section::Header,
+        "content_size",
+        &left.content_size,
+        &right.content_size,
+    );
+    differ.value(
+        Section::Header,
+        "content_crc",
+        &left.content_crc,
+        &right.content_crc,
+    );
+
+    differ.properties("properties", &left.properties, &right.properties);
+    differ.list(Section::Levels, "levels", &left.levels, &right.levels);
+    differ.list(
+        Section::KeyFrames,
+        "keyframes",
+        &left.keyframes,
+        &right.keyframes,
+    );
+    differ.list(
+        Section::DebugInfo,
+        "debug_info",
+        &left.debug_info,
+        &right.debug_info,
+    );
+    differ.list(
+        Section::TickMarks,
+        "tick_marks",
+        &left.tick_marks,
+        &right.tick_marks,
+    );
+    differ.list(
+        Section::Packages,
+        "packages",
+        &left.packages,
+        &right.packages,
+    );
+    differ.list(Section::Objects, "objects", &left.objects, &right.objects);
+    differ.list(Section::Names, "names", &left.names, &right.names);
+    differ.list(
+        Section::ClassIndices,
+        "class_indices",
+        &left.class_indices,
+        &right.class_indices,
+    );
+    differ.list(
+        Section::NetCache,
+        "net_cache",
+        &left.net_cache,
+        &right.net_cache,
+    );
+
+    match (&left.network_frames, &right.network_frames) {
+        (Some(l), Some(r)) => differ.frames(&l.frames, &r.frames),
+        (None, None) => {}
+        (l, r) => differ.push(
+            Section::NetworkFrames,
+            String::from("network_frames"),
+            l.as_ref().map(|x| format!("{} frames", x.frames.len())),
+            r.as_ref().map(|x| format!("{} frames", x.frames.len())),
+        ),
+    }
+
+    ReplayDiff {
+        differences: differ.differences,
+    }
+}
+
+struct Differ {
+    epsilon: f32,
+    differences: Vec<Difference>,
+}
+
+impl Differ {
+    fn push(
+        &mut self,
+        section: Section,
+        path: String,
+        left: Option<String>,
+        right: Option<String>,
+    ) {
+        self.differences.push(Difference {
+            section,
+            path,
+            left,
+            right,
+        });
+    }
+
+    fn value<T: PartialEq + Debug>(&mut self, section: Section, path: &str, left: &T, right: &T) {
+        if left != right {
+            self.push(
+                section,
+                String::from(path),
+                Some(format!("{:?}", left)),
+                Some(format!("{:?}", right)),
+            );
+        }
+    }
+
+    /// Compares lists element wise. Elements that exist on only one side are reported as missing
+    /// from the other.
+    fn list<T: PartialEq + Debug>(
+        &mut self,
+        section: Section,
+        path: &str,
+        left: &[T],
+        right: &[T],
+    ) {
+        for (i, (l, r)) in left.iter().zip(right.iter()).enumerate() {
+            self.value(section, &format!("{}[{}]", path, i), l, r);
+        }
+
+        self.extra(section, path, left, right);
+    }
+
+    fn extra<T: Debug>(&mut self, section: Section, path: &str, left: &[T], right: &[T]) {
+        let common = left.len().min(right.len());
+        for (i, l) in left.iter().enumerate().skip(common) {
+            self.push(
+                section,
+                format!("{}[{}]", path, i),
+                Some(format!("{:?}", l)),
+                None,
+            );
+        }
+
+        for (i, r) in right.iter().enumerate().skip(common) {
+            self.push(
+                section,
+                format!("{}[{}]", path, i),
+                None,
+                Some(format!("{:?}", r)),
+            );
+        }
+    }
+
+    fn float_eq(&self, left: f32, right: f32) -> bool {
+        left == right || (left - right).abs() <= self.epsilon || (left.is_nan() && right.is_nan())
+    }
+
+    fn properties(
+        &mut self,
+        path: &str,
+        left: &[(&str, HeaderProp<'_>)],
+        right: &[(&str, HeaderProp<'_>)],
+    ) {
+        for (i, ((lkey, lval), (rkey, rval))) in left.iter().zip(right.iter()).enumerate() {
+            if lkey != rkey {
+                self.push(
+                    Section::Properties,
+                    format!("{}[{}]", path, i),
+                    Some(String::from(*lkey)),
+                    Some(String::from(*rkey)),
+                );
+                continue;
+            }
+
+            let prop_path = format!("{}.{}", path, lkey);
+            match (lval, rval) {
+                (HeaderProp::Array(l), HeaderProp::Array(r)) => {
+                    for (j, (linner, rinner)) in l.iter().zip(r.iter()).enumerate() {
+                        self.properties(&format!("{}[{}]", prop_path, j), linner, rinner);
+                    }
+
+                    self.extra(Section::Properties, &prop_path, l, r);
+                }
+                (HeaderProp::Float(l), HeaderProp::Float(r)) => {
+                    if !self.float_eq(*l, *r) {
+                        self.value(Section::Properties, &prop_path, lval, rval);
+                    }
+                }
+                _ => self.value(Section::Properties, &prop_path, lval, rval),
+            }
+        }
+
+        self.extra(Section::Properties, path, left, right);
+    }
+
+    fn frames(&mut self, left: &[Frame], right: &[Frame]) {
+        for (i, (l, r)) in left.iter().zip(right.iter()).enumerate() {
+            let path = format!("frames[{}]", i);
+            if !self.float_eq(l.time, r.time) {
+                self.value(
+                    Section::NetworkFrames,
+                    &format!("{}.time", path),
+                    &l.time,
+                    &r.time,
+                );
+            }
+
+            if !self.float_eq(l.delta, r.delta) {
+                self.value(
+                    Section::NetworkFrames,
+                    &format!("{}.delta", path),
+                    &l.delta,
+                    &r.delta,
+                );
+            }
+
+            self.list(
+                Section::NetworkFrames,
+                &format!("{}.new_actors", path),
+                &l.new_actors,
+                &r.new_actors,
+            );
+            self.list(
+                Section::NetworkFrames,
+                &format!("{}.deleted_actors", path),
+                &l.deleted_actors,
+                &r.deleted_actors,
+            );
+            self.updated_actors(
+                &format!("{}.updated_actors", path),
+                &l.updated_actors,
+                &r.updated_actors,
+            );
+        }
+
+        self.extra(Section::NetworkFrames, "frames", left, right);
+    }
+
+    fn updated_actors(
+        &mut self,
+        path: &str,
+        left: &[UpdatedAttribute],
+        right: &[UpdatedAttribute],
+    ) {
+        for (i, (l, r)) in left.iter().zip(right.iter()).enumerate() {
+            if l.actor_id != r.actor_id
+                || l.stream_id != r.stream_id
+                || !self.attribute_eq(&l.attribute, &r.attribute)
+            {
+                self.value(Section::NetworkFrames, &format!("{}[{}]", path, i), l, r);
+            }
+        }
+
+        self.extra(Section::NetworkFrames, path, left, right);
+    }
+
+    fn attribute_eq(&self, left: &Attribute, right: &Attribute) -> bool {
+        match (left, right) {
+            (Attribute::Float(l), Attribute::Float(r)) => self.float_eq(*l, *r),
+            (Attribute::CamSettings(l), Attribute::CamSettings(r)) => {
+                self.float_eq(l.fov, r.fov)
+                    && self.float_eq(l.height, r.height)
+                    && self.float_eq(l.angle, r.angle)
+                    && self.float_eq(l.distance, r.distance)
+                    && self.float_eq(l.swiftness, r.swiftness)
+                    && self.float_eq(l.swivel, r.swivel)
+                    && match (l.transition, r.transition) {
+                        (Some(lt), Some(rt)) => self.float_eq(lt, rt),
+                        (lt, rt) => lt == rt,
+                    }
+            }
+            (Attribute::Welded(l), Attribute::Welded(r)) => {
+                l.active == r.active
+                    && l.actor_id == r.actor_id
+                    && l.offset == r.offset
+                    && l.rotation == r.rotation
+                    && self.float_eq(l.mass, r.mass)
+            }
+            (l, r) => l == r,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{ActorId, StreamId};
+    use crate::ParserBuilder;
+    use std::borrow::Cow;
+
+    fn rumble() -> Replay<'static> {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let replay = rumble();
+        let res = diff(&replay, &replay.clone());
+        assert!(res.is_empty());
+    }
+
+    #[test]
+    fn test_diff_header_property() {
+        let left = rumble();
+        let mut right = left.clone();
+        right.properties[0].1 = HeaderProp::Int(-1);
+        let res = diff(&left, &right);
+        assert_eq!(res.differences.len(), 1);
+        assert_eq!(res.differences[0].section, Section::Properties);
+        assert!(res.differences[0].path.starts_with("properties."));
+        assert_eq!(res.differences[0].right, Some(String::from("Int(-1)")));
+    }
+
+    #[test]
+    fn test_diff_missing_objects() {
+        let left = rumble();
+        let mut right = left.clone();
+        right.objects.push(Cow::Borrowed("Boxcars.Extra"));
+        let res = diff(&left, &right);
+        assert_eq!(res.differences.len(), 1);
+        assert_eq!(
+            res.differences[0],
+            Difference {
+                section: Section::Objects,
+                path: format!("objects[{}]", left.objects.len()),
+                left: None,
+                right: Some(String::from("\"Boxcars.Extra\"")),
+            }
+        );
+    }
+
+    #[test]
+    fn test_diff_float_tolerance() {
+        let left = rumble();
+        let mut right = left.clone();
+        {
+            let frames = &mut right.network_frames.as_mut().unwrap().frames;
+            frames[0].time += 1e-6;
+            frames[1].updated_actors.push(UpdatedAttribute {
+                actor_id: ActorId(1),
+                stream_id: StreamId(1),
+                attribute: Attribute::Float(1.0),
+            });
+        }
+
+        let res = diff(&left, &right);
+        assert_eq!(res.differences.len(), 1);
+        assert_eq!(res.in_section(Section::NetworkFrames).count(), 1);
+
+        let res = diff_with_tolerance(&left, &right, 0.0);
+        assert_eq!(res.differences.len(), 2);
+    }
+}
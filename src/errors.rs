@@ -1,31 +1,96 @@
+#[cfg(feature = "gzip")]
+use crate::compression::Compression;
 use crate::network::{ActorId, Attribute, ObjectId, StreamId, Trajectory};
+use crate::version::ReplayVersion;
+use failure::Fail;
+use std::fmt;
+#[cfg(feature = "gzip")]
+use std::io;
 use std::str;
 
-#[derive(PartialEq, Debug, Clone, Fail)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum ParseError {
-    #[fail(display = "A size of zero is not valid")]
     ZeroSize,
+    Utf8Error(str::Utf8Error),
+    InsufficientData(i32, i32),
+    UnexpectedProperty(String),
+    CrcMismatch(u32, u32),
+    LimitExceeded(LimitExceeded),
+    HeaderNotFound(usize),
+}
 
-    #[fail(display = "Unable decode data as utf8: {}", _0)]
-    Utf8Error(#[cause] str::Utf8Error),
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::ZeroSize => write!(f, "A size of zero is not valid"),
+            ParseError::Utf8Error(a) => write!(f, "Unable decode data as utf8: {a}"),
+            ParseError::InsufficientData(a, b) => write!(
+                f,
+                "Insufficient data. Expected {a} bytes, but only {b} left"
+            ),
+            ParseError::UnexpectedProperty(a) => write!(f, "Did not expect a property of: {a}"),
+            ParseError::CrcMismatch(a, b) => {
+                write!(f, "Crc mismatch. Expected {a} but received {b}")
+            }
+            ParseError::LimitExceeded(a) => write!(f, "{a}"),
+            ParseError::HeaderNotFound(a) => {
+                write!(f, "No replay header found within the first {a} bytes")
+            }
+        }
+    }
+}
 
-    #[fail(display = "Text of size {} is too large", _0)]
-    TextTooLarge(i32),
+impl Fail for ParseError {
+    fn cause(&self) -> Option<&dyn Fail> {
+        match self {
+            ParseError::Utf8Error(e) => Some(e),
+            ParseError::LimitExceeded(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
-    #[fail(
-        display = "Insufficient data. Expected {} bytes, but only {} left",
-        _0, _1
-    )]
-    InsufficientData(i32, i32),
+/// The kinds of limits that bound how much of a replay is decoded
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum LimitKind {
+    /// The number of network frames
+    Frames,
 
-    #[fail(display = "Did not expect a property of: {}", _0)]
-    UnexpectedProperty(String),
+    /// The number of actors alive at the same time
+    Actors,
 
-    #[fail(display = "Crc mismatch. Expected {} but received {}", _0, _1)]
-    CrcMismatch(u32, u32),
+    /// The number of characters in a string
+    StringLength,
+
+    /// The number of elements in a list
+    ListLength,
+}
+
+/// A replay requested more than a limit allows. The replay may not be corrupt, but decoding it
+/// would take more resources than the parser was configured to allow.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct LimitExceeded {
+    pub kind: LimitKind,
+
+    /// The configured limit
+    pub limit: usize,
 
-    #[fail(display = "list of size {} is too large", _0)]
-    ListTooLarge(usize),
+    /// The amount that the replay requested
+    pub requested: usize,
+}
+
+impl Fail for LimitExceeded {}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            LimitKind::Frames => write!(f, "Too many frames to decode: {}", self.requested)?,
+            LimitKind::Actors => write!(f, "Too many actors alive: {}", self.requested)?,
+            LimitKind::StringLength => write!(f, "Text of size {} is too large", self.requested)?,
+            LimitKind::ListLength => write!(f, "list of size {} is too large", self.requested)?,
+        }
+        write!(f, " (limit of {})", self.limit)
+    }
 }
 
 impl From<str::Utf8Error> for ParseError {
@@ -34,95 +99,290 @@ impl From<str::Utf8Error> for ParseError {
     }
 }
 
-#[derive(PartialEq, Debug, Clone, Fail)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum AttributeError {
-    #[fail(display = "Not enough data to decode attribute {}", _0)]
     NotEnoughDataFor(&'static str),
-
-    #[fail(display = "Unrecognized remote id of {}", _0)]
     UnrecognizedRemoteId(u8),
-
-    #[fail(display = "Does not have an attribute implementation")]
     Unimplemented,
-
-    #[fail(display = "Unexpected size for string: {}", _0)]
     TooBigString(i32),
 }
 
-#[derive(PartialEq, Debug, Clone, Fail)]
+impl fmt::Display for AttributeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeError::NotEnoughDataFor(a) => {
+                write!(f, "Not enough data to decode attribute {a}")
+            }
+            AttributeError::UnrecognizedRemoteId(a) => write!(f, "Unrecognized remote id of {a}"),
+            AttributeError::Unimplemented => write!(f, "Does not have an attribute implementation"),
+            AttributeError::TooBigString(a) => write!(f, "Unexpected size for string: {a}"),
+        }
+    }
+}
+
+impl Fail for AttributeError {}
+
+#[derive(PartialEq, Debug, Clone)]
 pub enum NetworkError {
-    #[fail(display = "Not enough data to decode {}", _0)]
     NotEnoughDataFor(&'static str),
-
-    #[fail(display = "Time is out of range: {}", _0)]
     TimeOutOfRange(f32),
+    TimeOutOfRangeUpdate(usize, usize, ActorId, StreamId, Box<Attribute>),
+    TimeOutOfRangeNew(
+        usize,
+        usize,
+        ActorId,
+        Option<i32>,
+        ObjectId,
+        String,
+        Trajectory,
+    ),
+    DeltaOutOfRange(f32),
+    MaxStreamIdTooLarge(i32, ObjectId),
+    ChannelsTooLarge(i32),
+    ObjectIdOutOfRange(ObjectId),
+    StreamTooLargeIndex(i32, i32),
+    MissingParentClass(String, String),
+    ParentHasNoAttributes(ObjectId, ObjectId),
+    MissingActor(ActorId),
+    MissingCache(ActorId, ObjectId, String),
+    MissingAttribute(ActorId, ObjectId, String, StreamId, String),
+    UnimplementedAttribute(ActorId, ObjectId, String, StreamId, String, String),
+    AttributeError(AttributeError),
+    TooManyFrames(i32),
+    Cancelled,
+    UnknownVersion(ReplayVersion, ReplayVersion),
+    LimitExceeded(LimitExceeded),
+    UnknownObject(ActorId, ObjectId, String),
+    MissingFrames(usize, usize),
+    TrailingData(usize),
+    UnsupportedGame(String),
+}
 
-    #[fail(
-        display = "Time was out of range. Backtracking from frame: {} to {}, the last actor ({}) had a stream id of {}. This may mean that a new update of Rocket League updated this attribute. Decoded into {:?}",
-        _0, _1, _2, _3, _4
-    )]
-    TimeOutOfRangeUpdate(usize, usize, ActorId, StreamId, Attribute),
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::NotEnoughDataFor(a) => write!(f, "Not enough data to decode {a}"),
+            NetworkError::TimeOutOfRange(a) => write!(f, "Time is out of range: {a}"),
+            NetworkError::TimeOutOfRangeUpdate(a, b, c, d, e) => write!(
+                f,
+                "Time was out of range. Backtracking from frame: {a} to {b}, the last actor ({c}) had a stream id of {d}. This may mean that a new update of Rocket League updated this attribute. Decoded into {e:?}"
+            ),
+            NetworkError::TimeOutOfRangeNew(a, b, c, d, e, g, h) => write!(
+                f,
+                "Time was out of range. Backtracking from frame: {a} to {b}, the last actor ({c}) had a name id of {d:?}, object id: {e} ({g}), and trajectory: {h:?}. This may mean that a new update of Rocket League updated this object."
+            ),
+            NetworkError::DeltaOutOfRange(a) => write!(f, "Delta is out of range: {a}"),
+            NetworkError::MaxStreamIdTooLarge(a, b) => write!(
+                f,
+                "Too many stream ids ({a}) for object id: {b}"
+            ),
+            NetworkError::ChannelsTooLarge(a) => write!(
+                f,
+                "Number of channels exceeds maximum: {a}"
+            ),
+            NetworkError::ObjectIdOutOfRange(a) => write!(f, "Object Id of {a} exceeds range"),
+            NetworkError::StreamTooLargeIndex(a, b) => write!(
+                f,
+                "Stream id of {a} references out of range object index: {b}"
+            ),
+            NetworkError::MissingParentClass(a, b) => write!(
+                f,
+                "Replay contained object: {a} but not the parent class: {b}"
+            ),
+            NetworkError::ParentHasNoAttributes(a, b) => write!(
+                f,
+                "Parent id of {a} for object id of {b} was not recognized to have attributes"
+            ),
+            NetworkError::MissingActor(a) => write!(f, "Actor id: {a} was not found"),
+            NetworkError::MissingCache(a, b, c) => write!(
+                f,
+                "Actor id: {a} of object id: {b} ({c}) but no attributes found"
+            ),
+            NetworkError::MissingAttribute(a, b, c, d, e) => write!(
+                f,
+                "Actor id: {a} of object id: {b} ({c}) but stream id: {d} not found in {e}"
+            ),
+            NetworkError::UnimplementedAttribute(a, b, c, d, e, g) => write!(
+                f,
+                "Actor id: {a} of object id: {b} ({c}) but stream id: {d} ({e}) was not implemented. Possible missing implementations for stream id {d}\n{g}"
+            ),
+            NetworkError::AttributeError(a) => write!(f, "Attribute error: {a}"),
+            NetworkError::TooManyFrames(a) => write!(f, "Too many frames to decode: {a}"),
+            NetworkError::Cancelled => write!(f, "Parsing was cancelled"),
+            NetworkError::UnknownVersion(a, b) => write!(
+                f,
+                "Replay version {a} is newer than the latest known version {b}"
+            ),
+            NetworkError::LimitExceeded(a) => write!(f, "{a}"),
+            NetworkError::UnknownObject(a, b, c) => write!(
+                f,
+                "Actor id: {a} spawned as object id: {b} ({c}), which is not a known object"
+            ),
+            NetworkError::MissingFrames(a, b) => write!(
+                f,
+                "Decoded {a} frames but the header lists {b}"
+            ),
+            NetworkError::TrailingData(a) => write!(
+                f,
+                "{a} bits were left over after decoding the frames"
+            ),
+            NetworkError::UnsupportedGame(a) => write!(
+                f,
+                "Game type of {a} is not from Rocket League (eg: Rocket League Sideswipe), so its network data can't be decoded"
+            ),
+        }
+    }
+}
 
-    #[fail(
-        display = "Time was out of range. Backtracking from frame: {} to {}, the last actor ({}) had a name id of {:?}, object id: {} ({}), and trajectory: {:?}. This may mean that a new update of Rocket League updated this object.",
-        _0, _1, _2, _3, _4, _5, _6
-    )]
-    TimeOutOfRangeNew(usize, usize, ActorId, Option<i32>, ObjectId, String, Trajectory),
+impl Fail for NetworkError {
+    fn cause(&self) -> Option<&dyn Fail> {
+        match self {
+            NetworkError::AttributeError(e) => Some(e),
+            NetworkError::LimitExceeded(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
-    #[fail(display = "Delta is out of range: {}", _0)]
-    DeltaOutOfRange(f32),
+/// An error reading data with the `unreal` module's parser
+#[derive(PartialEq, Debug, Clone)]
+pub enum UnrealError {
+    InsufficientData(usize, usize),
+    ZeroSize,
+    Utf8Error(str::Utf8Error),
+    UnexpectedProperty(String),
+    LimitExceeded(LimitExceeded),
+}
 
-    #[fail(display = "Too many stream ids ({}) for object id: {}", _0, _1)]
-    MaxStreamIdTooLarge(i32, ObjectId),
+impl fmt::Display for UnrealError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnrealError::InsufficientData(a, b) => write!(
+                f,
+                "Insufficient data. Expected {a} bytes, but only {b} left"
+            ),
+            UnrealError::ZeroSize => write!(f, "A size of zero is not valid"),
+            UnrealError::Utf8Error(a) => write!(f, "Unable decode data as utf8: {a}"),
+            UnrealError::UnexpectedProperty(a) => write!(f, "Did not expect a property of: {a}"),
+            UnrealError::LimitExceeded(a) => write!(f, "{a}"),
+        }
+    }
+}
 
-    #[fail(display = "Number of channels exceeds maximum: {}", _0)]
-    ChannelsTooLarge(i32),
+impl Fail for UnrealError {
+    fn cause(&self) -> Option<&dyn Fail> {
+        match self {
+            UnrealError::Utf8Error(e) => Some(e),
+            UnrealError::LimitExceeded(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
-    #[fail(display = "Object Id of {} exceeds range", _0)]
-    ObjectIdOutOfRange(ObjectId),
+impl From<ParseError> for UnrealError {
+    fn from(error: ParseError) -> Self {
+        match error {
+            ParseError::InsufficientData(expected, left) => {
+                UnrealError::InsufficientData(expected as usize, left as usize)
+            }
+            ParseError::ZeroSize => UnrealError::ZeroSize,
+            ParseError::Utf8Error(e) => UnrealError::Utf8Error(e),
+            ParseError::UnexpectedProperty(x) => UnrealError::UnexpectedProperty(x),
+            ParseError::LimitExceeded(e) => UnrealError::LimitExceeded(e),
+            ParseError::CrcMismatch(..) => unreachable!("the unreal parser doesn't check crcs"),
+            ParseError::HeaderNotFound(_) => unreachable!("the unreal parser doesn't salvage"),
+        }
+    }
+}
 
-    #[fail(
-        display = "Stream id of {} references out of range object index: {}",
-        _0, _1
-    )]
-    StreamTooLargeIndex(i32, i32),
+/// An error configuring the `ClassOverrides` of a parse
+#[derive(PartialEq, Debug, Clone)]
+pub enum OverrideError {
+    UnknownKind(String),
+}
 
-    #[fail(
-        display = "Replay contained object: {} but not the parent class: {}",
-        _0, _1
-    )]
-    MissingParentClass(String, String),
+impl fmt::Display for OverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverrideError::UnknownKind(a) => {
+                write!(f, "{a} is not a kind of attribute that can be decoded")
+            }
+        }
+    }
+}
 
-    #[fail(
-        display = "Parent id of {} for object id of {} was not recognized to have attributes",
-        _0, _1
-    )]
-    ParentHasNoAttributes(ObjectId, ObjectId),
+impl Fail for OverrideError {}
 
-    #[fail(display = "Actor id: {} was not found", _0)]
-    MissingActor(ActorId),
+/// An error decompressing a compressed replay (see the `compression` module)
+#[cfg(feature = "gzip")]
+#[derive(Debug)]
+pub enum DecompressError {
+    Unsupported(Compression),
+    InvalidGzip(&'static str),
+    TooLarge(usize),
+    Io(io::Error),
+}
 
-    #[fail(
-        display = "Actor id: {} of object id: {} ({}) but no attributes found",
-        _0, _1, _2
-    )]
-    MissingCache(ActorId, ObjectId, String),
+#[cfg(feature = "gzip")]
+impl fmt::Display for DecompressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecompressError::Unsupported(a) => {
+                write!(f, "{a:?} compressed replays are not supported")
+            }
+            DecompressError::InvalidGzip(a) => write!(f, "Invalid gzip data: {a}"),
+            DecompressError::TooLarge(a) => write!(f, "The decompressed replay exceeds {a} bytes"),
+            DecompressError::Io(a) => write!(f, "{a}"),
+        }
+    }
+}
 
-    #[fail(
-        display = "Actor id: {} of object id: {} ({}) but stream id: {} not found in {}",
-        _0, _1, _2, _3, _4
-    )]
-    MissingAttribute(ActorId, ObjectId, String, StreamId, String),
+#[cfg(feature = "gzip")]
+impl Fail for DecompressError {
+    fn cause(&self) -> Option<&dyn Fail> {
+        match self {
+            DecompressError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
-    #[fail(
-        display = "Actor id: {} of object id: {} ({}) but stream id: {} ({}) was not implemented. Possible missing implementations for stream id {}\n{}",
-        _0, _1, _2, _3, _4, _3, _5
-    )]
-    UnimplementedAttribute(ActorId, ObjectId, String, StreamId, String, String),
+#[cfg(feature = "gzip")]
+impl From<io::Error> for DecompressError {
+    fn from(error: io::Error) -> Self {
+        DecompressError::Io(error)
+    }
+}
 
-    #[fail(display = "Attribute error: {}", _0)]
-    AttributeError(#[cause] AttributeError),
+#[derive(PartialEq, Debug, Clone)]
+pub enum BuildError {
+    UnknownActor(ActorId),
+    ActorIdOutOfRange(ActorId, i32),
+    UnknownAttribute(String),
+    AttributeMismatch(String, String, String),
+    Incompatible(&'static str),
+    InvalidValue(&'static str),
+}
 
-    #[fail(display = "Too many frames to decode: {}", _0)]
-    TooManyFrames(i32),
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::UnknownActor(a) => write!(f, "Actor id: {a} has not been spawned"),
+            BuildError::ActorIdOutOfRange(a, b) => {
+                write!(f, "Actor id: {a} exceeds the number of channels: {b}")
+            }
+            BuildError::UnknownAttribute(a) => {
+                write!(f, "Object {a} does not have a known attribute type")
+            }
+            BuildError::AttributeMismatch(a, b, c) => {
+                write!(f, "Object {a} is decoded as {b} not {c}")
+            }
+            BuildError::Incompatible(a) => {
+                write!(f, "Unable to encode {a} for this replay version")
+            }
+            BuildError::InvalidValue(a) => write!(f, "Invalid value for {a}"),
+        }
+    }
 }
+
+impl Fail for BuildError {}
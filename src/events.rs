@@ -0,0 +1,274 @@
+//! A pull parser that yields a replay as a sequence of events, as a lower level alternative to
+//! parsing the whole replay into a `Replay`.
+//!
+//! Each section of the replay is parsed when its first event is pulled, so a consumer that only
+//! needs the header can stop pulling after the header properties and the rest of the replay won't
+//! be parsed. Events are yielded in the order of the replay data, with the exception of the
+//! network frames: decoding the network data depends on the footer, so the frames come last.
+
+use crate::header::Header;
+use crate::models::{ClassIndex, ClassNetCache, DebugInfo, HeaderProp, KeyFrame, TickMark};
+use crate::network::{self, ActorId, DecodeOptions, Frame, NewActor, UpdatedAttribute};
+use crate::parser::{Parser, ReplayBody};
+use failure::Error;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+
+/// A piece of the replay yielded by `ReplayEvents`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    /// Starts the header
+    Header {
+        major_version: i32,
+        minor_version: i32,
+        net_version: Option<i32>,
+        game_type: Cow<'a, str>,
+    },
+    HeaderProperty(&'a str, HeaderProp<'a>),
+    Level(Cow<'a, str>),
+    Keyframe(KeyFrame),
+    DebugInfo(DebugInfo<'a>),
+    TickMark(TickMark<'a>),
+    Package(Cow<'a, str>),
+    Object(Cow<'a, str>),
+    Name(Cow<'a, str>),
+    ClassIndex(ClassIndex<'a>),
+    NetCache(ClassNetCache),
+
+    /// Starts a frame, which is followed by the new actors, attribute updates, and deleted
+    /// actors of the frame
+    FrameStart {
+        time: f32,
+        delta: f32,
+    },
+    NewActor(NewActor),
+    ActorUpdate(UpdatedAttribute),
+    ActorDeleted(ActorId),
+    FrameEnd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Header,
+    Body,
+    Network,
+    Frames,
+    Done,
+}
+
+/// Pulls events out of a replay's data. Created with `ParserBuilder::events`, which configures
+/// the crc check and how the network data is decoded. Once an error is yielded, no further
+/// events are yielded.
+///
+/// The network frames are decoded together when the first frame is pulled and then yielded one
+/// frame at a time. Like `ParserBuilder::parse`, network data errors are ignored by default, in
+/// which case no frame events are yielded.
+///
+/// ```
+/// use boxcars::Event;
+///
+/// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+/// let events = boxcars::ParserBuilder::new(&data[..]).events();
+/// let mut frames = 0;
+/// for event in events {
+///     if let Event::FrameStart { .. } = event.unwrap() {
+///         frames += 1;
+///     }
+/// }
+/// assert!(frames > 0);
+/// ```
+#[derive(Debug)]
+pub struct ReplayEvents<'a> {
+    parser: Parser<'a>,
+    options: DecodeOptions,
+    stage: Stage,
+    pending: VecDeque<Event<'a>>,
+
+    /// The header and body are kept to decode the network data
+    header: Option<Header<'a>>,
+    body: Option<ReplayBody<'a>>,
+    frames: std::vec::IntoIter<Frame>,
+}
+
+impl<'a> ReplayEvents<'a> {
+    pub(crate) fn new(parser: Parser<'a>, options: DecodeOptions) -> Self {
+        ReplayEvents {
+            parser,
+            options,
+            stage: Stage::Header,
+            pending: VecDeque::new(),
+            header: None,
+            body: None,
+            frames: Vec::new().into_iter(),
+        }
+    }
+
+    fn header(&mut self) -> Result<Stage, Error> {
+        let (_, _, header) = self.parser.header_section()?;
+        self.pending.push_back(Event::Header {
+            major_version: header.major_version,
+            minor_version: header.minor_version,
+            net_version: header.net_version,
+            game_type: header.game_type.clone(),
+        });
+
+        let properties = header.properties.iter().cloned();
+        let events = properties.map(|(key, prop)| Event::HeaderProperty(key, prop));
+        self.pending.extend(events);
+        self.header = Some(header);
+        Ok(Stage::Body)
+    }
+
+    fn body(&mut self) -> Result<Stage, Error> {
+        let (_, _, body) = self.parser.body_section()?;
+        let pending = &mut self.pending;
+        pending.extend(body.levels.iter().cloned().map(Event::Level));
+        pending.extend(body.keyframes.iter().cloned().map(Event::Keyframe));
+        pending.extend(body.debug_info.iter().cloned().map(Event::DebugInfo));
+        pending.extend(body.tick_marks.iter().cloned().map(Event::TickMark));
+        pending.extend(body.packages.iter().cloned().map(Event::Package));
+        pending.extend(body.objects.iter().cloned().map(Event::Object));
+        pending.extend(body.names.iter().cloned().map(Event::Name));
+        pending.extend(body.class_indices.iter().cloned().map(Event::ClassIndex));
+        pending.extend(body.net_cache.iter().cloned().map(Event::NetCache));
+        self.body = Some(body);
+        Ok(Stage::Network)
+    }
+
+    fn network(&mut self) -> Result<Stage, Error> {
+        let (header, body) = match (self.header.take(), self.body.take()) {
+            (Some(header), Some(body)) => (header, body),
+            _ => return Ok(Stage::Done),
+        };
+
+        let options = &self.options;
+        let network = self.parser.network(&header, &body, |header, body| {
+            network::parse(header, body, options)
+        })?;
+
+        match network {
+            Some(network) => {
+                self.frames = network.frames.into_iter();
+                Ok(Stage::Frames)
+            }
+            None => Ok(Stage::Done),
+        }
+    }
+
+    fn frame(&mut self) -> Result<Stage, Error> {
+        let frame = match self.frames.next() {
+            Some(frame) => frame,
+            None => return Ok(Stage::Done),
+        };
+
+        let pending = &mut self.pending;
+        pending.push_back(Event::FrameStart {
+            time: frame.time,
+            delta: frame.delta,
+        });
+        pending.extend(frame.new_actors.into_iter().map(Event::NewActor));
+        pending.extend(frame.updated_actors.into_iter().map(Event::ActorUpdate));
+        pending.extend(frame.deleted_actors.into_iter().map(Event::ActorDeleted));
+        pending.push_back(Event::FrameEnd);
+        Ok(Stage::Frames)
+    }
+}
+
+impl<'a> Iterator for ReplayEvents<'a> {
+    type Item = Result<Event<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+
+            let stage = match self.stage {
+                Stage::Header => self.header(),
+                Stage::Body => self.body(),
+                Stage::Network => self.network(),
+                Stage::Frames => self.frame(),
+                Stage::Done => return None,
+            };
+
+            match stage {
+                Ok(stage) => self.stage = stage,
+                Err(e) => {
+                    self.stage = Stage::Done;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Event;
+    use crate::ParserBuilder;
+
+    #[test]
+    fn test_events() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+        let events: Vec<_> = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .events()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        match &events[0] {
+            Event::Header { game_type, .. } => assert_eq!(game_type, &replay.game_type),
+            x => panic!("unexpected event: {:?}", x),
+        }
+
+        let properties: Vec<_> = events
+            .iter()
+            .filter_map(|x| match x {
+                Event::HeaderProperty(key, prop) => Some((*key, prop.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(properties, replay.properties);
+
+        // Frames are rebuilt from the events between their start and end
+        let mut frames = Vec::new();
+        for event in events {
+            match event {
+                Event::FrameStart { time, delta } => frames.push(crate::Frame {
+                    time,
+                    delta,
+                    new_actors: Default::default(),
+                    deleted_actors: Default::default(),
+                    updated_actors: Default::default(),
+                }),
+                Event::NewActor(x) => frames.last_mut().unwrap().new_actors.push(x),
+                Event::ActorUpdate(x) => frames.last_mut().unwrap().updated_actors.push(x),
+                Event::ActorDeleted(x) => frames.last_mut().unwrap().deleted_actors.push(x),
+                _ => {}
+            }
+        }
+        assert_eq!(frames, replay.network_frames.unwrap().frames);
+    }
+
+    #[test]
+    fn test_events_stop_at_error() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let mut events = ParserBuilder::new(&data[..data.len() / 2]).events();
+
+        // The header is yielded before the truncated body is parsed
+        assert!(matches!(events.next(), Some(Ok(Event::Header { .. }))));
+        let rest: Vec<_> = events.collect();
+        assert!(rest.last().unwrap().is_err());
+        assert!(rest[..rest.len() - 1].iter().all(|x| x.is_ok()));
+
+        let header_only = ParserBuilder::new(&data[..])
+            .never_parse_network_data()
+            .events()
+            .all(|x| !matches!(x, Ok(Event::FrameStart { .. })));
+        assert!(header_only);
+    }
+}
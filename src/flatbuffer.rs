@@ -0,0 +1,236 @@
+//! Encodes the rigid bodies of the network frames with the FlatBuffers schema in
+//! `proto/boxcars.fbs`. Every field of the schema is four bytes wide, so the buffer is written
+//! front to back and offsets are patched once the data they point to is written.
+
+use crate::models::Replay;
+use crate::network::{Attribute, Quaternion, Vector3f};
+
+const FILE_IDENTIFIER: &[u8; 4] = b"BXFR";
+
+impl<'a> Replay<'a> {
+    /// Encodes the rigid bodies updated in each network frame as a `boxcars.Frames` FlatBuffer.
+    /// The buffer can be memory mapped by visualizers and read with code generated from
+    /// `proto/boxcars.fbs` without a deserialization step. Replays without network data encode
+    /// an empty list of frames.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..]).parse().unwrap();
+    /// let encoded = replay.frames_to_flatbuffer();
+    /// assert_eq!(&encoded[4..8], b"BXFR");
+    /// ```
+    pub fn frames_to_flatbuffer(&self) -> Vec<u8> {
+        let net_version = self.net_version.unwrap_or(0);
+        let frames = self
+            .network_frames
+            .as_ref()
+            .map(|x| &x.frames[..])
+            .unwrap_or(&[]);
+
+        let mut b = Builder::default();
+        let root = b.offset();
+        b.buf.extend_from_slice(FILE_IDENTIFIER);
+        let frames_vtable = b.vtable(2);
+        let frame_vtable = b.vtable(4);
+
+        b.patch(root);
+        b.table(frames_vtable);
+        b.i32(net_version);
+        let frames_field = b.offset();
+
+        b.patch(frames_field);
+        b.len(frames.len());
+        let frame_offsets: Vec<usize> = frames.iter().map(|_| b.offset()).collect();
+
+        for (frame, at) in frames.iter().zip(frame_offsets) {
+            b.patch(at);
+            b.table(frame_vtable);
+            b.f32(frame.time);
+            b.f32(frame.delta);
+            let bodies_field = b.offset();
+            let deleted_field = b.offset();
+
+            let bodies: Vec<_> = frame
+                .updated_actors
+                .iter()
+                .filter_map(|x| match x.attribute {
+                    Attribute::RigidBody(body) => Some((x.actor_id, body.decompress(net_version))),
+                    _ => None,
+                })
+                .collect();
+
+            b.patch(bodies_field);
+            b.len(bodies.len());
+            for (actor_id, body) in bodies {
+                b.i32(actor_id.0);
+                b.buf.extend_from_slice(&[u8::from(body.sleeping), 0, 0, 0]);
+                b.vector(body.location);
+                b.quaternion(body.rotation);
+                b.vector(body.linear_velocity.unwrap_or_default());
+                b.vector(body.angular_velocity.unwrap_or_default());
+            }
+
+            b.patch(deleted_field);
+            b.len(frame.deleted_actors.len());
+            for actor_id in frame.deleted_actors.iter() {
+                b.i32(actor_id.0);
+            }
+        }
+
+        b.buf
+    }
+}
+
+/// A FlatBuffer being written front to back. Tables only hold four byte fields, which keeps every
+/// table, vector, and struct aligned without padding.
+#[derive(Debug, Default)]
+struct Builder {
+    buf: Vec<u8>,
+}
+
+impl Builder {
+    fn u16(&mut self, value: u16) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i32(&mut self, value: i32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn len(&mut self, len: usize) {
+        self.buf.extend_from_slice(&(len as u32).to_le_bytes());
+    }
+
+    fn vector(&mut self, value: Vector3f) {
+        self.f32(value.x);
+        self.f32(value.y);
+        self.f32(value.z);
+    }
+
+    fn quaternion(&mut self, value: Quaternion) {
+        self.f32(value.x);
+        self.f32(value.y);
+        self.f32(value.z);
+        self.f32(value.w);
+    }
+
+    /// Reserves an offset that is later patched to point at the data that follows it
+    fn offset(&mut self) -> usize {
+        let at = self.buf.len();
+        self.buf.extend_from_slice(&[0; 4]);
+        at
+    }
+
+    /// Points the offset reserved at `at` to the end of the buffer
+    fn patch(&mut self, at: usize) {
+        let offset = (self.buf.len() - at) as u32;
+        self.buf[at..at + 4].copy_from_slice(&offset.to_le_bytes());
+    }
+
+    /// Writes the vtable for a table of four byte fields and returns its position
+    fn vtable(&mut self, fields: usize) -> usize {
+        let at = self.buf.len();
+        self.u16((4 + fields * 2) as u16);
+        self.u16((4 + fields * 4) as u16);
+        for i in 0..fields {
+            self.u16((4 + i * 4) as u16);
+        }
+
+        if fields % 2 == 1 {
+            self.u16(0);
+        }
+        at
+    }
+
+    /// Starts a table whose layout is described by the vtable at `vtable`
+    fn table(&mut self, vtable: usize) {
+        let offset = (self.buf.len() - vtable) as i32;
+        self.i32(offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ParserBuilder;
+
+    fn u32_at(buf: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes([buf[at], buf[at + 1], buf[at + 2], buf[at + 3]])
+    }
+
+    fn f32_at(buf: &[u8], at: usize) -> f32 {
+        f32::from_bits(u32_at(buf, at))
+    }
+
+    /// The position of a table's field, which is read through the table's vtable
+    fn field(buf: &[u8], table: usize, index: usize) -> usize {
+        let vtable = table - u32_at(buf, table) as usize;
+        let at = vtable + 4 + index * 2;
+        table + usize::from(u16::from_le_bytes([buf[at], buf[at + 1]]))
+    }
+
+    /// Follows the offset at `at`
+    fn deref(buf: &[u8], at: usize) -> usize {
+        at + u32_at(buf, at) as usize
+    }
+
+    #[test]
+    fn test_frames_to_flatbuffer() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let buf = replay.frames_to_flatbuffer();
+        assert_eq!(&buf[4..8], b"BXFR");
+
+        let root = deref(&buf, 0);
+        assert_eq!(u32_at(&buf, field(&buf, root, 0)) as i32, 0);
+
+        let frames = replay.network_frames.as_ref().unwrap();
+        let vector = deref(&buf, field(&buf, root, 1));
+        assert_eq!(u32_at(&buf, vector) as usize, frames.frames.len());
+
+        let (index, frame) = frames
+            .frames
+            .iter()
+            .enumerate()
+            .find(|(_, x)| {
+                !x.deleted_actors.is_empty()
+                    && x.updated_actors
+                        .iter()
+                        .any(|x| matches!(x.attribute, crate::Attribute::RigidBody(_)))
+            })
+            .unwrap();
+        let table = deref(&buf, vector + 4 + index * 4);
+        assert_eq!(f32_at(&buf, field(&buf, table, 0)), frame.time);
+        assert_eq!(f32_at(&buf, field(&buf, table, 1)), frame.delta);
+
+        let expected: Vec<_> = frame
+            .updated_actors
+            .iter()
+            .filter_map(|x| match x.attribute {
+                crate::Attribute::RigidBody(body) => Some((x.actor_id, body.decompress(0))),
+                _ => None,
+            })
+            .collect();
+        let bodies = deref(&buf, field(&buf, table, 2));
+        assert_eq!(u32_at(&buf, bodies) as usize, expected.len());
+
+        let (actor_id, body) = expected[0];
+        let first = bodies + 4;
+        assert_eq!(u32_at(&buf, first) as i32, actor_id.0);
+        assert_eq!(buf[first + 4] != 0, body.sleeping);
+        assert_eq!(f32_at(&buf, first + 8), body.location.x);
+        assert_eq!(f32_at(&buf, first + 16), body.location.z);
+        assert_eq!(f32_at(&buf, first + 32), body.rotation.w);
+
+        let deleted = deref(&buf, field(&buf, table, 3));
+        assert_eq!(u32_at(&buf, deleted) as usize, frame.deleted_actors.len());
+        assert_eq!(u32_at(&buf, deleted + 4) as i32, frame.deleted_actors[0].0);
+    }
+}
@@ -2,9 +2,14 @@ use crate::core_parser::CoreParser;
 use crate::errors::ParseError;
 use crate::models::HeaderProp;
 use crate::parsing_utils::{err_str, le_f32, le_i32, le_u64};
+use crate::version::ReplayVersion;
 use failure::{Error, ResultExt};
 use std::borrow::Cow;
 
+/// The prefix of the game type of Rocket League replays. Other games, like Rocket League
+/// Sideswipe, record replays with their own game types and network classes.
+pub(crate) const ROCKET_LEAGUE_GAME_TYPE: &str = "TAGame.Replay_";
+
 /// Intermediate parsing structure for the header
 #[derive(Debug, PartialEq)]
 pub struct Header<'a> {
@@ -16,34 +21,35 @@ pub struct Header<'a> {
 }
 
 impl<'a> Header<'a> {
+    pub fn version(&self) -> ReplayVersion {
+        ReplayVersion::new(
+            self.major_version,
+            self.minor_version,
+            self.net_version.unwrap_or(0),
+        )
+    }
+
+    pub fn is_rocket_league(&self) -> bool {
+        self.game_type.starts_with(ROCKET_LEAGUE_GAME_TYPE)
+    }
+
     pub fn num_frames(&self) -> Option<i32> {
         self.properties
             .iter()
             .find(|&&(key, _)| key == "NumFrames")
-            .and_then(|&(_, ref prop)| {
-                if let HeaderProp::Int(v) = *prop {
-                    Some(v)
-                } else {
-                    None
-                }
-            })
+            .and_then(|(_, prop)| prop.as_i32())
     }
 
     pub fn max_channels(&self) -> Option<i32> {
         self.properties
             .iter()
             .find(|&&(key, _)| key == "MaxChannels")
-            .and_then(|&(_, ref prop)| {
-                if let HeaderProp::Int(v) = *prop {
-                    Some(v)
-                } else {
-                    None
-                }
-            })
+            .and_then(|(_, prop)| prop.as_i32())
     }
 }
 
-pub fn parse_header<'a>(rlp: &mut CoreParser<'a>) -> Result<Header<'a>, Error> {
+/// Parses the header and returns it alongside the offset that its properties start at
+pub fn parse_header<'a>(rlp: &mut CoreParser<'a>) -> Result<(Header<'a>, usize), Error> {
     let major_version = rlp
         .take(4, le_i32)
         .with_context(|e| err_str(rlp.bytes_read(), "major version", e))?;
@@ -65,19 +71,23 @@ pub fn parse_header<'a>(rlp: &mut CoreParser<'a>) -> Result<Header<'a>, Error> {
         .parse_text()
         .with_context(|e| err_str(rlp.bytes_read(), "game type", e))?;
 
+    let properties_offset = rlp.bytes_read() as usize;
     let properties =
         parse_rdict(rlp).with_context(|e| err_str(rlp.bytes_read(), "header properties", e))?;
 
-    Ok(Header {
+    let header = Header {
         major_version,
         minor_version,
         net_version,
         game_type,
         properties,
-    })
+    };
+    Ok((header, properties_offset))
 }
 
-fn parse_rdict<'a>(rlp: &mut CoreParser<'a>) -> Result<Vec<(&'a str, HeaderProp<'a>)>, ParseError> {
+pub(crate) fn parse_rdict<'a>(
+    rlp: &mut CoreParser<'a>,
+) -> Result<Vec<(&'a str, HeaderProp<'a>)>, ParseError> {
     // Other the actual network data, the header property associative array is the hardest to parse.
     // The format is to:
     // - Read string
@@ -119,12 +129,21 @@ fn parse_rdict<'a>(rlp: &mut CoreParser<'a>) -> Result<Vec<(&'a str, HeaderProp<
 // decoded property type specific.
 
 fn byte_property<'a>(rlp: &mut CoreParser<'a>) -> Result<HeaderProp<'a>, ParseError> {
-    // It's unknown (to me at least) why the byte property has two strings in it.
+    // A byte property is an enum with the enum's name followed by the value. Some older replays
+    // only contain the platform value.
     rlp.take(8, |_d| ())?;
-    if rlp.parse_str()? != "OnlinePlatform_Steam" {
-        rlp.parse_str()?;
-    }
-    Ok(HeaderProp::Byte)
+    let kind = rlp.parse_str()?;
+    let value = if !is_legacy_platform(kind) {
+        Some(rlp.parse_str()?)
+    } else {
+        None
+    };
+    Ok(HeaderProp::Byte { kind, value })
+}
+
+/// Older replays record the platform byte property without the enum's name
+pub(crate) fn is_legacy_platform(kind: &str) -> bool {
+    kind == "OnlinePlatform_Steam" || kind == "OnlinePlatform_PS4"
 }
 
 fn str_property<'a>(rlp: &mut CoreParser<'a>) -> Result<HeaderProp<'a>, ParseError> {
@@ -155,7 +174,8 @@ fn qword_property<'a>(rlp: &mut CoreParser<'a>) -> Result<HeaderProp<'a>, ParseE
 
 fn array_property<'a>(rlp: &mut CoreParser<'a>) -> Result<HeaderProp<'a>, ParseError> {
     let size = rlp.take(12, |d| le_i32(&d[8..]))?;
-    let arr = CoreParser::repeat(size as usize, || parse_rdict(rlp))?;
+    let limits = rlp.limits;
+    let arr = CoreParser::repeat(size as usize, &limits, || parse_rdict(rlp))?;
     Ok(HeaderProp::Array(arr))
 }
 
@@ -302,6 +322,44 @@ mod tests {
         let data = append_none(include_bytes!("../assets/replays/partial/rdict_byte.replay"));
         let mut parser = CoreParser::new(&data[..]);
         let res = parse_rdict(&mut parser).unwrap();
-        assert_eq!(res, vec![("Platform", HeaderProp::Byte)]);
+        assert_eq!(
+            res,
+            vec![(
+                "Platform",
+                HeaderProp::Byte {
+                    kind: "OnlinePlatform",
+                    value: Some("OnlinePlatform_Steam"),
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn rdict_legacy_byte_element() {
+        // Older replays omit the enum name and only record the platform
+        let mut data = Vec::new();
+        for text in &["Platform", "ByteProperty"] {
+            data.extend_from_slice(&(text.len() as i32 + 1).to_le_bytes());
+            data.extend_from_slice(text.as_bytes());
+            data.push(0);
+        }
+
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&[0x13, 0x00, 0x00, 0x00]);
+        data.extend_from_slice(b"OnlinePlatform_PS4\0");
+
+        let data = append_none(&data);
+        let mut parser = CoreParser::new(&data[..]);
+        let res = parse_rdict(&mut parser).unwrap();
+        assert_eq!(
+            res,
+            vec![(
+                "Platform",
+                HeaderProp::Byte {
+                    kind: "OnlinePlatform_PS4",
+                    value: None,
+                }
+            )]
+        );
     }
 }
@@ -22,7 +22,46 @@
 //! Boxcars will also check for replay corruption on error, but this can be
 //! configured to always check for corruption or never check.
 //!
-//! Serialization support is provided through [serde](https://github.com/serde-rs/serde).
+//! Serialization support is provided through [serde](https://github.com/serde-rs/serde) and is
+//! enabled by the default `serde` feature. Consumers that only need to parse replays can opt out
+//! with `default-features = false`.
+//!
+//! Fields are serialized in snake case (eg: `header_size`). The `camel-case` feature serializes
+//! fields in camel case instead (eg: `headerSize`) for JavaScript consumers. Enum variants and
+//! header property names are serialized as is with either casing.
+//!
+//! The `typescript` feature exposes TypeScript definitions of the JSON output with
+//! `typescript::definitions`, which can be written to a `.d.ts` file.
+//!
+//! The `protobuf` feature encodes a replay as a protobuf message with `Replay::to_proto`. The
+//! schema is in `proto/boxcars.proto`.
+//!
+//! The `flatbuffers` feature encodes the rigid bodies of the network frames as a FlatBuffer with
+//! `Replay::frames_to_flatbuffer` so that visualizers can read positions in place. The schema is
+//! in `proto/boxcars.fbs`.
+//!
+//! The `sqlite` feature writes a replay as a SQLite script of normalized tables (players, frames,
+//! rigid bodies, and events) with `Replay::write_sql` for ad-hoc SQL analysis.
+//!
+//! The `arbitrary` feature implements
+//! [`Arbitrary`](https://docs.rs/arbitrary/1/arbitrary/trait.Arbitrary.html) for the replay data
+//! structures so that fuzzers can generate structured replays. Combined with `ReplayBuilder`,
+//! generated attributes can be encoded and parsed back for round-trip fuzzing.
+//!
+//! The `chrono` feature exposes when a replay was recorded with `Replay::recorded_at`.
+//!
+//! The `forbid-unsafe` feature compiles boxcars with `#![forbid(unsafe_code)]`. The hardware
+//! CRC path is replaced by its portable equivalent, and `MappedFile` reads the file into memory
+//! instead of mapping it. Parsing is somewhat slower.
+//!
+//! The `tracing` feature instruments parsing with
+//! [`tracing`](https://docs.rs/tracing/0.1/tracing/) spans at the debug level: `header`, `body`,
+//! and `network` around parsing the header, the body, and decoding the network data, `crc` around
+//! computing a crc, and `keyframe` around decoding the frames from one keyframe to the next.
+//!
+//! The `gzip` feature decompresses gzip compressed replays (eg: `.replay.gz`) with
+//! `compression::read` and `compression::decompress`. `MappedFile` and the `batch` module
+//! decompress such replays transparently. Zstd compressed replays are detected but not supported.
 //!
 //! Below is an example to output the replay structure to json:
 //!
@@ -52,6 +91,7 @@
 //! ```
 
 #![recursion_limit = "1000"]
+#![cfg_attr(feature = "forbid-unsafe", forbid(unsafe_code))]
 
 #[macro_use]
 extern crate failure;
@@ -59,24 +99,83 @@ extern crate failure;
 #[macro_use]
 extern crate if_chain;
 
+#[cfg(feature = "serde")]
 #[macro_use]
 extern crate serde;
 
+pub use self::builder::{FrameBuilder, ReplayBuilder};
+#[cfg(feature = "serde")]
+pub use self::denormalized::Denormalized;
+pub use self::diff::diff;
+pub use self::errors::{LimitExceeded, LimitKind};
+pub use self::events::{Event, ReplayEvents};
+pub use self::limits::Limits;
+#[cfg(feature = "mmap")]
+pub use self::mmap::MappedFile;
 pub use self::models::*;
 pub use self::network::attributes::Attribute;
 pub use self::network::*;
-pub use self::parser::{CrcCheck, NetworkParse, ParserBuilder};
+pub use self::parser::{
+    BodySection, ConcatenatedReplays, CrcCheck, NetworkParse, ParserBuilder, PartialReplay,
+    SalvagedReplay,
+};
+#[cfg(feature = "serde")]
+pub use self::precision::{FloatPrecision, RoundFloats};
+#[cfg(feature = "serde")]
+pub use self::rotations::{FormatRotations, RotationFormat};
+pub use self::sections::ReplaySections;
+pub use self::stats::ParseStats;
+pub use self::support::{supported_attributes, SupportedAttribute, SupportedAttributes};
+pub use self::timeline::{TimelineEvent, TimelineKind};
+#[cfg(feature = "serde")]
+pub use self::vectors::DecompressVectors;
+pub use self::version::{Feature, ReplayVersion, UnknownVersions};
+#[cfg(feature = "arenas")]
+pub mod arenas;
+pub mod batch;
+pub mod bits;
+pub mod boost;
+mod builder;
+pub mod compression;
 mod core_parser;
 pub mod crc;
+#[cfg(feature = "serde")]
+mod denormalized;
+pub mod diff;
 mod errors;
+mod events;
+#[cfg(feature = "flatbuffers")]
+mod flatbuffer;
 mod header;
+mod limits;
+#[cfg(feature = "mmap")]
+mod mmap;
 mod models;
 mod network;
 mod parser;
 mod parsing_utils;
+#[cfg(feature = "serde")]
+pub mod precision;
+#[cfg(feature = "protobuf")]
+mod proto;
+#[cfg(feature = "serde")]
+pub mod rotations;
+mod sections;
+#[cfg(feature = "serde")]
 mod serde_utils;
+#[cfg(feature = "sqlite")]
+mod sql;
+mod stats;
+mod support;
+mod timeline;
+#[cfg(feature = "typescript")]
+pub mod typescript;
+pub mod unreal;
+#[cfg(feature = "serde")]
+pub mod vectors;
+mod version;
 
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::all))]
+#[allow(clippy::all)]
 mod hashes {
     include!(concat!(env!("OUT_DIR"), "/generated.rs"));
 }
@@ -0,0 +1,50 @@
+/// Bounds on how much of a replay is decoded, which keep a malicious replay from exhausting
+/// memory or time. The string and list limits were originally the parser's internal sanity
+/// checks, while frames and actors are only bounded by the size of the network data unless set.
+///
+/// A replay that requests more than a limit allows fails with a `LimitExceeded` error, which
+/// tells which limit was exceeded and by how much, so that a replay that is merely bigger than
+/// the configured limits can be told apart from a corrupt one. A replay that lists more frames
+/// than its network data could hold is corrupt no matter the limits, and fails with an error
+/// that isn't a `LimitExceeded`.
+///
+/// ```
+/// let limits = boxcars::Limits {
+///     max_frames: Some(50_000),
+///     max_list_len: 100_000,
+///     ..boxcars::Limits::default()
+/// };
+///
+/// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+/// let replay = boxcars::ParserBuilder::new(&data[..])
+///     .must_parse_network_data()
+///     .with_limits(limits)
+///     .parse()
+///     .unwrap();
+/// assert!(replay.network_frames.is_some());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// The most frames that the header can list. Unbounded by default.
+    pub max_frames: Option<usize>,
+
+    /// The most actors alive at the same time. Unbounded by default.
+    pub max_actors: Option<usize>,
+
+    /// The most characters in a string of the header or body. Defaults to 10,000.
+    pub max_string_len: usize,
+
+    /// The most elements in a list of the header or body. Defaults to 25,000.
+    pub max_list_len: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_frames: None,
+            max_actors: None,
+            max_string_len: 10_000,
+            max_list_len: 25_000,
+        }
+    }
+}
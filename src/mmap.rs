@@ -0,0 +1,140 @@
+#[cfg(feature = "gzip")]
+use crate::compression;
+use crate::parser::ParserBuilder;
+#[cfg(not(feature = "forbid-unsafe"))]
+use memmap2::Mmap;
+#[cfg(feature = "gzip")]
+use std::borrow::Cow;
+#[cfg(not(feature = "forbid-unsafe"))]
+use std::fs::File;
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+
+/// A replay file that has been memory mapped. Parsing from a memory mapped file avoids reading
+/// the entire file into an intermediate buffer, as the decoded replay borrows directly from the
+/// mapped pages. The mapping is kept alive for as long as this structure lives, so the lifetime
+/// of any replay parsed from it is tied to the `MappedFile`.
+///
+/// Mapping a file is unsafe, so with the `forbid-unsafe` feature the file is read into memory
+/// instead. With the `gzip` feature, a compressed file is decompressed into memory.
+///
+/// ```no_run
+/// # fn main() -> Result<(), failure::Error> {
+/// let file = boxcars::ParserBuilder::from_path("assets/replays/good/rumble.replay")?;
+/// let replay = file.parser().never_parse_network_data().parse()?;
+/// println!("{}", replay.game_type);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MappedFile {
+    mmap: Mapping,
+
+    /// The decompressed data of a compressed file
+    #[cfg(feature = "gzip")]
+    decompressed: Option<Vec<u8>>,
+}
+
+#[cfg(not(feature = "forbid-unsafe"))]
+type Mapping = Mmap;
+
+#[cfg(feature = "forbid-unsafe")]
+type Mapping = Vec<u8>;
+
+impl MappedFile {
+    /// Memory maps the file at the given path
+    #[cfg(not(feature = "forbid-unsafe"))]
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<MappedFile> {
+        let file = File::open(path)?;
+
+        // The mapping is read only. Modifications to the underlying file while the map is alive
+        // are undefined behavior, which is the usual caveat for memory mapping files
+        let mmap = unsafe { Mmap::map(&file)? };
+        MappedFile::new(mmap)
+    }
+
+    /// Reads the file at the given path into memory
+    #[cfg(feature = "forbid-unsafe")]
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<MappedFile> {
+        let mmap = std::fs::read(path)?;
+        MappedFile::new(mmap)
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    fn new(mmap: Mapping) -> io::Result<MappedFile> {
+        Ok(MappedFile { mmap })
+    }
+
+    #[cfg(feature = "gzip")]
+    fn new(mmap: Mapping) -> io::Result<MappedFile> {
+        let decompressed = match compression::decompress(&mmap[..]) {
+            Ok(Cow::Borrowed(_)) => None,
+            Ok(Cow::Owned(x)) => Some(x),
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+        };
+
+        Ok(MappedFile { mmap, decompressed })
+    }
+
+    /// Returns a parser builder that will parse the mapped data
+    pub fn parser(&self) -> ParserBuilder<'_> {
+        ParserBuilder::new(self)
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        #[cfg(feature = "gzip")]
+        {
+            if let Some(data) = &self.decompressed {
+                return data;
+            }
+        }
+
+        &self.mmap[..]
+    }
+}
+
+impl<'a> ParserBuilder<'a> {
+    /// Memory maps the replay at the given path. Use the returned `MappedFile` to configure the
+    /// parser and parse the replay without reading the file into memory first.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<MappedFile> {
+        MappedFile::open(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mapped_file() {
+        let file = ParserBuilder::from_path("assets/replays/good/rumble.replay").unwrap();
+        let replay = file.parser().must_parse_network_data().parse().unwrap();
+        assert_eq!(replay.network_frames.unwrap().frames.len(), 7744);
+    }
+
+    #[test]
+    fn test_parse_mapped_file_matches_buffer() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let file = MappedFile::open("assets/replays/good/rumble.replay").unwrap();
+        assert_eq!(&file[..], &data[..]);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_mapped_file_decompressed() {
+        let data = include_bytes!("../assets/replays/good/small-frames.replay");
+        let file = MappedFile::open("assets/replays/compressed/small-frames.replay.gz").unwrap();
+        assert_eq!(&file[..], &data[..]);
+        assert!(file.parser().parse().is_ok());
+    }
+
+    #[test]
+    fn test_mapped_file_missing() {
+        assert!(ParserBuilder::from_path("assets/replays/good/missing.replay").is_err());
+    }
+}
@@ -8,14 +8,35 @@
 /// numeric/string types). Asking "why JSON" would be next logical step, and that's due to other
 /// rocket league replay parsers (like Octane) using JSON; however, the output of this library is
 /// not compatible with that of other rocket league replay parsers.
-use crate::network::Frame;
+///
+/// Serialization is only available when the default `serde` feature is enabled.
+use crate::header::{Header, ROCKET_LEAGUE_GAME_TYPE};
+use crate::network::attributes::{Attribute, RigidBody};
+use crate::network::{self, ActorId, DecodeOptions, Frame, FrameRange, ObjectId, UniqueId};
+use crate::parser::ReplayBody;
+use crate::parsing_utils::{decode_str, decode_utf16, decode_windows1252};
+use crate::version::ReplayVersion;
+use encoding_rs::{UTF_16LE, WINDOWS_1252};
+use fnv::FnvHasher;
+#[cfg(feature = "serde")]
 use serde::ser::{SerializeMap, SerializeSeq};
+#[cfg(feature = "serde")]
 use serde::{Serialize, Serializer};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
 
 /// The structure that a rocket league replay is parsed into.
-#[derive(Serialize, PartialEq, Debug, Clone)]
+///
+/// Serialization is deterministic: fields are serialized in the order that they are declared and
+/// every list, including the `properties` map and the properties of array elements, is
+/// serialized in the order that it appears in the replay. Properties can be sorted by key with
+/// `sort_properties` so that replays with the same properties always serialize the same.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Replay<'a> {
     pub header_size: i32,
     pub header_crc: u32,
@@ -26,11 +47,16 @@ pub struct Replay<'a> {
 
     /// Could use a map to represent properties but I don't want to assume that duplicate keys
     /// can't exist, so to be safe, use a traditional vector.
-    #[serde(serialize_with = "pair_vec")]
+    #[cfg_attr(feature = "serde", serde(serialize_with = "pair_vec"))]
     pub properties: Vec<(&'a str, HeaderProp<'a>)>,
     pub content_size: i32,
     pub content_crc: u32,
     pub network_frames: Option<NetworkFrames>,
+
+    /// The undecoded network data, which is kept so that the network frames can be decoded
+    /// later with `parse_network` (eg: after triaging the replay by its header)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub network_data: &'a [u8],
     pub levels: Vec<Cow<'a, str>>,
     pub keyframes: Vec<KeyFrame>,
     pub debug_info: Vec<DebugInfo<'a>>,
@@ -40,19 +66,915 @@ pub struct Replay<'a> {
     pub names: Vec<Cow<'a, str>>,
     pub class_indices: Vec<ClassIndex<'a>>,
     pub net_cache: Vec<ClassNetCache>,
+
+    /// The original bytes of the header and body strings, in the order they appear in the
+    /// replay. Only retained when parsing with `ParserBuilder::retain_raw_strings`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub raw_strings: Vec<RawString<'a>>,
+}
+
+impl<'a> Replay<'a> {
+    /// Returns the first header property with the given key (eg: "TeamSize")
+    pub fn get_property(&self, key: &str) -> Option<&HeaderProp<'a>> {
+        find_property(&self.properties, key)
+    }
+
+    /// Sorts the header properties by key, including the properties of each array element. The
+    /// sort is stable, so properties with the same key keep their order.
+    pub fn sort_properties(&mut self) {
+        sort_properties(&mut self.properties);
+    }
+
+    /// Decodes the network frames from the network data that was kept when the replay was
+    /// parsed, which avoids parsing the header and body again when the replay was parsed without
+    /// its network data. The frames are decoded with the default decoding options and replace the
+    /// replay's `network_frames`.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let mut replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .never_parse_network_data()
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(replay.network_frames.is_none());
+    ///
+    /// let frames = replay.parse_network().unwrap();
+    /// assert!(!frames.frames.is_empty());
+    /// ```
+    pub fn parse_network(&mut self) -> Result<&NetworkFrames, failure::Error> {
+        // The sections that decoding depends on are moved into the header and body for the
+        // duration of the decoding instead of being cloned
+        let header = Header {
+            major_version: self.major_version,
+            minor_version: self.minor_version,
+            net_version: self.net_version,
+            game_type: Cow::Borrowed(""),
+            properties: std::mem::take(&mut self.properties),
+        };
+
+        let body = ReplayBody {
+            objects: std::mem::take(&mut self.objects),
+            net_cache: std::mem::take(&mut self.net_cache),
+            network_data: self.network_data,
+            ..ReplayBody::default()
+        };
+
+        let frames = network::parse(&header, &body, &DecodeOptions::default());
+        self.properties = header.properties;
+        self.objects = body.objects;
+        self.net_cache = body.net_cache;
+        Ok(self.network_frames.insert(frames?))
+    }
+
+    /// Decodes only the network frames of the range, for viewers that jump around a match
+    /// without decoding the whole replay. The game spawns every live actor again at each
+    /// keyframe, so decoding starts at the keyframe before the range, and the actors that are
+    /// alive before the range are returned alongside its frames. The range is clamped to the
+    /// frames of the replay.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .never_parse_network_data()
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let range = replay.decode_frames(5000..5030).unwrap();
+    /// assert_eq!(range.start, 5000);
+    /// assert_eq!(range.frames.len(), 30);
+    /// assert!(!range.actors.is_empty());
+    /// ```
+    pub fn decode_frames(&self, range: Range<usize>) -> Result<FrameRange, failure::Error> {
+        // Decoding only depends on the header properties of the frame count and channels
+        let properties = self
+            .properties
+            .iter()
+            .filter(|(key, _)| matches!(*key, "NumFrames" | "MaxChannels"))
+            .cloned()
+            .collect();
+
+        let header = Header {
+            major_version: self.major_version,
+            minor_version: self.minor_version,
+            net_version: self.net_version,
+            game_type: Cow::Borrowed(""),
+            properties,
+        };
+
+        let body = ReplayBody {
+            objects: self.objects.clone(),
+            net_cache: self.net_cache.clone(),
+            network_data: self.network_data,
+            ..ReplayBody::default()
+        };
+
+        let offsets = self.keyframe_offsets();
+        let keyframe = offsets.iter().rev().find(|x| x.frame <= range.start);
+        network::parse_range(&header, &body, range, keyframe)
+    }
+
+    /// Returns the id of the object with the given name (eg: "TAGame.Car_TA:TeamPaint")
+    pub fn object_id(&self, name: &str) -> Option<ObjectId> {
+        self.objects
+            .iter()
+            .position(|x| x == name)
+            .map(|x| ObjectId(x as i32))
+    }
+
+    /// The end of match stats for each player from the "PlayerStats" header property. Replays
+    /// without the property return no stats.
+    pub fn player_stats(&self) -> Vec<PlayerStat<'_>> {
+        let mut stats: Vec<_> = self
+            .get_property("PlayerStats")
+            .and_then(|x| x.as_array())
+            .unwrap_or(&[])
+            .iter()
+            .map(|props| PlayerStat::from_properties(props))
+            .collect();
+
+        // The header doesn't record split-screen indices. Split-screen guests share the online
+        // id of the primary player and the game names them after the primary player with their
+        // (1-based) position appended, eg: "comagoosie(2)"
+        for i in 0..stats.len() {
+            let online_id = stats[i].online_id.filter(|&x| x != 0);
+            let shares_id = online_id.is_some()
+                && stats.iter().enumerate().any(|(j, x)| {
+                    i != j && x.online_id == online_id && x.platform == stats[i].platform
+                });
+
+            if shares_id {
+                stats[i].split_screen_index = stats[i].name.map_or(0, split_screen_suffix);
+            }
+        }
+
+        stats
+    }
+
+    /// The goals scored from the "Goals" header property, in the order that they were scored.
+    /// Each goal is correlated with the team's goal tick mark, which is recorded a moment before
+    /// the goal's frame.
+    pub fn goals(&self) -> Vec<GoalMark<'_>> {
+        let mut team_goals: [usize; 2] = [0, 0];
+        self.get_property("Goals")
+            .and_then(|x| x.as_array())
+            .unwrap_or(&[])
+            .iter()
+            .map(|props| {
+                let get = |key: &str| find_property(props, key);
+                let player_team = get("PlayerTeam").and_then(|x| x.as_i32());
+
+                // The nth goal of a team is the nth goal tick mark for that team
+                let tick_mark_frame = match player_team {
+                    Some(team @ 0..=1) => {
+                        let desc = if team == 0 { "Team0Goal" } else { "Team1Goal" };
+                        let nth = team_goals[team as usize];
+                        team_goals[team as usize] += 1;
+                        self.tick_marks
+                            .iter()
+                            .filter(|x| x.description == desc)
+                            .nth(nth)
+                            .map(|x| x.frame)
+                    }
+                    _ => None,
+                };
+
+                GoalMark {
+                    frame: get("frame").and_then(|x| x.as_i32()),
+                    player_name: get("PlayerName").and_then(|x| x.as_str()),
+                    player_team,
+                    tick_mark_frame,
+                }
+            })
+            .collect()
+    }
+
+    /// Joins the goals of the header, the goal tick marks, and the team scores of the network
+    /// frames (when they are decoded) into a single list of goals in the order that they were
+    /// scored. The nth goal of a team is matched with the nth goal tick mark and nth score
+    /// increase of that team.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .must_parse_network_data()
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let goals = replay.goal_events();
+    /// assert_eq!(goals.len(), 7);
+    /// assert_eq!(goals[0].team, Some(1));
+    /// assert_eq!(goals[0].frame, goals[0].score_frame);
+    /// assert!(goals[0].time.is_some());
+    /// ```
+    pub fn goal_events(&self) -> Vec<GoalEvent<'_>> {
+        let index = self
+            .network_frames
+            .as_ref()
+            .and_then(|_| network::FrameIndex::new(self).ok());
+        let scores = match &index {
+            Some(index) => [index.score_changes(0), index.score_changes(1)],
+            None => [Vec::new(), Vec::new()],
+        };
+
+        let mut team_goals: [usize; 2] = [0, 0];
+        self.goals()
+            .into_iter()
+            .map(|goal| {
+                let change = match goal.player_team {
+                    Some(team @ 0..=1) => {
+                        let nth = team_goals[team as usize];
+                        team_goals[team as usize] += 1;
+                        scores[team as usize].get(nth).cloned()
+                    }
+                    _ => None,
+                };
+
+                let score_frame = change.map(|(frame, _)| frame as i32);
+                let frame = goal.frame.or(score_frame).or(goal.tick_mark_frame);
+                GoalEvent {
+                    scorer: goal.player_name,
+                    team: goal.player_team,
+                    frame,
+                    tick_mark_frame: goal.tick_mark_frame,
+                    score_frame,
+                    score: change.map(|(_, score)| score),
+                    time: frame.and_then(|x| self.frame_time(x)),
+                }
+            })
+            .collect()
+    }
+
+    /// The time of the frame from the network frames, else interpolated from the keyframes
+    pub(crate) fn frame_time(&self, frame: i32) -> Option<f32> {
+        let network = self.network_frames.as_ref().and_then(|network| {
+            let frame = usize::try_from(frame).ok()?;
+            network.frames.get(frame).map(|x| x.time)
+        });
+
+        if network.is_some() {
+            return network;
+        }
+
+        // Frames outside of the keyframes are extrapolated from the closest two keyframes
+        let keyframes = &self.keyframes;
+        if keyframes.len() < 2 {
+            return None;
+        }
+
+        let at = keyframes
+            .iter()
+            .position(|x| x.frame > frame)
+            .unwrap_or(keyframes.len() - 1)
+            .max(1);
+        let (a, b) = (&keyframes[at - 1], &keyframes[at]);
+        if a.frame == b.frame {
+            return None;
+        }
+
+        let rate = (b.time - a.time) / (b.frame - a.frame) as f32;
+        Some(a.time + (frame - a.frame) as f32 * rate)
+    }
+
+    /// When the replay was recorded, from the "Date" header property. The date is formatted in
+    /// the local time of the machine that recorded the replay and the timezone is not recorded,
+    /// so the returned time is naive: two replays of the same match recorded in different
+    /// timezones will differ. Older replays only record the time to the minute.
+    #[cfg(feature = "chrono")]
+    pub fn recorded_at(&self) -> Option<chrono::NaiveDateTime> {
+        let date = self.get_property("Date").and_then(|x| x.as_str())?;
+        chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H-%M-%S")
+            .or_else(|_| chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d:%H-%M"))
+            .ok()
+    }
+
+    /// The arena that the match was played in, from the "MapName" header property
+    #[cfg(feature = "arenas")]
+    pub fn arena(&self) -> Option<&'static crate::arenas::Arena> {
+        let map_name = self.get_property("MapName").and_then(|x| x.as_str())?;
+        crate::arenas::arena(map_name)
+    }
+
+    /// The version of the game that recorded the replay. Check the version's supported features
+    /// instead of comparing against version numbers.
+    pub fn version(&self) -> ReplayVersion {
+        ReplayVersion::new(
+            self.major_version,
+            self.minor_version,
+            self.net_version.unwrap_or(0),
+        )
+    }
+
+    /// Whether Rocket League recorded the replay, as opposed to another game with a similar
+    /// replay format like Rocket League Sideswipe. The network data of other games can't be
+    /// decoded: requiring it fails with `NetworkError::UnsupportedGame`, and otherwise (eg:
+    /// `NetworkParse::IgnoreOnError`) the replay is parsed without network frames.
+    pub fn is_rocket_league(&self) -> bool {
+        self.game_type.starts_with(ROCKET_LEAGUE_GAME_TYPE)
+    }
+
+    /// The number of network frames in the replay from the "NumFrames" header property, falling
+    /// back to the number of decoded frames when the property is missing
+    pub fn frame_count(&self) -> Option<i32> {
+        self.get_property("NumFrames")
+            .and_then(|x| x.as_i32())
+            .or_else(|| self.network_frames.as_ref().map(|x| x.frames.len() as i32))
+    }
+
+    /// Estimates how many seconds the replay lasts without decoding the network data, for
+    /// pipelines that only parse the header. The game records frames slower than the
+    /// "RecordFPS" header property while the match is played, so the rate of frames is measured
+    /// from the time and frame of the first and last keyframes, and only falls back to the
+    /// property (or 30 frames a second) when there aren't enough keyframes. The duration is
+    /// exact when the network frames are decoded.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .never_parse_network_data()
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// // The replay lasts about seven minutes
+    /// assert_eq!(replay.frame_count(), Some(7744));
+    /// assert_eq!(replay.estimated_duration().unwrap().round(), 420.0);
+    /// ```
+    pub fn estimated_duration(&self) -> Option<f32> {
+        let frames = self.network_frames.as_ref().map(|x| &x.frames);
+        if let Some((first, last)) = frames.and_then(|x| Some((x.first()?, x.last()?))) {
+            return Some(last.time - first.time);
+        }
+
+        let frame_count = self.frame_count()?;
+        let measured = match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) if last.time > first.time && last.frame > first.frame => {
+                Some((last.frame - first.frame) as f32 / (last.time - first.time))
+            }
+            _ => None,
+        };
+
+        let rate = measured
+            .or_else(|| self.get_property("RecordFPS").and_then(|x| x.as_f32()))
+            .filter(|x| *x > 0.0)
+            .unwrap_or(30.0);
+        Some(frame_count as f32 / rate)
+    }
+
+    /// Resolves the keyframes to where their frames start in the network data, so that the raw
+    /// network data can be indexed and seeked into without decoding the frames. Keyframes that
+    /// don't point within the network data (eg: of a corrupt replay) are skipped.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .never_parse_network_data()
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let offsets = replay.keyframe_offsets();
+    /// assert_eq!(offsets.len(), replay.keyframes.len());
+    ///
+    /// // The first frame starts at the start of the network data
+    /// assert_eq!(offsets[0].frame, 0);
+    /// assert_eq!(offsets[0].bit_offset, 0);
+    /// ```
+    pub fn keyframe_offsets(&self) -> Vec<KeyFrameOffset> {
+        let bits = self.network_data.len() * 8;
+        self.keyframes
+            .iter()
+            .filter_map(|x| {
+                let frame = usize::try_from(x.frame).ok()?;
+                let bit_offset = usize::try_from(x.position).ok().filter(|x| *x < bits)?;
+                Some(KeyFrameOffset {
+                    time: x.time,
+                    frame,
+                    bit_offset,
+                    byte_offset: bit_offset / 8,
+                })
+            })
+            .collect()
+    }
+
+    /// The unique identifier of the match from the "MatchGuid" header property. Every player's
+    /// replay of a match shares the same guid. Only newer replays record the property.
+    pub fn match_guid(&self) -> Option<&str> {
+        self.get_property("MatchGuid")
+            .and_then(|x| x.as_str())
+            .filter(|x| !x.is_empty())
+    }
+
+    /// What the replay records about the ranking of the match, for tools that estimate skill from
+    /// replays. Rocket League doesn't record ranks or skill ratings in the header, so this is
+    /// limited to the kind of match: each field is `None` when the replay doesn't record it,
+    /// rather than guessed from other properties.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/d52eb.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .never_parse_network_data()
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let rank = replay.rank_info();
+    /// assert_eq!(rank.match_type, Some("Online"));
+    /// assert_eq!(rank.ranked, Some(true));
+    /// ```
+    pub fn rank_info(&self) -> RankInfo<'_> {
+        // The match type's class is among the objects that the network frames reference, which
+        // are available without decoding the frames
+        let classes: Vec<_> = self
+            .objects
+            .iter()
+            .filter_map(|x| x.strip_prefix("TAGame.MatchType_"))
+            .collect();
+        let ranked = if classes.is_empty() {
+            None
+        } else {
+            Some(classes.contains(&"PublicRanked_TA"))
+        };
+
+        RankInfo {
+            match_type: self.get_property("MatchType").and_then(|x| x.as_str()),
+            ranked,
+            team_size: self.get_property("TeamSize").and_then(|x| x.as_i32()),
+            unfair_team_size: self.get_property("UnfairTeamSize").and_then(|x| x.as_i32()),
+        }
+    }
+
+    /// Identifies the match that the replay recorded so that replays of the same match uploaded
+    /// by different players can be grouped together. The match guid is used when present.
+    /// Otherwise, older replays are fingerprinted from the header: the "Id" and "Date"
+    /// properties are unique to each player's recording (and the date is in the recorder's
+    /// local time), so the fingerprint is instead derived from what all recordings of the match
+    /// agree on: the key of the `match_fingerprint` (the map and players), the team size, the
+    /// score, and the scorer of each goal. The fingerprint is hashed from the bytes of each
+    /// field, so it's the same on every platform and across versions of Rust.
+    pub fn match_identity(&self) -> MatchIdentity<'_> {
+        let fingerprint = self.match_fingerprint();
+        if let Some(guid) = fingerprint.guid {
+            return MatchIdentity::Guid(guid);
+        }
+
+        let mut hasher = FnvHasher::default();
+        hasher.write(&fingerprint.key().to_le_bytes());
+        for key in &["TeamSize", "Team0Score", "Team1Score"] {
+            let value = self.get_property(key).and_then(|x| x.as_i32());
+            hash_i32(&mut hasher, value);
+        }
+
+        let goals = self.goals();
+        hasher.write(&(goals.len() as u64).to_le_bytes());
+        for goal in goals {
+            hash_i32(&mut hasher, goal.player_team);
+            hash_bytes(&mut hasher, goal.player_name.unwrap_or_default().as_bytes());
+        }
+
+        MatchIdentity::Fingerprint(hasher.finish())
+    }
+
+    /// The parts of the match that every player's recording of it agrees on, for finding the
+    /// recordings of the same match: the match guid, the map, the players, and the frames of the
+    /// goals. The "Date" property isn't part of the fingerprint as it's the local time of the
+    /// player that saved the replay.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .never_parse_network_data()
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let fingerprint = replay.match_fingerprint();
+    /// assert_eq!(fingerprint.goal_frames.len(), replay.goals().len());
+    /// assert!(fingerprint.matches(&replay.clone().match_fingerprint()));
+    /// ```
+    pub fn match_fingerprint(&self) -> MatchFingerprint<'_> {
+        let mut players: Vec<_> = self
+            .player_stats()
+            .into_iter()
+            .map(|x| (x.platform, x.online_id, x.name))
+            .collect();
+        players.sort();
+
+        MatchFingerprint {
+            guid: self.match_guid(),
+            map: self.get_property("MapName").and_then(|x| x.as_str()),
+            players,
+            goal_frames: self.goals().iter().filter_map(|x| x.frame).collect(),
+        }
+    }
+
+    /// A stable hash of the replay's content for detecting re-uploads of the same file: the
+    /// versions, game type, header properties, and network data. The properties are hashed in
+    /// sorted order, and the section sizes and crcs are left out, so a replay that was
+    /// re-serialized with its properties in another order hashes the same. The hash is computed
+    /// from the bytes of each field, so it doesn't change across versions of Rust or of this
+    /// library. Unlike `match_identity`, each player's recording of a match has its own hash.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let parse = || boxcars::ParserBuilder::new(&data[..]).never_parse_network_data().parse();
+    /// let replay = parse().unwrap();
+    /// let mut sorted = parse().unwrap();
+    /// sorted.sort_properties();
+    /// assert_eq!(replay.content_hash(), sorted.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        hasher.write(&self.major_version.to_le_bytes());
+        hasher.write(&self.minor_version.to_le_bytes());
+        hasher.write(&self.net_version.unwrap_or(0).to_le_bytes());
+        hash_bytes(&mut hasher, self.game_type.as_bytes());
+
+        let mut properties = self.properties.clone();
+        sort_properties(&mut properties);
+        hash_properties(&mut hasher, &properties);
+
+        hash_bytes(&mut hasher, self.network_data);
+        hasher.finish()
+    }
+
+    /// The highlights of the match from the "HighLights" header property
+    pub fn highlights(&self) -> Vec<Highlight<'_>> {
+        self.get_property("HighLights")
+            .or_else(|| self.get_property("Highlights"))
+            .and_then(|x| x.as_array())
+            .unwrap_or(&[])
+            .iter()
+            .map(|props| {
+                // An object name of "None" signifies that the object wasn't involved
+                let name = |key| {
+                    find_property(props, key)
+                        .and_then(|x| x.as_str())
+                        .filter(|&x| x != "None")
+                };
+
+                Highlight {
+                    frame: find_property(props, "frame").and_then(|x| x.as_i32()),
+                    car_name: name("CarName"),
+                    ball_name: name("BallName"),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Identifies the match that a replay recorded
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum MatchIdentity<'a> {
+    /// The "MatchGuid" header property shared by all recordings of the match
+    Guid(&'a str),
+
+    /// A stable hash of the header properties that all recordings of the match agree on (see
+    /// `Replay::match_identity`). The fingerprint is less precise than a guid: two matches
+    /// between the same players on the same map with the same goal scorers will share a
+    /// fingerprint.
+    Fingerprint(u64),
+}
+
+/// The parts of a match that every player's recording of it agrees on (see
+/// `Replay::match_fingerprint`)
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct MatchFingerprint<'a> {
+    /// The "MatchGuid" header property, which only newer replays record
+    pub guid: Option<&'a str>,
+    pub map: Option<&'a str>,
+
+    /// The platform, online id, and name of each player, sorted as the players are recorded in
+    /// an order relative to the recording player
+    pub players: Vec<(Option<&'a str>, Option<u64>, Option<&'a str>)>,
+
+    /// The frame of each goal in the order they were scored
+    pub goal_frames: Vec<i32>,
+}
+
+/// How far apart the frames of a goal may be in two recordings of the same match, as each player
+/// records the match at their own frame rate
+const GOAL_FRAME_TOLERANCE: i32 = 60;
+
+impl<'a> MatchFingerprint<'a> {
+    /// A stable hash of the guid when the match has one, else of the map and the players. The
+    /// goal frames aren't hashed since they differ slightly between recordings, so recordings of
+    /// the same match share a key, and recordings with the same key can be told apart with
+    /// `matches`.
+    pub fn key(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        if let Some(guid) = self.guid {
+            hash_bytes(&mut hasher, guid.as_bytes());
+            return hasher.finish();
+        }
+
+        let hash_str = |hasher: &mut FnvHasher, x: Option<&str>| {
+            hash_bytes(hasher, x.unwrap_or_default().as_bytes())
+        };
+        hash_str(&mut hasher, self.map);
+        for (platform, online_id, name) in &self.players {
+            hash_str(&mut hasher, *platform);
+            hasher.write(&online_id.unwrap_or(0).to_le_bytes());
+            hash_str(&mut hasher, *name);
+        }
+        hasher.finish()
+    }
+
+    /// Returns true when the fingerprints are of recordings of the same match. Matches with a
+    /// guid are compared by guid. Otherwise the map and players must be the same, and the goals
+    /// must be scored at about the same times. The goals are compared relative to the first
+    /// goal, as a player that joins a match late starts recording later.
+    pub fn matches(&self, other: &MatchFingerprint<'_>) -> bool {
+        if let (Some(guid), Some(other_guid)) = (self.guid, other.guid) {
+            return guid == other_guid;
+        }
+
+        let goals = |x: &MatchFingerprint<'_>| {
+            let first = x.goal_frames.first().cloned().unwrap_or(0);
+            x.goal_frames
+                .iter()
+                .map(|frame| frame - first)
+                .collect::<Vec<_>>()
+        };
+
+        self.map == other.map
+            && self.players == other.players
+            && self.goal_frames.len() == other.goal_frames.len()
+            && goals(self)
+                .iter()
+                .zip(goals(other).iter())
+                .all(|(a, b)| (a - b).abs() <= GOAL_FRAME_TOLERANCE)
+    }
+}
+
+/// The ranking of the match (see `Replay::rank_info`)
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct RankInfo<'a> {
+    /// The "MatchType" header property (eg: "Online", "Private", "Offline", or "Tournament").
+    /// Online matches include both casual and ranked playlists.
+    pub match_type: Option<&'a str>,
+
+    /// Whether the match was played in a ranked playlist, from the match type class that the
+    /// network data references. `None` when the replay doesn't reference a match type class.
+    pub ranked: Option<bool>,
+
+    /// The number of players on each team, from the "TeamSize" header property
+    pub team_size: Option<i32>,
+
+    /// The "UnfairTeamSize" header property, which some matches with teams that may differ in
+    /// size record as 1
+    pub unfair_team_size: Option<i32>,
+}
+
+/// A highlight of the match, such as a goal or save, that a replay viewer can seek to
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct Highlight<'a> {
+    pub frame: Option<i32>,
+
+    /// The name of the car's actor object (eg: "Car_TA_41")
+    pub car_name: Option<&'a str>,
+
+    /// The name of the ball's actor object (eg: "Ball_TA_8"). Most highlights don't involve the
+    /// ball.
+    pub ball_name: Option<&'a str>,
+}
+
+/// A goal scored in the match
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct GoalMark<'a> {
+    /// The frame that the goal was scored on
+    pub frame: Option<i32>,
+    pub player_name: Option<&'a str>,
+    pub player_team: Option<i32>,
+
+    /// The frame of the team's corresponding goal tick mark
+    pub tick_mark_frame: Option<i32>,
+}
+
+/// A goal of the match, joined from the header's goals, the goal tick marks, and the network
+/// frames (see `Replay::goal_events`)
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct GoalEvent<'a> {
+    /// The name of the player that scored
+    pub scorer: Option<&'a str>,
+    pub team: Option<i32>,
+
+    /// The frame that the goal was scored on, from the header where recorded, else from the
+    /// network frames or the tick mark
+    pub frame: Option<i32>,
+    pub tick_mark_frame: Option<i32>,
+
+    /// The frame that the team's score increased on, when the network frames are decoded
+    pub score_frame: Option<i32>,
+
+    /// The team's score after the goal, when the network frames are decoded
+    pub score: Option<i32>,
+
+    /// The time of the goal's frame in seconds, from the network frames when they are decoded,
+    /// else estimated from the keyframes
+    pub time: Option<f32>,
+}
+
+fn sort_properties(props: &mut [(&str, HeaderProp<'_>)]) {
+    props.sort_by(|a, b| a.0.cmp(b.0));
+    for (_, prop) in props.iter_mut() {
+        if let HeaderProp::Array(arr) = prop {
+            for inner in arr.iter_mut() {
+                sort_properties(inner);
+            }
+        }
+    }
+}
+
+/// Writes the length of the data before the data, so that consecutive fields can't be confused
+fn hash_bytes(hasher: &mut FnvHasher, data: &[u8]) {
+    hasher.write(&(data.len() as u64).to_le_bytes());
+    hasher.write(data);
+}
+
+fn hash_i32(hasher: &mut FnvHasher, value: Option<i32>) {
+    match value {
+        Some(x) => {
+            hasher.write(&[1]);
+            hasher.write(&x.to_le_bytes());
+        }
+        None => hasher.write(&[0]),
+    }
+}
+
+fn hash_properties(hasher: &mut FnvHasher, props: &[(&str, HeaderProp<'_>)]) {
+    hasher.write(&(props.len() as u64).to_le_bytes());
+    for (key, prop) in props {
+        hash_bytes(hasher, key.as_bytes());
+        match prop {
+            HeaderProp::Array(arr) => {
+                hasher.write(&[0]);
+                hasher.write(&(arr.len() as u64).to_le_bytes());
+                for inner in arr {
+                    hash_properties(hasher, inner);
+                }
+            }
+            HeaderProp::Bool(x) => hasher.write(&[1, u8::from(*x)]),
+            HeaderProp::Byte { kind, value } => {
+                hasher.write(&[2]);
+                hash_bytes(hasher, kind.as_bytes());
+                match value {
+                    Some(value) => {
+                        hasher.write(&[1]);
+                        hash_bytes(hasher, value.as_bytes());
+                    }
+                    None => hasher.write(&[0]),
+                }
+            }
+            HeaderProp::Float(x) => {
+                hasher.write(&[3]);
+                hasher.write(&x.to_bits().to_le_bytes());
+            }
+            HeaderProp::Int(x) => {
+                hasher.write(&[4]);
+                hasher.write(&x.to_le_bytes());
+            }
+            HeaderProp::Name(x) => {
+                hasher.write(&[5]);
+                hash_bytes(hasher, x.as_bytes());
+            }
+            HeaderProp::QWord(x) => {
+                hasher.write(&[6]);
+                hasher.write(&x.to_le_bytes());
+            }
+            HeaderProp::Str(x) => {
+                hasher.write(&[7]);
+                hash_bytes(hasher, x.as_bytes());
+            }
+        }
+    }
+}
+
+fn find_property<'b, 'a>(
+    props: &'b [(&'a str, HeaderProp<'a>)],
+    key: &str,
+) -> Option<&'b HeaderProp<'a>> {
+    props.iter().find(|&&(k, _)| k == key).map(|(_, prop)| prop)
+}
+
+/// A player's stats at the end of the match. Which stats are recorded has changed across replay
+/// versions, so every stat is optional.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct PlayerStat<'a> {
+    pub name: Option<&'a str>,
+
+    /// The online platform (eg: "OnlinePlatform_Steam")
+    pub platform: Option<&'a str>,
+    pub online_id: Option<u64>,
+    pub team: Option<i32>,
+    pub score: Option<i32>,
+    pub goals: Option<i32>,
+    pub assists: Option<i32>,
+    pub saves: Option<i32>,
+    pub shots: Option<i32>,
+    pub bot: Option<bool>,
+
+    /// The split-screen index of the player, as seen in the player's `UniqueId::local_id`. The
+    /// primary player is 0 and split-screen guests are numbered from 1.
+    pub split_screen_index: u8,
+}
+
+impl<'a> PlayerStat<'a> {
+    fn from_properties(props: &'a [(&'a str, HeaderProp<'a>)]) -> Self {
+        let get = |key: &str| find_property(props, key);
+        let int = |key: &str| get(key).and_then(|x| x.as_i32());
+        PlayerStat {
+            name: get("Name").and_then(|x| x.as_str()),
+            platform: get("Platform").and_then(|x| match *x {
+                HeaderProp::Byte { kind, value } => Some(value.unwrap_or(kind)),
+                _ => None,
+            }),
+            online_id: get("OnlineID").and_then(|x| x.as_u64()),
+            team: int("Team"),
+            score: int("Score"),
+            goals: int("Goals"),
+            assists: int("Assists"),
+            saves: int("Saves"),
+            shots: int("Shots"),
+            bot: get("bBot").and_then(|x| x.as_bool()),
+            split_screen_index: 0,
+        }
+    }
+
+    /// Returns true if the network data's unique id belongs to the player. Players are matched
+    /// on their online id and split-screen index, as split-screen guests share an online id.
+    pub fn is_player(&self, unique_id: &UniqueId) -> bool {
+        self.online_id.is_some()
+            && self.online_id == unique_id.remote_id.online_id()
+            && self.split_screen_index == unique_id.local_id
+    }
+}
+
+/// Parses the split-screen index from the "(2)" suffix that the game appends to guest names
+fn split_screen_suffix(name: &str) -> u8 {
+    name.strip_suffix(')')
+        .and_then(|x| x.rfind('(').map(|i| &x[i + 1..]))
+        .and_then(|x| x.parse::<u8>().ok())
+        .map_or(0, |x| x.saturating_sub(1))
 }
 
 /// The frames decoded from the network data
-#[derive(Serialize, PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct NetworkFrames {
     pub frames: Vec<Frame>,
 }
 
+impl NetworkFrames {
+    /// Every updated attribute as the frame index, the frame's time, the updated actor, and the
+    /// attribute, in the order that they were decoded
+    pub fn updates(&self) -> impl Iterator<Item = (usize, f32, ActorId, &Attribute)> + '_ {
+        self.frames.iter().enumerate().flat_map(|(i, frame)| {
+            frame
+                .updated_actors
+                .iter()
+                .map(move |x| (i, frame.time, x.actor_id, &x.attribute))
+        })
+    }
+
+    /// The updated attributes of the actor. As actor ids are reused, the updates include those of
+    /// all actors that were assigned the id (see `FrameIndex::instance_history`).
+    pub fn updates_for(
+        &self,
+        actor_id: ActorId,
+    ) -> impl Iterator<Item = (usize, f32, ActorId, &Attribute)> + '_ {
+        self.updates().filter(move |x| x.2 == actor_id)
+    }
+
+    /// Every rigid body update (eg: of cars and the ball). See `RigidBody::decompress` for
+    /// converting the rigid body into unreal units. Boost amounts need the replay's net cache to
+    /// tell them apart from other attributes, so they are found with `FrameIndex::boost_amounts`.
+    pub fn rigid_bodies(&self) -> impl Iterator<Item = (usize, f32, ActorId, &RigidBody)> + '_ {
+        self.updates().filter_map(|(i, time, actor_id, x)| match x {
+            Attribute::RigidBody(body) => Some((i, time, actor_id, body)),
+            _ => None,
+        })
+    }
+}
+
 /// In Rocket league replays, there are tickmarks that typically represent a significant event in
 /// the game (eg. a goal). The tick mark is placed before the event happens so there is a ramp-up
 /// time. For instance, a tickmark could be at frame 396 for a goal at frame 441. At 30 fps, this
 /// would be 1.5 seconds of ramp up time.
-#[derive(Serialize, PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct TickMark<'a> {
     pub description: Cow<'a, str>,
     pub frame: i32,
@@ -63,13 +985,35 @@ pub struct TickMark<'a> {
 /// match up with the frames decoded from the network data.
 ///
 /// [wikipedia]: https://en.wikipedia.org/wiki/Key_frame#Video_compression
-#[derive(Serialize, PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct KeyFrame {
     pub time: f32,
     pub frame: i32,
     pub position: i32,
 }
 
+/// A keyframe resolved to where its frame starts in the network data (see
+/// `Replay::keyframe_offsets`). Frames are rarely byte aligned, so the frame starts at bit
+/// `bit_offset % 8` of the byte at `byte_offset`, counting from the least significant bit.
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct KeyFrameOffset {
+    pub time: f32,
+
+    /// The index of the keyframe's frame in the network frames
+    pub frame: usize,
+
+    /// The bit offset of the frame from the start of `Replay::network_data`
+    pub bit_offset: usize,
+
+    /// The byte of `Replay::network_data` that the frame starts in
+    pub byte_offset: usize,
+}
+
 /// All the interesting data are stored as properties in the header, properties such as:
 ///
 /// - When and who scored a goal
@@ -79,10 +1023,17 @@ pub struct KeyFrame {
 /// A property can be a number, string, or a more complex object such as an array containing
 /// additional properties.
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum HeaderProp<'a> {
     Array(Vec<Vec<(&'a str, HeaderProp<'a>)>>),
     Bool(bool),
-    Byte,
+
+    /// An enum value, such as the "OnlinePlatform_Steam" value of the "OnlinePlatform" kind. Older
+    /// replays may only contain the value, which is then stored as the kind.
+    Byte {
+        kind: &'a str,
+        value: Option<&'a str>,
+    },
     Float(f32),
     Int(i32),
     Name(Cow<'a, str>),
@@ -90,16 +1041,135 @@ pub enum HeaderProp<'a> {
     Str(Cow<'a, str>),
 }
 
+impl<'a> HeaderProp<'a> {
+    /// The elements of an array property, where each element is a list of properties
+    pub fn as_array(&self) -> Option<&[Vec<(&'a str, HeaderProp<'a>)>]> {
+        if let HeaderProp::Array(arr) = self {
+            Some(arr)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        if let HeaderProp::Bool(x) = *self {
+            Some(x)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        if let HeaderProp::Float(x) = *self {
+            Some(x)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        if let HeaderProp::Int(x) = *self {
+            Some(x)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        if let HeaderProp::QWord(x) = *self {
+            Some(x)
+        } else {
+            None
+        }
+    }
+
+    /// The text of either a string or name property
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            HeaderProp::Str(x) | HeaderProp::Name(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
 /// Debugging info stored in the replay if debugging is enabled.
-#[derive(Serialize, PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct DebugInfo<'a> {
     pub frame: i32,
     pub user: Cow<'a, str>,
     pub text: Cow<'a, str>,
 }
 
+/// How a string is encoded in the replay
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum TextEncoding {
+    /// Names, such as property names and types
+    Utf8,
+    Windows1252,
+    Utf16,
+}
+
+/// The original bytes of a string. Decoding a string is lossy when, for instance, a UTF-16
+/// string has unpaired surrogates or isn't null terminated, so the bytes of a string are needed
+/// to write the replay back out byte for byte.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct RawString<'a> {
+    /// The byte offset of the string's length prefix from the start of the replay
+    pub offset: i32,
+    pub encoding: TextEncoding,
+
+    /// The bytes of the string, including the null terminator but not the length prefix
+    pub bytes: &'a [u8],
+}
+
+impl<'a> RawString<'a> {
+    /// The text of the string as it is decoded by the parser
+    pub fn text(&self) -> Cow<'a, str> {
+        let decoded = match self.encoding {
+            TextEncoding::Utf8 => decode_str(self.bytes).map(Cow::Borrowed),
+            TextEncoding::Windows1252 => decode_windows1252(self.bytes),
+            TextEncoding::Utf16 => decode_utf16(self.bytes),
+        };
+        decoded.unwrap_or_default()
+    }
+
+    /// Returns true when encoding the decoded text reproduces the original bytes
+    pub fn is_lossless(&self) -> bool {
+        let (data, terminator) = match self.encoding {
+            TextEncoding::Utf16 => self.bytes.split_at(self.bytes.len().saturating_sub(2)),
+            _ => self.bytes.split_at(self.bytes.len().saturating_sub(1)),
+        };
+
+        if terminator.is_empty() || terminator.iter().any(|x| *x != 0) {
+            return false;
+        }
+
+        match self.encoding {
+            TextEncoding::Utf8 => std::str::from_utf8(data).is_ok(),
+            TextEncoding::Windows1252 => {
+                let text = WINDOWS_1252.decode_without_bom_handling(data).0;
+                WINDOWS_1252.encode(&text).0 == data
+            }
+            TextEncoding::Utf16 => UTF_16LE
+                .decode_without_bom_handling_and_without_replacement(data)
+                .is_some(),
+        }
+    }
+}
+
 /// A mapping between an object's name and its index. Largely redundant
-#[derive(Serialize, PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct ClassIndex<'a> {
     /// Should be equivalent to `Replay::objects(self.index)`
     pub class: &'a str,
@@ -110,7 +1180,10 @@ pub struct ClassIndex<'a> {
 
 /// A mapping between an object (that's an attribute)'s index and what its id will be when encoded
 /// in the network data
-#[derive(Serialize, PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct CacheProp {
     /// The index that the object appears in the `Replay::objects`
     pub object_ind: i32,
@@ -121,7 +1194,10 @@ pub struct CacheProp {
 }
 
 /// Contains useful information when decoding the network stream
-#[derive(Serialize, PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct ClassNetCache {
     /// The index that the object appears in the `Replay::objects`
     pub object_ind: i32,
@@ -142,6 +1218,7 @@ pub struct ClassNetCache {
 /// map structure because most replay parser do this, so we should be compliant and the data format
 /// doesn't dictate that the keys in a sequence of key value pairs must be distinct. It's true,
 /// JSON doesn't need the keys to be unique: <http://stackoverflow.com/q/21832701/433785>
+#[cfg(feature = "serde")]
 fn pair_vec<K, V, S>(inp: &[(K, V)], serializer: S) -> Result<S::Ok, S::Error>
 where
     K: Serialize,
@@ -149,17 +1226,32 @@ where
     S: Serializer,
 {
     let mut state = serializer.serialize_map(Some(inp.len()))?;
-    for &(ref key, ref val) in inp.iter() {
+    for (key, val) in inp.iter() {
         state.serialize_key(key)?;
         state.serialize_value(val)?;
     }
     state.end()
 }
 
+/// Key value pairs that serialize as a map in their original order
+#[cfg(feature = "serde")]
+pub(crate) struct PairMap<'b, K, V>(pub &'b [(K, V)]);
+
+#[cfg(feature = "serde")]
+impl<'b, K: Serialize, V: Serialize> Serialize for PairMap<'b, K, V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        pair_vec(self.0, serializer)
+    }
+}
+
 /// By default serde will generate a serialization method that writes out the enum as well as the
 /// enum value. Since header values are self describing in JSON, we do not need to serialize the
 /// enum type. This is slightly lossy as in the serialized format it will be ambiguous if a value
 /// is a `Name` or `Str`, as well as `Byte`, `Float`, `Int`, or `QWord`.
+#[cfg(feature = "serde")]
 impl<'a> Serialize for HeaderProp<'a> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -169,17 +1261,12 @@ impl<'a> Serialize for HeaderProp<'a> {
             HeaderProp::Array(ref x) => {
                 let mut state = serializer.serialize_seq(Some(x.len()))?;
                 for inner in x {
-                    // Look for a better way to do this instead of allocating the intermediate map
-                    let mut els = HashMap::new();
-                    for &(key, ref val) in inner.iter() {
-                        els.insert(key, val);
-                    }
-                    state.serialize_element(&els)?;
+                    state.serialize_element(&PairMap(inner))?;
                 }
                 state.end()
             }
             HeaderProp::Bool(ref x) => serializer.serialize_bool(*x),
-            HeaderProp::Byte => serializer.serialize_u8(0),
+            HeaderProp::Byte { .. } => serializer.serialize_u8(0),
             HeaderProp::Float(ref x) => serializer.serialize_f32(*x),
             HeaderProp::Int(ref x) => serializer.serialize_i32(*x),
             HeaderProp::QWord(ref x) => serializer.collect_str(x),
@@ -188,12 +1275,9 @@ impl<'a> Serialize for HeaderProp<'a> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "serde"))]
 mod tests {
     use super::*;
-    use serde;
-    use serde_json;
-    use std;
     use std::borrow::Cow;
 
     fn to_json<T: serde::Serialize>(input: &T) -> std::string::String {
@@ -222,6 +1306,58 @@ mod tests {
         assert!(actual.contains("\"frame\":1738"));
     }
 
+    #[test]
+    fn serialize_header_array_in_order() {
+        let data = vec![vec![
+            ("PlayerName", HeaderProp::Str(Cow::Borrowed("rusty"))),
+            ("Goals", HeaderProp::Int(2)),
+            ("Assists", HeaderProp::Int(1)),
+        ]];
+        let mut replay =
+            crate::ParserBuilder::new(include_bytes!("../assets/replays/good/rumble.replay"))
+                .never_parse_network_data()
+                .parse()
+                .unwrap();
+        replay.properties = vec![
+            ("TeamSize", HeaderProp::Int(3)),
+            ("Stats", HeaderProp::Array(data)),
+        ];
+
+        let expected = r#"{"TeamSize":3,"Stats":[{"PlayerName":"rusty","Goals":2,"Assists":1}]}"#;
+        let json = to_json(&replay);
+        assert!(json.contains(expected), "{}", json);
+
+        replay.sort_properties();
+        let expected = r#"{"Stats":[{"Assists":1,"Goals":2,"PlayerName":"rusty"}],"TeamSize":3}"#;
+        assert!(to_json(&replay).contains(expected));
+    }
+
+    #[test]
+    fn serialize_field_casing() {
+        let stat = PlayerStat {
+            name: Some("comagoosie"),
+            platform: None,
+            online_id: Some(1),
+            team: None,
+            score: None,
+            goals: None,
+            assists: None,
+            saves: None,
+            shots: None,
+            bot: None,
+            split_screen_index: 0,
+        };
+
+        let actual = to_json(&stat);
+        if cfg!(feature = "camel-case") {
+            assert!(actual.contains("\"onlineId\":1"));
+            assert!(actual.contains("\"splitScreenIndex\":0"));
+        } else {
+            assert!(actual.contains("\"online_id\":1"));
+            assert!(actual.contains("\"split_screen_index\":0"));
+        }
+    }
+
     #[test]
     fn serialize_header_bool() {
         assert_eq!(to_json(&HeaderProp::Bool(false)), "false");
@@ -230,7 +1366,13 @@ mod tests {
 
     #[test]
     fn serialize_header_numbers() {
-        assert_eq!(to_json(&HeaderProp::Byte), "0");
+        assert_eq!(
+            to_json(&HeaderProp::Byte {
+                kind: "OnlinePlatform",
+                value: Some("OnlinePlatform_Steam"),
+            }),
+            "0"
+        );
         assert_eq!(to_json(&HeaderProp::QWord(10)), "\"10\"");
         assert_eq!(to_json(&HeaderProp::Float(10.2)), "10.2");
         assert_eq!(to_json(&HeaderProp::Int(11)), "11");
@@ -0,0 +1,198 @@
+use crate::network::frame_decoder::{FrameBuffers, FrameSink};
+use crate::network::models::{ActorId, Frame, NewActor, UpdatedAttribute};
+use failure::Error;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeSeq;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+use std::ops::Range;
+
+/// An alternative representation of the decoded network frames. Instead of each frame owning
+/// three vectors (which amounts to millions of small allocations for a full replay), the actors
+/// of all frames are stored contiguously and frames reference their slice of the data. Attributes
+/// that carry heap data (strings, online loadouts, etc) still own their allocation.
+///
+/// When serialized, the arena has the same shape as `NetworkFrames`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FrameArena {
+    frames: Vec<ArenaFrame>,
+    new_actors: Vec<NewActor>,
+    deleted_actors: Vec<ActorId>,
+    updated_actors: Vec<UpdatedAttribute>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ArenaFrame {
+    time: f32,
+    delta: f32,
+    new_actors: Range<usize>,
+    deleted_actors: Range<usize>,
+    updated_actors: Range<usize>,
+}
+
+/// A frame borrowed from a `FrameArena`. Mirrors `Frame` with slices in place of vectors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct FrameRef<'a> {
+    /// The time in seconds that the frame is recorded at
+    pub time: f32,
+
+    /// Time difference between previous frame
+    pub delta: f32,
+
+    /// List of new actors seen during the frame
+    pub new_actors: &'a [NewActor],
+
+    /// List of actor id's that are deleted / destroyed
+    pub deleted_actors: &'a [ActorId],
+
+    /// List of properties updated on the actors
+    pub updated_actors: &'a [UpdatedAttribute],
+}
+
+impl<'a> FrameRef<'a> {
+    /// Copies the borrowed frame into an owned `Frame`
+    pub fn to_frame(&self) -> Frame {
+        Frame {
+            time: self.time,
+            delta: self.delta,
+            new_actors: self.new_actors.iter().copied().collect(),
+            deleted_actors: self.deleted_actors.iter().copied().collect(),
+            updated_actors: self.updated_actors.iter().cloned().collect(),
+        }
+    }
+}
+
+impl FrameArena {
+    /// The number of frames in the arena
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns the frame at the given index
+    pub fn get(&self, index: usize) -> Option<FrameRef<'_>> {
+        self.frames.get(index).map(|x| self.resolve(x))
+    }
+
+    /// Iterates through the frames in the order they were decoded
+    pub fn iter(&self) -> impl Iterator<Item = FrameRef<'_>> + '_ {
+        self.frames.iter().map(move |x| self.resolve(x))
+    }
+
+    /// Converts the arena into the traditional one vector per frame representation
+    pub fn to_frames(&self) -> Vec<Frame> {
+        self.iter().map(|x| x.to_frame()).collect()
+    }
+
+    fn resolve(&self, frame: &ArenaFrame) -> FrameRef<'_> {
+        FrameRef {
+            time: frame.time,
+            delta: frame.delta,
+            new_actors: &self.new_actors[frame.new_actors.clone()],
+            deleted_actors: &self.deleted_actors[frame.deleted_actors.clone()],
+            updated_actors: &self.updated_actors[frame.updated_actors.clone()],
+        }
+    }
+}
+
+fn append<T>(dst: &mut Vec<T>, src: &mut Vec<T>) -> Range<usize> {
+    let start = dst.len();
+    dst.append(src);
+    start..dst.len()
+}
+
+impl FrameSink for FrameArena {
+    fn frame(&mut self, time: f32, delta: f32, buffers: &mut FrameBuffers) -> Result<(), Error> {
+        let new_actors = append(&mut self.new_actors, &mut buffers.new_actors);
+        let deleted_actors = append(&mut self.deleted_actors, &mut buffers.deleted_actors);
+        let updated_actors = append(&mut self.updated_actors, &mut buffers.updated_actors);
+        self.frames.push(ArenaFrame {
+            time,
+            delta,
+            new_actors,
+            deleted_actors,
+            updated_actors,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for FrameArena {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct NetworkFrames<'a> {
+            frames: ArenaFrames<'a>,
+        }
+
+        struct ArenaFrames<'a>(&'a FrameArena);
+
+        impl<'a> Serialize for ArenaFrames<'a> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let mut state = serializer.serialize_seq(Some(self.0.len()))?;
+                for frame in self.0.iter() {
+                    state.serialize_element(&frame)?;
+                }
+                state.end()
+            }
+        }
+
+        NetworkFrames {
+            frames: ArenaFrames(self),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ParserBuilder;
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_arena_matches_frames() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let (arena_replay, arena) = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse_arena()
+            .unwrap();
+
+        let arena = arena.unwrap();
+        let network = replay.network_frames.as_ref().unwrap();
+        assert!(arena_replay.network_frames.is_none());
+        assert_eq!(arena.len(), network.frames.len());
+        assert_eq!(arena.to_frames(), network.frames);
+        assert_eq!(arena.get(10).unwrap().to_frame(), network.frames[10]);
+        assert!(arena.get(arena.len()).is_none());
+        assert_eq!(
+            serde_json::to_string(&arena).unwrap(),
+            serde_json::to_string(network).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_arena_skip_network() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let (_, arena) = ParserBuilder::new(&data[..])
+            .never_parse_network_data()
+            .parse_arena()
+            .unwrap();
+        assert!(arena.is_none());
+    }
+}
@@ -0,0 +1,847 @@
+use crate::errors::BuildError;
+use crate::network::attributes::*;
+use crate::network::bit_writer::BitWriter;
+use crate::network::models::QUATERNION_BITS;
+use crate::network::{CompressedRotation, Rotation, SpawnTrajectory, Trajectory, Vector};
+use crate::version::ReplayVersion;
+use encoding_rs::WINDOWS_1252;
+
+/// The attribute tag that an attribute is decoded with
+pub(crate) fn attribute_tag(attr: &Attribute) -> AttributeTag {
+    match *attr {
+        Attribute::Boolean(_) => AttributeTag::Boolean,
+        Attribute::Byte(_) => AttributeTag::Byte,
+        Attribute::AppliedDamage(..) => AttributeTag::AppliedDamage,
+        Attribute::DamageState(..) => AttributeTag::DamageState,
+        Attribute::CamSettings(_) => AttributeTag::CamSettings,
+        Attribute::ClubColors(_) => AttributeTag::ClubColors,
+        Attribute::Demolish(_) => AttributeTag::Demolish,
+        Attribute::DemolishExtended(_) => AttributeTag::DemolishExtended,
+        Attribute::Enum(_) => AttributeTag::Enum,
+        Attribute::Explosion(_) => AttributeTag::Explosion,
+        Attribute::ExtendedExplosion(..) => AttributeTag::ExtendedExplosion,
+        Attribute::FlaggedByte(..) => AttributeTag::FlaggedByte,
+        Attribute::Flagged(..) => AttributeTag::Flagged,
+        Attribute::Float(_) => AttributeTag::Float,
+        Attribute::GameMode(..) => AttributeTag::GameMode,
+        Attribute::Int(_) => AttributeTag::Int,
+        Attribute::Int64(_) => AttributeTag::Int64,
+        Attribute::Loadout(_) => AttributeTag::Loadout,
+        Attribute::TeamLoadout(_) => AttributeTag::TeamLoadout,
+        Attribute::Location(_) => AttributeTag::Location,
+        Attribute::MusicStinger(_) => AttributeTag::MusicStinger,
+        Attribute::PlayerHistoryKey(_) => AttributeTag::PlayerHistoryKey,
+        Attribute::Pickup(_) => AttributeTag::Pickup,
+        Attribute::PickupNew(_) => AttributeTag::PickupNew,
+        Attribute::QWord(_) => AttributeTag::QWord,
+        Attribute::Welded(_) => AttributeTag::Welded,
+        Attribute::Title(..) => AttributeTag::Title,
+        Attribute::TeamPaint(_) => AttributeTag::TeamPaint,
+        Attribute::RigidBody(_) => AttributeTag::RigidBody,
+        Attribute::String(_) => AttributeTag::String,
+        Attribute::UniqueId(_) => AttributeTag::UniqueId,
+        Attribute::Reservation(_) => AttributeTag::Reservation,
+        Attribute::PartyLeader(_) => AttributeTag::PartyLeader,
+        Attribute::PrivateMatch(_) => AttributeTag::PrivateMatchSettings,
+        Attribute::LoadoutOnline(_) => AttributeTag::LoadoutOnline,
+        Attribute::LoadoutsOnline(_) => AttributeTag::LoadoutsOnline,
+        Attribute::StatEvent(..) => AttributeTag::StatEvent,
+        Attribute::ReplicatedBoost(_) => AttributeTag::ReplicatedBoost,
+    }
+}
+
+/// Writes attributes in the same format that the `AttributeDecoder` reads them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AttributeEncoder {
+    version: ReplayVersion,
+    product_decoder: ProductValueDecoder,
+}
+
+impl AttributeEncoder {
+    pub fn new(version: ReplayVersion, product_decoder: ProductValueDecoder) -> Self {
+        AttributeEncoder {
+            version,
+            product_decoder,
+        }
+    }
+
+    fn net_version(&self) -> i32 {
+        self.version.net
+    }
+
+    pub fn encode(&self, attr: &Attribute, bits: &mut BitWriter) -> Result<(), BuildError> {
+        match attr {
+            Attribute::Boolean(x) => bits.write_bit(*x),
+            Attribute::Byte(x) => bits.write_u8(*x),
+            Attribute::AppliedDamage(a, vector, b, c) => {
+                bits.write_u8(*a);
+                encode_vector(bits, vector, self.net_version())?;
+                bits.write_u32(*b);
+                bits.write_u32(*c);
+            }
+            Attribute::DamageState(da, db, dc, dd, de, df) => {
+                bits.write_u8(*da);
+                bits.write_bit(*db);
+                bits.write_u32(*dc);
+                encode_vector(bits, dd, self.net_version())?;
+                bits.write_bit(*de);
+                bits.write_bit(*df);
+            }
+            Attribute::CamSettings(cam) => {
+                bits.write_f32(cam.fov);
+                bits.write_f32(cam.height);
+                bits.write_f32(cam.angle);
+                bits.write_f32(cam.distance);
+                bits.write_f32(cam.swiftness);
+                bits.write_f32(cam.swivel);
+                match (self.version.at_least(868, 20, 0), cam.transition) {
+                    (true, Some(transition)) => bits.write_f32(transition),
+                    (false, None) => {}
+                    _ => return Err(BuildError::Incompatible("cam settings transition")),
+                }
+            }
+            Attribute::ClubColors(colors) => {
+                bits.write_bit(colors.blue_flag);
+                bits.write_u8(colors.blue_color);
+                bits.write_bit(colors.orange_flag);
+                bits.write_u8(colors.orange_color);
+            }
+            Attribute::Demolish(demo) => {
+                bits.write_bit(demo.attacker_flag);
+                bits.write_u32(demo.attacker_actor_id);
+                bits.write_bit(demo.victim_flag);
+                bits.write_u32(demo.victim_actor_id);
+                encode_vector(bits, &demo.attack_velocity, self.net_version())?;
+                encode_vector(bits, &demo.victim_velocity, self.net_version())?;
+            }
+            Attribute::DemolishExtended(demo) => {
+                bits.write_bit(demo.attacker_pri_flag);
+                bits.write_u32(demo.attacker_pri_actor_id);
+                bits.write_bit(demo.self_demo_flag);
+                bits.write_u32(demo.self_demo_actor_id);
+                bits.write_bit(demo.self_demolish);
+                bits.write_bit(demo.goal_explosion_owner_flag);
+                bits.write_u32(demo.goal_explosion_owner_actor_id);
+                bits.write_bit(demo.attacker_flag);
+                bits.write_u32(demo.attacker_actor_id);
+                bits.write_bit(demo.victim_flag);
+                bits.write_u32(demo.victim_actor_id);
+                encode_vector(bits, &demo.attack_velocity, self.net_version())?;
+                encode_vector(bits, &demo.victim_velocity, self.net_version())?;
+            }
+            Attribute::Enum(x) => {
+                check_bits(u64::from(*x), 11, "enum")?;
+                bits.write_u32_bits(u32::from(*x), 11);
+            }
+            Attribute::Explosion(explosion) => self.encode_explosion(bits, explosion)?,
+            Attribute::ExtendedExplosion(explosion, ea, eb) => {
+                self.encode_explosion(bits, explosion)?;
+                bits.write_bit(*ea);
+                bits.write_u32(*eb);
+            }
+            Attribute::FlaggedByte(b, data) => {
+                bits.write_bit(*b);
+                bits.write_u8(*data);
+            }
+            Attribute::Flagged(on, val) => {
+                bits.write_bit(*on);
+                bits.write_u32(*val);
+            }
+            Attribute::Float(x) => bits.write_f32(*x),
+            Attribute::GameMode(init, x) => {
+                let expected = if self.version.at_least(868, 12, 0) {
+                    8
+                } else {
+                    2
+                };
+
+                if *init != expected {
+                    return Err(BuildError::Incompatible("game mode"));
+                }
+
+                check_bits(u64::from(*x), i32::from(*init), "game mode")?;
+                bits.write_u32_bits(u32::from(*x), i32::from(*init));
+            }
+            Attribute::Int(x) => bits.write_i32(*x),
+            Attribute::Int64(x) => bits.write_i64(*x),
+            Attribute::Loadout(loadout) => encode_loadout(bits, loadout)?,
+            Attribute::TeamLoadout(loadout) => {
+                encode_loadout(bits, &loadout.blue)?;
+                encode_loadout(bits, &loadout.orange)?;
+            }
+            Attribute::Location(vector) => encode_vector(bits, vector, self.net_version())?,
+            Attribute::MusicStinger(stinger) => {
+                bits.write_bit(stinger.flag);
+                bits.write_u32(stinger.cue);
+                bits.write_u8(stinger.trigger);
+            }
+            Attribute::PlayerHistoryKey(x) => {
+                check_bits(u64::from(*x), 14, "player history key")?;
+                bits.write_u32_bits(u32::from(*x), 14);
+            }
+            Attribute::Pickup(pickup) => {
+                write_opt(bits, pickup.instigator_id, BitWriter::write_u32);
+                bits.write_bit(pickup.picked_up);
+            }
+            Attribute::PickupNew(pickup) => {
+                write_opt(bits, pickup.instigator_id, BitWriter::write_u32);
+                bits.write_u8(pickup.picked_up);
+            }
+            Attribute::QWord(x) => bits.write_u64(*x),
+            Attribute::Welded(welded) => {
+                bits.write_bit(welded.active);
+                bits.write_u32(welded.actor_id);
+                encode_vector(bits, &welded.offset, self.net_version())?;
+                bits.write_f32(welded.mass);
+                encode_rotation(bits, &welded.rotation);
+            }
+            Attribute::Title(u1, u2, u3, u4, u5, u6, u7, u8) => {
+                bits.write_bit(*u1);
+                bits.write_bit(*u2);
+                bits.write_u32(*u3);
+                bits.write_u32(*u4);
+                bits.write_u32(*u5);
+                bits.write_u32(*u6);
+                bits.write_u32(*u7);
+                bits.write_bit(*u8);
+            }
+            Attribute::TeamPaint(paint) => {
+                bits.write_u8(paint.team);
+                bits.write_u8(paint.primary_color);
+                bits.write_u8(paint.accent_color);
+                bits.write_u32(paint.primary_finish);
+                bits.write_u32(paint.accent_finish);
+            }
+            Attribute::RigidBody(body) => self.encode_rigid_body(bits, body)?,
+            Attribute::String(x) => encode_text(bits, x),
+            Attribute::UniqueId(id) => self.encode_unique_id(bits, id)?,
+            Attribute::Reservation(reservation) => {
+                check_bits(u64::from(reservation.number), 3, "reservation number")?;
+                bits.write_u32_bits(reservation.number, 3);
+                self.encode_unique_id(bits, &reservation.unique_id)?;
+                match (reservation.unique_id.system_id, &reservation.name) {
+                    (0, None) => {}
+                    (x, Some(name)) if x != 0 => encode_text(bits, name),
+                    _ => return Err(BuildError::InvalidValue("reservation name")),
+                }
+
+                bits.write_bit(reservation.unknown1);
+                bits.write_bit(reservation.unknown2);
+                match (self.version.at_least(868, 12, 0), reservation.unknown3) {
+                    (true, Some(x)) => {
+                        check_bits(u64::from(x), 6, "reservation unknown3")?;
+                        bits.write_u32_bits(u32::from(x), 6);
+                    }
+                    (false, None) => {}
+                    _ => return Err(BuildError::Incompatible("reservation")),
+                }
+            }
+            Attribute::PartyLeader(None) => bits.write_u8(0),
+            Attribute::PartyLeader(Some(id)) => {
+                if id.system_id == 0 {
+                    return Err(BuildError::InvalidValue("party leader system id"));
+                }
+                self.encode_unique_id(bits, id)?;
+            }
+            Attribute::PrivateMatch(settings) => {
+                encode_text(bits, &settings.mutators);
+                bits.write_u32(settings.joinable_by);
+                bits.write_u32(settings.max_players);
+                encode_text(bits, &settings.game_name);
+                encode_text(bits, &settings.password);
+                bits.write_bit(settings.flag);
+            }
+            Attribute::LoadoutOnline(loadout) => self.encode_online_loadout(bits, loadout)?,
+            Attribute::LoadoutsOnline(loadouts) => {
+                self.encode_online_loadout(bits, &loadouts.blue)?;
+                self.encode_online_loadout(bits, &loadouts.orange)?;
+                bits.write_bit(loadouts.unknown1);
+                bits.write_bit(loadouts.unknown2);
+            }
+            Attribute::StatEvent(u1, id) => {
+                bits.write_bit(*u1);
+                bits.write_u32(*id);
+            }
+            Attribute::ReplicatedBoost(boost) => {
+                bits.write_u8(boost.grant_count);
+                bits.write_u8(boost.boost_amount);
+                bits.write_u8(boost.component_state[0]);
+                bits.write_u8(boost.component_state[1]);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn encode_explosion(
+        &self,
+        bits: &mut BitWriter,
+        explosion: &Explosion,
+    ) -> Result<(), BuildError> {
+        bits.write_bit(explosion.flag);
+        bits.write_u32(explosion.actor_id);
+        encode_vector(bits, &explosion.location, self.net_version())
+    }
+
+    fn encode_rigid_body(&self, bits: &mut BitWriter, body: &RigidBody) -> Result<(), BuildError> {
+        let net_version = self.net_version();
+        bits.write_bit(body.sleeping);
+        encode_vector(bits, &body.location, net_version)?;
+        match (net_version >= 7, body.rotation) {
+            (false, CompressedRotation::Rotator { pitch, yaw, roll }) => {
+                bits.write_u32_bits(u32::from(pitch), 16);
+                bits.write_u32_bits(u32::from(yaw), 16);
+                bits.write_u32_bits(u32::from(roll), 16);
+            }
+            (true, CompressedRotation::Quaternion { largest, a, b, c }) => {
+                if largest > 3 || (a | b | c) >> QUATERNION_BITS != 0 {
+                    return Err(BuildError::InvalidValue("rigid body rotation"));
+                }
+
+                bits.write_u32_bits(u32::from(largest), 2);
+                bits.write_u32_bits(a, QUATERNION_BITS);
+                bits.write_u32_bits(b, QUATERNION_BITS);
+                bits.write_u32_bits(c, QUATERNION_BITS);
+            }
+            _ => return Err(BuildError::Incompatible("rigid body rotation")),
+        }
+
+        match (body.sleeping, &body.linear_velocity, &body.angular_velocity) {
+            (true, None, None) => Ok(()),
+            (false, Some(linear), Some(angular)) => {
+                encode_vector(bits, linear, net_version)?;
+                encode_vector(bits, angular, net_version)
+            }
+            _ => Err(BuildError::InvalidValue("rigid body velocity")),
+        }
+    }
+
+    fn encode_unique_id(&self, bits: &mut BitWriter, id: &UniqueId) -> Result<(), BuildError> {
+        let system_id = match id.remote_id {
+            RemoteId::SplitScreen(_) => 0,
+            RemoteId::Steam(_) => 1,
+            RemoteId::PlayStation(_) => 2,
+            RemoteId::Xbox(_) => 4,
+            RemoteId::Switch(_) => 6,
+            RemoteId::PsyNet(_) => 7,
+        };
+
+        if system_id != id.system_id {
+            return Err(BuildError::InvalidValue("unique id system id"));
+        }
+
+        bits.write_u8(system_id);
+        match &id.remote_id {
+            RemoteId::SplitScreen(x) => {
+                check_bits(u64::from(*x), 24, "split screen id")?;
+                bits.write_u32_bits(*x, 24);
+            }
+            RemoteId::Steam(x) | RemoteId::Xbox(x) => bits.write_u64(*x),
+            RemoteId::PlayStation(ps4) => {
+                let (name, _, had_errors) = WINDOWS_1252.encode(&ps4.name);
+                if had_errors || name.len() > 16 || name.contains(&0) {
+                    return Err(BuildError::InvalidValue("PS4 name"));
+                }
+
+                let mut name_bytes = [0u8; 16];
+                name_bytes[..name.len()].copy_from_slice(&name);
+                bits.write_bytes(&name_bytes);
+
+                let to_read = if self.net_version() >= 1 { 16 } else { 8 };
+                if ps4.unknown1.len() != to_read {
+                    return Err(BuildError::Incompatible("PS4 id"));
+                }
+
+                bits.write_bytes(&ps4.unknown1);
+                bits.write_u64(ps4.online_id);
+            }
+            RemoteId::Switch(switch) => {
+                if switch.unknown1.len() != 24 {
+                    return Err(BuildError::InvalidValue("Switch id"));
+                }
+
+                bits.write_u64(switch.online_id);
+                bits.write_bytes(&switch.unknown1);
+            }
+            RemoteId::PsyNet(psy) => {
+                // Decoding falls back to the other era's layout, so either is encoded as is
+                if psy.unknown1.len() != 24 && !psy.unknown1.is_empty() {
+                    return Err(BuildError::Incompatible("PsyNet id"));
+                }
+
+                bits.write_u64(psy.online_id);
+                bits.write_bytes(&psy.unknown1);
+            }
+        }
+
+        bits.write_u8(id.local_id);
+        Ok(())
+    }
+
+    fn encode_online_loadout(
+        &self,
+        bits: &mut BitWriter,
+        loadout: &[Vec<Product>],
+    ) -> Result<(), BuildError> {
+        if loadout.len() > usize::from(u8::MAX) {
+            return Err(BuildError::InvalidValue("online loadout"));
+        }
+
+        bits.write_u8(loadout.len() as u8);
+        for products in loadout {
+            if products.len() > usize::from(u8::MAX) {
+                return Err(BuildError::InvalidValue("online loadout"));
+            }
+
+            bits.write_u8(products.len() as u8);
+            for product in products {
+                self.encode_product(bits, product)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn encode_product(&self, bits: &mut BitWriter, product: &Product) -> Result<(), BuildError> {
+        if !self
+            .product_decoder
+            .decodes_as(product.object_ind, &product.value)
+        {
+            return Err(BuildError::InvalidValue("product object index"));
+        }
+
+        let new_color = self.version.at_least(868, 23, 8);
+        let new_paint = self.version.at_least(868, 18, 0);
+
+        bits.write_bit(product.unknown);
+        bits.write_u32(product.object_ind);
+        match &product.value {
+            ProductValue::NoColor if !new_color => bits.write_bit(false),
+            ProductValue::OldColor(x) if !new_color => {
+                bits.write_bit(true);
+                check_bits(u64::from(*x), 31, "product color")?;
+                bits.write_u32_bits(*x, 31);
+            }
+            ProductValue::NewColor(x) if new_color => bits.write_u32(*x),
+            ProductValue::OldPaint(x) | ProductValue::OldTeamEdition(x) if !new_paint => {
+                if *x >= 14 {
+                    return Err(BuildError::InvalidValue("product paint"));
+                }
+                bits.write_bits_max(*x, 4, 14);
+            }
+            ProductValue::NewPaint(x)
+            | ProductValue::NewTeamEdition(x)
+            | ProductValue::SpecialEdition(x) => {
+                if !new_paint && !matches!(product.value, ProductValue::SpecialEdition(_)) {
+                    return Err(BuildError::Incompatible("product"));
+                }
+                check_bits(u64::from(*x), 31, "product")?;
+                bits.write_u32_bits(*x, 31);
+            }
+            ProductValue::Title(x) => encode_text(bits, x),
+            ProductValue::Absent => {}
+            _ => return Err(BuildError::Incompatible("product")),
+        }
+
+        Ok(())
+    }
+}
+
+fn check_bits(value: u64, bits: i32, desc: &'static str) -> Result<(), BuildError> {
+    if value >> bits != 0 {
+        Err(BuildError::InvalidValue(desc))
+    } else {
+        Ok(())
+    }
+}
+
+fn write_opt<T: Copy, F>(bits: &mut BitWriter, value: Option<T>, mut f: F)
+where
+    F: FnMut(&mut BitWriter, T),
+{
+    bits.write_bit(value.is_some());
+    if let Some(x) = value {
+        f(bits, x);
+    }
+}
+
+pub(crate) fn encode_vector(
+    bits: &mut BitWriter,
+    vector: &Vector,
+    net_version: i32,
+) -> Result<(), BuildError> {
+    let max = if net_version >= 7 { 22 } else { 20 };
+    if vector.bias < 2 || vector.bias.count_ones() != 1 {
+        return Err(BuildError::InvalidValue("vector bias"));
+    }
+
+    let size_bits = vector.bias.trailing_zeros() - 1;
+    if size_bits >= max {
+        return Err(BuildError::InvalidValue("vector bias"));
+    }
+
+    let bit_limit = (size_bits + 2) as i32;
+    bits.write_bits_max(size_bits, 5, max as i32);
+    for &d in &[vector.dx, vector.dy, vector.dz] {
+        if d < 0 {
+            return Err(BuildError::InvalidValue("vector"));
+        }
+        check_bits(d as u64, bit_limit, "vector")?;
+        bits.write_u32_bits(d as u32, bit_limit);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn encode_rotation(bits: &mut BitWriter, rotation: &Rotation) {
+    write_opt(bits, rotation.yaw, BitWriter::write_i8);
+    write_opt(bits, rotation.pitch, BitWriter::write_i8);
+    write_opt(bits, rotation.roll, BitWriter::write_i8);
+}
+
+/// Writes the trajectory of a new actor. Parts of the trajectory that aren't encoded for the
+/// spawn type are ignored, and missing parts that are required are written as zeros.
+pub(crate) fn encode_trajectory(
+    bits: &mut BitWriter,
+    trajectory: &Trajectory,
+    spawn: SpawnTrajectory,
+    net_version: i32,
+) -> Result<(), BuildError> {
+    let zero = Vector {
+        bias: 2,
+        dx: 2,
+        dy: 2,
+        dz: 2,
+    };
+
+    match spawn {
+        SpawnTrajectory::None => Ok(()),
+        SpawnTrajectory::Location => encode_vector(
+            bits,
+            trajectory.location.as_ref().unwrap_or(&zero),
+            net_version,
+        ),
+        SpawnTrajectory::LocationAndRotation => {
+            encode_vector(
+                bits,
+                trajectory.location.as_ref().unwrap_or(&zero),
+                net_version,
+            )?;
+            let rotation = trajectory.rotation.unwrap_or(Rotation {
+                yaw: None,
+                pitch: None,
+                roll: None,
+            });
+            encode_rotation(bits, &rotation);
+            Ok(())
+        }
+    }
+}
+
+/// Writes text in the format read by the attribute decoder: Windows-1252 when possible, else
+/// UTF-16.
+pub(crate) fn encode_text(bits: &mut BitWriter, text: &str) {
+    if text.is_empty() {
+        bits.write_i32(0);
+        return;
+    }
+
+    let (data, _, had_errors) = WINDOWS_1252.encode(text);
+    if !had_errors {
+        bits.write_i32(data.len() as i32 + 1);
+        bits.write_bytes(&data);
+        bits.write_u8(0);
+    } else {
+        let units: Vec<u16> = text.encode_utf16().collect();
+        bits.write_i32(-(units.len() as i32 + 1));
+        for unit in units {
+            bits.write_u32_bits(u32::from(unit), 16);
+        }
+        bits.write_u32_bits(0, 16);
+    }
+}
+
+fn encode_loadout(bits: &mut BitWriter, loadout: &Loadout) -> Result<(), BuildError> {
+    let version = loadout.version;
+    bits.write_u8(version);
+    bits.write_u32(loadout.body);
+    bits.write_u32(loadout.decal);
+    bits.write_u32(loadout.wheels);
+    bits.write_u32(loadout.rocket_trail);
+    bits.write_u32(loadout.antenna);
+    bits.write_u32(loadout.topper);
+    bits.write_u32(loadout.unknown1);
+
+    let fields = [
+        (version > 10, loadout.unknown2),
+        (version >= 16, loadout.engine_audio),
+        (version >= 16, loadout.trail),
+        (version >= 16, loadout.goal_explosion),
+        (version >= 17, loadout.banner),
+        (version >= 19, loadout.unknown3),
+    ];
+
+    for &(present, value) in &fields {
+        match (present, value) {
+            (true, Some(x)) => bits.write_u32(x),
+            (false, None) => {}
+            _ => return Err(BuildError::InvalidValue("loadout")),
+        }
+    }
+
+    if version >= 22 {
+        bits.write_u32(0);
+        bits.write_u32(0);
+        bits.write_u32(0);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::AttributeError;
+    use crate::network::bit_reader::BitGet;
+    use crate::network::ObjectId;
+    use std::collections::HashMap;
+
+    fn encoder_decoder(version: ReplayVersion) -> (AttributeEncoder, AttributeDecoder) {
+        let mut name_obj_ind = HashMap::new();
+        name_obj_ind.insert("TAGame.ProductAttribute_UserColor_TA", ObjectId(1));
+        name_obj_ind.insert("TAGame.ProductAttribute_Painted_TA", ObjectId(2));
+        name_obj_ind.insert("TAGame.ProductAttribute_TitleID_TA", ObjectId(3));
+        name_obj_ind.insert("TAGame.ProductAttribute_SpecialEdition_TA", ObjectId(4));
+        name_obj_ind.insert("TAGame.ProductAttribute_TeamEdition_TA", ObjectId(5));
+        let product_decoder = ProductValueDecoder::create(version, &name_obj_ind);
+        (
+            AttributeEncoder::new(version, product_decoder),
+            AttributeDecoder::new(version, product_decoder),
+        )
+    }
+
+    fn assert_round_trip(version: ReplayVersion, attr: Attribute) {
+        let (encoder, mut decoder) = encoder_decoder(version);
+        let mut bits = BitWriter::new();
+        encoder.encode(&attr, &mut bits).unwrap();
+        let data = bits.into_bytes();
+        let mut bits = BitGet::new(&data[..]);
+        let decoded = decoder.decode(attribute_tag(&attr), &mut bits).unwrap();
+        assert_eq!(decoded, attr);
+    }
+
+    fn vector(d: i32) -> Vector {
+        Vector {
+            bias: 1 << 10,
+            dx: d,
+            dy: d + 1,
+            dz: d + 2,
+        }
+    }
+
+    fn steam_id() -> UniqueId {
+        UniqueId {
+            system_id: 1,
+            remote_id: RemoteId::Steam(76561198101748375),
+            local_id: 0,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_simple_attributes() {
+        let version = ReplayVersion::new(868, 24, 10);
+        assert_round_trip(version, Attribute::Boolean(true));
+        assert_round_trip(version, Attribute::Byte(200));
+        assert_round_trip(version, Attribute::Enum(513));
+        assert_round_trip(version, Attribute::FlaggedByte(true, 3));
+        assert_round_trip(version, Attribute::Flagged(false, 40));
+        assert_round_trip(version, Attribute::Float(-1.25));
+        assert_round_trip(version, Attribute::Int(-7));
+        assert_round_trip(version, Attribute::Int64(-7_000_000_000));
+        assert_round_trip(version, Attribute::QWord(u64::MAX));
+        assert_round_trip(version, Attribute::Location(vector(1000)));
+        assert_round_trip(version, Attribute::String("comagoosie".into()));
+        assert_round_trip(version, Attribute::String("日本語".into()));
+        assert_round_trip(version, Attribute::String("".into()));
+        assert_round_trip(version, Attribute::StatEvent(false, 9));
+    }
+
+    #[test]
+    fn test_round_trip_psynet_ids() {
+        let psynet = |unknown1: Vec<u8>| {
+            Attribute::UniqueId(UniqueId {
+                system_id: 7,
+                remote_id: RemoteId::PsyNet(PsyNetId {
+                    online_id: 15633594671552264637,
+                    unknown1,
+                }),
+                local_id: 0,
+            })
+        };
+
+        let long = ReplayVersion::new(868, 20, 7);
+        let compact = ReplayVersion::new(868, 24, 10);
+        assert_round_trip(long, psynet(vec![0xab; 24]));
+        assert_round_trip(compact, psynet(Vec::new()));
+
+        // Ids in the other era's layout are decoded with that layout
+        assert_round_trip(long, psynet(Vec::new()));
+        assert_round_trip(compact, psynet(vec![0xab; 24]));
+
+        let (encoder, mut decoder) = encoder_decoder(compact);
+        let mut bits = BitWriter::new();
+        assert!(encoder.encode(&psynet(vec![0xab; 20]), &mut bits).is_err());
+
+        // Neither layout is followed by a plausible local id
+        let mut bits = BitWriter::new();
+        bits.write_u8(7);
+        bits.write_u64(1);
+        bits.write_u8(200);
+        let data = bits.into_bytes();
+        let mut bits = BitGet::new(&data[..]);
+        let err = decoder
+            .decode(AttributeTag::UniqueId, &mut bits)
+            .unwrap_err();
+        assert_eq!(err, AttributeError::NotEnoughDataFor("PsyNet ID Unknown"));
+    }
+
+    #[test]
+    fn test_round_trip_structured_attributes() {
+        let version = ReplayVersion::new(868, 24, 10);
+        assert_round_trip(
+            version,
+            Attribute::RigidBody(RigidBody {
+                sleeping: true,
+                location: vector(12),
+                rotation: CompressedRotation::Quaternion {
+                    largest: 3,
+                    a: 1,
+                    b: 2,
+                    c: (1 << 18) - 1,
+                },
+                linear_velocity: None,
+                angular_velocity: None,
+            }),
+        );
+        assert_round_trip(
+            version,
+            Attribute::Demolish(Demolish {
+                attacker_flag: true,
+                attacker_actor_id: 4,
+                victim_flag: true,
+                victim_actor_id: 5,
+                attack_velocity: vector(100),
+                victim_velocity: vector(200),
+            }),
+        );
+        assert_round_trip(
+            version,
+            Attribute::DemolishExtended(DemolishExtended {
+                attacker_pri_flag: true,
+                attacker_pri_actor_id: 6,
+                self_demo_flag: false,
+                self_demo_actor_id: 0,
+                self_demolish: false,
+                goal_explosion_owner_flag: true,
+                goal_explosion_owner_actor_id: 6,
+                attacker_flag: true,
+                attacker_actor_id: 4,
+                victim_flag: true,
+                victim_actor_id: 5,
+                attack_velocity: vector(100),
+                victim_velocity: vector(200),
+            }),
+        );
+        assert_round_trip(
+            version,
+            Attribute::Explosion(Explosion {
+                flag: false,
+                actor_id: 12,
+                location: vector(300),
+            }),
+        );
+        assert_round_trip(
+            version,
+            Attribute::PickupNew(PickupNew {
+                instigator_id: Some(7),
+                picked_up: 3,
+            }),
+        );
+        assert_round_trip(
+            version,
+            Attribute::ReplicatedBoost(ReplicatedBoost {
+                grant_count: 4,
+                boost_amount: 85,
+                component_state: [1, 0],
+            }),
+        );
+        assert_round_trip(version, Attribute::UniqueId(steam_id()));
+        assert_round_trip(version, Attribute::PartyLeader(None));
+        assert_round_trip(version, Attribute::PartyLeader(Some(steam_id())));
+        assert_round_trip(
+            version,
+            Attribute::Reservation(Reservation {
+                number: 1,
+                unique_id: steam_id(),
+                name: Some("comagoosie".into()),
+                unknown1: false,
+                unknown2: true,
+                unknown3: Some(5),
+            }),
+        );
+        assert_round_trip(
+            version,
+            Attribute::PrivateMatch(PrivateMatchSettings {
+                mutators: "".into(),
+                joinable_by: 1,
+                max_players: 6,
+                game_name: "game".into(),
+                password: "pass".into(),
+                flag: true,
+            }),
+        );
+        assert_round_trip(
+            version,
+            Attribute::LoadoutOnline(vec![
+                vec![Product {
+                    unknown: false,
+                    object_ind: 2,
+                    value: ProductValue::NewPaint(11),
+                }],
+                vec![Product {
+                    unknown: false,
+                    object_ind: 3,
+                    value: ProductValue::Title("Champion".into()),
+                }],
+                vec![],
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_encode_attribute_product_mismatch() {
+        let (encoder, _) = encoder_decoder(ReplayVersion::new(868, 24, 10));
+        let attr = Attribute::LoadoutOnline(vec![vec![Product {
+            unknown: false,
+            object_ind: 2,
+            value: ProductValue::SpecialEdition(1),
+        }]]);
+        let mut bits = BitWriter::new();
+        assert!(encoder.encode(&attr, &mut bits).is_err());
+    }
+
+    #[test]
+    fn test_encode_vector_bias() {
+        let mut bits = BitWriter::new();
+        let vector = Vector {
+            bias: 3,
+            dx: 0,
+            dy: 0,
+            dz: 0,
+        };
+        assert_eq!(
+            encode_vector(&mut bits, &vector, 10),
+            Err(BuildError::InvalidValue("vector bias"))
+        );
+    }
+}
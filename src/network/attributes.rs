@@ -1,10 +1,15 @@
 use crate::errors::AttributeError;
-use crate::network::{ObjectId, Rotation, Vector, VersionTriplet};
+use crate::network::bit_reader::BitGet;
+use crate::network::interner::Interner;
+use crate::network::{
+    CompressedRotation, ObjectId, Quaternion, Rotation, SharedStr, Vector, Vector3f,
+};
 use crate::parsing_utils::{decode_utf16, decode_windows1252};
-use bitter::BitGet;
+use crate::version::{Feature, ReplayVersion};
 use encoding_rs::WINDOWS_1252;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum AttributeTag {
@@ -15,6 +20,7 @@ pub(crate) enum AttributeTag {
     CamSettings,
     ClubColors,
     Demolish,
+    DemolishExtended,
     Enum,
     Explosion,
     ExtendedExplosion,
@@ -29,6 +35,7 @@ pub(crate) enum AttributeTag {
     Location,
     MusicStinger,
     Pickup,
+    PickupNew,
     PlayerHistoryKey,
     QWord,
     Welded,
@@ -44,9 +51,110 @@ pub(crate) enum AttributeTag {
     LoadoutOnline,
     LoadoutsOnline,
     StatEvent,
+    ReplicatedBoost,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+impl AttributeTag {
+    /// The tags that decode into an attribute
+    const DECODABLE: [AttributeTag; 38] = [
+        AttributeTag::Boolean,
+        AttributeTag::Byte,
+        AttributeTag::AppliedDamage,
+        AttributeTag::DamageState,
+        AttributeTag::CamSettings,
+        AttributeTag::ClubColors,
+        AttributeTag::Demolish,
+        AttributeTag::DemolishExtended,
+        AttributeTag::Enum,
+        AttributeTag::Explosion,
+        AttributeTag::ExtendedExplosion,
+        AttributeTag::FlaggedByte,
+        AttributeTag::Flagged,
+        AttributeTag::Float,
+        AttributeTag::GameMode,
+        AttributeTag::Int,
+        AttributeTag::Int64,
+        AttributeTag::Loadout,
+        AttributeTag::TeamLoadout,
+        AttributeTag::Location,
+        AttributeTag::MusicStinger,
+        AttributeTag::Pickup,
+        AttributeTag::PickupNew,
+        AttributeTag::PlayerHistoryKey,
+        AttributeTag::QWord,
+        AttributeTag::Welded,
+        AttributeTag::RigidBody,
+        AttributeTag::Title,
+        AttributeTag::TeamPaint,
+        AttributeTag::String,
+        AttributeTag::UniqueId,
+        AttributeTag::Reservation,
+        AttributeTag::PartyLeader,
+        AttributeTag::PrivateMatchSettings,
+        AttributeTag::LoadoutOnline,
+        AttributeTag::LoadoutsOnline,
+        AttributeTag::StatEvent,
+        AttributeTag::ReplicatedBoost,
+    ];
+
+    /// The tag that decodes into the `Attribute` variant with the given name, the inverse of
+    /// `name`
+    pub fn from_name(name: &str) -> Option<AttributeTag> {
+        AttributeTag::DECODABLE
+            .iter()
+            .cloned()
+            .find(|x| x.name() == name)
+    }
+
+    /// The name of the tag, which is the name of the `Attribute` variant that it decodes into
+    pub fn name(self) -> &'static str {
+        match self {
+            AttributeTag::Boolean => "Boolean",
+            AttributeTag::Byte => "Byte",
+            AttributeTag::AppliedDamage => "AppliedDamage",
+            AttributeTag::DamageState => "DamageState",
+            AttributeTag::CamSettings => "CamSettings",
+            AttributeTag::ClubColors => "ClubColors",
+            AttributeTag::Demolish => "Demolish",
+            AttributeTag::DemolishExtended => "DemolishExtended",
+            AttributeTag::Enum => "Enum",
+            AttributeTag::Explosion => "Explosion",
+            AttributeTag::ExtendedExplosion => "ExtendedExplosion",
+            AttributeTag::FlaggedByte => "FlaggedByte",
+            AttributeTag::Flagged => "Flagged",
+            AttributeTag::Float => "Float",
+            AttributeTag::GameMode => "GameMode",
+            AttributeTag::Int => "Int",
+            AttributeTag::Int64 => "Int64",
+            AttributeTag::Loadout => "Loadout",
+            AttributeTag::TeamLoadout => "TeamLoadout",
+            AttributeTag::Location => "Location",
+            AttributeTag::MusicStinger => "MusicStinger",
+            AttributeTag::Pickup => "Pickup",
+            AttributeTag::PickupNew => "PickupNew",
+            AttributeTag::PlayerHistoryKey => "PlayerHistoryKey",
+            AttributeTag::QWord => "QWord",
+            AttributeTag::Welded => "Welded",
+            AttributeTag::RigidBody => "RigidBody",
+            AttributeTag::Title => "Title",
+            AttributeTag::TeamPaint => "TeamPaint",
+            AttributeTag::NotImplemented => "NotImplemented",
+            AttributeTag::String => "String",
+            AttributeTag::UniqueId => "UniqueId",
+            AttributeTag::Reservation => "Reservation",
+            AttributeTag::PartyLeader => "PartyLeader",
+            AttributeTag::PrivateMatchSettings => "PrivateMatch",
+            AttributeTag::LoadoutOnline => "LoadoutOnline",
+            AttributeTag::LoadoutsOnline => "LoadoutsOnline",
+            AttributeTag::StatEvent => "StatEvent",
+            AttributeTag::ReplicatedBoost => "ReplicatedBoost",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Attribute {
     Boolean(bool),
     Byte(u8),
@@ -55,6 +163,7 @@ pub enum Attribute {
     CamSettings(CamSettings),
     ClubColors(ClubColors),
     Demolish(Demolish),
+    DemolishExtended(DemolishExtended),
     Enum(u16),
     Explosion(Explosion),
     ExtendedExplosion(Explosion, bool, u32),
@@ -64,7 +173,10 @@ pub enum Attribute {
     GameMode(u8, u8),
     Int(i32),
 
-    #[serde(serialize_with = "crate::serde_utils::display_it")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_utils::display_it")
+    )]
     Int64(i64),
     Loadout(Loadout),
     TeamLoadout(TeamLoadout),
@@ -72,24 +184,34 @@ pub enum Attribute {
     MusicStinger(MusicStinger),
     PlayerHistoryKey(u16),
     Pickup(Pickup),
+    PickupNew(PickupNew),
 
-    #[serde(serialize_with = "crate::serde_utils::display_it")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_utils::display_it")
+    )]
     QWord(u64),
     Welded(Welded),
     Title(bool, bool, u32, u32, u32, u32, u32, bool),
     TeamPaint(TeamPaint),
     RigidBody(RigidBody),
-    String(String),
+    String(SharedStr),
     UniqueId(UniqueId),
     Reservation(Reservation),
     PartyLeader(Option<UniqueId>),
     PrivateMatch(PrivateMatchSettings),
     LoadoutOnline(Vec<Vec<Product>>),
     LoadoutsOnline(LoadoutsOnline),
+    /// An unknown flag and the index of the stat's object (eg: `StatEvents.Events.Save`), or
+    /// `u32::MAX` once the event is cleared. See `FrameIndex::stat_events`.
     StatEvent(bool, u32),
+    ReplicatedBoost(ReplicatedBoost),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct CamSettings {
     pub fov: f32,
     pub height: f32,
@@ -100,7 +222,10 @@ pub struct CamSettings {
     pub transition: Option<f32>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct ClubColors {
     pub blue_flag: bool,
     pub blue_color: u8,
@@ -108,7 +233,10 @@ pub struct ClubColors {
     pub orange_color: u8,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Demolish {
     pub attacker_flag: bool,
     pub attacker_actor_id: u32,
@@ -118,14 +246,47 @@ pub struct Demolish {
     pub victim_velocity: Vector,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+/// A demolition replicated with the goal explosion that the victim's car is demolished with, as
+/// seen in newer replays. Each actor reference is preceded by a flag of whether it is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct DemolishExtended {
+    /// The player replication info of the attacker
+    pub attacker_pri_flag: bool,
+    pub attacker_pri_actor_id: u32,
+    pub self_demo_flag: bool,
+    pub self_demo_actor_id: u32,
+
+    /// Whether the victim demolished themselves
+    pub self_demolish: bool,
+
+    /// The player whose goal explosion is shown for the demolition
+    pub goal_explosion_owner_flag: bool,
+    pub goal_explosion_owner_actor_id: u32,
+    pub attacker_flag: bool,
+    pub attacker_actor_id: u32,
+    pub victim_flag: bool,
+    pub victim_actor_id: u32,
+    pub attack_velocity: Vector,
+    pub victim_velocity: Vector,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Explosion {
     pub flag: bool,
     pub actor_id: u32,
     pub location: Vector,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Loadout {
     pub version: u8,
     pub body: u32,
@@ -143,26 +304,85 @@ pub struct Loadout {
     pub unknown3: Option<u32>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct TeamLoadout {
     pub blue: Loadout,
     pub orange: Loadout,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct MusicStinger {
     pub flag: bool,
     pub cue: u32,
     pub trigger: u8,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Pickup {
     pub instigator_id: Option<u32>,
     pub picked_up: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+/// The pickup data of newer replays, which replaces the picked up flag with a byte that changes
+/// each time the pickup is picked up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct PickupNew {
+    pub instigator_id: Option<u32>,
+    pub picked_up: u8,
+}
+
+/// The boost of a car's boost component, which newer replays replicate in place of the lone
+/// boost amount byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ReplicatedBoost {
+    /// The number of times that the car has been granted boost (eg: by a boost pad), which
+    /// wraps around after 255
+    pub grant_count: u8,
+
+    /// The boost amount from 0 to 255
+    pub boost_amount: u8,
+
+    /// The replicated state of the boost component, which isn't understood yet
+    pub component_state: [u8; 2],
+}
+
+impl ReplicatedBoost {
+    /// The boost amount as a fraction of a full tank, from 0.0 to 1.0
+    ///
+    /// ```
+    /// use boxcars::attributes::ReplicatedBoost;
+    ///
+    /// let boost = ReplicatedBoost {
+    ///     grant_count: 3,
+    ///     boost_amount: 255,
+    ///     component_state: [0, 0],
+    /// };
+    /// assert_eq!(boost.fraction(), 1.0);
+    /// ```
+    pub fn fraction(&self) -> f32 {
+        f32::from(self.boost_amount) / 255.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Welded {
     pub active: bool,
     pub actor_id: u32,
@@ -171,7 +391,10 @@ pub struct Welded {
     pub rotation: Rotation,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct TeamPaint {
     pub team: u8,
     pub primary_color: u8,
@@ -180,88 +403,480 @@ pub struct TeamPaint {
     pub accent_finish: u32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct RigidBody {
     pub sleeping: bool,
     pub location: Vector,
-    pub x: u16,
-    pub y: u16,
-    pub z: u16,
+    pub rotation: CompressedRotation,
     pub linear_velocity: Option<Vector>,
     pub angular_velocity: Option<Vector>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+/// A rigid body with its location, rotation, and velocities decompressed
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct RigidBodyState {
+    pub sleeping: bool,
+
+    /// The location in unreal units
+    pub location: Vector3f,
+    pub rotation: Quaternion,
+
+    /// The linear velocity in unreal units per second
+    pub linear_velocity: Option<Vector3f>,
+
+    /// The angular velocity in hundredths of a radian per second
+    pub angular_velocity: Option<Vector3f>,
+}
+
+impl RigidBody {
+    /// Decompresses the rigid body of a replay with the given net version
+    pub fn decompress(&self, net_version: i32) -> RigidBodyState {
+        RigidBodyState {
+            sleeping: self.sleeping,
+            location: self.location.to_vector3f(net_version),
+            rotation: self.rotation.to_quaternion(),
+            linear_velocity: self.linear_velocity.map(|x| x.to_velocity(net_version)),
+            angular_velocity: self.angular_velocity.map(|x| x.to_velocity(net_version)),
+        }
+    }
+}
+
+impl RigidBodyState {
+    /// The location in meters
+    pub fn location_meters(&self) -> Vector3f {
+        self.location.to_meters()
+    }
+
+    /// The linear velocity in meters per second
+    pub fn linear_velocity_meters(&self) -> Option<Vector3f> {
+        self.linear_velocity.map(Vector3f::to_meters)
+    }
+
+    /// The angular velocity in radians per second
+    pub fn angular_velocity_radians(&self) -> Option<Vector3f> {
+        self.angular_velocity.map(|x| Vector3f {
+            x: x.x / 100.0,
+            y: x.y / 100.0,
+            z: x.z / 100.0,
+        })
+    }
+
+    /// Converts the state to the right handed y up coordinate system of `Vector3f::to_y_up`.
+    /// The units are unchanged.
+    pub fn to_y_up(&self) -> RigidBodyState {
+        // Angular velocity is an axis of rotation so it is mirrored like the rotation's axis
+        let mirror = |x: Vector3f| Vector3f {
+            x: -x.x,
+            y: -x.z,
+            z: -x.y,
+        };
+
+        RigidBodyState {
+            sleeping: self.sleeping,
+            location: self.location.to_y_up(),
+            rotation: self.rotation.to_y_up(),
+            linear_velocity: self.linear_velocity.map(Vector3f::to_y_up),
+            angular_velocity: self.angular_velocity.map(mirror),
+        }
+    }
+
+    /// Interpolates between the states, where a `t` of 0 is `self` and 1 is `other`. Locations
+    /// and velocities are linearly interpolated and rotations are spherically interpolated.
+    pub fn interpolate(&self, other: &RigidBodyState, t: f32) -> RigidBodyState {
+        // Values that can't be interpolated are taken from the nearest state
+        let nearest = if t < 0.5 { self } else { other };
+        let lerp = |a: Option<Vector3f>, b: Option<Vector3f>, nearest| match (a, b) {
+            (Some(a), Some(b)) => Some(a.lerp(b, t)),
+            _ => nearest,
+        };
+
+        RigidBodyState {
+            sleeping: nearest.sleeping,
+            location: self.location.lerp(other.location, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+            linear_velocity: lerp(
+                self.linear_velocity,
+                other.linear_velocity,
+                nearest.linear_velocity,
+            ),
+            angular_velocity: lerp(
+                self.angular_velocity,
+                other.angular_velocity,
+                nearest.angular_velocity,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct UniqueId {
+    /// The platform byte that leads the id and determines the layout of the remote id: 0 for
+    /// split-screen (offline) players, 1 for Steam, 2 for PlayStation, 4 for Xbox, 6 for Switch,
+    /// and 7 for PsyNet. See `platform` for the platform that the byte denotes.
     pub system_id: u8,
     pub remote_id: RemoteId,
+
+    /// The split-screen index of the player on their machine. The primary player is 0 and
+    /// split-screen guests, who share the primary player's remote id, are numbered from 1.
     pub local_id: u8,
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+impl UniqueId {
+    /// Returns true if the player is a split-screen guest of another player
+    pub fn is_split_screen_guest(&self) -> bool {
+        self.local_id > 0
+    }
+
+    pub fn platform(&self) -> Platform {
+        self.remote_id.platform()
+    }
+}
+
+/// Formats the remote id, followed by the local id for split-screen guests (eg:
+/// `76561198101748375:1`) so that they are told apart from the primary player
+impl fmt::Display for UniqueId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_split_screen_guest() {
+            write!(f, "{}:{}", self.remote_id, self.local_id)
+        } else {
+            write!(f, "{}", self.remote_id)
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct PsyNetId {
-    #[serde(serialize_with = "crate::serde_utils::display_it")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_utils::display_it")
+    )]
     pub online_id: u64,
     pub unknown1: Vec<u8>,
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct SwitchId {
-    #[serde(serialize_with = "crate::serde_utils::display_it")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_utils::display_it")
+    )]
     pub online_id: u64,
+
+    /// Three 8 byte words (see `extra_words`)
     pub unknown1: Vec<u8>,
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+impl SwitchId {
+    /// The three little endian words that follow the online id. Unlike the online id, they
+    /// aren't the same for a player across replays, or even within a replay: party leader and
+    /// reservation ids may have zeros instead.
+    pub fn extra_words(&self) -> Option<[u64; 3]> {
+        if self.unknown1.len() != 24 {
+            return None;
+        }
+
+        let mut words = [0; 3];
+        for (word, chunk) in words.iter_mut().zip(self.unknown1.chunks_exact(8)) {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(chunk);
+            *word = u64::from_le_bytes(bytes);
+        }
+        Some(words)
+    }
+
+    /// The id with the extra words zeroed, so that the ids of a player compare equal across
+    /// replays
+    pub fn normalized(&self) -> SwitchId {
+        SwitchId {
+            online_id: self.online_id,
+            unknown1: vec![0; 24],
+        }
+    }
+
+    /// Returns true if both ids belong to the same player, regardless of the extra words
+    pub fn is_same_player(&self, other: &SwitchId) -> bool {
+        self.online_id == other.online_id
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Ps4Id {
-    #[serde(serialize_with = "crate::serde_utils::display_it")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_utils::display_it")
+    )]
     pub online_id: u64,
-    pub name: String,
+
+    /// The player's PSN online id, which is recorded in 16 bytes padded with zeros
+    pub name: SharedStr,
+
+    /// The 8 bytes of the account's platform tag, followed by 8 more bytes since
+    /// `Feature::LongPs4Ids`. See `platform_tag` and `extra`.
     pub unknown1: Vec<u8>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+impl Ps4Id {
+    /// The platform tag of the account, which is the account's region followed by the console
+    /// (eg: `c2usps4`). Ids without one, such as some party leaders, have a tag of zeros.
+    pub fn platform_tag(&self) -> Option<&str> {
+        let tag = self.unknown1.get(..8)?;
+        let len = tag.iter().position(|&x| x == 0).unwrap_or(tag.len());
+        std::str::from_utf8(&tag[..len])
+            .ok()
+            .filter(|x| !x.is_empty())
+    }
+
+    /// The bytes that follow the platform tag, which are empty in replays that predate
+    /// `Feature::LongPs4Ids`
+    pub fn extra(&self) -> &[u8] {
+        self.unknown1.get(8..).unwrap_or(&[])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum RemoteId {
     PlayStation(Ps4Id),
     PsyNet(PsyNetId),
     SplitScreen(u32),
 
-    #[serde(serialize_with = "crate::serde_utils::display_it")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_utils::display_it")
+    )]
     Steam(u64),
     Switch(SwitchId),
 
-    #[serde(serialize_with = "crate::serde_utils::display_it")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "crate::serde_utils::display_it")
+    )]
     Xbox(u64),
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+impl RemoteId {
+    /// The online id of the player's platform account. Offline split-screen players don't have
+    /// an account.
+    pub fn online_id(&self) -> Option<u64> {
+        match *self {
+            RemoteId::PlayStation(ref x) => Some(x.online_id),
+            RemoteId::PsyNet(ref x) => Some(x.online_id),
+            RemoteId::SplitScreen(_) => None,
+            RemoteId::Steam(x) => Some(x),
+            RemoteId::Switch(ref x) => Some(x.online_id),
+            RemoteId::Xbox(x) => Some(x),
+        }
+    }
+
+    /// The player's Steam id, if they play on Steam
+    pub fn steam_id(&self) -> Option<SteamId> {
+        match *self {
+            RemoteId::Steam(x) => Some(SteamId(x)),
+            _ => None,
+        }
+    }
+
+    /// The player's XUID, if they play on Xbox
+    pub fn xbox_id(&self) -> Option<XboxId> {
+        match *self {
+            RemoteId::Xbox(x) => Some(XboxId(x)),
+            _ => None,
+        }
+    }
+
+    /// The platform of the player's account
+    pub fn platform(&self) -> Platform {
+        match *self {
+            RemoteId::PlayStation(_) => Platform::PlayStation,
+            RemoteId::PsyNet(_) => Platform::Epic,
+            RemoteId::SplitScreen(_) => Platform::Offline,
+            RemoteId::Steam(_) => Platform::Steam,
+            RemoteId::Switch(_) => Platform::Switch,
+            RemoteId::Xbox(_) => Platform::Xbox,
+        }
+    }
+}
+
+/// Formats the id as it is conventionally written for the platform: the player's name for
+/// PlayStation, `bot` for players without an online account, and the online id in decimal
+/// otherwise (eg: the steam64 id for Steam and the XUID for Xbox)
+impl fmt::Display for RemoteId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteId::PlayStation(x) => write!(f, "{}", x.name),
+            RemoteId::SplitScreen(_) => write!(f, "bot"),
+            RemoteId::PsyNet(PsyNetId { online_id, .. })
+            | RemoteId::Switch(SwitchId { online_id, .. })
+            | RemoteId::Steam(online_id)
+            | RemoteId::Xbox(online_id) => write!(f, "{}", online_id),
+        }
+    }
+}
+
+/// A steam64 id, which packs the account number with the account's instance, type, and universe.
+/// Displays as the steam64 id.
+///
+/// ```
+/// use boxcars::attributes::SteamId;
+///
+/// let id = SteamId(76561198033459682);
+/// assert_eq!(id.account_id(), 73193954);
+/// assert_eq!(id.universe(), 1);
+/// assert_eq!(id.steam2(), "STEAM_1:0:36596977");
+/// assert_eq!(id.steam3(), "[U:1:73193954]");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SteamId(pub u64);
+
+impl SteamId {
+    /// The account number, which is the lower 32 bits
+    pub fn account_id(self) -> u32 {
+        self.0 as u32
+    }
+
+    /// The instance of the account, which is 1 for the desktop client of player accounts
+    pub fn instance(self) -> u32 {
+        ((self.0 >> 32) & 0xf_ffff) as u32
+    }
+
+    /// The type of the account, which is 1 for individual (player) accounts
+    pub fn account_type(self) -> u8 {
+        ((self.0 >> 52) & 0xf) as u8
+    }
+
+    /// The universe of the account, which is 1 for the public universe
+    pub fn universe(self) -> u8 {
+        (self.0 >> 56) as u8
+    }
+
+    /// The legacy textual id (eg: `STEAM_1:0:36596977`), written with the account's universe
+    pub fn steam2(self) -> String {
+        let account = self.account_id();
+        format!("STEAM_{}:{}:{}", self.universe(), account & 1, account >> 1)
+    }
+
+    /// The current textual id (eg: `[U:1:73193954]`), where the letter is the account's type
+    pub fn steam3(self) -> String {
+        let kind = match self.account_type() {
+            1 => 'U',
+            2 => 'M',
+            3 => 'G',
+            4 => 'A',
+            5 => 'P',
+            6 => 'C',
+            7 => 'g',
+            8 => 'T',
+            10 => 'a',
+            _ => 'I',
+        };
+        format!("[{}:{}:{}]", kind, self.universe(), self.account_id())
+    }
+}
+
+impl fmt::Display for SteamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// An Xbox user id (XUID). Displays in decimal, as the Xbox Live APIs expect.
+///
+/// ```
+/// use boxcars::attributes::XboxId;
+///
+/// let id = XboxId(2533274808104947);
+/// assert_eq!(id.to_string(), "2533274808104947");
+/// assert_eq!(id.hex(), "00090000010E37F3");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct XboxId(pub u64);
+
+impl XboxId {
+    /// The XUID as 16 uppercase hex digits, as it is shown by Xbox tooling
+    pub fn hex(self) -> String {
+        format!("{:016X}", self.0)
+    }
+}
+
+impl fmt::Display for XboxId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The platform of a player's account, as told by the system id of their unique id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum Platform {
+    /// Bots and local players without an online account
+    Offline,
+    Steam,
+    PlayStation,
+    Xbox,
+    Switch,
+
+    /// Epic Games accounts, which have PsyNet ids
+    Epic,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Reservation {
     pub number: u32,
     pub unique_id: UniqueId,
-    pub name: Option<String>,
+    pub name: Option<SharedStr>,
     pub unknown1: bool,
     pub unknown2: bool,
     pub unknown3: Option<u8>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct PrivateMatchSettings {
-    pub mutators: String,
+    pub mutators: SharedStr,
     pub joinable_by: u32,
     pub max_players: u32,
-    pub game_name: String,
-    pub password: String,
+    pub game_name: SharedStr,
+    pub password: SharedStr,
     pub flag: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Product {
     pub unknown: bool,
     pub object_ind: u32,
     pub value: ProductValue,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct LoadoutsOnline {
     pub blue: Vec<Vec<Product>>,
     pub orange: Vec<Vec<Product>>,
@@ -269,7 +884,9 @@ pub struct LoadoutsOnline {
     pub unknown2: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum ProductValue {
     NoColor,
     Absent,
@@ -277,7 +894,7 @@ pub enum ProductValue {
     NewColor(u32),
     OldPaint(u32),
     NewPaint(u32),
-    Title(String),
+    Title(SharedStr),
     SpecialEdition(u32),
     OldTeamEdition(u32),
     NewTeamEdition(u32),
@@ -285,7 +902,7 @@ pub enum ProductValue {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct ProductValueDecoder {
-    version: VersionTriplet,
+    version: ReplayVersion,
     color_ind: u32,
     painted_ind: u32,
     special_edition_ind: u32,
@@ -294,7 +911,7 @@ pub(crate) struct ProductValueDecoder {
 }
 
 impl ProductValueDecoder {
-    pub fn create(version: VersionTriplet, name_obj_ind: &HashMap<&str, ObjectId>) -> Self {
+    pub fn create(version: ReplayVersion, name_obj_ind: &HashMap<&str, ObjectId>) -> Self {
         let color_ind = name_obj_ind
             .get("TAGame.ProductAttribute_UserColor_TA")
             .map(|&x| i32::from(x))
@@ -326,26 +943,31 @@ impl ProductValueDecoder {
         }
     }
 
-    pub fn decode(&self, bits: &mut BitGet<'_>, obj_ind: u32) -> Option<ProductValue> {
+    pub fn decode(
+        &self,
+        bits: &mut BitGet<'_>,
+        obj_ind: u32,
+        interner: &mut Interner,
+    ) -> Option<ProductValue> {
         if obj_ind == self.color_ind {
-            if self.version >= VersionTriplet(868, 23, 8) {
+            if self.version.at_least(868, 23, 8) {
                 bits.read_u32().map(ProductValue::NewColor)
             } else {
                 bits.if_get(|b| b.read_u32_bits(31).map(ProductValue::OldColor))
                     .map(|x| x.unwrap_or(ProductValue::NoColor))
             }
         } else if obj_ind == self.painted_ind {
-            if self.version >= VersionTriplet(868, 18, 0) {
+            if self.version.at_least(868, 18, 0) {
                 bits.read_u32_bits(31).map(ProductValue::NewPaint)
             } else {
                 bits.read_bits_max(4, 14).map(ProductValue::OldPaint)
             }
         } else if obj_ind == self.title_ind {
-            decode_text(bits).ok().map(ProductValue::Title)
+            decode_text(bits, interner).ok().map(ProductValue::Title)
         } else if obj_ind == self.special_edition_ind {
             bits.read_u32_bits(31).map(ProductValue::SpecialEdition)
         } else if obj_ind == self.team_edition_ind {
-            if self.version >= VersionTriplet(868, 18, 0) {
+            if self.version.at_least(868, 18, 0) {
                 bits.read_u32_bits(31).map(ProductValue::NewTeamEdition)
             } else {
                 bits.read_bits_max(4, 14).map(ProductValue::OldTeamEdition)
@@ -354,24 +976,53 @@ impl ProductValueDecoder {
             Some(ProductValue::Absent)
         }
     }
+
+    /// Returns true if a product that references the object index would be decoded into the
+    /// same kind of value
+    pub fn decodes_as(&self, obj_ind: u32, value: &ProductValue) -> bool {
+        let decoded = [
+            self.color_ind,
+            self.painted_ind,
+            self.title_ind,
+            self.special_edition_ind,
+            self.team_edition_ind,
+        ]
+        .iter()
+        .position(|&x| x == obj_ind);
+
+        let expected = match *value {
+            ProductValue::NoColor | ProductValue::OldColor(_) | ProductValue::NewColor(_) => {
+                Some(0)
+            }
+            ProductValue::OldPaint(_) | ProductValue::NewPaint(_) => Some(1),
+            ProductValue::Title(_) => Some(2),
+            ProductValue::SpecialEdition(_) => Some(3),
+            ProductValue::OldTeamEdition(_) | ProductValue::NewTeamEdition(_) => Some(4),
+            ProductValue::Absent => None,
+        };
+
+        decoded == expected
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub(crate) struct AttributeDecoder {
-    version: VersionTriplet,
+    version: ReplayVersion,
     product_decoder: ProductValueDecoder,
+    interner: Interner,
 }
 
 impl AttributeDecoder {
-    pub fn new(version: VersionTriplet, product_decoder: ProductValueDecoder) -> Self {
+    pub fn new(version: ReplayVersion, product_decoder: ProductValueDecoder) -> Self {
         AttributeDecoder {
             version,
             product_decoder,
+            interner: Interner::default(),
         }
     }
 
     pub fn decode(
-        &self,
+        &mut self,
         tag: AttributeTag,
         bits: &mut BitGet<'_>,
     ) -> Result<Attribute, AttributeError> {
@@ -383,6 +1034,7 @@ impl AttributeDecoder {
             AttributeTag::CamSettings => self.decode_cam_settings(bits),
             AttributeTag::ClubColors => self.decode_club_colors(bits),
             AttributeTag::Demolish => self.decode_demolish(bits),
+            AttributeTag::DemolishExtended => self.decode_demolish_extended(bits),
             AttributeTag::Enum => self.decode_enum(bits),
             AttributeTag::Explosion => self.decode_explosion(bits),
             AttributeTag::ExtendedExplosion => self.decode_extended_explosion(bits),
@@ -397,6 +1049,7 @@ impl AttributeDecoder {
             AttributeTag::Location => self.decode_location(bits),
             AttributeTag::MusicStinger => self.decode_music_stinger(bits),
             AttributeTag::Pickup => self.decode_pickup(bits),
+            AttributeTag::PickupNew => self.decode_pickup_new(bits),
             AttributeTag::PlayerHistoryKey => self.decode_player_history_key(bits),
             AttributeTag::QWord => self.decode_qword(bits),
             AttributeTag::Welded => self.decode_welded(bits),
@@ -412,13 +1065,14 @@ impl AttributeDecoder {
             AttributeTag::LoadoutOnline => self.decode_loadout_online(bits),
             AttributeTag::LoadoutsOnline => self.decode_loadouts_online(bits),
             AttributeTag::StatEvent => self.decode_stat_event(bits),
+            AttributeTag::ReplicatedBoost => self.decode_replicated_boost(bits),
         }
     }
 
     pub fn decode_byte(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
         bits.read_u8()
             .map(Attribute::Byte)
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Byte"))
+            .ok_or(AttributeError::NotEnoughDataFor("Byte"))
     }
 
     pub fn decode_player_history_key(
@@ -427,7 +1081,7 @@ impl AttributeDecoder {
     ) -> Result<Attribute, AttributeError> {
         bits.read_u32_bits(14)
             .map(|x| Attribute::PlayerHistoryKey(x as u16))
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("PlayerHistoryKey"))
+            .ok_or(AttributeError::NotEnoughDataFor("PlayerHistoryKey"))
     }
 
     pub fn decode_flagged_byte(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
@@ -445,7 +1099,7 @@ impl AttributeDecoder {
     pub fn decode_boolean(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
         bits.read_bit()
             .map(Attribute::Boolean)
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Boolean"))
+            .ok_or(AttributeError::NotEnoughDataFor("Boolean"))
     }
 
     pub fn decode_applied_damage(
@@ -454,7 +1108,7 @@ impl AttributeDecoder {
     ) -> Result<Attribute, AttributeError> {
         if_chain! {
             if let Some(a) = bits.read_u8();
-            if let Some(vector) = Vector::decode(bits, self.version.net_version());
+            if let Some(vector) = Vector::decode(bits, self.version.net);
             if let Some(b) = bits.read_u32();
             if let Some(c) = bits.read_u32();
             then {
@@ -470,7 +1124,7 @@ impl AttributeDecoder {
             if let Some(da) = bits.read_u8();
             if let Some(db) = bits.read_bit();
             if let Some(dc) = bits.read_u32();
-            if let Some(dd) = Vector::decode(bits, self.version.net_version());
+            if let Some(dd) = Vector::decode(bits, self.version.net);
             if let Some(de) = bits.read_bit();
             if let Some(df) = bits.read_bit();
             then {
@@ -489,7 +1143,7 @@ impl AttributeDecoder {
             if let Some(distance) = bits.read_f32();
             if let Some(swiftness) = bits.read_f32();
             if let Some(swivel) = bits.read_f32();
-            if let Some(transition) = if self.version >= VersionTriplet(868, 20, 0) {
+            if let Some(transition) = if self.version.at_least(868, 20, 0) {
                 bits.read_f32().map(Some)
             } else {
                 Some(None)
@@ -536,8 +1190,8 @@ impl AttributeDecoder {
             if let Some(attacker_actor_id) = bits.read_u32();
             if let Some(victim_flag) = bits.read_bit();
             if let Some(victim_actor_id) = bits.read_u32();
-            if let Some(attack_velocity) = Vector::decode(bits, self.version.net_version());
-            if let Some(victim_velocity) = Vector::decode(bits, self.version.net_version());
+            if let Some(attack_velocity) = Vector::decode(bits, self.version.net);
+            if let Some(victim_velocity) = Vector::decode(bits, self.version.net);
             then {
                 Ok(Attribute::Demolish(Demolish {
                     attacker_flag,
@@ -553,16 +1207,56 @@ impl AttributeDecoder {
         }
     }
 
+    pub fn decode_demolish_extended(
+        &self,
+        bits: &mut BitGet<'_>,
+    ) -> Result<Attribute, AttributeError> {
+        if_chain! {
+            if let Some(attacker_pri_flag) = bits.read_bit();
+            if let Some(attacker_pri_actor_id) = bits.read_u32();
+            if let Some(self_demo_flag) = bits.read_bit();
+            if let Some(self_demo_actor_id) = bits.read_u32();
+            if let Some(self_demolish) = bits.read_bit();
+            if let Some(goal_explosion_owner_flag) = bits.read_bit();
+            if let Some(goal_explosion_owner_actor_id) = bits.read_u32();
+            if let Some(attacker_flag) = bits.read_bit();
+            if let Some(attacker_actor_id) = bits.read_u32();
+            if let Some(victim_flag) = bits.read_bit();
+            if let Some(victim_actor_id) = bits.read_u32();
+            if let Some(attack_velocity) = Vector::decode(bits, self.version.net);
+            if let Some(victim_velocity) = Vector::decode(bits, self.version.net);
+            then {
+                Ok(Attribute::DemolishExtended(DemolishExtended {
+                    attacker_pri_flag,
+                    attacker_pri_actor_id,
+                    self_demo_flag,
+                    self_demo_actor_id,
+                    self_demolish,
+                    goal_explosion_owner_flag,
+                    goal_explosion_owner_actor_id,
+                    attacker_flag,
+                    attacker_actor_id,
+                    victim_flag,
+                    victim_actor_id,
+                    attack_velocity,
+                    victim_velocity,
+                }))
+            } else {
+                Err(AttributeError::NotEnoughDataFor("Demolish Extended"))
+            }
+        }
+    }
+
     pub fn decode_enum(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
         bits.read_u32_bits(11)
             .map(|x| Attribute::Enum(x as u16))
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Enum"))
+            .ok_or(AttributeError::NotEnoughDataFor("Enum"))
     }
 
     pub fn decode_explosion(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
-        decode_explosion(bits, self.version.net_version())
+        decode_explosion(bits, self.version.net)
             .map(Attribute::Explosion)
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Explosion"))
+            .ok_or(AttributeError::NotEnoughDataFor("Explosion"))
     }
 
     pub fn decode_stat_event(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
@@ -582,7 +1276,7 @@ impl AttributeDecoder {
         bits: &mut BitGet<'_>,
     ) -> Result<Attribute, AttributeError> {
         if_chain! {
-            if let Some(explosion) = decode_explosion(bits, self.version.net_version());
+            if let Some(explosion) = decode_explosion(bits, self.version.net);
             if let Some(ea) = bits.read_bit();
             if let Some(eb) = bits.read_u32();
             then {
@@ -608,37 +1302,37 @@ impl AttributeDecoder {
     pub fn decode_float(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
         bits.read_f32()
             .map(Attribute::Float)
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Float"))
+            .ok_or(AttributeError::NotEnoughDataFor("Float"))
     }
 
     pub fn decode_game_mode(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
-        let init: u8 = if self.version < VersionTriplet(868, 12, 0) {
-            2
-        } else {
+        let init: u8 = if self.version.at_least(868, 12, 0) {
             8
+        } else {
+            2
         };
 
         bits.read_u32_bits(i32::from(init))
             .map(|x| Attribute::GameMode(init, x as u8))
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Game Mode"))
+            .ok_or(AttributeError::NotEnoughDataFor("Game Mode"))
     }
 
     pub fn decode_int(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
         bits.read_i32()
             .map(Attribute::Int)
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Int"))
+            .ok_or(AttributeError::NotEnoughDataFor("Int"))
     }
 
     pub fn decode_int64(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
         bits.read_i64()
             .map(Attribute::Int64)
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Int64"))
+            .ok_or(AttributeError::NotEnoughDataFor("Int64"))
     }
 
     pub fn decode_loadout(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
         decode_loadout(bits)
             .map(Attribute::Loadout)
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Loadout"))
+            .ok_or(AttributeError::NotEnoughDataFor("Loadout"))
     }
 
     pub fn decode_team_loadout(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
@@ -657,9 +1351,9 @@ impl AttributeDecoder {
     }
 
     pub fn decode_location(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
-        Vector::decode(bits, self.version.net_version())
+        Vector::decode(bits, self.version.net)
             .map(Attribute::Location)
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Location"))
+            .ok_or(AttributeError::NotEnoughDataFor("Location"))
     }
 
     pub fn decode_music_stinger(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
@@ -694,17 +1388,53 @@ impl AttributeDecoder {
         }
     }
 
+    pub fn decode_pickup_new(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
+        if_chain! {
+            if let Some(instigator_id) = bits.if_get(BitGet::read_u32);
+            if let Some(picked_up) = bits.read_u8();
+            then {
+                Ok(Attribute::PickupNew(PickupNew {
+                    instigator_id,
+                    picked_up,
+                }))
+            } else {
+                Err(AttributeError::NotEnoughDataFor("PickupNew"))
+            }
+        }
+    }
+
+    pub fn decode_replicated_boost(
+        &self,
+        bits: &mut BitGet<'_>,
+    ) -> Result<Attribute, AttributeError> {
+        if_chain! {
+            if let Some(grant_count) = bits.read_u8();
+            if let Some(boost_amount) = bits.read_u8();
+            if let Some(state1) = bits.read_u8();
+            if let Some(state2) = bits.read_u8();
+            then {
+                Ok(Attribute::ReplicatedBoost(ReplicatedBoost {
+                    grant_count,
+                    boost_amount,
+                    component_state: [state1, state2],
+                }))
+            } else {
+                Err(AttributeError::NotEnoughDataFor("ReplicatedBoost"))
+            }
+        }
+    }
+
     pub fn decode_qword(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
         bits.read_u64()
             .map(Attribute::QWord)
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("QWord"))
+            .ok_or(AttributeError::NotEnoughDataFor("QWord"))
     }
 
     pub fn decode_welded(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
         if_chain! {
             if let Some(active) = bits.read_bit();
             if let Some(actor_id) = bits.read_u32();
-            if let Some(offset) = Vector::decode(bits, self.version.net_version());
+            if let Some(offset) = Vector::decode(bits, self.version.net);
             if let Some(mass) = bits.read_f32();
             if let Some(rotation) = Rotation::decode(bits);
             then {
@@ -772,24 +1502,13 @@ impl AttributeDecoder {
     pub fn decode_rigid_body(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
         if_chain! {
             if let Some(sleeping) = bits.read_bit();
-            if let Some(location) = Vector::decode(bits, self.version.net_version());
-
-            if let Some(_u1) = if self.version.net_version() >= 7 {
-                bits.read_bit()
-            } else { Some(true) };
-
-            if let Some(x) = bits.read_u32_bits(if self.version.net_version() >= 7 { 18 } else { 16 });
-            if let Some(y) = bits.read_u32_bits(if self.version.net_version() >= 7 { 18 } else { 16 });
-            if let Some(z) = bits.read_u32_bits(if self.version.net_version() >= 7 { 18 } else { 16 });
-
-            if let Some(_u2) = if self.version.net_version() >= 7 {
-                bits.read_bit()
-            } else { Some(true) };
+            if let Some(location) = Vector::decode(bits, self.version.net);
 
+            if let Some(rotation) = CompressedRotation::decode(bits, self.version.net);
 
             if let Some((linear_velocity, angular_velocity)) = if !sleeping {
-                let lv = Vector::decode(bits, self.version.net_version());
-                let av = Vector::decode(bits, self.version.net_version());
+                let lv = Vector::decode(bits, self.version.net);
+                let av = Vector::decode(bits, self.version.net);
                 if lv.is_some() && av.is_some() {
                     Some((lv, av))
                 } else {
@@ -803,9 +1522,7 @@ impl AttributeDecoder {
                 Ok(Attribute::RigidBody(RigidBody {
                     sleeping,
                     location,
-                    x: x as u16,
-                    y: y as u16,
-                    z: z as u16,
+                    rotation,
                     linear_velocity,
                     angular_velocity,
                 }))
@@ -822,27 +1539,30 @@ impl AttributeDecoder {
         Err(AttributeError::Unimplemented)
     }
 
-    pub fn decode_string(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
-        Ok(Attribute::String(decode_text(bits)?))
+    pub fn decode_string(&mut self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
+        Ok(Attribute::String(decode_text(bits, &mut self.interner)?))
     }
 
-    pub fn decode_unique_id(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
-        decode_unique_id(bits, self.version.net_version()).map(Attribute::UniqueId)
+    pub fn decode_unique_id(&mut self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
+        decode_unique_id(bits, self.version, &mut self.interner).map(Attribute::UniqueId)
     }
 
-    pub fn decode_reservation(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
+    pub fn decode_reservation(
+        &mut self,
+        bits: &mut BitGet<'_>,
+    ) -> Result<Attribute, AttributeError> {
         if_chain! {
             if let Some(number) = bits.read_u32_bits(3);
-            let unique = decode_unique_id(bits, self.version.net_version())?;
+            let unique = decode_unique_id(bits, self.version, &mut self.interner)?;
             if let Some(name) = if unique.system_id != 0 {
-                Some(Some(decode_text(bits)?))
+                Some(Some(decode_text(bits, &mut self.interner)?))
             } else {
                 Some(None)
             };
 
             if let Some(unknown1) = bits.read_bit();
             if let Some(unknown2) = bits.read_bit();
-            if let Some(unknown3) = if self.version >= VersionTriplet(868, 12, 0) {
+            if let Some(unknown3) = if self.version.at_least(868, 12, 0) {
                 bits.read_u32_bits(6).map(|x| Some(x as u8))
             } else {
                 Some(None)
@@ -863,11 +1583,18 @@ impl AttributeDecoder {
         }
     }
 
-    pub fn decode_party_leader(&self, bits: &mut BitGet<'_>) -> Result<Attribute, AttributeError> {
+    pub fn decode_party_leader(
+        &mut self,
+        bits: &mut BitGet<'_>,
+    ) -> Result<Attribute, AttributeError> {
         if let Some(system_id) = bits.read_u8() {
             if system_id != 0 {
-                let id =
-                    decode_unique_id_with_system_id(bits, self.version.net_version(), system_id)?;
+                let id = decode_unique_id_with_system_id(
+                    bits,
+                    self.version,
+                    system_id,
+                    &mut self.interner,
+                )?;
                 Ok(Attribute::PartyLeader(Some(id)))
             } else {
                 Ok(Attribute::PartyLeader(None))
@@ -878,15 +1605,15 @@ impl AttributeDecoder {
     }
 
     pub fn decode_private_match_settings(
-        &self,
+        &mut self,
         bits: &mut BitGet<'_>,
     ) -> Result<Attribute, AttributeError> {
         if_chain! {
-            let mutators = decode_text(bits)?;
+            let mutators = decode_text(bits, &mut self.interner)?;
             if let Some(joinable_by) = bits.read_u32();
             if let Some(max_players) = bits.read_u32();
-            let game_name = decode_text(bits)?;
-            let password = decode_text(bits)?;
+            let game_name = decode_text(bits, &mut self.interner)?;
+            let password = decode_text(bits, &mut self.interner)?;
             if let Some(flag) = bits.read_bit();
 
             then {
@@ -905,16 +1632,16 @@ impl AttributeDecoder {
     }
 
     pub fn decode_loadout_online(
-        &self,
+        &mut self,
         bits: &mut BitGet<'_>,
     ) -> Result<Attribute, AttributeError> {
         self.inner_decode_online_loadout(bits)
             .map(Attribute::LoadoutOnline)
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Loadout Online"))
+            .ok_or(AttributeError::NotEnoughDataFor("Loadout Online"))
     }
 
     pub fn decode_loadouts_online(
-        &self,
+        &mut self,
         bits: &mut BitGet<'_>,
     ) -> Result<Attribute, AttributeError> {
         if_chain! {
@@ -935,11 +1662,11 @@ impl AttributeDecoder {
         }
     }
 
-    fn decode_product(&self, bits: &mut BitGet<'_>) -> Option<Product> {
+    fn decode_product(&mut self, bits: &mut BitGet<'_>) -> Option<Product> {
         if_chain! {
             if let Some(unknown) = bits.read_bit();
             if let Some(obj_ind) = bits.read_u32();
-            if let Some(val) = self.product_decoder.decode(bits, obj_ind);
+            if let Some(val) = self.product_decoder.decode(bits, obj_ind, &mut self.interner);
 
             then {
                 Some(Product {
@@ -953,7 +1680,7 @@ impl AttributeDecoder {
         }
     }
 
-    fn inner_decode_online_loadout(&self, bits: &mut BitGet<'_>) -> Option<Vec<Vec<Product>>> {
+    fn inner_decode_online_loadout(&mut self, bits: &mut BitGet<'_>) -> Option<Vec<Vec<Product>>> {
         if let Some(size) = bits.read_u8() {
             let mut res = Vec::with_capacity(size as usize);
             for _ in 0..size {
@@ -995,24 +1722,38 @@ fn decode_explosion(bits: &mut BitGet<'_>, net_version: i32) -> Option<Explosion
     }
 }
 
-fn decode_text(bits: &mut BitGet<'_>) -> Result<String, AttributeError> {
+fn decode_text(
+    bits: &mut BitGet<'_>,
+    interner: &mut Interner,
+) -> Result<SharedStr, AttributeError> {
+    read_text(bits, |x| interner.intern(x))
+}
+
+/// Reads a length prefixed string, where a negative length denotes a UTF-16 string, and maps it
+/// with the given function
+pub(crate) fn read_text<T, F>(bits: &mut BitGet<'_>, f: F) -> Result<T, AttributeError>
+where
+    F: FnOnce(&str) -> T,
+{
     let size = bits
         .read_i32()
-        .ok_or_else(|| AttributeError::NotEnoughDataFor("text string"))?;
+        .ok_or(AttributeError::NotEnoughDataFor("text string"))?;
 
     // A zero length string for attributes is fine (this differs from the replay header where we
     // never see zero length strings)
     if size == 0 {
-        Ok(String::from(""))
+        Ok(f(""))
     } else if size < 0 {
-        let len = size.checked_mul(-2).ok_or_else(|| AttributeError::TooBigString(size))?;
+        let len = size
+            .checked_mul(-2)
+            .ok_or(AttributeError::TooBigString(size))?;
         bits.read_bytes(len)
-            .and_then(|data| decode_utf16(&data[..]).map(Cow::into_owned).ok())
-            .ok_or_else(|| AttributeError::TooBigString(len))
+            .and_then(|data| decode_utf16(&data[..]).ok().map(|x| f(&x)))
+            .ok_or(AttributeError::TooBigString(len))
     } else {
         bits.read_bytes(size)
-            .and_then(|data| decode_windows1252(&data[..]).map(Cow::into_owned).ok())
-            .ok_or_else(|| AttributeError::TooBigString(size))
+            .and_then(|data| decode_windows1252(&data[..]).ok().map(|x| f(&x)))
+            .ok_or(AttributeError::TooBigString(size))
     }
 }
 
@@ -1097,66 +1838,114 @@ fn decode_loadout(bits: &mut BitGet<'_>) -> Option<Loadout> {
     }
 }
 
-fn decode_unique_id(bits: &mut BitGet<'_>, net_version: i32) -> Result<UniqueId, AttributeError> {
+fn decode_unique_id(
+    bits: &mut BitGet<'_>,
+    version: ReplayVersion,
+    interner: &mut Interner,
+) -> Result<UniqueId, AttributeError> {
     let system_id = bits
         .read_u8()
-        .ok_or_else(|| AttributeError::NotEnoughDataFor("System id"))?;
-    decode_unique_id_with_system_id(bits, net_version, system_id)
+        .ok_or(AttributeError::NotEnoughDataFor("System id"))?;
+    decode_unique_id_with_system_id(bits, version, system_id, interner)
+}
+
+/// Split-screen supports up to four players on a machine, so larger local ids are a sign that
+/// the preceding remote id was decoded with the wrong layout
+const MAX_LOCAL_ID: u8 = 3;
+
+/// Decodes a PsyNet id, which is the online id followed by 24 bytes of additional data before
+/// `Feature::CompactPsyNetIds`. The layout of the replay's era is tried first, but if the id
+/// can't be read with it or it isn't followed by a plausible local id, the other era's layout is
+/// used instead. Either way the bytes that follow the online id are kept as is in `unknown1`
+/// (and encoded back as is), so ids of mismatched eras survive a round trip.
+fn decode_psynet_id(
+    bits: &mut BitGet<'_>,
+    version: ReplayVersion,
+) -> Result<PsyNetId, AttributeError> {
+    let online_id = bits
+        .read_u64()
+        .ok_or(AttributeError::NotEnoughDataFor("PsyNet ID"))?;
+
+    let layouts = if version.supports(Feature::CompactPsyNetIds) {
+        [0, 24]
+    } else {
+        [24, 0]
+    };
+
+    for &len in &layouts {
+        let mut lookahead = *bits;
+        if let Some(unknown1) = lookahead.read_bytes(len) {
+            if lookahead.read_u8().is_some_and(|x| x <= MAX_LOCAL_ID) {
+                bits.read_bytes(len);
+                return Ok(PsyNetId {
+                    online_id,
+                    unknown1: unknown1.into_owned(),
+                });
+            }
+        }
+    }
+
+    Err(AttributeError::NotEnoughDataFor("PsyNet ID Unknown"))
 }
 
 fn decode_unique_id_with_system_id(
     bits: &mut BitGet<'_>,
-    net_version: i32,
+    version: ReplayVersion,
     system_id: u8,
+    interner: &mut Interner,
 ) -> Result<UniqueId, AttributeError> {
     let remote_id = match system_id {
         0 => bits
             .read_u32_bits(24)
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("SplitScreen"))
+            .ok_or(AttributeError::NotEnoughDataFor("SplitScreen"))
             .map(RemoteId::SplitScreen),
         1 => bits
             .read_u64()
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Steam"))
+            .ok_or(AttributeError::NotEnoughDataFor("Steam"))
             .map(RemoteId::Steam),
         2 => {
             let name_bytes = bits
                 .read_bytes(16)
-                .ok_or_else(|| AttributeError::NotEnoughDataFor("PS4 Name"))?
+                .ok_or(AttributeError::NotEnoughDataFor("PS4 Name"))?
                 .iter()
                 .take_while(|&&x| x != 0)
                 .cloned()
                 .collect::<Vec<u8>>();
 
             let (name, _) = WINDOWS_1252.decode_without_bom_handling(&name_bytes[..]);
-            let to_read = if net_version >= 1 { 16 } else { 8 };
+            let to_read = if version.supports(Feature::LongPs4Ids) {
+                16
+            } else {
+                8
+            };
 
             let unknown1 = bits
                 .read_bytes(to_read)
-                .ok_or_else(|| AttributeError::NotEnoughDataFor("PS4 Unknown"))
+                .ok_or(AttributeError::NotEnoughDataFor("PS4 Unknown"))
                 .map(Cow::into_owned)?;
 
             let online_id = bits
                 .read_u64()
-                .ok_or_else(|| AttributeError::NotEnoughDataFor("PS4 ID"))?;
+                .ok_or(AttributeError::NotEnoughDataFor("PS4 ID"))?;
 
             Ok(RemoteId::PlayStation(Ps4Id {
-                name: name.to_string(),
+                name: interner.intern(&name),
                 unknown1,
                 online_id,
             }))
         }
         4 => bits
             .read_u64()
-            .ok_or_else(|| AttributeError::NotEnoughDataFor("Xbox"))
+            .ok_or(AttributeError::NotEnoughDataFor("Xbox"))
             .map(RemoteId::Xbox),
         6 => {
             let online_id = bits
                 .read_u64()
-                .ok_or_else(|| AttributeError::NotEnoughDataFor("Switch ID"))?;
+                .ok_or(AttributeError::NotEnoughDataFor("Switch ID"))?;
 
             let unknown1 = bits
                 .read_bytes(24)
-                .ok_or_else(|| AttributeError::NotEnoughDataFor("Switch ID Unknown"))
+                .ok_or(AttributeError::NotEnoughDataFor("Switch ID Unknown"))
                 .map(Cow::into_owned)?;
 
             Ok(RemoteId::Switch(SwitchId {
@@ -1164,34 +1953,13 @@ fn decode_unique_id_with_system_id(
                 unknown1,
             }))
         }
-        7 => {
-            let online_id = bits
-                .read_u64()
-                .ok_or_else(|| AttributeError::NotEnoughDataFor("PsyNet ID"))?;
-
-            if net_version < 10 {
-                let unknown1 = bits
-                    .read_bytes(24)
-                    .ok_or_else(|| AttributeError::NotEnoughDataFor("PsyNet ID Unknown"))
-                    .map(Cow::into_owned)?;
-
-                Ok(RemoteId::PsyNet(PsyNetId {
-                    online_id,
-                    unknown1,
-                }))
-            } else {
-                Ok(RemoteId::PsyNet(PsyNetId {
-                    online_id,
-                    ..Default::default()
-                }))
-            }
-        }
+        7 => decode_psynet_id(bits, version).map(RemoteId::PsyNet),
         x => Err(AttributeError::UnrecognizedRemoteId(x)),
     }?;
 
     let local_id = bits
         .read_u8()
-        .ok_or_else(|| AttributeError::NotEnoughDataFor("UniqueId local_id"))?;
+        .ok_or(AttributeError::NotEnoughDataFor("UniqueId local_id"))?;
     Ok(UniqueId {
         system_id,
         remote_id,
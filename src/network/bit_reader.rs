@@ -0,0 +1,384 @@
+use std::borrow::Cow;
+use std::convert::{TryFrom, TryInto};
+
+/// Reads a little endian bit stream, where the least significant bit of each value is read
+/// first. The reader only tracks its bit position: each read loads the eight bytes at the current
+/// byte as a single word and shifts out the requested bits, so there isn't a buffered word that
+/// has to be refilled (and branched on) as the reader crosses word boundaries. Reads near the end
+/// of the data, where eight bytes aren't available, load the remaining bytes into a zeroed word.
+///
+/// Checked reads return `None` without advancing when there aren't enough bits left. Unchecked
+/// reads skip the check and read zeros past the end of the data, which leaves the reader empty.
+///
+/// This is the reader that the network models decode from (eg: `Vector::decode`), while
+/// `BitReader` wraps it with a friendlier interface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitGet<'a> {
+    data: &'a [u8],
+
+    /// The number of bits read
+    pos: usize,
+
+    /// The number of bits in the data
+    len: usize,
+}
+
+/// The most bits that can be read out of a single word, as the word starts at the byte of the
+/// position and the first up to seven bits of it may already be read
+const WORD_BITS: usize = 56;
+
+macro_rules! gen_read {
+    ($name:ident, $unchecked:ident, $t:ty) => {
+        #[inline]
+        pub fn $name(&mut self) -> Option<$t> {
+            self.read_bits(std::mem::size_of::<$t>() * 8)
+                .map(|x| x as $t)
+        }
+
+        #[inline]
+        pub fn $unchecked(&mut self) -> $t {
+            self.read_bits_unchecked(std::mem::size_of::<$t>() * 8) as $t
+        }
+    };
+}
+
+impl<'a> BitGet<'a> {
+    pub fn new(data: &'a [u8]) -> BitGet<'a> {
+        BitGet {
+            data,
+            pos: 0,
+            len: data.len() * 8,
+        }
+    }
+
+    gen_read!(read_u8, read_u8_unchecked, u8);
+    gen_read!(read_i8, read_i8_unchecked, i8);
+    gen_read!(read_u16, read_u16_unchecked, u16);
+    gen_read!(read_u32, read_u32_unchecked, u32);
+    gen_read!(read_i32, read_i32_unchecked, i32);
+    gen_read!(read_u64, read_u64_unchecked, u64);
+    gen_read!(read_i64, read_i64_unchecked, i64);
+
+    /// The word of the data starting at the given byte, padded with zeros past the end
+    #[inline]
+    fn word(&self, byte: usize) -> u64 {
+        match self.data.get(byte..byte + 8) {
+            Some(x) => u64::from_le_bytes(x.try_into().unwrap()),
+            None => {
+                let mut word = [0u8; 8];
+                let tail = self.data.get(byte..).unwrap_or(&[]);
+                word[..tail.len()].copy_from_slice(tail);
+                u64::from_le_bytes(word)
+            }
+        }
+    }
+
+    /// Peeks up to 56 bits
+    #[inline]
+    fn peek_word(&self, bits: usize) -> u64 {
+        debug_assert!(bits <= WORD_BITS);
+        let word = self.word(self.pos >> 3) >> (self.pos & 7);
+        word & ((1 << bits) - 1)
+    }
+
+    /// Reads up to 64 bits without checking that there are enough bits left
+    #[inline]
+    fn read_bits_unchecked(&mut self, bits: usize) -> u64 {
+        if bits <= WORD_BITS {
+            let res = self.peek_word(bits);
+            self.pos += bits;
+            res
+        } else {
+            let low = self.peek_word(32);
+            self.pos += 32;
+            let high = self.peek_word(bits - 32);
+            self.pos += bits - 32;
+            low | (high << 32)
+        }
+    }
+
+    /// Reads up to 64 bits
+    #[inline]
+    fn read_bits(&mut self, bits: usize) -> Option<u64> {
+        if self.has_bits_remaining(bits) {
+            Some(self.read_bits_unchecked(bits))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn has_bits_remaining(&self, bits: usize) -> bool {
+        self.len.saturating_sub(self.pos) >= bits
+    }
+
+    /// The number of bits left to read
+    #[inline]
+    pub fn bits_remaining(&self) -> usize {
+        self.len.saturating_sub(self.pos)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.len
+    }
+
+    #[inline]
+    pub fn read_bit(&mut self) -> Option<bool> {
+        let byte = *self.data.get(self.pos >> 3)?;
+        let bit = (byte >> (self.pos & 7)) & 1 != 0;
+        self.pos += 1;
+        Some(bit)
+    }
+
+    #[inline]
+    pub fn read_bit_unchecked(&mut self) -> bool {
+        self.read_bit().unwrap_or_else(|| {
+            self.pos += 1;
+            false
+        })
+    }
+
+    /// Reads the given number of bits (up to 32) as an unsigned integer
+    #[inline]
+    pub fn read_u32_bits(&mut self, bits: i32) -> Option<u32> {
+        let bits = bits_arg(bits)?;
+        self.read_bits(bits).map(|x| x as u32)
+    }
+
+    /// Reads the given number of bits (up to 32) as an unsigned integer without checking that
+    /// there are enough bits left. Wider reads are clamped to 32 bits.
+    #[inline]
+    pub fn read_u32_bits_unchecked(&mut self, bits: i32) -> u32 {
+        debug_assert!((0..=32).contains(&bits));
+        self.read_bits_unchecked(bits.clamp(0, 32) as usize) as u32
+    }
+
+    #[inline]
+    pub fn read_i32_bits(&mut self, bits: i32) -> Option<i32> {
+        self.read_u32_bits(bits).map(|x| x as i32)
+    }
+
+    #[inline]
+    pub fn read_f32(&mut self) -> Option<f32> {
+        self.read_u32().map(f32::from_bits)
+    }
+
+    pub fn if_get<T, F>(&mut self, mut f: F) -> Option<Option<T>>
+    where
+        F: FnMut(&mut Self) -> Option<T>,
+    {
+        if self.read_bit()? {
+            f(self).map(Some)
+        } else {
+            Some(None)
+        }
+    }
+
+    pub fn if_get_unchecked<T, F>(&mut self, mut f: F) -> Option<T>
+    where
+        F: FnMut(&mut Self) -> T,
+    {
+        if self.read_bit_unchecked() {
+            Some(f(self))
+        } else {
+            None
+        }
+    }
+
+    /// Reads three consecutive values of the given number of bits (up to 32), such as the
+    /// components of a vector. The values are extracted out of a single read when they fit
+    /// within a word.
+    #[inline]
+    pub fn read_packed3(&mut self, bits: i32) -> Option<[u32; 3]> {
+        let width = usize::try_from(bits).ok().filter(|x| *x <= 32)?;
+        if !self.has_bits_remaining(width * 3) {
+            return None;
+        }
+
+        if width * 3 <= 64 {
+            let word = self.read_bits_unchecked(width * 3);
+            let mask = (1 << width) - 1;
+            Some([
+                (word & mask) as u32,
+                ((word >> width) & mask) as u32,
+                ((word >> (width * 2)) & mask) as u32,
+            ])
+        } else {
+            Some([
+                self.read_bits_unchecked(width) as u32,
+                self.read_bits_unchecked(width) as u32,
+                self.read_bits_unchecked(width) as u32,
+            ])
+        }
+    }
+
+    /// Reads the bytes, which are borrowed when the reader is at a byte boundary
+    pub fn read_bytes(&mut self, bytes: i32) -> Option<Cow<'a, [u8]>> {
+        let len = usize::try_from(bytes).ok()?;
+        if !self.has_bits_remaining(len.checked_mul(8)?) {
+            return None;
+        }
+
+        if self.pos & 7 == 0 {
+            // The position may be past the end of the data after unchecked reads
+            let start = self.pos >> 3;
+            let res = self.data.get(start..start.checked_add(len)?)?;
+            self.pos += len * 8;
+            Some(Cow::Borrowed(res))
+        } else {
+            let res = (0..len).map(|_| self.read_u8_unchecked()).collect();
+            Some(Cow::Owned(res))
+        }
+    }
+
+    /// Reads a compressed integer that is less than or equal to `max`, which takes at most the
+    /// given number of bits (1 to 32). The most significant bit is only read if setting it
+    /// wouldn't exceed `max`.
+    #[inline]
+    pub fn read_bits_max(&mut self, bits: i32, max: i32) -> Option<u32> {
+        let bits = bits_arg(bits).filter(|x| *x > 0)?;
+        let data = self.read_bits(bits - 1)? as u32;
+        let up = data + (1 << (bits - 1));
+        if up >= max as u32 {
+            Some(data)
+        } else {
+            self.read_bit().map(|x| if x { up } else { data })
+        }
+    }
+
+    /// Reads a compressed integer without checking that there are enough bits left. Widths
+    /// outside of 1 to 32 bits are clamped.
+    #[inline]
+    pub fn read_bits_max_unchecked(&mut self, bits: i32, max: i32) -> u32 {
+        debug_assert!((1..=32).contains(&bits));
+        let bits = bits.clamp(1, 32);
+        let data = self.read_u32_bits_unchecked(bits - 1);
+        let up = data + (1 << (bits - 1));
+        if up >= max as u32 || !self.read_bit_unchecked() {
+            data
+        } else {
+            up
+        }
+    }
+}
+
+/// A number of bits to read into a `u32`, which rejects reads that wouldn't fit
+#[inline]
+fn bits_arg(bits: i32) -> Option<usize> {
+    usize::try_from(bits).ok().filter(|x| *x <= 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_reads() {
+        let data = [0b1010_1010, 0b0101_0101, 0xff, 0x01, 0, 0, 0, 0, 0x80, 0x7f];
+        let mut bits = BitGet::new(&data);
+        assert_eq!(bits.bits_remaining(), 80);
+        assert_eq!(bits.read_bit(), Some(false));
+        assert_eq!(bits.read_u32_bits(3), Some(0b101));
+        assert_eq!(bits.read_u8(), Some(0b0101_1010));
+        assert_eq!(bits.read_u32_bits(0), Some(0));
+
+        // Crosses the end of the first word
+        assert_eq!(bits.read_u64(), Some(0xf800_0000_0000_1ff5));
+        assert_eq!(bits.bits_remaining(), 4);
+        assert_eq!(bits.read_u8(), None);
+        assert_eq!(bits.read_u32_bits(5), None);
+        assert_eq!(bits.read_u32_bits(4), Some(0x7));
+        assert!(bits.is_empty());
+        assert_eq!(bits.read_bit(), None);
+        assert_eq!(bits.read_bytes(0).as_deref(), Some(&[][..]));
+    }
+
+    #[test]
+    fn test_read_packed3() {
+        let data = [
+            0xab, 0xcd, 0xef, 0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x12,
+        ];
+        for width in 0..=32 {
+            let mut packed = BitGet::new(&data);
+            assert_eq!(packed.read_bit(), Some(true));
+            let values = packed.read_packed3(width);
+
+            let mut bits = BitGet::new(&data);
+            assert_eq!(bits.read_bit(), Some(true));
+            let expected = (|| {
+                Some([
+                    bits.read_u32_bits(width)?,
+                    bits.read_u32_bits(width)?,
+                    bits.read_u32_bits(width)?,
+                ])
+            })();
+            assert_eq!(values, expected, "width {}", width);
+            if values.is_some() {
+                assert_eq!(packed.bits_remaining(), bits.bits_remaining());
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_bytes() {
+        let data = [0x12, 0x34, 0x56, 0x78];
+        let mut bits = BitGet::new(&data);
+        assert!(matches!(
+            bits.read_bytes(2),
+            Some(Cow::Borrowed(&[0x12, 0x34]))
+        ));
+        assert_eq!(bits.read_bytes(3), None);
+        assert_eq!(bits.read_bytes(-1), None);
+
+        assert_eq!(bits.read_u32_bits(4), Some(0x6));
+        assert_eq!(bits.read_bytes(1).as_deref(), Some(&[0x85][..]));
+        assert_eq!(bits.read_bytes(1), None);
+        assert_eq!(bits.bits_remaining(), 4);
+    }
+
+    #[test]
+    fn test_read_bytes_past_end() {
+        let data = [0x12, 0x34];
+        let mut bits = BitGet::new(&data);
+        assert_eq!(bits.read_u32_unchecked(), 0x3412);
+        assert_eq!(bits.read_bytes(0), None);
+        assert_eq!(bits.read_bytes(1), None);
+        assert!(bits.is_empty());
+    }
+
+    #[test]
+    fn test_read_u32_bits_width() {
+        let data = [0xff; 8];
+        let mut bits = BitGet::new(&data);
+        assert_eq!(bits.read_u32_bits(33), None);
+        assert_eq!(bits.read_u32_bits(64), None);
+        assert_eq!(bits.read_i32_bits(33), None);
+        assert_eq!(bits.bits_remaining(), 64);
+        assert_eq!(bits.read_u32_bits(32), Some(u32::MAX));
+    }
+
+    #[test]
+    fn test_read_bits_max_width() {
+        let data = [0xff; 8];
+        let mut bits = BitGet::new(&data);
+        assert_eq!(bits.read_bits_max(0, 20), None);
+        assert_eq!(bits.read_bits_max(33, i32::MAX), None);
+        assert_eq!(bits.read_bits_max(-1, 20), None);
+        assert_eq!(bits.bits_remaining(), 64);
+        assert_eq!(bits.read_bits_max(32, i32::MAX), Some(0x7fff_ffff));
+        assert_eq!(bits.bits_remaining(), 33);
+        assert_eq!(bits.read_bits_max_unchecked(32, 0), 0x7fff_ffff);
+        assert_eq!(bits.bits_remaining(), 2);
+    }
+
+    #[test]
+    fn test_read_unchecked() {
+        let mut bits = BitGet::new(&[0xff]);
+        assert_eq!(bits.read_u32_bits_unchecked(4), 0xf);
+        assert_eq!(bits.read_u32_bits_unchecked(8), 0xf);
+        assert!(bits.is_empty());
+        assert_eq!(bits.bits_remaining(), 0);
+        assert_eq!(bits.read_bits_max(5, 20), None);
+    }
+}
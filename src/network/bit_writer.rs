@@ -0,0 +1,119 @@
+/// The inverse of `BitGet`: packs values into a little endian bit stream where the least
+/// significant bit of each value is written first.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BitWriter {
+    data: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        BitWriter::default()
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        if self.bits % 8 == 0 {
+            self.data.push(0);
+        }
+
+        if bit {
+            let last = self.data.len() - 1;
+            self.data[last] |= 1 << (self.bits % 8);
+        }
+
+        self.bits += 1;
+    }
+
+    /// Writes the lowest `bits` bits of the value
+    pub fn write_u64_bits(&mut self, value: u64, bits: i32) {
+        for i in 0..bits {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    pub fn write_u32_bits(&mut self, value: u32, bits: i32) {
+        self.write_u64_bits(u64::from(value), bits)
+    }
+
+    /// Writes a value that will be read back with `BitGet::read_bits_max`. The value must be less
+    /// than `max`.
+    pub fn write_bits_max(&mut self, value: u32, bits: i32, max: i32) {
+        let high = 1 << (bits - 1);
+        let data = value & (high - 1);
+        self.write_u32_bits(data, bits - 1);
+        if data + high < max as u32 {
+            self.write_bit(value & high != 0);
+        }
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.write_u32_bits(u32::from(value), 8)
+    }
+
+    pub fn write_i8(&mut self, value: i8) {
+        self.write_u8(value as u8)
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_u32_bits(value, 32)
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.write_u32(value as u32)
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write_u64_bits(value, 64)
+    }
+
+    pub fn write_i64(&mut self, value: i64) {
+        self.write_u64(value as u64)
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_u32(value.to_bits())
+    }
+
+    pub fn write_bytes(&mut self, data: &[u8]) {
+        for &b in data {
+            self.write_u8(b);
+        }
+    }
+
+    /// Returns the written data, with the last byte padded with zeros
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::bit_reader::BitGet;
+
+    #[test]
+    fn test_write_read_round_trip() {
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        writer.write_u32_bits(5, 3);
+        writer.write_i32(-2000);
+        writer.write_u64(0xDEAD_BEEF_CAFE_BABE);
+        writer.write_f32(1.5);
+        writer.write_bytes(b"abc");
+        writer.write_bits_max(17, 5, 20);
+        writer.write_bits_max(6, 5, 20);
+        writer.write_i8(-3);
+        let data = writer.into_bytes();
+
+        let mut bits = BitGet::new(&data[..]);
+        assert_eq!(bits.read_bit(), Some(true));
+        assert_eq!(bits.read_u32_bits(3), Some(5));
+        assert_eq!(bits.read_i32(), Some(-2000));
+        assert_eq!(bits.read_u64(), Some(0xDEAD_BEEF_CAFE_BABE));
+        assert_eq!(bits.read_f32(), Some(1.5));
+        assert_eq!(bits.read_bytes(3).unwrap().as_ref(), b"abc");
+        assert_eq!(bits.read_bits_max(5, 20), Some(17));
+        assert_eq!(bits.read_bits_max(5, 20), Some(6));
+        assert_eq!(bits.read_i8(), Some(-3));
+    }
+}
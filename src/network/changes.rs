@@ -0,0 +1,100 @@
+use crate::network::attributes::Attribute;
+use crate::network::frame_decoder::{FrameBuffers, FrameSink};
+use crate::network::models::{ActorId, StreamId};
+use failure::Error;
+use fnv::FnvHashMap;
+
+/// Drops attribute updates that replicate the value an actor already has, so that the wrapped
+/// sink only receives the attributes that changed since the actor's previous update. An actor's
+/// values are forgotten when it is deleted or respawns.
+pub(crate) struct ChangedAttributes<'a, S> {
+    sink: &'a mut S,
+    values: FnvHashMap<ActorId, FnvHashMap<StreamId, Attribute>>,
+}
+
+impl<'a, S: FrameSink> ChangedAttributes<'a, S> {
+    pub fn new(sink: &'a mut S) -> Self {
+        ChangedAttributes {
+            sink,
+            values: FnvHashMap::default(),
+        }
+    }
+}
+
+/// Attributes that signal an event are kept even when repeated, as a repeat is another event
+fn is_event(attribute: &Attribute) -> bool {
+    matches!(
+        attribute,
+        Attribute::AppliedDamage(..)
+            | Attribute::Demolish(_)
+            | Attribute::DemolishExtended(_)
+            | Attribute::Explosion(_)
+            | Attribute::ExtendedExplosion(..)
+            | Attribute::MusicStinger(_)
+            | Attribute::StatEvent(..)
+    )
+}
+
+impl<'a, S: FrameSink> FrameSink for ChangedAttributes<'a, S> {
+    fn frame(&mut self, time: f32, delta: f32, buffers: &mut FrameBuffers) -> Result<(), Error> {
+        for actor in &buffers.new_actors {
+            self.values.remove(&actor.actor_id);
+        }
+
+        let values = &mut self.values;
+        buffers.updated_actors.retain(|update| {
+            if is_event(&update.attribute) {
+                return true;
+            }
+
+            let actor = values.entry(update.actor_id).or_default();
+            match actor.get(&update.stream_id) {
+                Some(previous) if *previous == update.attribute => false,
+                _ => {
+                    actor.insert(update.stream_id, update.attribute.clone());
+                    true
+                }
+            }
+        });
+
+        for actor_id in &buffers.deleted_actors {
+            self.values.remove(actor_id);
+        }
+
+        self.sink.frame(time, delta, buffers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::network::FrameIndex;
+    use crate::ParserBuilder;
+
+    #[test]
+    fn test_only_changed_attributes() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+        let changed = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .only_changed_attributes()
+            .parse()
+            .unwrap();
+
+        let frames = &replay.network_frames.as_ref().unwrap().frames;
+        let changed_frames = &changed.network_frames.as_ref().unwrap().frames;
+        assert_eq!(frames.len(), changed_frames.len());
+
+        let updates = |x: &[crate::Frame]| x.iter().map(|f| f.updated_actors.len()).sum::<usize>();
+        assert!(updates(changed_frames) < updates(frames));
+
+        // Both reconstruct the same state throughout the replay
+        let index = FrameIndex::new(&replay).unwrap();
+        let changed_index = FrameIndex::new(&changed).unwrap();
+        for i in (0..frames.len()).step_by(500) {
+            assert_eq!(index.state_at(i), changed_index.state_at(i));
+        }
+    }
+}
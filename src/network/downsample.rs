@@ -0,0 +1,169 @@
+use crate::network::frame_decoder::{FrameBuffers, FrameSink};
+use failure::Error;
+
+/// Merges decoded frames so that the wrapped sink only receives a frame per sample period.
+/// Frames are applied as new actors, updated attributes, and then deleted actors, so a merged
+/// frame keeps the latest update of each attribute and reconstructs the same state as applying
+/// every frame it replaces.
+pub(crate) struct Downsample<'a, S> {
+    sink: &'a mut S,
+    period: f32,
+
+    /// The time of the next sample, which is unset until the first frame is seen
+    next_sample: Option<f32>,
+    pending: FrameBuffers,
+    pending_frames: usize,
+    time: f32,
+    delta: f32,
+}
+
+impl<'a, S: FrameSink> Downsample<'a, S> {
+    pub fn new(sink: &'a mut S, hz: u32) -> Self {
+        Downsample {
+            sink,
+            period: 1.0 / hz as f32,
+            next_sample: None,
+            pending: FrameBuffers::default(),
+            pending_frames: 0,
+            time: 0.0,
+            delta: 0.0,
+        }
+    }
+
+    /// Sends the frames merged since the last sample to the sink
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if self.pending_frames == 0 {
+            return Ok(());
+        }
+
+        // Only the latest update of an actor's attribute is kept, in the order of the updates
+        let mut seen = fnv::FnvHashSet::default();
+        let updates = &mut self.pending.updated_actors;
+        updates.reverse();
+        updates.retain(|x| seen.insert((x.actor_id, x.stream_id)));
+        updates.reverse();
+
+        self.sink.frame(self.time, self.delta, &mut self.pending)?;
+        self.pending.clear();
+        self.pending_frames = 0;
+        self.delta = 0.0;
+        Ok(())
+    }
+
+    fn merge(&mut self, buffers: &mut FrameBuffers) -> Result<(), Error> {
+        // A deleted actor that is recreated in the same window can't be represented in a single
+        // frame, as deletions are applied last
+        let recreated = buffers
+            .new_actors
+            .iter()
+            .any(|x| self.pending.deleted_actors.contains(&x.actor_id));
+        if recreated {
+            self.flush()?;
+        }
+
+        // An actor that respawns without being deleted starts over with no attributes
+        for actor in &buffers.new_actors {
+            let id = actor.actor_id;
+            self.pending.new_actors.retain(|x| x.actor_id != id);
+            self.pending.updated_actors.retain(|x| x.actor_id != id);
+        }
+
+        self.pending.new_actors.append(&mut buffers.new_actors);
+        self.pending
+            .updated_actors
+            .append(&mut buffers.updated_actors);
+        self.pending
+            .deleted_actors
+            .append(&mut buffers.deleted_actors);
+        Ok(())
+    }
+}
+
+impl<'a, S: FrameSink> FrameSink for Downsample<'a, S> {
+    fn frame(&mut self, time: f32, delta: f32, buffers: &mut FrameBuffers) -> Result<(), Error> {
+        self.merge(buffers)?;
+        self.pending_frames += 1;
+        self.time = time;
+        self.delta += delta;
+
+        // Samples are taken on a fixed grid from the first frame so that late frames don't
+        // cause the sample times to drift
+        let next_sample = *self.next_sample.get_or_insert(time);
+        if time >= next_sample {
+            let mut next = next_sample;
+            while next <= time {
+                next += self.period;
+            }
+            self.next_sample = Some(next);
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::network::{FrameIndex, WorldState};
+    use crate::ParserBuilder;
+
+    fn final_state(replay: &crate::Replay<'_>) -> Vec<String> {
+        let index = FrameIndex::new(replay).unwrap();
+        let mut state = WorldState::default();
+        let frames = replay.network_frames.as_ref().unwrap().frames.len();
+        for i in 0..frames {
+            state.apply(&index, i);
+        }
+
+        let mut actors: Vec<_> = state
+            .actors
+            .iter()
+            .map(|(id, x)| {
+                let mut attributes: Vec<_> = x.attributes.iter().collect();
+                attributes.sort_by_key(|(k, _)| **k);
+                format!("{:?} {:?} {:?}", id, x.object_id, attributes)
+            })
+            .collect();
+        actors.sort();
+        actors
+    }
+
+    #[test]
+    fn test_sample_rate() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+        let sampled = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .sample_rate(10)
+            .parse()
+            .unwrap();
+
+        let frames = &replay.network_frames.as_ref().unwrap().frames;
+        let sampled_frames = &sampled.network_frames.as_ref().unwrap().frames;
+        assert!(sampled_frames.len() < frames.len() / 2);
+        assert_eq!(sampled_frames[0].time, frames[0].time);
+        assert_eq!(sampled_frames[0].new_actors, frames[0].new_actors);
+        assert_eq!(
+            sampled_frames.last().unwrap().time,
+            frames.last().unwrap().time
+        );
+
+        // There is at most a sample for each tenth of a second
+        let duration = frames.last().unwrap().time - frames[0].time;
+        assert!(
+            sampled_frames.len() <= (duration * 10.0) as usize + 2,
+            "{} samples over {} seconds",
+            sampled_frames.len(),
+            duration
+        );
+
+        let total: f32 = frames.iter().map(|x| x.delta).sum();
+        let sampled_total: f32 = sampled_frames.iter().map(|x| x.delta).sum();
+        assert!((total - sampled_total).abs() < total * 1e-3);
+
+        assert_eq!(final_state(&sampled), final_state(&replay));
+    }
+}
@@ -1,14 +1,24 @@
-use crate::errors::{AttributeError, NetworkError};
-use crate::hashes::ATTRIBUTES;
+use crate::errors::{AttributeError, LimitExceeded, LimitKind, NetworkError};
+use crate::hashes::{ATTRIBUTES, SPAWN_STATS};
+use crate::limits::Limits;
 use crate::network::attributes::{AttributeDecoder, ProductValueDecoder};
+use crate::network::bit_reader::BitGet;
 use crate::network::models::{
-    ActorId, Frame, NewActor, ObjectId, SpawnTrajectory, StreamId, Trajectory, UpdatedAttribute,
+    normalize_object, ActorId, Frame, NewActor, ObjectId, SpawnTrajectory, StreamId, Trajectory,
+    UpdatedAttribute,
 };
-use crate::network::{CacheInfo, ObjectAttribute, VersionTriplet};
+use crate::network::overrides::{has_class, ClassOverrides};
+use crate::network::pool::DecodeScratch;
+use crate::network::progress::{Cancellation, Progress, ProgressHook};
+use crate::network::seek::Seek;
+use crate::network::skip::{SkipHook, SkippedActors};
+use crate::network::spans::BitSpan;
+use crate::network::unknown::{UnknownActor, UnknownHook, UnknownObjects};
+use crate::network::{CacheInfo, ObjectAttribute};
 use crate::parser::ReplayBody;
-use bitter::BitGet;
+use crate::version::ReplayVersion;
 use failure::Error;
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
 use std::collections::HashMap;
 use std::ops::Deref;
 
@@ -20,7 +30,25 @@ pub(crate) struct FrameDecoder<'a, 'b: 'a> {
     pub spawns: &'a Vec<SpawnTrajectory>,
     pub object_ind_attributes: FnvHashMap<ObjectId, CacheInfo>,
     pub object_ind_attrs: HashMap<ObjectId, HashMap<StreamId, ObjectAttribute>>,
-    pub version: VersionTriplet,
+    pub version: ReplayVersion,
+    pub progress: Option<&'a ProgressHook>,
+    pub cancel: Option<&'a Cancellation>,
+    pub limits: Limits,
+    pub strict: bool,
+    pub skip: Option<&'a SkipHook>,
+    pub unknown_objects: UnknownObjects,
+    pub on_unknown: Option<&'a UnknownHook>,
+    pub overrides: Option<&'a ClassOverrides>,
+    pub seek: Option<Seek>,
+}
+
+/// Counts of what was decoded from the network data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct DecodeStats {
+    pub frames: usize,
+    pub actors_created: usize,
+    pub attributes_decoded: usize,
+    pub bits_unread: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -67,13 +95,12 @@ impl<'a, 'b> FrameDecoder<'a, 'b> {
         self.body
             .net_cache
             .iter()
-            .map(|x| {
+            .flat_map(|x| {
                 x.properties
                     .iter()
                     .map(|prop| (x.object_ind, prop.object_ind, prop.stream_id))
                     .collect::<Vec<(i32, i32, i32)>>()
             })
-            .flat_map(|x| x)
             .filter(|&(_obj_id, _prop_id, prop_stream_id)| StreamId(prop_stream_id) == stream_id)
             .map(|(obj_id, prop_id, _prop_stream_id)| {
                 let obj_id = ObjectId(obj_id);
@@ -123,12 +150,12 @@ impl<'a, 'b> FrameDecoder<'a, 'b> {
 
     fn parse_new_actor(
         &self,
-        mut bits: &mut BitGet<'_>,
+        bits: &mut BitGet<'_>,
         actor_id: ActorId,
     ) -> Result<NewActor, NetworkError> {
         if_chain! {
             if let Some(name_id) =
-                if self.version >= VersionTriplet(868, 14, 0) {
+                if self.version.at_least(868, 14, 0) {
                     bits.read_i32().map(Some)
                 } else {
                     Some(None)
@@ -137,9 +164,9 @@ impl<'a, 'b> FrameDecoder<'a, 'b> {
             if let Some(_) = bits.read_bit();
             if let Some(object_id) = bits.read_i32().map(ObjectId);
             let spawn = self.spawns.get(usize::from(object_id))
-                .ok_or_else(|| NetworkError::ObjectIdOutOfRange(object_id))?;
+                .ok_or(NetworkError::ObjectIdOutOfRange(object_id))?;
 
-            if let Some(traj) = Trajectory::from_spawn(&mut bits, *spawn, self.version.net_version());
+            if let Some(traj) = Trajectory::from_spawn(bits, *spawn, self.version.net);
             then {
                 Ok(NewActor {
                     actor_id,
@@ -153,50 +180,177 @@ impl<'a, 'b> FrameDecoder<'a, 'b> {
         }
     }
 
-    fn decode_frame(
+    /// An object is known when the tables (or the overrides) describe how it spawns or which
+    /// class it belongs to.
+    /// An unknown object is assumed to spawn without a trajectory, which may be wrong.
+    fn is_known_object(&self, object_id: ObjectId) -> bool {
+        self.body
+            .objects
+            .get(usize::from(object_id))
+            .is_some_and(|name| {
+                SPAWN_STATS.contains_key(name.deref())
+                    || has_class(self.overrides, normalize_object(name))
+            })
+    }
+
+    /// The network data starting at the given bit
+    fn bits_at(&self, position: usize) -> Option<BitGet<'b>> {
+        let mut bits = BitGet::new(self.body.network_data.get(position / 8..)?);
+        if position % 8 != 0 {
+            bits.read_u32_bits((position % 8) as i32)?;
+        }
+        Some(bits)
+    }
+
+    /// Finds the start of the frame after the frame at the given time failed to decode. The
+    /// frame starts after the bit that ends the actor data, and its time is the previous time
+    /// plus its delta, which is unlikely to happen by chance. Frames after a pause in the
+    /// replay (eg: after a goal) don't line up and can't be found.
+    fn next_frame(&self, from: usize, time: f32) -> Option<(BitGet<'b>, usize)> {
+        let bits_len = self.body.network_data.len() * 8;
+        (from + 1..bits_len).find_map(|position| {
+            let mut bits = self.bits_at(position - 1)?;
+            if bits.read_bit()? {
+                return None;
+            }
+
+            let next_time = bits.read_f32()?;
+            let delta = bits.read_f32()?;
+            if delta > 0.0 && delta <= 1.0 && (next_time - time - delta).abs() < 1e-3 {
+                self.bits_at(position).map(|x| (x, position))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The number of bits that have been read from the network data
+    fn bit_position(&self, bits: &BitGet<'_>) -> usize {
+        self.body.network_data.len() * 8 - bits.bits_remaining()
+    }
+
+    fn progress_at(&self, frames_decoded: usize, bits: &BitGet<'_>) -> Progress {
+        Progress {
+            frames_decoded,
+            frames_len: self.frames_len,
+            bytes_read: self.bit_position(bits) / 8,
+            bytes_len: self.body.network_data.len(),
+        }
+    }
+
+    /// The span from the given starting bit to the current position in the network data
+    fn span_from(&self, start: usize, bits: &BitGet<'_>) -> BitSpan {
+        BitSpan {
+            start,
+            len: self.bit_position(bits) - start,
+        }
+    }
+
+    /// Decides what happens to a new actor of an object that may be unknown. Returns whether the
+    /// actor is skipped.
+    fn unknown_actor(&self, frame: usize, actor: &NewActor) -> Result<bool, NetworkError> {
+        if (self.unknown_objects == UnknownObjects::Static && self.on_unknown.is_none())
+            || self.is_known_object(actor.object_id)
+        {
+            return Ok(false);
+        }
+
+        let object = self.object_ind_to_string(actor.object_id);
+        if self.unknown_objects == UnknownObjects::Fail {
+            return Err(NetworkError::UnknownObject(
+                actor.actor_id,
+                actor.object_id,
+                object,
+            ));
+        }
+
+        if let Some(hook) = self.on_unknown {
+            (hook.0)(UnknownActor {
+                frame,
+                actor: *actor,
+                object,
+            });
+        }
+
+        Ok(self.unknown_objects == UnknownObjects::Skip)
+    }
+
+    /// Decodes the actors of a frame. The actors of unknown objects that are skipped are still
+    /// decoded so that the following actors can be, but they are left out of the buffers.
+    fn decode_frame<S: FrameSink>(
         &self,
-        attr_decoder: &AttributeDecoder,
-        mut bits: &mut BitGet<'_>,
+        frame: usize,
+        attr_decoder: &mut AttributeDecoder,
+        bits: &mut BitGet<'_>,
         actors: &mut FnvHashMap<ActorId, ObjectId>,
-        time: f32,
-        delta: f32,
-    ) -> Result<Frame, NetworkError> {
-        let mut new_actors = Vec::new();
-        let mut updated_actors = Vec::new();
-        let mut deleted_actors = Vec::new();
-
-        while bits
-            .read_bit()
-            .ok_or_else(|| NetworkError::NotEnoughDataFor("Actor data"))?
-        {
+        skipped: &mut FnvHashSet<ActorId>,
+        buffers: &mut FrameBuffers,
+    ) -> Result<(), NetworkError> {
+        loop {
+            let actor_start = if S::RECORD_SPANS {
+                self.bit_position(bits)
+            } else {
+                0
+            };
+
+            if !bits
+                .read_bit()
+                .ok_or(NetworkError::NotEnoughDataFor("Actor data"))?
+            {
+                break;
+            }
+
             let actor_id = bits
                 .read_i32_bits(self.channel_bits)
                 .map(ActorId)
-                .ok_or_else(|| NetworkError::NotEnoughDataFor("Actor Id"))?;
+                .ok_or(NetworkError::NotEnoughDataFor("Actor Id"))?;
 
             // alive
             if bits
                 .read_bit()
-                .ok_or_else(|| NetworkError::NotEnoughDataFor("Is actor alive"))?
+                .ok_or(NetworkError::NotEnoughDataFor("Is actor alive"))?
             {
                 // new
                 if bits
                     .read_bit()
-                    .ok_or_else(|| NetworkError::NotEnoughDataFor("Is new actor"))?
+                    .ok_or(NetworkError::NotEnoughDataFor("Is new actor"))?
                 {
-                    let actor = self.parse_new_actor(&mut bits, actor_id)?;
+                    let actor = self.parse_new_actor(bits, actor_id)?;
+                    let skip = self.unknown_actor(frame, &actor)?;
 
                     // Insert the new actor so we can keep track of it for attribute
                     // updates. It's common for an actor id to already exist, so we
                     // overwrite it.
                     actors.insert(actor.actor_id, actor.object_id);
-                    new_actors.push(actor);
+                    match self.limits.max_actors {
+                        Some(limit) if actors.len() > limit => {
+                            return Err(NetworkError::LimitExceeded(LimitExceeded {
+                                kind: LimitKind::Actors,
+                                limit,
+                                requested: actors.len(),
+                            }));
+                        }
+                        _ => {}
+                    }
+
+                    if skip {
+                        skipped.insert(actor_id);
+                        continue;
+                    } else if !skipped.is_empty() {
+                        skipped.remove(&actor_id);
+                    }
+
+                    buffers.new_actors.push(actor);
+                    if S::RECORD_SPANS {
+                        let span = self.span_from(actor_start, bits);
+                        buffers.new_actor_spans.push(span);
+                    }
                 } else {
                     // We'll be updating an existing actor with some attributes so we need
                     // to track down what the actor's type is
                     let object_id = actors
                         .get(&actor_id)
-                        .ok_or_else(|| NetworkError::MissingActor(actor_id))?;
+                        .ok_or(NetworkError::MissingActor(actor_id))?;
 
                     // Once we have the type we need to look up what attributes are
                     // available for said type
@@ -209,18 +363,26 @@ impl<'a, 'b> FrameDecoder<'a, 'b> {
                             )
                         })?;
 
+                    let skip = !skipped.is_empty() && skipped.contains(&actor_id);
+
                     // While there are more attributes to update for our actor:
                     while bits
                         .read_bit()
-                        .ok_or_else(|| NetworkError::NotEnoughDataFor("Is prop present"))?
+                        .ok_or(NetworkError::NotEnoughDataFor("Is prop present"))?
                     {
+                        let attribute_start = if S::RECORD_SPANS {
+                            self.bit_position(bits)
+                        } else {
+                            0
+                        };
+
                         // We've previously calculated the max the stream id can be for a
                         // given type and how many bits that it encompasses so use those
                         // values now
                         let stream_id = bits
                             .read_bits_max(cache_info.prop_id_bits, cache_info.max_prop_id)
                             .map(|x| StreamId(x as i32))
-                            .ok_or_else(|| NetworkError::NotEnoughDataFor("Prop id"))?;
+                            .ok_or(NetworkError::NotEnoughDataFor("Prop id"))?;
 
                         // Look the stream id up and find the corresponding attribute
                         // decoding function. Experience has told me replays that fail to
@@ -230,119 +392,284 @@ impl<'a, 'b> FrameDecoder<'a, 'b> {
                             self.missing_attribute(cache_info, actor_id, *object_id, stream_id)
                         })?;
 
-                        let attribute =
-                            attr_decoder.decode(*attr, &mut bits).map_err(|e| match e {
-                                AttributeError::Unimplemented => {
-                                    self.unimplemented_attribute(actor_id, *object_id, stream_id)
-                                }
-                                _ => NetworkError::AttributeError(e),
-                            })?;
+                        let attribute = attr_decoder.decode(*attr, bits).map_err(|e| match e {
+                            AttributeError::Unimplemented => {
+                                self.unimplemented_attribute(actor_id, *object_id, stream_id)
+                            }
+                            _ => NetworkError::AttributeError(e),
+                        })?;
+
+                        if skip {
+                            continue;
+                        }
 
-                        updated_actors.push(UpdatedAttribute {
+                        buffers.updated_actors.push(UpdatedAttribute {
                             actor_id,
                             stream_id,
                             attribute,
                         });
+                        if S::RECORD_SPANS {
+                            let span = self.span_from(attribute_start, bits);
+                            buffers.updated_actor_spans.push(span);
+                        }
                     }
                 }
             } else {
-                deleted_actors.push(actor_id);
+                if skipped.is_empty() || !skipped.remove(&actor_id) {
+                    buffers.deleted_actors.push(actor_id);
+                }
                 actors.remove(&actor_id);
             }
         }
 
-        Ok(Frame {
-            time,
-            delta,
-            new_actors,
-            deleted_actors,
-            updated_actors,
-        })
+        Ok(())
+    }
+
+    /// When the time or delta of a frame is out of range, it most likely means that the previous
+    /// frame was decoded incorrectly, so find the last decoded new actor or attribute to blame.
+    fn out_of_range_context(&self, frames_len: usize, last: &LastDecoded) -> Option<NetworkError> {
+        match (&last.update, &last.new_actor) {
+            (Some((i, update)), new_actor) if new_actor.as_ref().map_or(true, |(j, _)| i >= j) => {
+                Some(NetworkError::TimeOutOfRangeUpdate(
+                    frames_len,
+                    *i,
+                    update.actor_id,
+                    update.stream_id,
+                    Box::new(update.attribute.clone()),
+                ))
+            }
+            (_, Some((i, new_actor))) => Some(NetworkError::TimeOutOfRangeNew(
+                frames_len,
+                *i,
+                new_actor.actor_id,
+                new_actor.name_id,
+                new_actor.object_id,
+                self.object_ind_to_string(new_actor.object_id),
+                new_actor.initial_trajectory,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Whether a keyframe starts at the frame. Keyframes are sorted by their frame.
+    #[cfg(feature = "tracing")]
+    fn is_keyframe(&self, frame: usize) -> bool {
+        let keyframes = &self.body.keyframes;
+        keyframes
+            .binary_search_by_key(&(frame as i64), |x| i64::from(x.frame))
+            .is_ok()
     }
 
-    pub fn decode_frames(&self) -> Result<Vec<Frame>, Error> {
-        let attr_decoder = AttributeDecoder::new(self.version, self.product_decoder);
-        let mut frames: Vec<Frame> = Vec::with_capacity(self.frames_len);
-        let mut actors = FnvHashMap::default();
-        let mut bits = BitGet::new(self.body.network_data);
-        while !bits.is_empty() && frames.len() < self.frames_len {
+    pub fn decode_frames<S: FrameSink>(
+        &self,
+        sink: &mut S,
+        scratch: &mut DecodeScratch,
+    ) -> Result<DecodeStats, Error> {
+        let mut attr_decoder = AttributeDecoder::new(self.version, self.product_decoder);
+        let mut stats = DecodeStats::default();
+        let mut last = LastDecoded::default();
+        let DecodeScratch {
+            buffers,
+            actors,
+            skipped,
+            ..
+        } = scratch;
+
+        let (mut frames_len, mut bits, end) = match self.seek {
+            Some(seek) => {
+                let bits = self
+                    .bits_at(seek.bit)
+                    .ok_or(NetworkError::NotEnoughDataFor("Keyframe"))?;
+                (seek.frame, bits, seek.end.min(self.frames_len))
+            }
+            None => (0, BitGet::new(self.body.network_data), self.frames_len),
+        };
+
+        let first_frame = frames_len;
+
+        #[cfg(feature = "tracing")]
+        let mut keyframe_span = None;
+
+        while !bits.is_empty() && frames_len < end {
+            if self.cancel.is_some_and(|x| x.is_cancelled()) {
+                return Err(NetworkError::Cancelled)?;
+            }
+
+            #[cfg(feature = "tracing")]
+            if keyframe_span.is_none() || self.is_keyframe(frames_len) {
+                // Exit the span of the previous keyframe so that the next isn't nested in it
+                keyframe_span.take();
+                let span = tracing::debug_span!("keyframe", frame = frames_len);
+                keyframe_span = Some(span.entered());
+            }
+
+            let frame_start = if S::RECORD_SPANS {
+                self.bit_position(&bits)
+            } else {
+                0
+            };
+
             let time = bits
                 .read_f32()
-                .ok_or_else(|| NetworkError::NotEnoughDataFor("Time"))?;
+                .ok_or(NetworkError::NotEnoughDataFor("Time"))?;
 
             if time < 0.0 || (time > 0.0 && time < 1e-10) {
-                for (i, frame) in frames.iter().enumerate().rev() {
-                    if let Some(last_update) = frame.updated_actors.last() {
-                        return Err(NetworkError::TimeOutOfRangeUpdate(
-                            frames.len(),
-                            i,
-                            last_update.actor_id,
-                            last_update.stream_id,
-                            last_update.attribute.clone(),
-                        ))?;
-                    }
-
-                    if let Some(last_new) = frame.new_actors.last() {
-                        return Err(NetworkError::TimeOutOfRangeNew(
-                            frames.len(),
-                            i,
-                            last_new.actor_id,
-                            last_new.name_id,
-                            last_new.object_id,
-                            self.object_ind_to_string(last_new.object_id),
-                            last_new.initial_trajectory,
-                        ))?;
-                    }
-                }
-
-                return Err(NetworkError::TimeOutOfRange(time))?;
+                let err = self.out_of_range_context(frames_len, &last);
+                return Err(err.unwrap_or(NetworkError::TimeOutOfRange(time)))?;
             }
 
             let delta = bits
                 .read_f32()
-                .ok_or_else(|| NetworkError::NotEnoughDataFor("Delta"))?;
+                .ok_or(NetworkError::NotEnoughDataFor("Delta"))?;
 
             if delta < 0.0 || (delta > 0.0 && delta < 1e-10) {
-                for (i, frame) in frames.iter().enumerate().rev() {
-                    if let Some(last_update) = frame.updated_actors.last() {
-                        return Err(NetworkError::TimeOutOfRangeUpdate(
-                            frames.len(),
-                            i,
-                            last_update.actor_id,
-                            last_update.stream_id,
-                            last_update.attribute.clone(),
-                        ))?;
-                    }
+                let err = self.out_of_range_context(frames_len, &last);
+                return Err(err.unwrap_or(NetworkError::DeltaOutOfRange(delta)))?;
+            }
 
-                    if let Some(last_new) = frame.new_actors.last() {
-                        return Err(NetworkError::TimeOutOfRangeNew(
-                            frames.len(),
-                            i,
-                            last_new.actor_id,
-                            last_new.name_id,
-                            last_new.object_id,
-                            self.object_ind_to_string(last_new.object_id),
-                            last_new.initial_trajectory,
-                        ))?;
-                    }
+            if time == 0.0 && delta == 0.0 {
+                break;
+            }
+
+            let decoded = self.decode_frame::<S>(
+                frames_len,
+                &mut attr_decoder,
+                &mut bits,
+                actors,
+                skipped,
+                buffers,
+            );
+            match (decoded, self.skip) {
+                (Ok(()), _) => {}
+                (Err(e), Some(hook)) => {
+                    let start = self.bit_position(&bits);
+                    let (next, end) = match self.next_frame(start, time) {
+                        Some(next) => next,
+                        None => return Err(e)?,
+                    };
+
+                    bits = next;
+                    (hook.0)(SkippedActors {
+                        frame: frames_len,
+                        time,
+                        span: BitSpan {
+                            start,
+                            len: end - start,
+                        },
+                        error: e.to_string(),
+                    });
                 }
+                (Err(e), None) => return Err(e)?,
+            }
 
-                return Err(NetworkError::DeltaOutOfRange(delta))?;
+            if S::RECORD_SPANS {
+                buffers.frame_span = self.span_from(frame_start, &bits);
             }
 
-            if time == 0.0 && delta == 0.0 {
-                break;
+            if let Some(update) = buffers.updated_actors.last() {
+                last.update = Some((frames_len, update.clone()));
             }
 
-            let frame = self.decode_frame(&attr_decoder, &mut bits, &mut actors, time, delta)?;
-            frames.push(frame);
+            if let Some(new_actor) = buffers.new_actors.last() {
+                last.new_actor = Some((frames_len, *new_actor));
+            }
+
+            stats.actors_created += buffers.new_actors.len();
+            stats.attributes_decoded += buffers.updated_actors.len();
+            sink.frame(time, delta, buffers)?;
+            buffers.clear();
+            frames_len += 1;
+
+            if let Some(hook) = self.progress.filter(|x| frames_len % x.every == 0) {
+                (hook.callback)(self.progress_at(frames_len, &bits));
+            }
+        }
+
+        // Report the end of decoding unless it was just reported
+        if let Some(hook) = self.progress.filter(|x| frames_len % x.every != 0) {
+            (hook.callback)(self.progress_at(frames_len, &bits));
+        }
+
+        // Decoding stopped before the last frame, so there isn't a trailer to read yet
+        if frames_len < self.frames_len && frames_len == end {
+            stats.frames = frames_len - first_frame;
+            return Ok(stats);
         }
 
-        if self.version >= VersionTriplet(868, 24, 10) {
+        if self.version.at_least(868, 24, 10) {
             bits.read_u32()
-                .ok_or_else(|| NetworkError::NotEnoughDataFor("Trailer"))?;
+                .ok_or(NetworkError::NotEnoughDataFor("Trailer"))?;
         }
 
-        Ok(frames)
+        stats.frames = frames_len - first_frame;
+        stats.bits_unread = bits.bits_remaining();
+        if self.strict {
+            if frames_len < self.frames_len {
+                return Err(NetworkError::MissingFrames(frames_len, self.frames_len))?;
+            }
+
+            // The network data is padded with zeros
+            let remaining = bits.bits_remaining();
+            while let Some(bit) = bits.read_bit() {
+                if bit {
+                    return Err(NetworkError::TrailingData(remaining))?;
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+/// The last new actor and updated attribute (and their frame index) seen while decoding
+#[derive(Debug, Default)]
+struct LastDecoded {
+    update: Option<(usize, UpdatedAttribute)>,
+    new_actor: Option<(usize, NewActor)>,
+}
+
+/// Scratch space for the frame currently being decoded. The buffers are reused between frames so
+/// a `FrameSink` should move out the data it wants to keep.
+#[derive(Debug, Default)]
+pub(crate) struct FrameBuffers {
+    pub new_actors: Vec<NewActor>,
+    pub deleted_actors: Vec<ActorId>,
+    pub updated_actors: Vec<UpdatedAttribute>,
+
+    /// The spans are only recorded for sinks that request them
+    pub frame_span: BitSpan,
+    pub new_actor_spans: Vec<BitSpan>,
+    pub updated_actor_spans: Vec<BitSpan>,
+}
+
+impl FrameBuffers {
+    pub fn clear(&mut self) {
+        self.new_actors.clear();
+        self.deleted_actors.clear();
+        self.updated_actors.clear();
+        self.new_actor_spans.clear();
+        self.updated_actor_spans.clear();
+    }
+}
+
+/// Receives each frame as soon as it is decoded. A sink that returns an error aborts decoding.
+pub(crate) trait FrameSink {
+    /// Whether the decoder should record where each frame, new actor, and attribute are located
+    /// in the network data. Opt in as it costs time to calculate.
+    const RECORD_SPANS: bool = false;
+
+    fn frame(&mut self, time: f32, delta: f32, buffers: &mut FrameBuffers) -> Result<(), Error>;
+}
+
+impl FrameSink for Vec<Frame> {
+    fn frame(&mut self, time: f32, delta: f32, buffers: &mut FrameBuffers) -> Result<(), Error> {
+        self.push(Frame {
+            time,
+            delta,
+            new_actors: buffers.new_actors.drain(..).collect(),
+            deleted_actors: buffers.deleted_actors.drain(..).collect(),
+            updated_actors: buffers.updated_actors.drain(..).collect(),
+        });
+        Ok(())
     }
 }
@@ -0,0 +1,138 @@
+use crate::errors::BuildError;
+use crate::network::attribute_encoder::{attribute_tag, encode_trajectory, AttributeEncoder};
+use crate::network::bit_writer::BitWriter;
+use crate::network::models::{ActorId, Frame, ObjectId, SpawnTrajectory};
+use crate::network::CacheInfo;
+use crate::version::ReplayVersion;
+use fnv::FnvHashMap;
+
+/// Writes frames in the same format that the `FrameDecoder` reads them. This is the inverse of
+/// frame decoding, so the stream ids of updated attributes must already be resolved against the
+/// net cache of the replay that the frames will be embedded in.
+pub(crate) struct FrameEncoder<'a> {
+    pub attr_encoder: AttributeEncoder,
+    pub channel_bits: i32,
+    pub spawns: &'a [SpawnTrajectory],
+    pub object_ind_attributes: &'a FnvHashMap<ObjectId, CacheInfo>,
+    pub version: ReplayVersion,
+}
+
+impl<'a> FrameEncoder<'a> {
+    fn write_actor_id(&self, bits: &mut BitWriter, actor_id: ActorId) -> Result<(), BuildError> {
+        if actor_id.0 < 0 || (actor_id.0 as u64) >> self.channel_bits != 0 {
+            return Err(BuildError::ActorIdOutOfRange(
+                actor_id,
+                1 << self.channel_bits,
+            ));
+        }
+
+        // Signifies that there is actor data
+        bits.write_bit(true);
+        bits.write_u32_bits(actor_id.0 as u32, self.channel_bits);
+        Ok(())
+    }
+
+    fn encode_frame(
+        &self,
+        frame: &Frame,
+        actors: &mut FnvHashMap<ActorId, ObjectId>,
+        bits: &mut BitWriter,
+    ) -> Result<(), BuildError> {
+        if frame.time < 0.0 || (frame.time > 0.0 && frame.time < 1e-10) {
+            return Err(BuildError::InvalidValue("frame time"));
+        }
+
+        if frame.delta < 0.0 || (frame.delta > 0.0 && frame.delta < 1e-10) {
+            return Err(BuildError::InvalidValue("frame delta"));
+        }
+
+        // A time and delta of zero signals the end of the network data
+        if frame.time == 0.0 && frame.delta == 0.0 {
+            return Err(BuildError::InvalidValue("frame time and delta"));
+        }
+
+        bits.write_f32(frame.time);
+        bits.write_f32(frame.delta);
+
+        for actor in &frame.new_actors {
+            self.write_actor_id(bits, actor.actor_id)?;
+
+            // alive and new
+            bits.write_bit(true);
+            bits.write_bit(true);
+
+            match (self.version.at_least(868, 14, 0), actor.name_id) {
+                (true, Some(name_id)) => bits.write_i32(name_id),
+                (false, None) => {}
+                _ => return Err(BuildError::Incompatible("new actor name id")),
+            }
+
+            bits.write_bit(false);
+            bits.write_i32(actor.object_id.0);
+
+            let spawn = self
+                .spawns
+                .get(usize::from(actor.object_id))
+                .ok_or(BuildError::InvalidValue("new actor object id"))?;
+            encode_trajectory(bits, &actor.initial_trajectory, *spawn, self.version.net)?;
+            actors.insert(actor.actor_id, actor.object_id);
+        }
+
+        for update in &frame.updated_actors {
+            let object_id = actors
+                .get(&update.actor_id)
+                .ok_or(BuildError::UnknownActor(update.actor_id))?;
+            let cache_info = self
+                .object_ind_attributes
+                .get(object_id)
+                .ok_or(BuildError::UnknownActor(update.actor_id))?;
+
+            let tag = attribute_tag(&update.attribute);
+            if cache_info.attributes.get(&update.stream_id) != Some(&tag) {
+                return Err(BuildError::InvalidValue("updated attribute stream id"));
+            }
+
+            self.write_actor_id(bits, update.actor_id)?;
+
+            // alive and not new
+            bits.write_bit(true);
+            bits.write_bit(false);
+
+            // A single attribute is present
+            bits.write_bit(true);
+            bits.write_bits_max(
+                update.stream_id.0 as u32,
+                cache_info.prop_id_bits,
+                cache_info.max_prop_id,
+            );
+            self.attr_encoder.encode(&update.attribute, bits)?;
+            bits.write_bit(false);
+        }
+
+        for actor_id in &frame.deleted_actors {
+            self.write_actor_id(bits, *actor_id)?;
+
+            // no longer alive
+            bits.write_bit(false);
+            actors.remove(actor_id);
+        }
+
+        // No more actor data in this frame
+        bits.write_bit(false);
+        Ok(())
+    }
+
+    pub fn encode_frames(&self, frames: &[Frame]) -> Result<Vec<u8>, BuildError> {
+        let mut bits = BitWriter::new();
+        let mut actors = FnvHashMap::default();
+        for frame in frames {
+            self.encode_frame(frame, &mut actors, &mut bits)?;
+        }
+
+        if self.version.at_least(868, 24, 10) {
+            bits.write_u32(0);
+        }
+
+        Ok(bits.into_bytes())
+    }
+}
@@ -0,0 +1,1902 @@
+use crate::models::{PlayerStat, Replay};
+use crate::network::attributes::{Attribute, CamSettings, RigidBodyState, UniqueId};
+use crate::network::models::{ActorId, Frame, ObjectId, StreamId, Trajectory, Vector3f};
+use crate::network::object_attributes;
+use failure::Error;
+use fnv::FnvHashMap;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Range;
+
+/// The frame and update index of an update, and the instance that was updated
+type Update = (usize, usize, InstanceId);
+
+/// Indexes the decoded network frames of a replay so that questions like "what was this car's
+/// boost amount over time" don't require hand-rolled scans over every frame.
+///
+/// ```
+/// # let data = include_bytes!("../../assets/replays/good/rumble.replay");
+/// let replay = boxcars::ParserBuilder::new(&data[..])
+///     .must_parse_network_data()
+///     .parse()
+///     .unwrap();
+///
+/// let index = boxcars::FrameIndex::new(&replay).unwrap();
+/// let boost = replay
+///     .object_id("TAGame.CarComponent_Boost_TA:ReplicatedBoostAmount")
+///     .unwrap();
+/// for (frame, attribute) in index.attribute_history(boxcars::ActorId(50), boost) {
+///     println!("{}: {:?}", frame, attribute);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrameIndex<'a> {
+    frames: &'a [Frame],
+
+    /// Each object's attributes keyed by their stream id
+    attributes: HashMap<ObjectId, HashMap<StreamId, ObjectId>>,
+
+    /// The updates of each actor's attribute keyed by the actor and attribute object
+    history: FnvHashMap<(ActorId, ObjectId), Vec<Update>>,
+
+    /// Every actor instance in the order that they spawned, and the instances of each actor id
+    instances: Vec<ActorInstance>,
+    actor_instances: FnvHashMap<ActorId, Vec<InstanceId>>,
+
+    /// The rigid body attribute (if the replay has one) for sampling
+    rigid_body: Option<ObjectId>,
+
+    /// The player attributes (if the replay has them) for associating players with actors
+    unique_id: Option<ObjectId>,
+    player_name: Option<ObjectId>,
+
+    /// The attributes that make up a player's inputs
+    inputs: InputAttributes,
+
+    /// The attributes of a player's camera, and the name of the player that recorded the replay
+    cameras: CameraAttributes,
+    recorder: Option<&'a str>,
+
+    /// The boost amount attribute of boost components, and the typed boost that newer replays
+    /// replicate
+    boost_amount: Option<ObjectId>,
+    replicated_boost: Option<ObjectId>,
+
+    /// The attributes and objects that determine a player's team, and the header's player stats
+    /// for players whose team isn't replicated
+    teams: TeamAttributes,
+    stats: Vec<PlayerStat<'a>>,
+
+    /// The attributes and names of the game's state for deriving the phases of the match
+    phases: PhaseAttributes,
+
+    /// The stat event attribute, the stats' names, and the players' stat counters
+    stat_events: StatAttributes<'a>,
+    net_version: i32,
+}
+
+impl<'a> FrameIndex<'a> {
+    /// Indexes the network frames of the replay. A replay without network frames results in an
+    /// empty index.
+    pub fn new(replay: &'a Replay<'_>) -> Result<Self, Error> {
+        let frames = replay
+            .network_frames
+            .as_ref()
+            .map(|x| x.frames.as_slice())
+            .unwrap_or(&[]);
+
+        let attributes = object_attributes(&replay.objects, &replay.net_cache)?;
+
+        let mut actors: FnvHashMap<ActorId, (ObjectId, InstanceId)> = FnvHashMap::default();
+        let mut history: FnvHashMap<(ActorId, ObjectId), Vec<Update>> = FnvHashMap::default();
+        let mut instances: Vec<ActorInstance> = Vec::new();
+        let mut actor_instances: FnvHashMap<ActorId, Vec<InstanceId>> = FnvHashMap::default();
+        for (i, frame) in frames.iter().enumerate() {
+            for actor in &frame.new_actors {
+                let instance_id = InstanceId(instances.len());
+                let previous = actors.insert(actor.actor_id, (actor.object_id, instance_id));
+
+                // An actor that respawns without being deleted replaces the previous instance
+                if let Some((_, previous)) = previous {
+                    instances[previous.0].deleted_at = Some(i);
+                }
+
+                instances.push(ActorInstance {
+                    instance_id,
+                    actor_id: actor.actor_id,
+                    object_id: actor.object_id,
+                    spawned_at: i,
+                    deleted_at: None,
+                });
+                actor_instances
+                    .entry(actor.actor_id)
+                    .or_default()
+                    .push(instance_id);
+            }
+
+            for (j, update) in frame.updated_actors.iter().enumerate() {
+                let (object_id, instance_id) = match actors.get(&update.actor_id) {
+                    Some(x) => *x,
+                    None => continue,
+                };
+
+                let attribute = attributes
+                    .get(&object_id)
+                    .and_then(|x| x.get(&update.stream_id));
+
+                if let Some(attribute) = attribute {
+                    history
+                        .entry((update.actor_id, *attribute))
+                        .or_default()
+                        .push((i, j, instance_id));
+                }
+            }
+
+            for actor_id in &frame.deleted_actors {
+                if let Some((_, instance_id)) = actors.remove(actor_id) {
+                    instances[instance_id.0].deleted_at = Some(i);
+                }
+            }
+        }
+
+        Ok(FrameIndex {
+            frames,
+            attributes,
+            history,
+            instances,
+            actor_instances,
+            rigid_body: replay.object_id("TAGame.RBActor_TA:ReplicatedRBState"),
+            unique_id: replay.object_id("Engine.PlayerReplicationInfo:UniqueId"),
+            player_name: replay.object_id("Engine.PlayerReplicationInfo:PlayerName"),
+            inputs: InputAttributes::new(replay),
+            cameras: CameraAttributes::new(replay),
+            recorder: replay.get_property("PlayerName").and_then(|x| x.as_str()),
+            boost_amount: replay.object_id("TAGame.CarComponent_Boost_TA:ReplicatedBoostAmount"),
+            replicated_boost: replay.object_id("TAGame.CarComponent_Boost_TA:ReplicatedBoost"),
+            teams: TeamAttributes::new(replay),
+            stats: replay.player_stats(),
+            phases: PhaseAttributes::new(replay),
+            stat_events: StatAttributes::new(replay),
+            net_version: replay.net_version.unwrap_or(0),
+        })
+    }
+
+    /// The indexed frames
+    pub fn frames(&self) -> &'a [Frame] {
+        self.frames
+    }
+
+    /// Returns the attribute object (eg: "TAGame.Car_TA:TeamPaint") that a stream id refers to
+    /// for an actor of the given object
+    pub fn attribute_object(
+        &self,
+        actor_object: ObjectId,
+        stream_id: StreamId,
+    ) -> Option<ObjectId> {
+        self.attributes
+            .get(&actor_object)
+            .and_then(|x| x.get(&stream_id))
+            .copied()
+    }
+
+    /// Reconstructs the state of the world after the frame at the given index by replaying the
+    /// actor deltas of every frame up to and including it. Attributes hold their latest value,
+    /// so an attribute that isn't updated in the frame carries its value from an earlier frame.
+    /// Returns `None` if the index is out of range.
+    ///
+    /// Reconstruction starts from the first frame each time, so prefer `WorldState::apply` when
+    /// walking through consecutive frames.
+    pub fn state_at(&self, frame_idx: usize) -> Option<WorldState> {
+        if frame_idx >= self.frames.len() {
+            return None;
+        }
+
+        let mut state = WorldState::default();
+        for i in 0..=frame_idx {
+            state.apply(self, i);
+        }
+        Some(state)
+    }
+
+    /// Samples the rigid body of every actor at the given time in seconds by interpolating
+    /// between the states of the frames surrounding the time. Useful for rendering and
+    /// resampling at a fixed rate. An actor that spawns or is deleted in between the frames
+    /// takes the state of the frame that it is alive in. Times after the last frame take the
+    /// state of the last frame, while times before the first frame return `None`.
+    ///
+    /// Like `state_at`, the world state is reconstructed from the first frame on each call.
+    pub fn sample(&self, time: f32) -> Option<FnvHashMap<ActorId, RigidBodyState>> {
+        // The index of the first frame after the time
+        let next = self.frames.partition_point(|x| x.time <= time);
+        let before = self.state_at(next.checked_sub(1)?)?;
+        let mut after = before.clone();
+        after.apply(self, next);
+
+        let rigid_body = match self.rigid_body {
+            Some(x) => x,
+            None => return Some(FnvHashMap::default()),
+        };
+
+        let body = |actor: &ActorState| match actor.attribute(rigid_body) {
+            Some(Attribute::RigidBody(body)) => Some(body.decompress(self.net_version)),
+            _ => None,
+        };
+
+        let t = match (self.frames.get(next - 1), self.frames.get(next)) {
+            (Some(a), Some(b)) if b.time > a.time => (time - a.time) / (b.time - a.time),
+            _ => 0.0,
+        };
+
+        let mut result = FnvHashMap::default();
+        for (actor_id, actor) in &before.actors {
+            let next_actor = after
+                .actor(*actor_id)
+                .filter(|x| x.spawned_at == actor.spawned_at);
+
+            let state = match (body(actor), next_actor.and_then(body)) {
+                (Some(a), Some(b)) => a.interpolate(&b, t),
+                (Some(a), None) => a,
+                (None, _) => continue,
+            };
+            result.insert(*actor_id, state);
+        }
+
+        for (actor_id, actor) in &after.actors {
+            if !result.contains_key(actor_id) {
+                if let Some(state) = body(actor) {
+                    result.insert(*actor_id, state);
+                }
+            }
+        }
+
+        Some(result)
+    }
+
+    /// The player replication info actors paired with the latest unique id and name that they
+    /// were assigned, ordered by the frame that the unique id was first seen. Split-screen
+    /// players share a remote id and are told apart by their unique id's local id, which can
+    /// be matched against the header with `PlayerStat::is_player`.
+    pub fn players(&self) -> Vec<PlayerActor<'a>> {
+        let attribute = match self.unique_id {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+
+        let mut players: Vec<_> = self
+            .history
+            .keys()
+            .filter(|(_, attr)| *attr == attribute)
+            .filter_map(|&(actor_id, _)| {
+                let first_frame = self.attribute_history(actor_id, attribute).next()?.0;
+                let unique_id = self
+                    .attribute_history(actor_id, attribute)
+                    .filter_map(|(_, x)| match x {
+                        Attribute::UniqueId(id) => Some(id),
+                        _ => None,
+                    })
+                    .last()?;
+
+                let name = self.player_name.and_then(|x| {
+                    self.attribute_history(actor_id, x)
+                        .filter_map(|(_, x)| match x {
+                            Attribute::String(name) => Some(&**name),
+                            _ => None,
+                        })
+                        .last()
+                });
+
+                Some((
+                    first_frame,
+                    PlayerActor {
+                        actor_id,
+                        unique_id,
+                        name,
+                    },
+                ))
+            })
+            .collect();
+
+        players.sort_by_key(|(frame, player)| (*frame, player.actor_id));
+        players.into_iter().map(|(_, player)| player).collect()
+    }
+
+    /// The inputs of the player with the given player replication info actor (see `players`),
+    /// with an entry for every frame that the player has a car. Inputs hold their latest
+    /// replicated value, as they are only replicated when they change.
+    pub fn inputs(&self, player: ActorId) -> Vec<PlayerInput> {
+        let attrs = self.inputs;
+        let flagged_actor = |actor: &ActorState, attribute| match actor.optional(attribute) {
+            Some(Attribute::Flagged(_, id)) => Some(ActorId(*id as i32)),
+            _ => None,
+        };
+
+        let mut result = Vec::new();
+        let mut state = WorldState::default();
+        for (i, frame) in self.frames.iter().enumerate() {
+            state.apply(self, i);
+
+            // A player's demolished car may linger for a moment after the next car spawns
+            let car = state
+                .actors
+                .iter()
+                .filter(|(_, actor)| flagged_actor(actor, attrs.player) == Some(player))
+                .max_by_key(|(id, actor)| (actor.spawned_at, **id));
+
+            let (car_id, car) = match car {
+                Some(x) => x,
+                None => continue,
+            };
+
+            let axis = |attribute| match car.optional(attribute) {
+                Some(Attribute::Byte(x)) => decode_axis(*x),
+                _ => 0.0,
+            };
+
+            let dodge_torque = state
+                .actors
+                .values()
+                .filter(|actor| flagged_actor(actor, attrs.vehicle) == Some(*car_id))
+                .filter(|actor| match actor.optional(attrs.active) {
+                    Some(Attribute::Byte(x)) => x % 2 == 1,
+                    _ => false,
+                })
+                .find_map(|actor| match actor.optional(attrs.dodge_torque) {
+                    Some(Attribute::Location(x)) => Some(x.to_vector3f(self.net_version)),
+                    _ => None,
+                });
+
+            result.push(PlayerInput {
+                frame: i,
+                time: frame.time,
+                car: *car_id,
+                throttle: axis(attrs.throttle),
+                steer: axis(attrs.steer),
+                handbrake: match car.optional(attrs.handbrake) {
+                    Some(Attribute::Boolean(x)) => *x,
+                    _ => false,
+                },
+                dodge_torque,
+            });
+        }
+
+        result
+    }
+
+    /// The player replication info actor of the player that recorded the replay, which is the
+    /// player named by the header's `PlayerName`. Replays that are saved by a spectator or
+    /// downloaded from a replay service may not have one.
+    pub fn recorder(&self) -> Option<ActorId> {
+        let name = self.recorder?;
+        self.players()
+            .into_iter()
+            .find(|x| x.name == Some(name))
+            .map(|x| x.actor_id)
+    }
+
+    /// The camera of the player with the given player replication info actor (see `players`),
+    /// with an entry for every frame that the player is alive. A player's camera is replicated
+    /// on a camera settings actor that refers back to the player, while older replays replicate
+    /// it on the player itself. Like inputs, the camera holds its latest replicated value.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../../assets/replays/good/rumble.replay");
+    /// # let replay = boxcars::ParserBuilder::new(&data[..])
+    /// #     .must_parse_network_data()
+    /// #     .parse()
+    /// #     .unwrap();
+    /// let index = boxcars::FrameIndex::new(&replay).unwrap();
+    /// let recorder = index.recorder().unwrap();
+    /// for camera in index.cameras(recorder) {
+    ///     println!("{}: ball cam {}", camera.frame, camera.ball_cam);
+    /// }
+    /// ```
+    pub fn cameras(&self, player: ActorId) -> Vec<CameraState> {
+        let attrs = self.cameras;
+        let mut result = Vec::new();
+        let mut state = WorldState::default();
+        for (i, frame) in self.frames.iter().enumerate() {
+            state.apply(self, i);
+
+            let pri = match state.actor(player) {
+                Some(x) => x,
+                None => continue,
+            };
+
+            // A camera settings actor may linger for a moment after the next one spawns
+            let camera = state
+                .actors
+                .iter()
+                .filter(|(_, actor)| match actor.optional(attrs.player) {
+                    Some(Attribute::Flagged(_, id)) => ActorId(*id as i32) == player,
+                    _ => false,
+                })
+                .max_by_key(|(id, actor)| (actor.spawned_at, **id));
+
+            let (camera, actor, fields) = match camera {
+                Some((id, actor)) => (Some(*id), actor, &attrs.actor),
+                None => (None, pri, &attrs.pri),
+            };
+
+            let flag = |actor: &ActorState, attribute| match actor.optional(attribute) {
+                Some(Attribute::Boolean(x)) => *x,
+                _ => false,
+            };
+
+            let axis = |attribute| match actor.optional(attribute) {
+                Some(Attribute::Byte(x)) => decode_axis(*x),
+                _ => 0.0,
+            };
+
+            result.push(CameraState {
+                frame: i,
+                time: frame.time,
+                camera,
+                settings: match actor.optional(fields.settings) {
+                    Some(Attribute::CamSettings(x)) => Some(*x),
+                    _ => None,
+                },
+                ball_cam: flag(actor, fields.ball_cam),
+                behind_view: flag(actor, fields.behind_view),
+                swivel: flag(actor, fields.swivel),
+                yaw: axis(fields.yaw),
+                pitch: axis(fields.pitch),
+                spectator: flag(pri, attrs.spectator),
+                spectator_shortcut: match pri.optional(attrs.spectator_shortcut) {
+                    Some(Attribute::Int(x)) => Some(*x),
+                    _ => None,
+                },
+            });
+        }
+
+        result
+    }
+
+    /// The location and boost amount of an actor instance (eg: a car or the ball) at every
+    /// frame that it is alive, as parallel columns that numeric and plotting libraries can
+    /// consume without transposing the frames. Values hold their latest replicated value, and
+    /// are NaN until they are first replicated. A car's boost amount is replicated on its boost
+    /// component, so the boost amounts of actors that aren't cars are NaN.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../../assets/replays/good/rumble.replay");
+    /// # let replay = boxcars::ParserBuilder::new(&data[..])
+    /// #     .must_parse_network_data()
+    /// #     .parse()
+    /// #     .unwrap();
+    /// let index = boxcars::FrameIndex::new(&replay).unwrap();
+    /// let car = replay.object_id("Archetypes.Car.Car_Default").unwrap();
+    /// let instance = index.instances().iter().find(|x| x.object_id == car).unwrap();
+    /// let series = index.series(instance.instance_id).unwrap();
+    /// assert_eq!(series.time.len(), series.boost.len());
+    /// ```
+    pub fn series(&self, instance_id: InstanceId) -> Option<ActorSeries> {
+        let instance = self.instance(instance_id)?;
+        let alive = instance.spawned_at..instance.deleted_at.unwrap_or(self.frames.len());
+
+        let locations = self
+            .rigid_body
+            .into_iter()
+            .flat_map(|x| self.instance_history(instance_id, x))
+            .filter_map(|(i, x)| match x {
+                Attribute::RigidBody(body) => {
+                    Some((i, body.location.to_vector3f(self.net_version)))
+                }
+                _ => None,
+            });
+        let locations = forward_fill(alive.clone(), locations);
+
+        // The boost components that were attached to the actor while it was alive
+        let vehicle = self.inputs.vehicle;
+        let attached = |component: &ActorInstance| {
+            vehicle
+                .into_iter()
+                .flat_map(|x| self.instance_history(component.instance_id, x))
+                .any(|(i, x)| match x {
+                    Attribute::Flagged(_, id) => {
+                        alive.contains(&i) && ActorId(*id as i32) == instance.actor_id
+                    }
+                    _ => false,
+                })
+        };
+
+        let boost_attributes = self.boost_attributes();
+        let mut boosts: Vec<(usize, f32)> = self
+            .instances
+            .iter()
+            .filter(|x| x.instance_id != instance_id && attached(x))
+            .flat_map(|x| {
+                boost_attributes
+                    .clone()
+                    .flat_map(move |boost| self.instance_history(x.instance_id, boost))
+            })
+            .filter(|(i, _)| alive.contains(i))
+            .filter_map(|(i, x)| boost_amount(x).map(|amount| (i, f32::from(amount))))
+            .collect();
+        boosts.sort_by_key(|(i, _)| *i);
+        let boosts = forward_fill(alive.clone(), boosts.into_iter());
+
+        let coordinate = |f: fn(&Vector3f) -> f32| {
+            locations
+                .iter()
+                .map(|x| x.as_ref().map(f).unwrap_or(f32::NAN))
+                .collect()
+        };
+
+        Some(ActorSeries {
+            instance_id,
+            actor_id: instance.actor_id,
+            frame: alive.clone().collect(),
+            time: self.frames[alive].iter().map(|x| x.time).collect(),
+            x: coordinate(|v| v.x),
+            y: coordinate(|v| v.y),
+            z: coordinate(|v| v.z),
+            boost: boosts.into_iter().map(|x| x.unwrap_or(f32::NAN)).collect(),
+        })
+    }
+
+    /// The team changes of each player (see `players`) throughout the match. A player's team is
+    /// replicated on their player replication info, which captures players that switch teams
+    /// mid-match in private matches. Until their team is replicated (or if it never is), a
+    /// player is on their team from the header's player stats.
+    pub fn teams(&self) -> Vec<PlayerTeams<'a>> {
+        self.players()
+            .into_iter()
+            .map(|player| PlayerTeams {
+                changes: self.team_changes(player.actor_id),
+                player,
+            })
+            .collect()
+    }
+
+    /// The team (0 for blue and 1 for orange) of the player with the given player replication
+    /// info actor after the frame at the given index
+    pub fn player_team(&self, player: ActorId, frame_idx: usize) -> Option<u8> {
+        team_at(&self.team_changes(player), frame_idx)
+    }
+
+    /// The team of the car's player after the frame at the given index. A car whose player
+    /// isn't known falls back to the team of its paint.
+    pub fn car_team(&self, car: ActorId, frame_idx: usize) -> Option<u8> {
+        let instance_id = self.instance_at(car, frame_idx)?;
+        let latest = |attribute: Option<ObjectId>| {
+            let attribute = attribute?;
+            self.instance_history(instance_id, attribute)
+                .take_while(|(i, _)| *i <= frame_idx)
+                .last()
+                .map(|(_, x)| x)
+        };
+
+        let player = match latest(self.inputs.player) {
+            Some(Attribute::Flagged(_, id)) => self.player_team(ActorId(*id as i32), frame_idx),
+            _ => None,
+        };
+
+        player.or_else(|| match latest(self.teams.team_paint) {
+            Some(Attribute::TeamPaint(paint)) => Some(paint.team),
+            _ => None,
+        })
+    }
+
+    fn team_changes(&self, player: ActorId) -> Vec<TeamChange> {
+        let mut changes: Vec<TeamChange> = Vec::new();
+        let mut change = |frame, team| match changes.last_mut() {
+            Some(last) if last.frame == frame => last.team = team,
+            Some(last) if last.team == team => {}
+            _ => changes.push(TeamChange { frame, team }),
+        };
+
+        if let Some(team) = self.header_team(player) {
+            change(0, Some(team));
+        }
+
+        let attribute = match self.teams.team {
+            Some(x) => x,
+            None => return changes,
+        };
+
+        for (frame, attribute) in self.attribute_history(player, attribute) {
+            if let Attribute::Flagged(_, id) = attribute {
+                change(frame, self.team_of(ActorId(*id as i32), frame));
+            }
+        }
+
+        changes
+    }
+
+    /// The team of the player from the header's player stats
+    fn header_team(&self, player: ActorId) -> Option<u8> {
+        let unique_id = self
+            .attribute_history(player, self.unique_id?)
+            .filter_map(|(_, x)| match x {
+                Attribute::UniqueId(id) => Some(id),
+                _ => None,
+            })
+            .last()?;
+
+        let stat = self.stats.iter().find(|x| x.is_player(unique_id))?;
+        stat.team.and_then(|x| u8::try_from(x).ok())
+    }
+
+    /// The frames that the team's score increased on and the team's score after each goal. The
+    /// team is 0 for blue and 1 for orange.
+    pub fn score_changes(&self, team: u8) -> Vec<(usize, i32)> {
+        let object_id = match team {
+            0 => self.teams.blue,
+            1 => self.teams.orange,
+            _ => None,
+        };
+
+        let (object_id, score) = match (object_id, self.teams.score) {
+            (Some(object_id), Some(score)) => (object_id, score),
+            _ => return Vec::new(),
+        };
+
+        let mut changes = Vec::new();
+        let mut previous = 0;
+        for instance in self.instances.iter().filter(|x| x.object_id == object_id) {
+            for (frame, attribute) in self.instance_history(instance.instance_id, score) {
+                if let Attribute::Int(score) = *attribute {
+                    if score > previous {
+                        changes.push((frame, score));
+                    }
+                    previous = score;
+                }
+            }
+        }
+
+        changes
+    }
+
+    /// The phase of the match at each of the frames. The phases follow the state that the game
+    /// event replicates, so the frames of the countdown before each kickoff and of the
+    /// celebration and replay after each goal can be left out of stats about active play.
+    /// Active play after overtime starts is `GamePhase::Overtime`. Empty when the replay
+    /// doesn't replicate the game's state.
+    pub fn phases(&self) -> Vec<GamePhase> {
+        let state_name = match self.phases.state_name {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+
+        // Unknown states keep the phase of the earlier state
+        let states = self
+            .updates_of(state_name)
+            .filter_map(|(i, _, _, x)| match x {
+                Attribute::Int(name) => Some((i, self.phases.phase(*name)?)),
+                _ => None,
+            });
+        let states = forward_fill(0..self.frames.len(), states);
+
+        let overtime = self
+            .phases
+            .overtime
+            .into_iter()
+            .flat_map(|x| self.updates_of(x))
+            .find(|(_, _, _, x)| **x == Attribute::Boolean(true))
+            .map(|(i, _, _, _)| i);
+
+        states
+            .into_iter()
+            .enumerate()
+            .map(|(i, phase)| match phase.unwrap_or(GamePhase::PreKickoff) {
+                GamePhase::Active if overtime.is_some_and(|x| x <= i) => GamePhase::Overtime,
+                phase => phase,
+            })
+            .collect()
+    }
+
+    /// The stat events (eg: saves and goals) that the game event replicated for the feed, in the
+    /// order of the frames. The event doesn't replicate who it is for, so the player is inferred
+    /// as the first player whose counter of the stat (eg: `MatchSaves`) went up within a couple
+    /// seconds of the event. Stats without a counter (eg: `Demolish`) and events whose counter
+    /// never went up don't have a player.
+    pub fn stat_events(&self) -> Vec<StatEvent<'a>> {
+        let event = match self.stat_events.event {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+
+        // The increments of each counter as the time and the player, and whether an event was
+        // already credited with the increment
+        let mut increments: FnvHashMap<ObjectId, Vec<(f32, ActorId, bool)>> = FnvHashMap::default();
+        let mut events = Vec::new();
+        for (frame, time, subject_actor, x) in self.updates_of(event) {
+            let object = match x {
+                Attribute::StatEvent(_, x) => *x,
+                _ => continue,
+            };
+
+            // The event is cleared (with an invalid object) shortly after it is shown
+            let name = match self.stat_events.names.get(&object) {
+                Some(x) => *x,
+                None => continue,
+            };
+
+            let player = self.stat_events.counter(name).and_then(|counter| {
+                let increments = increments
+                    .entry(counter)
+                    .or_insert_with(|| self.increments(counter));
+                let credited = increments
+                    .iter_mut()
+                    .filter(|(at, _, _)| *at >= time && *at <= time + STAT_EVENT_WINDOW)
+                    .find(|(_, _, credited)| !credited)?;
+                credited.2 = true;
+                Some(credited.1)
+            });
+
+            events.push(StatEvent {
+                frame,
+                time,
+                subject_actor,
+                stat_object: ObjectId(object as i32),
+                name,
+                player,
+            });
+        }
+
+        events
+    }
+
+    /// The stat events that were credited to the player (see `stat_events`)
+    pub fn stat_events_of(&self, player: ActorId) -> impl Iterator<Item = StatEvent<'a>> {
+        self.stat_events()
+            .into_iter()
+            .filter(move |x| x.player == Some(player))
+    }
+
+    /// The times that the counter of a player went up and the player
+    fn increments(&self, counter: ObjectId) -> Vec<(f32, ActorId, bool)> {
+        let mut counts: FnvHashMap<ActorId, i32> = FnvHashMap::default();
+        let mut increments = Vec::new();
+        for (_, time, actor_id, x) in self.updates_of(counter) {
+            if let Attribute::Int(count) = *x {
+                let previous = counts.insert(actor_id, count).unwrap_or(0);
+                if count > previous {
+                    increments.push((time, actor_id, false));
+                }
+            }
+        }
+        increments
+    }
+
+    /// The team of the team actor that is alive after the frame at the given index
+    fn team_of(&self, team: ActorId, frame_idx: usize) -> Option<u8> {
+        let instance = self.instance(self.instance_at(team, frame_idx)?)?;
+        let object_id = Some(instance.object_id);
+        if object_id == self.teams.blue {
+            Some(0)
+        } else if object_id == self.teams.orange {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    /// Every update of an actor's attribute as pairs of the frame index and the updated value,
+    /// in the order that they were decoded. As actor ids are reused, the history includes
+    /// updates to all actors that were assigned the id (see `instance_history`).
+    pub fn attribute_history(
+        &self,
+        actor_id: ActorId,
+        attribute: ObjectId,
+    ) -> impl Iterator<Item = (usize, &'a Attribute)> + '_ {
+        self.updates(actor_id, attribute)
+            .map(move |&(i, j, _)| (i, &self.frames[i].updated_actors[j].attribute))
+    }
+
+    /// Like `attribute_history` but only includes the updates to a single instance of an actor
+    pub fn instance_history(
+        &self,
+        instance_id: InstanceId,
+        attribute: ObjectId,
+    ) -> impl Iterator<Item = (usize, &'a Attribute)> + '_ {
+        let actor_id = self
+            .instance(instance_id)
+            .map(|x| x.actor_id)
+            .unwrap_or(ActorId(-1));
+        self.updates(actor_id, attribute)
+            .filter(move |x| x.2 == instance_id)
+            .map(move |&(i, j, _)| (i, &self.frames[i].updated_actors[j].attribute))
+    }
+
+    fn updates(
+        &self,
+        actor_id: ActorId,
+        attribute: ObjectId,
+    ) -> impl Iterator<Item = &Update> + '_ {
+        self.history
+            .get(&(actor_id, attribute))
+            .map(|x| x.as_slice())
+            .unwrap_or(&[])
+            .iter()
+    }
+
+    /// Every update of the attribute across all actors as the frame index, the frame's time, the
+    /// updated actor, and the updated value, in the order that they were decoded
+    pub fn updates_of(
+        &self,
+        attribute: ObjectId,
+    ) -> impl Iterator<Item = (usize, f32, ActorId, &'a Attribute)> + '_ {
+        let mut updates: Vec<_> = self
+            .history
+            .iter()
+            .filter(|((_, attr), _)| *attr == attribute)
+            .flat_map(|(_, updates)| updates.iter().map(|&(i, j, _)| (i, j)))
+            .collect();
+        updates.sort_unstable();
+
+        updates.into_iter().map(move |(i, j)| {
+            let frame = &self.frames[i];
+            let update = &frame.updated_actors[j];
+            (i, frame.time, update.actor_id, &update.attribute)
+        })
+    }
+
+    /// Every boost amount update (from 0 to 255) in the order that they were decoded. Boost
+    /// amounts are replicated on a car's boost component, so the actor is the boost component
+    /// rather than the car (see `series` for the boost amounts of a car).
+    pub fn boost_amounts(&self) -> impl Iterator<Item = (usize, f32, ActorId, u8)> + '_ {
+        self.boost_attributes()
+            .flat_map(move |x| self.updates_of(x))
+            .filter_map(|(i, time, actor_id, x)| {
+                boost_amount(x).map(|amount| (i, time, actor_id, amount))
+            })
+    }
+
+    /// The attributes that replicate a boost component's amount. A replay replicates one or the
+    /// other, so chaining their updates keeps them in the order that they were decoded.
+    fn boost_attributes(&self) -> impl Iterator<Item = ObjectId> + Clone {
+        self.boost_amount.into_iter().chain(self.replicated_boost)
+    }
+
+    /// Every instance of an actor in the order that they spawned. Rocket League reuses the ids
+    /// of deleted actors, so aggregating by instance instead of actor id keeps the actors that
+    /// shared an id apart.
+    pub fn instances(&self) -> &[ActorInstance] {
+        &self.instances
+    }
+
+    pub fn instance(&self, instance_id: InstanceId) -> Option<&ActorInstance> {
+        self.instances.get(instance_id.0)
+    }
+
+    /// The instance of the actor that is alive after the frame at the given index
+    pub fn instance_at(&self, actor_id: ActorId, frame_idx: usize) -> Option<InstanceId> {
+        let instances = self.actor_instances.get(&actor_id)?;
+        let spawned = instances.partition_point(|x| self.instances[x.0].spawned_at <= frame_idx);
+        let instance = &self.instances[instances[spawned.checked_sub(1)?].0];
+        match instance.deleted_at {
+            Some(deleted) if deleted <= frame_idx => None,
+            _ => Some(instance.instance_id),
+        }
+    }
+
+    /// The instance of the actor that spawned as the given new actor of the frame
+    fn spawned_instance(&self, frame_idx: usize, new_actor_idx: usize) -> InstanceId {
+        let first = self.instances.partition_point(|x| x.spawned_at < frame_idx);
+        InstanceId(first + new_actor_idx)
+    }
+}
+
+/// Identifies an actor instance for the lifetime of the actor, from when it spawns until it is
+/// deleted. Unlike actor ids, instance ids are never reused within a replay. Instance ids are
+/// assigned by boxcars in the order that the actors spawn, starting from zero.
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct InstanceId(pub usize);
+
+impl fmt::Display for InstanceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The lifetime of an actor instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct ActorInstance {
+    pub instance_id: InstanceId,
+    pub actor_id: ActorId,
+    pub object_id: ObjectId,
+
+    /// The frame that the instance spawned on
+    pub spawned_at: usize,
+
+    /// The frame that the instance was deleted on, or the frame that its actor id respawned on
+    /// without being deleted. Instances that are alive at the end of the replay aren't deleted.
+    pub deleted_at: Option<usize>,
+}
+
+/// A player replication info actor and the player that it represents
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerActor<'a> {
+    pub actor_id: ActorId,
+    pub unique_id: &'a UniqueId,
+    pub name: Option<&'a str>,
+}
+
+/// The inputs of a player's car at a frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerInput {
+    /// The index of the frame
+    pub frame: usize,
+    pub time: f32,
+
+    /// The car that the player is driving
+    pub car: ActorId,
+
+    /// From -1 (full reverse) to 1 (full throttle)
+    pub throttle: f32,
+
+    /// From -1 (full left) to 1 (full right)
+    pub steer: f32,
+    pub handbrake: bool,
+
+    /// The torque of the car's dodge while the car is dodging
+    pub dodge_torque: Option<Vector3f>,
+}
+
+/// The camera of a player at a frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct CameraState {
+    /// The index of the frame
+    pub frame: usize,
+    pub time: f32,
+
+    /// The camera settings actor of the player, or `None` when the camera is replicated on the
+    /// player replication info actor
+    pub camera: Option<ActorId>,
+
+    /// The camera settings of the player's profile (field of view, distance, etc)
+    pub settings: Option<CamSettings>,
+
+    /// Whether the camera follows the ball rather than facing ahead of the car
+    pub ball_cam: bool,
+
+    /// Whether the camera looks behind the car
+    pub behind_view: bool,
+
+    /// Whether the camera is being swiveled, where the yaw and pitch hold the swivel
+    pub swivel: bool,
+
+    /// From -1 (full left) to 1 (full right), where 0 is straight ahead
+    pub yaw: f32,
+
+    /// From -1 (full down) to 1 (full up), where 0 is level
+    pub pitch: f32,
+
+    /// Whether the player is spectating rather than playing
+    pub spectator: bool,
+
+    /// The number that spectators press to view the player
+    pub spectator_shortcut: Option<i32>,
+}
+
+/// The state of an actor instance over its lifetime as parallel columns, where the values at an
+/// index are of the frame at that index of `frame`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct ActorSeries {
+    pub instance_id: InstanceId,
+    pub actor_id: ActorId,
+
+    /// The index of each frame
+    pub frame: Vec<usize>,
+    pub time: Vec<f32>,
+
+    /// The components of the location in unreal units
+    pub x: Vec<f32>,
+    pub y: Vec<f32>,
+    pub z: Vec<f32>,
+
+    /// The boost amount from 0 to 255
+    pub boost: Vec<f32>,
+}
+
+/// The team that a player is on from a frame onward
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct TeamChange {
+    /// The index of the frame
+    pub frame: usize,
+
+    /// 0 for blue and 1 for orange, or `None` when the player isn't on a team (eg: a spectator)
+    pub team: Option<u8>,
+}
+
+/// The phase of the match at a frame (see `FrameIndex::phases`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum GamePhase {
+    /// Waiting for players or counting down to a kickoff
+    PreKickoff,
+    Active,
+
+    /// The celebration and replay after a goal, until the next countdown
+    GoalReplay,
+
+    /// Active play after overtime starts
+    Overtime,
+}
+
+impl GamePhase {
+    /// Whether the ball is in play
+    pub fn is_live(self) -> bool {
+        matches!(self, GamePhase::Active | GamePhase::Overtime)
+    }
+}
+
+/// A stat event that the game event replicated (see `FrameIndex::stat_events`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct StatEvent<'a> {
+    /// The index of the frame
+    pub frame: usize,
+    pub time: f32,
+
+    /// The actor that replicated the event, which is the game event
+    pub subject_actor: ActorId,
+
+    /// The object of the stat (eg: `StatEvents.Events.EpicSave`)
+    pub stat_object: ObjectId,
+
+    /// The name of the stat without its package (eg: `EpicSave`)
+    pub name: &'a str,
+
+    /// The player replication info actor of the player that the event is inferred to be for
+    pub player: Option<ActorId>,
+}
+
+/// How long after a stat event (in seconds) a player's counter of the stat can go up for the
+/// event to be credited to the player. Saves are counted over a second after they are shown.
+const STAT_EVENT_WINDOW: f32 = 2.0;
+
+/// A player and the changes of their team in the order of the frames
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerTeams<'a> {
+    pub player: PlayerActor<'a>,
+    pub changes: Vec<TeamChange>,
+}
+
+impl PlayerTeams<'_> {
+    /// The player's team after the frame at the given index
+    pub fn team_at(&self, frame_idx: usize) -> Option<u8> {
+        team_at(&self.changes, frame_idx)
+    }
+}
+
+fn team_at(changes: &[TeamChange], frame_idx: usize) -> Option<u8> {
+    let i = changes.partition_point(|x| x.frame <= frame_idx);
+    changes[..i].last().and_then(|x| x.team)
+}
+
+/// The attribute objects of a car's inputs and how to find the car's components
+#[derive(Debug, Clone, Copy)]
+struct InputAttributes {
+    player: Option<ObjectId>,
+    throttle: Option<ObjectId>,
+    steer: Option<ObjectId>,
+    handbrake: Option<ObjectId>,
+    vehicle: Option<ObjectId>,
+    active: Option<ObjectId>,
+    dodge_torque: Option<ObjectId>,
+}
+
+impl InputAttributes {
+    fn new(replay: &Replay<'_>) -> Self {
+        InputAttributes {
+            player: replay.object_id("Engine.Pawn:PlayerReplicationInfo"),
+            throttle: replay.object_id("TAGame.Vehicle_TA:ReplicatedThrottle"),
+            steer: replay.object_id("TAGame.Vehicle_TA:ReplicatedSteer"),
+            handbrake: replay.object_id("TAGame.Vehicle_TA:bReplicatedHandbrake"),
+            vehicle: replay.object_id("TAGame.CarComponent_TA:Vehicle"),
+            active: replay.object_id("TAGame.CarComponent_TA:ReplicatedActive"),
+            dodge_torque: replay.object_id("TAGame.CarComponent_Dodge_TA:DodgeTorque"),
+        }
+    }
+}
+
+/// The attribute objects of a player's camera on a camera settings actor and on a player
+/// replication info actor, and the spectator attributes of a player
+#[derive(Debug, Clone, Copy)]
+struct CameraAttributes {
+    player: Option<ObjectId>,
+    actor: CameraFields,
+    pri: CameraFields,
+    spectator: Option<ObjectId>,
+    spectator_shortcut: Option<ObjectId>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CameraFields {
+    settings: Option<ObjectId>,
+    ball_cam: Option<ObjectId>,
+    behind_view: Option<ObjectId>,
+    swivel: Option<ObjectId>,
+    yaw: Option<ObjectId>,
+    pitch: Option<ObjectId>,
+}
+
+impl CameraAttributes {
+    fn new(replay: &Replay<'_>) -> Self {
+        CameraAttributes {
+            player: replay.object_id("TAGame.CameraSettingsActor_TA:PRI"),
+            actor: CameraFields {
+                settings: replay.object_id("TAGame.CameraSettingsActor_TA:ProfileSettings"),
+                ball_cam: replay.object_id("TAGame.CameraSettingsActor_TA:bUsingSecondaryCamera"),
+                behind_view: replay.object_id("TAGame.CameraSettingsActor_TA:bUsingBehindView"),
+                swivel: replay.object_id("TAGame.CameraSettingsActor_TA:bUsingSwivel"),
+                yaw: replay.object_id("TAGame.CameraSettingsActor_TA:CameraYaw"),
+                pitch: replay.object_id("TAGame.CameraSettingsActor_TA:CameraPitch"),
+            },
+            pri: CameraFields {
+                settings: replay.object_id("TAGame.PRI_TA:CameraSettings"),
+                ball_cam: replay.object_id("TAGame.PRI_TA:bUsingSecondaryCamera"),
+                behind_view: replay.object_id("TAGame.PRI_TA:bUsingBehindView"),
+                swivel: None,
+                yaw: replay.object_id("TAGame.PRI_TA:CameraYaw"),
+                pitch: replay.object_id("TAGame.PRI_TA:CameraPitch"),
+            },
+            spectator: replay.object_id("Engine.PlayerReplicationInfo:bIsSpectator"),
+            spectator_shortcut: replay.object_id("TAGame.PRI_TA:SpectatorShortcut"),
+        }
+    }
+}
+
+/// The attribute objects of a player's team and the objects of the team actors
+#[derive(Debug, Clone, Copy)]
+struct TeamAttributes {
+    team: Option<ObjectId>,
+    team_paint: Option<ObjectId>,
+    blue: Option<ObjectId>,
+    orange: Option<ObjectId>,
+    score: Option<ObjectId>,
+}
+
+impl TeamAttributes {
+    fn new(replay: &Replay<'_>) -> Self {
+        TeamAttributes {
+            team: replay.object_id("Engine.PlayerReplicationInfo:Team"),
+            team_paint: replay.object_id("TAGame.Car_TA:TeamPaint"),
+            blue: replay.object_id("Archetypes.Teams.Team0"),
+            orange: replay.object_id("Archetypes.Teams.Team1"),
+            score: replay.object_id("Engine.TeamInfo:Score"),
+        }
+    }
+}
+
+/// The stat event attribute, the names of the stat objects keyed by their index, and the
+/// attributes that count each stat of a player
+#[derive(Debug, Clone)]
+struct StatAttributes<'a> {
+    event: Option<ObjectId>,
+    names: FnvHashMap<u32, &'a str>,
+    goals: Option<ObjectId>,
+    saves: Option<ObjectId>,
+    shots: Option<ObjectId>,
+    assists: Option<ObjectId>,
+}
+
+impl<'a> StatAttributes<'a> {
+    fn new(replay: &'a Replay<'_>) -> Self {
+        let names = replay
+            .objects
+            .iter()
+            .enumerate()
+            .filter_map(|(i, x)| {
+                let name = x.strip_prefix("StatEvents.Events.")?;
+                Some((u32::try_from(i).ok()?, name))
+            })
+            .collect();
+
+        StatAttributes {
+            event: replay.object_id("TAGame.GameEvent_Soccar_TA:ReplicatedStatEvent"),
+            names,
+            goals: replay.object_id("TAGame.PRI_TA:MatchGoals"),
+            saves: replay.object_id("TAGame.PRI_TA:MatchSaves"),
+            shots: replay.object_id("TAGame.PRI_TA:MatchShots"),
+            assists: replay.object_id("TAGame.PRI_TA:MatchAssists"),
+        }
+    }
+
+    fn counter(&self, name: &str) -> Option<ObjectId> {
+        match name {
+            "Goal" => self.goals,
+            "Save" | "EpicSave" => self.saves,
+            "Shot" => self.shots,
+            "Assist" => self.assists,
+            _ => None,
+        }
+    }
+}
+
+/// The attribute objects of the game's state and the names of the states. The state attribute
+/// refers to the state's name in the replay's names.
+#[derive(Debug, Clone, Copy)]
+struct PhaseAttributes {
+    state_name: Option<ObjectId>,
+    overtime: Option<ObjectId>,
+    waiting: Option<i32>,
+    countdown: Option<i32>,
+    active: Option<i32>,
+    post_goal: Option<i32>,
+}
+
+impl PhaseAttributes {
+    fn new(replay: &Replay<'_>) -> Self {
+        let name = |name: &str| {
+            let position = replay.names.iter().position(|x| x == name)?;
+            i32::try_from(position).ok()
+        };
+
+        PhaseAttributes {
+            state_name: replay.object_id("TAGame.GameEvent_TA:ReplicatedStateName"),
+            overtime: replay.object_id("TAGame.GameEvent_Soccar_TA:bOverTime"),
+            waiting: name("WaitingForPlayers"),
+            countdown: name("Countdown"),
+            active: name("Active"),
+            post_goal: name("PostGoalScored"),
+        }
+    }
+
+    fn phase(&self, name: i32) -> Option<GamePhase> {
+        let name = Some(name);
+        if name == self.waiting || name == self.countdown {
+            Some(GamePhase::PreKickoff)
+        } else if name == self.active {
+            Some(GamePhase::Active)
+        } else if name == self.post_goal {
+            Some(GamePhase::GoalReplay)
+        } else {
+            None
+        }
+    }
+}
+
+/// The latest of the updates (ordered by frame) at each of the frames
+fn forward_fill<T: Copy>(
+    frames: Range<usize>,
+    updates: impl Iterator<Item = (usize, T)>,
+) -> Vec<Option<T>> {
+    let mut updates = updates.peekable();
+    let mut latest = None;
+    frames
+        .map(|i| {
+            while let Some((_, x)) = updates.next_if(|(frame, _)| *frame <= i) {
+                latest = Some(x);
+            }
+            latest
+        })
+        .collect()
+}
+
+/// The boost amount of either of the boost attributes (see `FrameIndex::boost_attributes`)
+fn boost_amount(attribute: &Attribute) -> Option<u8> {
+    match attribute {
+        Attribute::Byte(amount) => Some(*amount),
+        Attribute::ReplicatedBoost(boost) => Some(boost.boost_amount),
+        _ => None,
+    }
+}
+
+/// Replicated axes are bytes where 128 is neutral and 255 is fully engaged
+fn decode_axis(x: u8) -> f32 {
+    ((f32::from(x) - 128.0) / 127.0).max(-1.0)
+}
+
+/// The state of an actor reconstructed from the network frames
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActorState {
+    /// The actor's object (eg: "Archetypes.Car.Car_Default")
+    pub object_id: ObjectId,
+    pub name_id: Option<i32>,
+
+    /// The trajectory that the actor spawned with
+    pub initial_trajectory: Trajectory,
+
+    /// The latest value of each attribute keyed by the attribute's object (eg:
+    /// "TAGame.RBActor_TA:ReplicatedRBState")
+    pub attributes: FnvHashMap<ObjectId, Attribute>,
+
+    /// The frame that the actor spawned on
+    pub spawned_at: usize,
+
+    /// The instance of the actor, which tells apart the actors that share an actor id
+    pub instance_id: InstanceId,
+}
+
+impl ActorState {
+    /// Returns the latest value of the attribute
+    pub fn attribute(&self, attribute: ObjectId) -> Option<&Attribute> {
+        self.attributes.get(&attribute)
+    }
+
+    /// Returns the latest value of an attribute that may not exist in the replay
+    fn optional(&self, attribute: Option<ObjectId>) -> Option<&Attribute> {
+        attribute.and_then(|x| self.attribute(x))
+    }
+}
+
+/// The actors that are alive at a frame along with the latest values of their attributes
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WorldState {
+    pub actors: FnvHashMap<ActorId, ActorState>,
+}
+
+impl WorldState {
+    /// Returns the state of the actor if it is alive
+    pub fn actor(&self, actor_id: ActorId) -> Option<&ActorState> {
+        self.actors.get(&actor_id)
+    }
+
+    /// Applies the deltas of the indexed frame in the order that they are decoded: new actors,
+    /// updated attributes, and then deleted actors. Frames should be applied in order starting
+    /// with the first frame. An out of range frame index is ignored.
+    pub fn apply(&mut self, index: &FrameIndex<'_>, frame_idx: usize) {
+        let frame = match index.frames.get(frame_idx) {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        for (i, actor) in frame.new_actors.iter().enumerate() {
+            self.actors.insert(
+                actor.actor_id,
+                ActorState {
+                    object_id: actor.object_id,
+                    name_id: actor.name_id,
+                    initial_trajectory: actor.initial_trajectory,
+                    attributes: FnvHashMap::default(),
+                    spawned_at: frame_idx,
+                    instance_id: index.spawned_instance(frame_idx, i),
+                },
+            );
+        }
+
+        for update in &frame.updated_actors {
+            if let Some(actor) = self.actors.get_mut(&update.actor_id) {
+                if let Some(attr) = index.attribute_object(actor.object_id, update.stream_id) {
+                    actor.attributes.insert(attr, update.attribute.clone());
+                }
+            }
+        }
+
+        for actor_id in &frame.deleted_actors {
+            self.actors.remove(actor_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParserBuilder;
+
+    #[test]
+    fn test_attribute_history() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        let boost = replay
+            .object_id("TAGame.CarComponent_Boost_TA:ReplicatedBoostAmount")
+            .unwrap();
+
+        // Scan for the updates of the first boost component until it is deleted
+        let frames = index.frames();
+        let (spawn_frame, actor) = frames
+            .iter()
+            .enumerate()
+            .flat_map(|(i, frame)| frame.new_actors.iter().map(move |x| (i, x)))
+            .find(|(_, x)| {
+                x.object_name(&replay) == Some("Archetypes.CarComponents.CarComponent_Boost")
+            })
+            .unwrap();
+
+        let mut expected = Vec::new();
+        for (i, frame) in frames.iter().enumerate().skip(spawn_frame) {
+            for update in &frame.updated_actors {
+                let attribute = index.attribute_object(actor.object_id, update.stream_id);
+                if update.actor_id == actor.actor_id && attribute == Some(boost) {
+                    expected.push((i, &update.attribute));
+                }
+            }
+
+            if frame.deleted_actors.contains(&actor.actor_id) {
+                break;
+            }
+        }
+
+        let history: Vec<_> = index.attribute_history(actor.actor_id, boost).collect();
+        assert!(!expected.is_empty());
+        assert_eq!(history[..expected.len()], expected[..]);
+        assert!(history.iter().all(|(_, x)| matches!(x, Attribute::Byte(_))));
+
+        assert_eq!(index.attribute_history(ActorId(-1), boost).count(), 0);
+
+        // Boost amounts of every actor are in the order of the frames
+        let amounts: Vec<_> = index.boost_amounts().collect();
+        assert_eq!(amounts.len(), index.updates_of(boost).count());
+        assert!(amounts.windows(2).all(|x| x[0].0 <= x[1].0));
+        let first = history[0];
+        assert!(amounts.iter().any(
+            |x| (x.0, x.2, Attribute::Byte(x.3)) == (first.0, actor.actor_id, first.1.clone())
+        ));
+    }
+
+    #[test]
+    fn test_state_at() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        let frames = index.frames();
+        let rigid_body = replay
+            .object_id("TAGame.RBActor_TA:ReplicatedRBState")
+            .unwrap();
+
+        let mut state = WorldState::default();
+        for i in 0..frames.len() {
+            state.apply(&index, i);
+        }
+        assert_eq!(index.state_at(frames.len() - 1), Some(state));
+        assert_eq!(index.state_at(frames.len()), None);
+
+        // The ball's rigid body in a frame is the latest update up to that frame
+        let ball = frames
+            .iter()
+            .flat_map(|x| x.new_actors.iter())
+            .find(|x| x.object_name(&replay) == Some("Archetypes.Ball.Ball_Default"))
+            .unwrap();
+
+        let (last_update, attribute) = index
+            .attribute_history(ball.actor_id, rigid_body)
+            .nth(10)
+            .unwrap();
+
+        let state = index.state_at(last_update).unwrap();
+        let actor = state.actor(ball.actor_id).unwrap();
+        assert_eq!(actor.object_id, ball.object_id);
+        assert_eq!(actor.attribute(rigid_body), Some(attribute));
+        assert!(matches!(attribute, Attribute::RigidBody(_)));
+
+        // Deleted actors are no longer in the world
+        let (deleted_frame, deleted) = frames
+            .iter()
+            .enumerate()
+            .find_map(|(i, x)| x.deleted_actors.first().map(|a| (i, *a)))
+            .unwrap();
+        let respawned = frames[deleted_frame]
+            .new_actors
+            .iter()
+            .any(|x| x.actor_id == deleted);
+        if !respawned {
+            assert!(index
+                .state_at(deleted_frame)
+                .unwrap()
+                .actor(deleted)
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn test_sample() {
+        let data = include_bytes!("../../assets/replays/good/fc427.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        let frames = index.frames();
+        let rigid_body = replay
+            .object_id("TAGame.RBActor_TA:ReplicatedRBState")
+            .unwrap();
+        let ball = frames
+            .iter()
+            .flat_map(|x| x.new_actors.iter())
+            .find(|x| x.object_name(&replay) == Some("Archetypes.Ball.Ball_Default"))
+            .unwrap();
+
+        // Find consecutive frames where the moving ball is updated
+        let history: Vec<_> = index.attribute_history(ball.actor_id, rigid_body).collect();
+        let (i, (a, b)) = history
+            .windows(2)
+            .filter(|x| x[1].0 == x[0].0 + 1)
+            .find_map(|x| match (x[0].1, x[1].1) {
+                (Attribute::RigidBody(a), Attribute::RigidBody(b)) if !a.sleeping => {
+                    Some((x[0].0, (a.decompress(10), b.decompress(10))))
+                }
+                _ => None,
+            })
+            .unwrap();
+
+        let sampled = index.sample(frames[i].time).unwrap();
+        assert_eq!(sampled[&ball.actor_id], a);
+
+        let time = (frames[i].time + frames[i + 1].time) / 2.0;
+        let sampled = index.sample(time).unwrap()[&ball.actor_id];
+        let expected = a.location.lerp(b.location, 0.5);
+        assert!((sampled.location.x - expected.x).abs() < 1e-3);
+        assert!((sampled.location.y - expected.y).abs() < 1e-3);
+        assert!((sampled.location.z - expected.z).abs() < 1e-3);
+        assert_ne!(sampled.location, a.location);
+
+        // Locations are in unreal units
+        assert!(sampled.location.z > 0.0 && sampled.location.z < 2100.0);
+
+        assert!(index.sample(frames[0].time - 1.0).is_none());
+        assert!(index.sample(frames[frames.len() - 1].time + 1.0).is_some());
+    }
+
+    #[test]
+    fn test_inputs() {
+        let data = include_bytes!("../../assets/replays/good/fc427.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        let player = &index.players()[0];
+        let inputs = index.inputs(player.actor_id);
+        assert!(inputs.len() > index.frames().len() / 2);
+        assert!(inputs.windows(2).all(|x| x[0].frame < x[1].frame));
+        assert!(inputs
+            .iter()
+            .all(|x| x.throttle >= -1.0 && x.throttle <= 1.0));
+        assert!(inputs.iter().all(|x| x.steer >= -1.0 && x.steer <= 1.0));
+        assert!(inputs.iter().any(|x| x.throttle == 1.0));
+        assert!(inputs.iter().any(|x| x.steer < 0.0));
+        assert!(inputs.iter().any(|x| x.handbrake));
+        assert!(inputs.iter().any(|x| x.dodge_torque.is_some()));
+        assert!(inputs.iter().any(|x| x.dodge_torque.is_none()));
+
+        // Inputs match the car's replicated throttle at the frame
+        let throttle = replay
+            .object_id("TAGame.Vehicle_TA:ReplicatedThrottle")
+            .unwrap();
+        let input = inputs[inputs.len() / 2];
+        let state = index.state_at(input.frame).unwrap();
+        let car = state.actor(input.car).unwrap();
+        let expected = match car.attribute(throttle) {
+            Some(Attribute::Byte(x)) => decode_axis(*x),
+            _ => 0.0,
+        };
+        assert_eq!(input.throttle, expected);
+
+        assert!(index.inputs(ActorId(-1)).is_empty());
+    }
+
+    #[test]
+    fn test_cameras() {
+        let data = include_bytes!("../../assets/replays/good/fc427.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        let recorder = index.recorder().unwrap();
+        let player = index
+            .players()
+            .into_iter()
+            .find(|x| x.actor_id == recorder)
+            .unwrap();
+        assert_eq!(player.name, Some("HyperNova"));
+
+        let cameras = index.cameras(recorder);
+        assert_eq!(cameras.len(), index.frames().len());
+        assert!(cameras.iter().all(|x| x.camera.is_some()));
+        assert!(cameras.iter().any(|x| x.ball_cam));
+        assert!(cameras.iter().any(|x| !x.ball_cam));
+        assert!(cameras.iter().all(|x| x.yaw >= -1.0 && x.yaw <= 1.0));
+        assert!(cameras.iter().all(|x| !x.spectator));
+        assert_eq!(cameras[0].spectator_shortcut, Some(1));
+
+        // The camera matches the camera settings actor's attributes at the frame
+        let secondary = replay
+            .object_id("TAGame.CameraSettingsActor_TA:bUsingSecondaryCamera")
+            .unwrap();
+        let camera = cameras[cameras.len() / 2];
+        let state = index.state_at(camera.frame).unwrap();
+        let actor = state.actor(camera.camera.unwrap()).unwrap();
+        let expected = actor.attribute(secondary) == Some(&Attribute::Boolean(true));
+        assert_eq!(camera.ball_cam, expected);
+
+        assert!(index.cameras(ActorId(-1)).is_empty());
+    }
+
+    #[test]
+    fn test_cameras_on_players() {
+        // Older replays replicate the camera on the player replication info actor
+        let data = include_bytes!("../../assets/replays/good/551c.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        let cameras = index.cameras(index.recorder().unwrap());
+        assert!(!cameras.is_empty());
+        assert!(cameras.iter().all(|x| x.camera.is_none()));
+        assert!(cameras.iter().all(|x| x.settings.is_some()));
+        assert!(cameras.iter().any(|x| x.ball_cam));
+    }
+
+    #[test]
+    fn test_series() {
+        let data = include_bytes!("../../assets/replays/good/fc427.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        let rigid_body = replay
+            .object_id("TAGame.RBActor_TA:ReplicatedRBState")
+            .unwrap();
+        let boost = replay
+            .object_id("TAGame.CarComponent_Boost_TA:ReplicatedBoostAmount")
+            .unwrap();
+
+        let player = &index.players()[0];
+        let input = index.inputs(player.actor_id)[100];
+        let car = index.instance_at(input.car, input.frame).unwrap();
+        let series = index.series(car).unwrap();
+        let instance = index.instance(car).unwrap();
+        assert_eq!(series.frame[0], instance.spawned_at);
+        assert!(series.frame.windows(2).all(|x| x[1] == x[0] + 1));
+        for column in &[&series.time, &series.x, &series.y, &series.z, &series.boost] {
+            assert_eq!(column.len(), series.frame.len());
+        }
+
+        // Columns agree with the reconstructed state of the frame
+        let at = series.frame.len() / 2;
+        let state = index.state_at(series.frame[at]).unwrap();
+        let location = match state.actor(input.car).unwrap().attribute(rigid_body) {
+            Some(Attribute::RigidBody(x)) => x.location.to_vector3f(replay.net_version.unwrap()),
+            x => panic!("unexpected rigid body: {:?}", x),
+        };
+        assert_eq!(series.time[at], index.frames()[series.frame[at]].time);
+        assert_eq!(
+            (series.x[at], series.y[at], series.z[at]),
+            (location.x, location.y, location.z)
+        );
+
+        let expected = state
+            .actors
+            .values()
+            .filter(|x| {
+                x.optional(index.inputs.vehicle)
+                    == Some(&Attribute::Flagged(true, input.car.0 as u32))
+            })
+            .find_map(|x| match x.attribute(boost) {
+                Some(Attribute::Byte(x)) => Some(f32::from(*x)),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(series.boost[at], expected);
+        assert!(series
+            .boost
+            .iter()
+            .all(|x| x.is_nan() || (0.0..=255.0).contains(x)));
+
+        // The ball doesn't have a boost amount
+        let ball = replay.object_id("Archetypes.Ball.Ball_Default").unwrap();
+        let ball = index
+            .instances()
+            .iter()
+            .find(|x| x.object_id == ball)
+            .unwrap();
+        let series = index.series(ball.instance_id).unwrap();
+        assert!(series.boost.iter().all(|x| x.is_nan()));
+        assert!(series.z.iter().any(|x| !x.is_nan()));
+
+        assert!(index.series(InstanceId(index.instances().len())).is_none());
+    }
+
+    #[test]
+    fn test_instances() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        let spawns: usize = index.frames().iter().map(|x| x.new_actors.len()).sum();
+        assert_eq!(index.instances().len(), spawns);
+
+        // Find an attribute updated on an actor id that is reused
+        let instances = index.instances();
+        let (reused, attribute) = index
+            .history
+            .keys()
+            .find(|(actor_id, _)| instances.iter().filter(|x| x.actor_id == *actor_id).count() > 1)
+            .copied()
+            .unwrap();
+
+        let mut histories = 0;
+        for instance in instances.iter().filter(|x| x.actor_id == reused) {
+            let deleted_at = instance.deleted_at.unwrap_or(index.frames().len());
+            for (frame, _) in index.instance_history(instance.instance_id, attribute) {
+                assert!(frame >= instance.spawned_at && frame <= deleted_at);
+                histories += 1;
+            }
+
+            let state = index.state_at(instance.spawned_at).unwrap();
+            let actor = state.actor(reused).unwrap();
+            assert_eq!(actor.instance_id, instance.instance_id);
+            assert_eq!(
+                index.instance_at(reused, instance.spawned_at),
+                Some(instance.instance_id)
+            );
+
+            if let Some(deleted_at) = instance.deleted_at {
+                assert_ne!(
+                    index.instance_at(reused, deleted_at),
+                    Some(instance.instance_id)
+                );
+            }
+        }
+
+        let history = index.attribute_history(reused, attribute);
+        assert_eq!(histories, history.count());
+        assert!(index
+            .instance_history(InstanceId(spawns), attribute)
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn test_teams() {
+        let data = include_bytes!("../../assets/replays/good/fc427.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        let teams = index.teams();
+        assert!(!teams.is_empty());
+
+        // Replicated teams agree with the header
+        let last = index.frames().len() - 1;
+        let stats = replay.player_stats();
+        for player in &teams {
+            assert!(player.changes.windows(2).all(|x| x[0].frame < x[1].frame));
+            let stat = stats.iter().find(|x| x.is_player(player.player.unique_id));
+            let expected = stat.and_then(|x| x.team).map(|x| x as u8);
+            assert_eq!(player.team_at(last), expected);
+            assert_eq!(index.player_team(player.player.actor_id, last), expected);
+        }
+
+        // Teams are resolved from the replicated team actors, not just the header
+        let team = replay
+            .object_id("Engine.PlayerReplicationInfo:Team")
+            .unwrap();
+        let player = teams[0].player.actor_id;
+        let (frame, attribute) = index.attribute_history(player, team).next().unwrap();
+        match attribute {
+            Attribute::Flagged(_, id) => {
+                assert!(index.team_of(ActorId(*id as i32), frame).is_some())
+            }
+            x => panic!("unexpected team: {:?}", x),
+        }
+
+        // A car is on its player's team
+        let input = index.inputs(teams[0].player.actor_id)[0];
+        let team = index.car_team(input.car, input.frame);
+        assert_eq!(team, teams[0].team_at(input.frame));
+        assert!(team.is_some());
+        assert_eq!(index.car_team(ActorId(-1), input.frame), None);
+    }
+
+    #[test]
+    fn test_decode_axis() {
+        assert_eq!(decode_axis(0), -1.0);
+        assert_eq!(decode_axis(128), 0.0);
+        assert_eq!(decode_axis(255), 1.0);
+    }
+
+    #[test]
+    fn test_index_without_frames() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .never_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        assert!(index.frames().is_empty());
+    }
+
+    #[test]
+    fn test_phases() {
+        let data = include_bytes!("../../assets/replays/good/d52eb.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        let phases = index.phases();
+        assert_eq!(phases.len(), index.frames().len());
+        assert_eq!(phases[0], GamePhase::PreKickoff);
+
+        // Each goal ends active play (the header's frame may trail the state by a frame), and
+        // play resumes after a countdown
+        for goal in replay.goal_events() {
+            let frame = goal.frame.unwrap() as usize;
+            assert_eq!(phases[frame], GamePhase::GoalReplay);
+            let before = phases[..frame]
+                .iter()
+                .rev()
+                .find(|x| !matches!(x, GamePhase::GoalReplay));
+            assert!(before.unwrap().is_live());
+        }
+
+        let mut changes = phases.clone();
+        changes.dedup();
+        let kickoffs = changes
+            .windows(2)
+            .filter(|x| x[0] == GamePhase::PreKickoff && x[1].is_live())
+            .count();
+        assert_eq!(kickoffs, 8);
+
+        // The match went to overtime, which is the last stretch of play
+        let overtime = phases
+            .iter()
+            .position(|x| *x == GamePhase::Overtime)
+            .unwrap();
+        assert!(phases[overtime..].iter().all(|x| *x != GamePhase::Active));
+        assert!(phases[..overtime].contains(&GamePhase::Active));
+    }
+
+    #[test]
+    fn test_stat_events() {
+        let data = include_bytes!("../../assets/replays/good/fc427.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        let events = index.stat_events();
+        let names: Vec<_> = events.iter().map(|x| x.name).collect();
+        assert_eq!(
+            names,
+            vec!["Goal", "EpicSave", "Save", "Goal", "Save", "EpicSave", "Goal", "Goal", "Save"]
+        );
+        assert!(events.iter().all(|x| x.player.is_some()));
+        assert_eq!(
+            replay.objects[usize::from(events[1].stat_object)],
+            "StatEvents.Events.EpicSave"
+        );
+
+        // The events credited to each player add up to the player's goals and saves, where
+        // players without stats in the header (eg: a bot that was replaced) have none
+        let stats = replay.player_stats();
+        for player in index.players() {
+            let expected = stats
+                .iter()
+                .find(|x| x.name == player.name)
+                .map_or(0, |x| x.goals.unwrap() + x.saves.unwrap());
+            let credited = index.stat_events_of(player.actor_id).count();
+            assert_eq!(credited as i32, expected);
+        }
+    }
+}
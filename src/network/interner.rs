@@ -0,0 +1,156 @@
+use fnv::FnvHashSet;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// An immutable string decoded from the network data. The same player names, titles, and match
+/// settings are replicated many times throughout a replay, so these strings are interned while
+/// decoding: each distinct string is allocated once and every attribute that contains it shares
+/// the allocation.
+///
+/// `SharedStr` dereferences to `str` and can be compared directly with string slices.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct SharedStr(Arc<str>);
+
+impl SharedStr {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns true if both strings share the same allocation
+    pub fn ptr_eq(this: &SharedStr, other: &SharedStr) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+impl Default for SharedStr {
+    fn default() -> Self {
+        SharedStr(Arc::from(""))
+    }
+}
+
+impl Deref for SharedStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for SharedStr {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for SharedStr {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> From<&'a str> for SharedStr {
+    fn from(x: &'a str) -> Self {
+        SharedStr(Arc::from(x))
+    }
+}
+
+impl From<String> for SharedStr {
+    fn from(x: String) -> Self {
+        SharedStr(Arc::from(x))
+    }
+}
+
+impl From<SharedStr> for String {
+    fn from(x: SharedStr) -> String {
+        String::from(x.as_str())
+    }
+}
+
+impl PartialEq<str> for SharedStr {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for SharedStr {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for SharedStr {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl fmt::Debug for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for SharedStr {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Deduplicates the strings decoded from the network data
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Interner {
+    strings: FnvHashSet<SharedStr>,
+}
+
+impl Interner {
+    pub fn intern(&mut self, s: &str) -> SharedStr {
+        if let Some(x) = self.strings.get(s) {
+            x.clone()
+        } else {
+            let res = SharedStr::from(s);
+            self.strings.insert(res.clone());
+            res
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_shares_allocation() {
+        let mut interner = Interner::default();
+        let a = interner.intern("comagoosie");
+        let b = interner.intern(&String::from("comagoosie"));
+        let c = interner.intern("rusty");
+        assert!(SharedStr::ptr_eq(&a, &b));
+        assert!(!SharedStr::ptr_eq(&a, &c));
+        assert_eq!(a, "comagoosie");
+        assert_eq!(c, String::from("rusty"));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_shared_str_serialize() {
+        let data = SharedStr::from("hello world");
+        assert_eq!(serde_json::to_string(&data).unwrap(), "\"hello world\"");
+        assert_eq!(format!("{:?}", data), "\"hello world\"");
+        assert_eq!(format!("{}", data), "hello world");
+    }
+}
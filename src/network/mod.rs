@@ -1,22 +1,78 @@
+pub use self::arena::{FrameArena, FrameRef};
 pub(crate) use self::attributes::*;
+pub use self::index::{
+    ActorInstance, ActorSeries, ActorState, CameraState, FrameIndex, GamePhase, InstanceId,
+    PlayerActor, PlayerInput, PlayerTeams, StatEvent, TeamChange, WorldState,
+};
+pub use self::interner::SharedStr;
 pub use self::models::*;
+pub use self::overrides::{ClassOverrides, OverrideError};
+pub use self::pool::ParserPool;
+pub use self::progress::Progress;
+pub use self::quantized::{Bitset, QuantizedSeries};
+pub use self::seek::FrameRange;
+pub use self::skip::SkippedActors;
+pub use self::spans::{BitSpan, FrameSpan, FrameSpans};
+pub use self::unknown::{UnknownActor, UnknownObjects};
+pub use self::visitor::FrameVisitor;
 
+mod arena;
+mod attribute_encoder;
 pub mod attributes;
+pub(crate) mod bit_reader;
+mod bit_writer;
+mod changes;
+mod downsample;
 mod frame_decoder;
+mod frame_encoder;
+mod index;
+mod interner;
 mod models;
+mod overrides;
+mod pool;
+mod progress;
+mod quantized;
+mod seek;
+mod skip;
+mod spans;
+#[cfg(feature = "serde")]
+mod stream;
+mod unknown;
+mod visitor;
 
-use crate::errors::NetworkError;
-use crate::hashes::{ATTRIBUTES, OBJECT_CLASSES, PARENT_CLASSES, SPAWN_STATS};
+use crate::errors::{LimitExceeded, LimitKind, NetworkError};
+use crate::hashes::{PARENT_CLASSES, SPAWN_STATS};
 use crate::header::Header;
+use crate::limits::Limits;
 use crate::models::*;
-use crate::network::frame_decoder::FrameDecoder;
+pub(crate) use crate::network::attribute_encoder::attribute_tag;
+use crate::network::attribute_encoder::AttributeEncoder;
+use crate::network::changes::ChangedAttributes;
+use crate::network::downsample::Downsample;
+pub(crate) use crate::network::frame_decoder::DecodeStats;
+use crate::network::frame_decoder::{FrameDecoder, FrameSink};
+use crate::network::frame_encoder::FrameEncoder;
+use crate::network::overrides::{object_classes, object_tag, spawn_trajectory};
+use crate::network::pool::DecodeScratch;
+pub(crate) use crate::network::progress::{Cancellation, ProgressHook};
+use crate::network::seek::{RangeFrames, Seek};
+pub(crate) use crate::network::skip::SkipHook;
+use crate::network::spans::SpannedFrames;
+#[cfg(feature = "serde")]
+use crate::network::stream::SerializeFrames;
+pub(crate) use crate::network::unknown::UnknownHook;
+use crate::network::visitor::VisitFrames;
 use crate::parser::ReplayBody;
 use crate::parsing_utils::log2;
+use crate::version::{ReplayVersion, UnknownVersions, VersionHook};
 use failure::Error;
 use fnv::FnvHashMap;
 use multimap::MultiMap;
+#[cfg(feature = "serde")]
+use serde::ser::{SerializeSeq, Serializer};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 
 pub(crate) struct CacheInfo {
     max_prop_id: i32,
@@ -30,29 +86,331 @@ pub(crate) struct ObjectAttribute {
     object_id: ObjectId,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) struct VersionTriplet(pub i32, pub i32, pub i32);
+/// Options that control how the network data is decoded into frames
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct DecodeOptions {
+    /// Merge the decoded frames so that there is a frame per sample at the given rate
+    pub sample_rate: Option<u32>,
 
-impl VersionTriplet {
-    pub fn net_version(&self) -> i32 {
-        self.2
+    /// Drop attribute updates that don't change the actor's value
+    pub only_changed_attributes: bool,
+
+    /// Report the progress of decoding every so many frames
+    pub progress: Option<ProgressHook>,
+
+    /// Stop decoding with an error once cancelled
+    pub cancel: Option<Cancellation>,
+
+    /// Bounds the number of frames and actors
+    pub limits: Limits,
+
+    /// Fail on unknown objects and data left over after the frames
+    pub strict: bool,
+
+    /// Skip to the next frame when an actor can't be decoded
+    pub skip: Option<SkipHook>,
+
+    /// What to do with actors of unknown objects, which defaults to failing in strict mode and
+    /// keeping the actors otherwise
+    pub unknown_objects: Option<UnknownObjects>,
+
+    /// Report each actor of an unknown object
+    pub on_unknown: Option<UnknownHook>,
+
+    /// What to do with replays newer than boxcars knows, which defaults to decoding them
+    pub unknown_versions: Option<UnknownVersions>,
+
+    /// Report a replay newer than boxcars knows
+    pub on_unknown_version: Option<VersionHook>,
+
+    /// Reuse the allocations of previous parses
+    pub pool: Option<ParserPool>,
+
+    /// Patch up net caches that don't resolve as is
+    pub recover_net_cache: bool,
+
+    /// Mappings merged over the built-in tables
+    pub overrides: Option<ClassOverrides>,
+
+    /// Decode only from a keyframe up to a frame
+    pub seek: Option<Seek>,
+}
+
+pub(crate) fn parse(
+    header: &Header<'_>,
+    body: &ReplayBody<'_>,
+    options: &DecodeOptions,
+) -> Result<NetworkFrames, Error> {
+    let (frames, _) = decode_pooled(header, body, options)?;
+    Ok(NetworkFrames { frames })
+}
+
+/// Decodes the frames and counts what was decoded
+pub(crate) fn parse_stats(
+    header: &Header<'_>,
+    body: &ReplayBody<'_>,
+    options: &DecodeOptions,
+) -> Result<(NetworkFrames, DecodeStats), Error> {
+    let (frames, stats) = decode_pooled(header, body, options)?;
+    Ok((NetworkFrames { frames }, stats))
+}
+
+/// Decodes the frames into the recycled frames of the pool when there is one
+fn decode_pooled(
+    header: &Header<'_>,
+    body: &ReplayBody<'_>,
+    options: &DecodeOptions,
+) -> Result<(Vec<Frame>, DecodeStats), Error> {
+    match options.pool.as_ref() {
+        Some(pool) => {
+            let frames = header.num_frames().unwrap_or(0).max(0) as usize;
+            let mut sink = pool.frames(frames.min(body.network_data.len()));
+            let stats = decode(header, body, options, &mut sink);
+            let frames = pool.finish(sink);
+            Ok((frames, stats?))
+        }
+        None => {
+            let mut frames = Vec::new();
+            let stats = decode(header, body, options, &mut frames)?;
+            Ok((frames, stats))
+        }
     }
 }
 
-pub(crate) fn parse(header: &Header<'_>, body: &ReplayBody<'_>) -> Result<NetworkFrames, Error> {
-    let version = VersionTriplet(
-        header.major_version,
-        header.minor_version,
-        header.net_version.unwrap_or(0),
-    );
+pub(crate) fn parse_arena(
+    header: &Header<'_>,
+    body: &ReplayBody<'_>,
+    options: &DecodeOptions,
+) -> Result<FrameArena, Error> {
+    let mut arena = FrameArena::default();
+    decode(header, body, options, &mut arena)?;
+    Ok(arena)
+}
 
-    // Create a parallel vector where each object has it's name normalized
-    let normalized_objects: Vec<&str> = body.objects.iter().map(|x| normalize_object(x)).collect();
+/// Decodes the frames into the visitor instead of collecting them
+pub(crate) fn visit<V: FrameVisitor + ?Sized>(
+    header: &Header<'_>,
+    body: &ReplayBody<'_>,
+    options: &DecodeOptions,
+    visitor: &mut V,
+) -> Result<(), Error> {
+    decode(header, body, options, &mut VisitFrames(visitor))?;
+    Ok(())
+}
+
+/// Decodes the frames alongside their spans. Spans locate frames in the network data, so the
+/// frames are never downsampled.
+pub(crate) fn parse_spans(
+    header: &Header<'_>,
+    body: &ReplayBody<'_>,
+    options: &DecodeOptions,
+) -> Result<(NetworkFrames, FrameSpans), Error> {
+    let options = DecodeOptions {
+        sample_rate: None,
+        only_changed_attributes: false,
+        ..options.clone()
+    };
+
+    let mut sink = SpannedFrames::default();
+    sink.spans.network_offset = body.network_offset;
+    decode(header, body, &options, &mut sink)?;
+    let frames = NetworkFrames {
+        frames: sink.frames,
+    };
+    Ok((frames, sink.spans))
+}
+
+/// Decodes the frames of the range from the keyframe before it, or from the first frame when
+/// there is no keyframe before it. The range is clamped to the frames of the replay.
+pub(crate) fn parse_range(
+    header: &Header<'_>,
+    body: &ReplayBody<'_>,
+    range: Range<usize>,
+    keyframe: Option<&KeyFrameOffset>,
+) -> Result<FrameRange, Error> {
+    let frames = header.num_frames().unwrap_or(0).max(0) as usize;
+    let start = range.start.min(frames);
+    let end = range.end.clamp(start, frames);
+    let (frame, bit) = keyframe.map_or((0, 0), |x| (x.frame, x.bit_offset));
+    let options = DecodeOptions {
+        seek: Some(Seek { frame, bit, end }),
+        ..DecodeOptions::default()
+    };
+
+    let mut sink = RangeFrames::new(frame, start);
+    decode(header, body, &options, &mut sink)?;
+    Ok(sink.finish())
+}
+
+/// Serializes the frames as a sequence as they are decoded. An error from the serializer is
+/// returned as the inner error.
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_frames<S: Serializer>(
+    header: &Header<'_>,
+    body: &ReplayBody<'_>,
+    options: &DecodeOptions,
+    serializer: S,
+) -> Result<Result<S::Ok, S::Error>, Error> {
+    let mut sink = match SerializeFrames::new(serializer) {
+        Ok(sink) => sink,
+        Err(e) => return Ok(Err(e)),
+    };
+
+    match decode(header, body, options, &mut sink) {
+        Ok(_) => Ok(sink.seq.end()),
+        Err(e) => sink.error.take().map(Err).ok_or(e),
+    }
+}
+
+fn decode<S: FrameSink>(
+    header: &Header<'_>,
+    body: &ReplayBody<'_>,
+    options: &DecodeOptions,
+    sink: &mut S,
+) -> Result<DecodeStats, Error> {
+    if options.only_changed_attributes {
+        sample(header, body, options, &mut ChangedAttributes::new(sink))
+    } else {
+        sample(header, body, options, sink)
+    }
+}
+
+/// Decodes the frames and downsamples them when a sample rate is set. The frames are merged
+/// before the unchanged attributes are dropped so that only the changes between samples remain.
+fn sample<S: FrameSink>(
+    header: &Header<'_>,
+    body: &ReplayBody<'_>,
+    options: &DecodeOptions,
+    sink: &mut S,
+) -> Result<DecodeStats, Error> {
+    match options.sample_rate {
+        Some(hz) if hz > 0 => {
+            let mut sampled = Downsample::new(sink, hz);
+            let stats = decode_frames(header, body, options, &mut sampled)?;
+            sampled.flush()?;
+            Ok(stats)
+        }
+        _ => decode_frames(header, body, options, sink),
+    }
+}
+
+/// Decodes the frames with the scratch space of the pool when there is one
+fn decode_frames<S: FrameSink>(
+    header: &Header<'_>,
+    body: &ReplayBody<'_>,
+    options: &DecodeOptions,
+    sink: &mut S,
+) -> Result<DecodeStats, Error> {
+    match options.pool.as_ref() {
+        Some(pool) => {
+            let mut scratch = pool.take_scratch();
+            let stats = decode_frames_with(header, body, options, sink, &mut scratch);
+            pool.give_scratch(scratch);
+            stats
+        }
+        None => decode_frames_with(header, body, options, sink, &mut DecodeScratch::default()),
+    }
+}
+
+fn decode_frames_with<S: FrameSink>(
+    header: &Header<'_>,
+    body: &ReplayBody<'_>,
+    options: &DecodeOptions,
+    sink: &mut S,
+    scratch: &mut DecodeScratch,
+) -> Result<DecodeStats, Error> {
+    let version = header.version();
 
     // Create a parallel vector where we lookup how to decode an object's initial trajectory
     // when they spawn as a new actor
-    let spawns: Vec<SpawnTrajectory> = body
+    let mut spawns = std::mem::take(&mut scratch.spawns);
+    spawns.clear();
+    spawns.extend(
+        body.objects
+            .iter()
+            .map(|x| spawn_trajectory(options.overrides.as_ref(), x)),
+    );
+
+    // Map each object's name to it's index
+    let name_obj_ind: HashMap<&str, ObjectId> = body
         .objects
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.deref(), ObjectId(i as i32)))
+        .collect();
+
+    let (object_ind_attrs, object_ind_attributes) = attribute_caches(
+        &body.objects,
+        &body.net_cache,
+        options.recover_net_cache,
+        options.overrides.as_ref(),
+    )?;
+
+    let product_decoder = ProductValueDecoder::create(version, &name_obj_ind);
+
+    let channel_bits = channel_bits(header.max_channels())?;
+    let num_frames = header.num_frames();
+
+    if let Some(frame_len) = num_frames {
+        if frame_len as usize > body.network_data.len() {
+            return Err(Error::from(NetworkError::TooManyFrames(frame_len)));
+        }
+
+        match options.limits.max_frames {
+            Some(limit) if frame_len as usize > limit => {
+                return Err(Error::from(NetworkError::LimitExceeded(LimitExceeded {
+                    kind: LimitKind::Frames,
+                    limit,
+                    requested: frame_len as usize,
+                })));
+            }
+            _ => {}
+        }
+
+        let frame_decoder = FrameDecoder {
+            frames_len: frame_len as usize,
+            product_decoder,
+            channel_bits,
+            body,
+            spawns: &spawns,
+            object_ind_attributes,
+            object_ind_attrs,
+            version,
+            progress: options.progress.as_ref(),
+            cancel: options.cancel.as_ref(),
+            limits: options.limits,
+            strict: options.strict,
+            skip: options.skip.as_ref(),
+            unknown_objects: options.unknown_objects.unwrap_or(if options.strict {
+                UnknownObjects::Fail
+            } else {
+                UnknownObjects::Static
+            }),
+            on_unknown: options.on_unknown.as_ref(),
+            overrides: options.overrides.as_ref(),
+            seek: options.seek,
+        };
+        let stats = frame_decoder.decode_frames(sink, scratch);
+        scratch.spawns = spawns;
+        stats
+    } else {
+        Ok(DecodeStats {
+            bits_unread: body.network_data.len() * 8,
+            ..DecodeStats::default()
+        })
+    }
+}
+
+/// Encodes frames into the network data of a replay with the given version, objects, and net
+/// cache. The inverse of `decode`.
+pub(crate) fn encode(
+    version: ReplayVersion,
+    max_channels: Option<i32>,
+    objects: &[Cow<'_, str>],
+    net_cache: &[ClassNetCache],
+    frames: &[Frame],
+) -> Result<Vec<u8>, Error> {
+    let spawns: Vec<SpawnTrajectory> = objects
         .iter()
         .map(|x| {
             SPAWN_STATS
@@ -62,14 +420,66 @@ pub(crate) fn parse(header: &Header<'_>, body: &ReplayBody<'_>) -> Result<Networ
         })
         .collect();
 
+    let name_obj_ind: HashMap<&str, ObjectId> = objects
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.deref(), ObjectId(i as i32)))
+        .collect();
+
+    let (_, object_ind_attributes) = attribute_caches(objects, net_cache, false, None)?;
+    let product_decoder = ProductValueDecoder::create(version, &name_obj_ind);
+    let frame_encoder = FrameEncoder {
+        attr_encoder: AttributeEncoder::new(version, product_decoder),
+        channel_bits: channel_bits(max_channels)?,
+        spawns: &spawns,
+        object_ind_attributes: &object_ind_attributes,
+        version,
+    };
+
+    Ok(frame_encoder.encode_frames(frames)?)
+}
+
+/// The number of bits used to encode an actor id
+pub(crate) fn channel_bits(max_channels: Option<i32>) -> Result<i32, NetworkError> {
+    // 1023 stolen from rattletrap
+    let channels = max_channels.unwrap_or(1023);
+    let channels = (channels as u32)
+        .checked_next_power_of_two()
+        .ok_or(NetworkError::ChannelsTooLarge(channels))?;
+    Ok(log2(channels) as i32)
+}
+
+/// Each object's attributes keyed by their stream id, and the derived information for decoding
+/// the stream ids of an object's attributes in the network data
+type AttributeCaches = (
+    HashMap<ObjectId, HashMap<StreamId, ObjectAttribute>>,
+    FnvHashMap<ObjectId, CacheInfo>,
+);
+
+/// Resolves the attributes of each object from the net cache and the class hierarchy.
+///
+/// Some replays have net caches that don't resolve as is: a class may be listed before its
+/// parent, a cache id may be reused so that a parent id refers to the wrong class, and an object
+/// may reference a class that isn't in the net cache. When recovering, these are patched up the
+/// way that other parsers do: a parent id refers to the closest preceding class with that cache
+/// id, the classes are resolved a second time so that parents listed after their children are
+/// inherited, and objects whose class can't be found are left without attributes instead of
+/// failing.
+///
+/// The overrides are merged over the built-in tables, and their additional attributes of a class
+/// are added to the class's attributes in the net cache.
+pub(crate) fn attribute_caches(
+    objects: &[Cow<'_, str>],
+    net_cache: &[ClassNetCache],
+    recover: bool,
+    overrides: Option<&ClassOverrides>,
+) -> Result<AttributeCaches, Error> {
+    // Create a parallel vector where each object has it's name normalized
+    let normalized_objects: Vec<&str> = objects.iter().map(|x| normalize_object(x)).collect();
+
     let attrs: Vec<_> = normalized_objects
         .iter()
-        .map(|x| {
-            ATTRIBUTES
-                .get(x.deref())
-                .cloned()
-                .unwrap_or(AttributeTag::NotImplemented)
-        })
+        .map(|x| object_tag(overrides, x))
         .collect();
 
     // Create a map of an object's normalized name to a list of indices in the object
@@ -81,83 +491,134 @@ pub(crate) fn parse(header: &Header<'_>, body: &ReplayBody<'_>) -> Result<Networ
         .collect();
 
     // Map each object's name to it's index
-    let name_obj_ind: HashMap<&str, ObjectId> = body
-        .objects
+    let name_obj_ind: HashMap<&str, ObjectId> = objects
         .iter()
         .enumerate()
         .map(|(i, name)| (name.deref(), ObjectId(i as i32)))
         .collect();
 
-    let mut object_ind_attrs: HashMap<ObjectId, HashMap<StreamId, ObjectAttribute>> =
-        HashMap::new();
-    for cache in &body.net_cache {
-        let mut all_props: HashMap<StreamId, ObjectAttribute> = cache
-            .properties
+    // The attributes that the overrides add to the class
+    let class_attributes = |class: &str| -> Vec<(StreamId, ObjectAttribute)> {
+        let additional = overrides.map(|x| x.class_attributes(class)).unwrap_or(&[]);
+        additional
             .iter()
-            .map(|x| {
-                let attr = attrs
-                    .get(x.object_ind as usize)
-                    .ok_or_else(|| NetworkError::StreamTooLargeIndex(x.stream_id, x.object_ind))?;
-                Ok((
-                    StreamId(x.stream_id),
+            .filter_map(|(stream_id, name)| {
+                let object_id = *name_obj_ind.get(name.as_str())?;
+                let attribute = attrs[usize::from(object_id)];
+                Some((
+                    *stream_id,
                     ObjectAttribute {
-                        attribute: *attr,
-                        object_id: ObjectId(x.object_ind),
+                        attribute,
+                        object_id,
                     },
                 ))
             })
-            .collect::<Result<HashMap<_, _>, NetworkError>>()?;
+            .collect()
+    };
 
-        let mut had_parent = false;
+    let mut object_ind_attrs: HashMap<ObjectId, HashMap<StreamId, ObjectAttribute>> =
+        HashMap::new();
+    let passes = if recover { 2 } else { 1 };
+    for _ in 0..passes {
+        for (cache_ind, cache) in net_cache.iter().enumerate() {
+            let mut all_props: HashMap<StreamId, ObjectAttribute> = cache
+                .properties
+                .iter()
+                .map(|x| {
+                    let attr = attrs
+                        .get(x.object_ind as usize)
+                        .ok_or(NetworkError::StreamTooLargeIndex(x.stream_id, x.object_ind))?;
+                    Ok((
+                        StreamId(x.stream_id),
+                        ObjectAttribute {
+                            attribute: *attr,
+                            object_id: ObjectId(x.object_ind),
+                        },
+                    ))
+                })
+                .collect::<Result<HashMap<_, _>, NetworkError>>()?;
 
-        // We are going to recursively resolve an object's name to find their direct parent.
-        // Parents have parents as well (etc), so we repeatedly walk up the chain picking up
-        // attributes on parent objects until we reach an object with no parent (`Core.Object`)
-        let mut object_name: &str = &*body.objects.get(cache.object_ind as usize)
-            .ok_or_else(|| NetworkError::ObjectIdOutOfRange(ObjectId(cache.object_ind)))?;
+            let mut had_parent = false;
 
-        while let Some(parent_name) = PARENT_CLASSES.get(object_name) {
-            had_parent = true;
-            if let Some(parent_ind) = name_obj_ind.get(parent_name) {
-                if let Some(parent_attrs) = object_ind_attrs.get(parent_ind) {
-                    all_props.extend(parent_attrs.iter());
+            // We are going to recursively resolve an object's name to find their direct parent.
+            // Parents have parents as well (etc), so we repeatedly walk up the chain picking up
+            // attributes on parent objects until we reach an object with no parent (`Core.Object`)
+            let class_name: &str = objects
+                .get(cache.object_ind as usize)
+                .ok_or(NetworkError::ObjectIdOutOfRange(ObjectId(cache.object_ind)))?;
+            let mut object_name = class_name;
+
+            while let Some(parent_name) = PARENT_CLASSES.get(object_name) {
+                had_parent = true;
+                if let Some(parent_ind) = name_obj_ind.get(parent_name) {
+                    if let Some(parent_attrs) = object_ind_attrs.get(parent_ind) {
+                        all_props.extend(parent_attrs.iter());
+                    }
                 }
+
+                object_name = parent_name;
             }
 
-            object_name = parent_name;
-        }
+            // Sometimes our hierarchy set up in build.rs isn't perfect so if we don't find a
+            // parent and a parent cache id is set, try and find this parent id and carry down
+            // their props.
+            if !had_parent && cache.parent_id != 0 {
+                let parent = if recover {
+                    net_cache[..cache_ind]
+                        .iter()
+                        .rev()
+                        .find(|x| x.cache_id == cache.parent_id)
+                } else {
+                    net_cache.iter().find(|x| x.cache_id == cache.parent_id)
+                };
 
-        // Sometimes our hierarchy set up in build.rs isn't perfect so if we don't find a
-        // parent and a parent cache id is set, try and find this parent id and carry down
-        // their props.
-        if !had_parent && cache.parent_id != 0 {
-            if let Some(parent) = body
-                .net_cache
-                .iter()
-                .find(|x| x.cache_id == cache.parent_id)
-            {
-                if let Some(parent_attrs) = object_ind_attrs.get(&ObjectId(parent.object_ind)) {
-                    all_props.extend(parent_attrs.iter());
+                if let Some(parent) = parent {
+                    if let Some(parent_attrs) = object_ind_attrs.get(&ObjectId(parent.object_ind)) {
+                        all_props.extend(parent_attrs.iter());
+                    }
                 }
             }
+
+            all_props.extend(class_attributes(class_name));
+
+            object_ind_attrs.insert(ObjectId(cache.object_ind), all_props);
         }
+    }
 
-        object_ind_attrs.insert(ObjectId(cache.object_ind), all_props);
+    // The additional attributes of classes that aren't in the net cache
+    for class in overrides.iter().flat_map(|x| x.classes_with_attributes()) {
+        if let Some(class_id) = name_obj_ind.get(class) {
+            object_ind_attrs
+                .entry(*class_id)
+                .or_insert_with(|| class_attributes(class).into_iter().collect());
+        }
     }
 
-    for (obj, parent) in OBJECT_CLASSES.entries() {
+    for (obj, parent) in object_classes(overrides) {
         // It's ok if an object class doesn't appear in our replay. For instance, basketball
         // objects don't appear in a soccer replay.
         if let Some(object_ids) = normalized_name_obj_ind.get_vec(obj) {
-            let parent_id = name_obj_ind.get(parent).ok_or_else(|| {
-                NetworkError::MissingParentClass(String::from(*obj), String::from(*parent))
-            })?;
+            let parent_id = match name_obj_ind.get(parent) {
+                Some(x) => x,
+                None if recover => continue,
+                None => {
+                    return Err(Error::from(NetworkError::MissingParentClass(
+                        String::from(obj),
+                        String::from(parent),
+                    )))
+                }
+            };
 
             for i in object_ids {
-                let parent_attrs: HashMap<_, _> = object_ind_attrs
-                    .get(parent_id)
-                    .ok_or_else(|| NetworkError::ParentHasNoAttributes(*parent_id, *i))?
-                    .clone();
+                let parent_attrs = match object_ind_attrs.get(parent_id) {
+                    Some(x) => x.clone(),
+                    None if recover => continue,
+                    None => {
+                        return Err(Error::from(NetworkError::ParentHasNoAttributes(
+                            *parent_id, *i,
+                        )))
+                    }
+                };
                 object_ind_attrs.insert(*i, parent_attrs);
             }
         }
@@ -170,7 +631,7 @@ pub(crate) fn parse(header: &Header<'_>, body: &ReplayBody<'_>) -> Result<Networ
             let max = attrs.keys().map(|&x| i32::from(x)).max().unwrap_or(2) + 1;
             let next_max = (max as u32)
                 .checked_next_power_of_two()
-                .ok_or_else(|| NetworkError::MaxStreamIdTooLarge(max, id))?;
+                .ok_or(NetworkError::MaxStreamIdTooLarge(max, id))?;
             Ok((
                 id,
                 CacheInfo {
@@ -182,37 +643,28 @@ pub(crate) fn parse(header: &Header<'_>, body: &ReplayBody<'_>) -> Result<Networ
         })
         .collect::<Result<FnvHashMap<_, _>, NetworkError>>()?;
 
-    let product_decoder = ProductValueDecoder::create(version, &name_obj_ind);
-
-    // 1023 stolen from rattletrap
-    let channels = header.max_channels().unwrap_or(1023);
-    let channels = (channels as u32)
-        .checked_next_power_of_two()
-        .ok_or_else(|| NetworkError::ChannelsTooLarge(channels))?;
-    let channel_bits = log2(channels as u32) as i32;
-    let num_frames = header.num_frames();
+    Ok((object_ind_attrs, object_ind_attributes))
+}
 
-    if let Some(frame_len) = num_frames {
-        if frame_len as usize > body.network_data.len() {
-            return Err(Error::from(NetworkError::TooManyFrames(frame_len)));
-        }
+/// The attribute object that each stream id of an object refers to, for naming the attributes of
+/// decoded frames
+pub(crate) fn object_attributes(
+    objects: &[Cow<'_, str>],
+    net_cache: &[ClassNetCache],
+) -> Result<HashMap<ObjectId, HashMap<StreamId, ObjectId>>, Error> {
+    // The replay doesn't record whether the net cache was recovered while decoding, so a net
+    // cache that doesn't resolve as is must have been
+    let (object_ind_attrs, _) = attribute_caches(objects, net_cache, false, None)
+        .or_else(|_| attribute_caches(objects, net_cache, true, None))?;
 
-        let frame_decoder = FrameDecoder {
-            frames_len: frame_len as usize,
-            product_decoder,
-            channel_bits,
-            body,
-            spawns: &spawns,
-            object_ind_attributes,
-            object_ind_attrs,
-            version,
-        };
-        Ok(NetworkFrames {
-            frames: frame_decoder.decode_frames()?,
+    let attributes = object_ind_attrs
+        .into_iter()
+        .map(|(obj, attrs)| {
+            let attrs = attrs.into_iter().map(|(k, v)| (k, v.object_id)).collect();
+            (obj, attrs)
         })
-    } else {
-        Ok(NetworkFrames { frames: Vec::new() })
-    }
+        .collect();
+    Ok(attributes)
 }
 
 #[cfg(test)]
@@ -220,16 +672,47 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_version_triplets() {
-        let version = VersionTriplet(18, 27, 1);
-        assert_eq!(version.net_version(), 1);
-
-        assert!(version < VersionTriplet(19, 27, 1));
-        assert!(version < VersionTriplet(18, 28, 1));
-        assert!(version < VersionTriplet(18, 27, 2));
-        assert_eq!(version, VersionTriplet(18, 27, 1));
-        assert!(version > VersionTriplet(17, 27, 1));
-        assert!(version > VersionTriplet(18, 26, 1));
-        assert!(version > VersionTriplet(18, 27, 0));
+    fn test_encode_decode_round_trip() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let replay = crate::ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+        let version = replay.version();
+        let frames = replay.network_frames.unwrap().frames;
+
+        let header = Header {
+            major_version: replay.major_version,
+            minor_version: replay.minor_version,
+            net_version: replay.net_version,
+            game_type: replay.game_type,
+            properties: replay.properties,
+        };
+
+        let network_data = encode(
+            version,
+            header.max_channels(),
+            &replay.objects,
+            &replay.net_cache,
+            &frames,
+        )
+        .unwrap();
+
+        let body = ReplayBody {
+            levels: replay.levels,
+            keyframes: replay.keyframes,
+            debug_info: replay.debug_info,
+            tick_marks: replay.tick_marks,
+            packages: replay.packages,
+            objects: replay.objects,
+            names: replay.names,
+            class_indices: replay.class_indices,
+            net_cache: replay.net_cache,
+            network_data: &network_data,
+            network_offset: 0,
+        };
+
+        let decoded = parse(&header, &body, &DecodeOptions::default()).unwrap();
+        assert_eq!(decoded.frames, frames);
     }
 }
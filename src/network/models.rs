@@ -1,9 +1,15 @@
+use crate::models::Replay;
 use crate::network::attributes::Attribute;
-use bitter::BitGet;
+use crate::network::bit_reader::BitGet;
+use smallvec::SmallVec;
+use std::convert::TryFrom;
 use std::fmt;
+use std::num::TryFromIntError;
 
 /// An object's current vector
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
 pub struct Vector {
     pub bias: i32,
     pub dx: i32,
@@ -11,18 +17,32 @@ pub struct Vector {
     pub dz: i32,
 }
 
+/// Only vectors that can be written back out are generated: the bias is a power of two and each
+/// component fits within the number of bits that the bias implies.
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Vector {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let size_bits = u.int_in_range(0..=19)?;
+        let limit = (1 << (size_bits + 2)) - 1;
+        Ok(Vector {
+            bias: 1 << (size_bits + 1),
+            dx: u.int_in_range(0..=limit)?,
+            dy: u.int_in_range(0..=limit)?,
+            dz: u.int_in_range(0..=limit)?,
+        })
+    }
+}
+
 impl Vector {
     pub fn decode(bits: &mut BitGet<'_>, net_version: i32) -> Option<Vector> {
         if_chain! {
             if let Some(size_bits) = bits.read_bits_max(5, if net_version >= 7 { 22 } else { 20 });
             let bias = 1 << (size_bits + 1);
             let bit_limit = (size_bits + 2) as i32;
-            if let Some(dx) = bits.read_u32_bits(bit_limit);
-            if let Some(dy) = bits.read_u32_bits(bit_limit);
-            if let Some(dz) = bits.read_u32_bits(bit_limit);
+            if let Some([dx, dy, dz]) = bits.read_packed3(bit_limit);
             then {
                 Some(Vector {
-                    bias: bias as i32,
+                    bias,
                     dx: dx as i32,
                     dy: dy as i32,
                     dz: dz as i32,
@@ -41,7 +61,7 @@ impl Vector {
         let dy = bits.read_u32_bits_unchecked(bit_limit);
         let dz = bits.read_u32_bits_unchecked(bit_limit);
         Vector {
-            bias: bias as i32,
+            bias,
             dx: dx as i32,
             dy: dy as i32,
             dz: dz as i32,
@@ -50,7 +70,10 @@ impl Vector {
 }
 
 /// An object's current rotation
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Rotation {
     pub yaw: Option<i8>,
     pub pitch: Option<i8>,
@@ -58,6 +81,13 @@ pub struct Rotation {
 }
 
 impl Rotation {
+    /// Converts the rotation to a quaternion, where a missing component is no rotation about
+    /// that axis
+    pub fn to_quaternion(&self) -> Quaternion {
+        let angle = |x: Option<i8>| f32::from(x.unwrap_or(0)) / 128.0 * std::f32::consts::PI;
+        Quaternion::from_rotator(angle(self.pitch), angle(self.yaw), angle(self.roll))
+    }
+
     pub fn decode(bits: &mut BitGet<'_>) -> Option<Rotation> {
         if_chain! {
             if let Some(yaw) = bits.if_get(BitGet::read_i8);
@@ -83,6 +113,299 @@ impl Rotation {
     }
 }
 
+/// A vector of floats that a `Vector` decompresses into
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct Vector3f {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// The number of unreal units (the unit of distance in the network data) in a meter
+pub const UNREAL_UNITS_PER_METER: f32 = 100.0;
+
+impl Vector3f {
+    /// Linearly interpolates between the vectors, where a `t` of 0 is `self` and 1 is `other`
+    pub fn lerp(self, other: Vector3f, t: f32) -> Vector3f {
+        Vector3f {
+            x: self.x + (other.x - self.x) * t,
+            y: self.y + (other.y - self.y) * t,
+            z: self.z + (other.z - self.z) * t,
+        }
+    }
+
+    fn scale(self, factor: f32) -> Vector3f {
+        Vector3f {
+            x: self.x * factor,
+            y: self.y * factor,
+            z: self.z * factor,
+        }
+    }
+
+    /// Converts a vector in unreal units (eg: a location or velocity) to meters
+    pub fn to_meters(self) -> Vector3f {
+        self.scale(1.0 / UNREAL_UNITS_PER_METER)
+    }
+
+    /// Rocket League uses unreal's left handed coordinate system where x is forward, y is
+    /// right, and z is up. This converts the vector to a right handed coordinate system where y
+    /// is up by swapping the y and z axes, so that x is forward and z is right.
+    pub fn to_y_up(self) -> Vector3f {
+        Vector3f {
+            x: self.x,
+            y: self.z,
+            z: self.y,
+        }
+    }
+}
+
+impl Vector {
+    fn unbiased(&self, scale: f32) -> Vector3f {
+        Vector3f {
+            x: (self.dx - self.bias) as f32 / scale,
+            y: (self.dy - self.bias) as f32 / scale,
+            z: (self.dz - self.bias) as f32 / scale,
+        }
+    }
+
+    /// Decompresses a location into unreal units. Starting with net version 5, components are
+    /// sent with two decimal places of precision (ie: a unit of the integer vector is a
+    /// hundredth of an unreal unit).
+    pub fn to_vector3f(&self, net_version: i32) -> Vector3f {
+        self.unbiased(if net_version >= 5 { 100.0 } else { 1.0 })
+    }
+
+    /// Decompresses a rigid body's velocity. Prior to net version 5, velocities are sent with
+    /// one decimal place of precision (where locations have none) and afterwards with two
+    /// decimal places like locations. Linear velocities are decompressed into unreal units per
+    /// second and angular velocities into hundredths of a radian per second.
+    pub fn to_velocity(&self, net_version: i32) -> Vector3f {
+        self.unbiased(if net_version >= 5 { 100.0 } else { 10.0 })
+    }
+}
+
+/// A unit quaternion
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct Quaternion {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 1.0,
+        }
+    }
+}
+
+impl Quaternion {
+    /// Converts an Unreal rotator (in radians) to a quaternion
+    pub fn from_rotator(pitch: f32, yaw: f32, roll: f32) -> Quaternion {
+        let (sp, cp) = (pitch / 2.0).sin_cos();
+        let (sy, cy) = (yaw / 2.0).sin_cos();
+        let (sr, cr) = (roll / 2.0).sin_cos();
+        Quaternion {
+            x: cr * sp * sy - sr * cp * cy,
+            y: -cr * sp * cy - sr * cp * sy,
+            z: cr * cp * sy - sr * sp * cy,
+            w: cr * cp * cy + sr * sp * sy,
+        }
+    }
+
+    /// Converts the quaternion to an Unreal rotator as the pitch, yaw, and roll in radians,
+    /// which is the inverse of `from_rotator`. The pitch is between -pi/2 and pi/2.
+    pub fn to_rotator(self) -> (f32, f32, f32) {
+        let Quaternion { x, y, z, w } = self;
+        let pitch = (2.0 * (z * x - w * y)).clamp(-1.0, 1.0).asin();
+        let yaw = (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z));
+        let roll = (-2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y));
+        (pitch, yaw, roll)
+    }
+
+    /// Rotates the vector by the quaternion
+    pub fn rotate(self, v: Vector3f) -> Vector3f {
+        // v + 2w(u x v) + 2u x (u x v), where u is the vector part of the quaternion
+        let cross = |a: Vector3f, b: Vector3f| Vector3f {
+            x: a.y * b.z - a.z * b.y,
+            y: a.z * b.x - a.x * b.z,
+            z: a.x * b.y - a.y * b.x,
+        };
+
+        let u = Vector3f {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+        };
+        let uv = cross(u, v);
+        let uuv = cross(u, uv);
+        Vector3f {
+            x: v.x + 2.0 * (self.w * uv.x + uuv.x),
+            y: v.y + 2.0 * (self.w * uv.y + uuv.y),
+            z: v.z + 2.0 * (self.w * uv.z + uuv.z),
+        }
+    }
+
+    /// Converts the rotation to the right handed y up coordinate system of
+    /// `Vector3f::to_y_up`. As swapping axes mirrors the coordinate system, the axis of rotation
+    /// is mirrored as well.
+    pub fn to_y_up(self) -> Quaternion {
+        Quaternion {
+            x: -self.x,
+            y: -self.z,
+            z: -self.y,
+            w: self.w,
+        }
+    }
+
+    fn dot(self, other: Quaternion) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    /// Spherically interpolates between the rotations along the shortest path, where a `t` of 0
+    /// is `self` and 1 is `other`
+    pub fn slerp(self, other: Quaternion, t: f32) -> Quaternion {
+        let mut dot = self.dot(other);
+        let mut other = other;
+        if dot < 0.0 {
+            dot = -dot;
+            other = Quaternion {
+                x: -other.x,
+                y: -other.y,
+                z: -other.z,
+                w: -other.w,
+            };
+        }
+
+        // Nearly parallel rotations are linearly interpolated to avoid dividing by zero
+        let (a, b) = if dot > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = dot.acos();
+            let sin_theta = theta.sin();
+            (
+                ((1.0 - t) * theta).sin() / sin_theta,
+                (t * theta).sin() / sin_theta,
+            )
+        };
+
+        let result = Quaternion {
+            x: a * self.x + b * other.x,
+            y: a * self.y + b * other.y,
+            z: a * self.z + b * other.z,
+            w: a * self.w + b * other.w,
+        };
+
+        let norm = result.dot(result).sqrt();
+        Quaternion {
+            x: result.x / norm,
+            y: result.y / norm,
+            z: result.z / norm,
+            w: result.w / norm,
+        }
+    }
+}
+
+/// The rotation of a rigid body as it is compressed in the network data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum CompressedRotation {
+    /// Prior to net version 7, the pitch, yaw, and roll are each compressed into 16 bits that
+    /// span a full turn
+    Rotator { pitch: u16, yaw: u16, roll: u16 },
+
+    /// Starting with net version 7, the largest component of the quaternion (0 through 3 for x,
+    /// y, z, and w) is omitted and the remaining three are each compressed into 18 bits
+    Quaternion { largest: u8, a: u32, b: u32, c: u32 },
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for CompressedRotation {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        if u.arbitrary()? {
+            Ok(CompressedRotation::Rotator {
+                pitch: u.arbitrary()?,
+                yaw: u.arbitrary()?,
+                roll: u.arbitrary()?,
+            })
+        } else {
+            let limit = (1 << QUATERNION_BITS) - 1;
+            Ok(CompressedRotation::Quaternion {
+                largest: u.int_in_range(0..=3)?,
+                a: u.int_in_range(0..=limit)?,
+                b: u.int_in_range(0..=limit)?,
+                c: u.int_in_range(0..=limit)?,
+            })
+        }
+    }
+}
+
+pub(crate) const QUATERNION_BITS: i32 = 18;
+
+impl CompressedRotation {
+    pub fn decode(bits: &mut BitGet<'_>, net_version: i32) -> Option<CompressedRotation> {
+        if net_version >= 7 {
+            if_chain! {
+                if let Some(largest) = bits.read_u32_bits(2);
+                if let Some([a, b, c]) = bits.read_packed3(QUATERNION_BITS);
+                then {
+                    Some(CompressedRotation::Quaternion {
+                        largest: largest as u8,
+                        a,
+                        b,
+                        c,
+                    })
+                } else {
+                    None
+                }
+            }
+        } else {
+            if_chain! {
+                if let Some(pitch) = bits.read_u16();
+                if let Some(yaw) = bits.read_u16();
+                if let Some(roll) = bits.read_u16();
+                then {
+                    Some(CompressedRotation::Rotator { pitch, yaw, roll })
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Decompresses the rotation into a quaternion
+    pub fn to_quaternion(&self) -> Quaternion {
+        match *self {
+            CompressedRotation::Rotator { pitch, yaw, roll } => {
+                let angle = |x: u16| f32::from(x as i16) / 32768.0 * std::f32::consts::PI;
+                Quaternion::from_rotator(angle(pitch), angle(yaw), angle(roll))
+            }
+            CompressedRotation::Quaternion { largest, a, b, c } => {
+                let max = ((1 << QUATERNION_BITS) - 1) as f32;
+                let part = |x: u32| (x as f32 / max - 0.5) * std::f32::consts::SQRT_2;
+                let (a, b, c) = (part(a), part(b), part(c));
+                let d = (1.0 - a * a - b * b - c * c).max(0.0).sqrt();
+                let (x, y, z, w) = match largest {
+                    0 => (d, a, b, c),
+                    1 => (a, d, b, c),
+                    2 => (a, b, d, c),
+                    _ => (a, b, c, d),
+                };
+                Quaternion { x, y, z, w }
+            }
+        }
+    }
+}
+
 /// When a new actor spawns in rocket league it will either have a location, location and rotation,
 /// or none of the above
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -94,7 +417,10 @@ pub enum SpawnTrajectory {
 
 /// Notifies that an actor has had one of their properties updated (most likely their rigid body
 /// state (location / rotation) has changed)
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct UpdatedAttribute {
     /// The actor that had an attribute updated
     pub actor_id: ActorId,
@@ -106,8 +432,21 @@ pub struct UpdatedAttribute {
     pub attribute: Attribute,
 }
 
+/// New actors seen during a frame. Most frames don't spawn any actors, so one is stored inline
+pub type NewActors = SmallVec<[NewActor; 1]>;
+
+/// Actors deleted during a frame. Deletions are rare and cheap to store inline
+pub type DeletedActors = SmallVec<[ActorId; 2]>;
+
+/// Attributes updated during a frame. The majority of frames contain no more than eight updates,
+/// which are stored inline to avoid a heap allocation per frame.
+pub type UpdatedActors = SmallVec<[UpdatedAttribute; 8]>;
+
 /// Contains the time and any new information that occurred during a frame
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Frame {
     /// The time in seconds that the frame is recorded at
     pub time: f32,
@@ -116,19 +455,21 @@ pub struct Frame {
     pub delta: f32,
 
     /// List of new actors seen during the frame
-    pub new_actors: Vec<NewActor>,
+    pub new_actors: NewActors,
 
     /// List of actor id's that are deleted / destroyed
-    pub deleted_actors: Vec<ActorId>,
+    pub deleted_actors: DeletedActors,
 
     /// List of properties updated on the actors
-    pub updated_actors: Vec<UpdatedAttribute>,
+    pub updated_actors: UpdatedActors,
 }
 
 /// A replay encodes a list of objects that appear in the network data. The index of an object in
 /// this list is used as a key in many places: reconstructing the attribute hierarchy and new
 /// actors in the network data.
-#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Hash, Serialize)]
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct ObjectId(pub i32);
 
 impl From<ObjectId> for i32 {
@@ -143,6 +484,30 @@ impl From<ObjectId> for usize {
     }
 }
 
+impl From<i32> for ObjectId {
+    fn from(x: i32) -> ObjectId {
+        ObjectId(x)
+    }
+}
+
+impl TryFrom<usize> for ObjectId {
+    type Error = TryFromIntError;
+
+    fn try_from(x: usize) -> Result<ObjectId, Self::Error> {
+        i32::try_from(x).map(ObjectId)
+    }
+}
+
+impl ObjectId {
+    /// Returns the name of the object (eg: "Archetypes.Car.Car_Default") from the replay's objects
+    pub fn lookup<'b>(self, replay: &'b Replay<'_>) -> Option<&'b str> {
+        usize::try_from(self.0)
+            .ok()
+            .and_then(|x| replay.objects.get(x))
+            .map(|x| x.as_ref())
+    }
+}
+
 impl fmt::Display for ObjectId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -152,7 +517,9 @@ impl fmt::Display for ObjectId {
 /// A `StreamId` is an attribute's object id in the network data. It is a more compressed form of
 /// the object id. Whereas the an object id might need to take up 9 bits, a stream id may only take
 /// up 6 bits.
-#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Hash, Serialize)]
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct StreamId(pub i32);
 
 impl From<StreamId> for i32 {
@@ -161,6 +528,12 @@ impl From<StreamId> for i32 {
     }
 }
 
+impl From<i32> for StreamId {
+    fn from(x: i32) -> StreamId {
+        StreamId(x)
+    }
+}
+
 impl fmt::Display for StreamId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -169,7 +542,9 @@ impl fmt::Display for StreamId {
 
 /// An actor in the network data stream. Could identify a ball, car, etc. Ids are not unique
 /// across a replay (eg. an actor that is destroyed may have its id repurposed).
-#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Hash, Serialize)]
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct ActorId(pub i32);
 
 impl From<ActorId> for i32 {
@@ -178,6 +553,12 @@ impl From<ActorId> for i32 {
     }
 }
 
+impl From<i32> for ActorId {
+    fn from(x: i32) -> ActorId {
+        ActorId(x)
+    }
+}
+
 impl fmt::Display for ActorId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -185,7 +566,10 @@ impl fmt::Display for ActorId {
 }
 
 /// Information for a new actor that appears in the game
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct NewActor {
     /// The id given to the new actor
     pub actor_id: ActorId,
@@ -200,8 +584,27 @@ pub struct NewActor {
     pub initial_trajectory: Trajectory,
 }
 
+impl NewActor {
+    /// Returns the actor's name (eg: "Car_TA_41") from the replay's names. Only newer replays
+    /// record the name of an actor.
+    pub fn name<'b>(&self, replay: &'b Replay<'_>) -> Option<&'b str> {
+        self.name_id
+            .and_then(|x| usize::try_from(x).ok())
+            .and_then(|x| replay.names.get(x))
+            .map(|x| x.as_ref())
+    }
+
+    /// Returns the name of the actor's object from the replay's objects
+    pub fn object_name<'b>(&self, replay: &'b Replay<'_>) -> Option<&'b str> {
+        self.object_id.lookup(replay)
+    }
+}
+
 /// Contains the optional location and rotation of an object when it spawns
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Trajectory {
     pub location: Option<Vector>,
     pub rotation: Option<Rotation>,
@@ -288,6 +691,150 @@ pub(crate) fn normalize_object(name: &str) -> &str {
 mod tests {
     use super::*;
 
+    fn assert_quaternion_eq(a: Quaternion, b: Quaternion) {
+        // Quaternions q and -q are the same rotation
+        let dot = a.x * b.x + a.y * b.y + a.z * b.z + a.w * b.w;
+        assert!((dot.abs() - 1.0).abs() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_decompress_rotation() {
+        let rotator = CompressedRotation::Rotator {
+            pitch: 0,
+            yaw: 0,
+            roll: 0,
+        };
+        assert_quaternion_eq(rotator.to_quaternion(), Quaternion::default());
+
+        // A quarter turn of yaw
+        let rotator = CompressedRotation::Rotator {
+            pitch: 0,
+            yaw: 16384,
+            roll: 0,
+        };
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let expected = Quaternion {
+            x: 0.0,
+            y: 0.0,
+            z: half,
+            w: half,
+        };
+        assert_quaternion_eq(rotator.to_quaternion(), expected);
+
+        let mid = 1 << 17;
+        let quaternion = CompressedRotation::Quaternion {
+            largest: 3,
+            a: mid,
+            b: mid,
+            c: mid,
+        };
+        assert_quaternion_eq(quaternion.to_quaternion(), Quaternion::default());
+
+        // The maximum compressed value is the largest a non-largest component can be
+        let quaternion = CompressedRotation::Quaternion {
+            largest: 3,
+            a: mid,
+            b: mid,
+            c: (1 << 18) - 1,
+        };
+        assert_quaternion_eq(quaternion.to_quaternion(), expected);
+    }
+
+    #[test]
+    fn test_to_rotator() {
+        let (pitch, yaw, roll) = (0.3, -2.0, 1.2);
+        let (p, y, r) = Quaternion::from_rotator(pitch, yaw, roll).to_rotator();
+        assert!((p - pitch).abs() < 1e-5 && (y - yaw).abs() < 1e-5 && (r - roll).abs() < 1e-5);
+
+        let rotation = Rotation {
+            yaw: Some(64),
+            pitch: None,
+            roll: None,
+        };
+        let (pitch, yaw, roll) = rotation.to_quaternion().to_rotator();
+        assert!(pitch.abs() < 1e-5 && roll.abs() < 1e-5);
+        assert!((yaw - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_to_y_up() {
+        let v = Vector3f {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        };
+        let up = v.to_y_up();
+        assert_eq!((up.x, up.y, up.z), (1.0, 3.0, 2.0));
+        assert_eq!(v.to_meters().z, 0.03);
+
+        // Rotating and then converting is the same as converting and then rotating
+        let rotation = CompressedRotation::Rotator {
+            pitch: 3000,
+            yaw: 20000,
+            roll: 60000,
+        }
+        .to_quaternion();
+        let a = rotation.rotate(v).to_y_up();
+        let b = rotation.to_y_up().rotate(v.to_y_up());
+        assert!((a.x - b.x).abs() < 1e-4);
+        assert!((a.y - b.y).abs() < 1e-4);
+        assert!((a.z - b.z).abs() < 1e-4);
+
+        // A positive yaw turns forward towards the right
+        let yaw = CompressedRotation::Rotator {
+            pitch: 0,
+            yaw: 16384,
+            roll: 0,
+        };
+        let forward = Vector3f {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        let right = yaw.to_quaternion().rotate(forward);
+        assert!(right.x.abs() < 1e-4 && (right.y - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_slerp() {
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        let quarter_turn = Quaternion {
+            x: 0.0,
+            y: 0.0,
+            z: half,
+            w: half,
+        };
+        let identity = Quaternion::default();
+        assert_quaternion_eq(identity.slerp(quarter_turn, 0.0), identity);
+        assert_quaternion_eq(identity.slerp(quarter_turn, 1.0), quarter_turn);
+
+        let eighth = std::f32::consts::FRAC_PI_8;
+        let eighth_turn = Quaternion {
+            x: 0.0,
+            y: 0.0,
+            z: eighth.sin(),
+            w: eighth.cos(),
+        };
+        assert_quaternion_eq(identity.slerp(quarter_turn, 0.5), eighth_turn);
+
+        let from = Vector3f {
+            x: 0.0,
+            y: 10.0,
+            z: -4.0,
+        };
+        let to = Vector3f {
+            x: 2.0,
+            y: 0.0,
+            z: 4.0,
+        };
+        let expected = Vector3f {
+            x: 0.5,
+            y: 7.5,
+            z: -2.0,
+        };
+        assert_eq!(from.lerp(to, 0.25), expected);
+    }
+
     #[test]
     fn test_decode_vector() {
         let mut bitter = BitGet::new(&[0b0000_0110, 0b0000_1000, 0b1101_1000, 0b0000_1101]);
@@ -345,5 +892,4 @@ mod tests {
             }
         );
     }
-
 }
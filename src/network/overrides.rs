@@ -0,0 +1,260 @@
+use crate::hashes::{ATTRIBUTES, OBJECT_CLASSES, SPAWN_STATS};
+use crate::network::models::{normalize_object, SpawnTrajectory, StreamId};
+use crate::network::AttributeTag;
+use std::collections::HashMap;
+
+pub use crate::errors::OverrideError;
+
+/// Mappings that are merged over the tables that boxcars decodes the network data with, for
+/// decoding replays with objects and attributes that boxcars doesn't know, such as replays of a
+/// modded game or of a Rocket League patch that boxcars doesn't support yet. The overrides only
+/// apply to the parse that they are given to (see `ParserBuilder::with_overrides`).
+///
+/// Attribute kinds are the names of `Attribute` variants (eg: `Int`), like the kinds of
+/// `SupportedAttribute`.
+///
+/// ```
+/// let overrides = boxcars::ClassOverrides::new()
+///     .object_class("Archetypes.Car.Car_Modded", "TAGame.Car_TA")
+///     .attribute("TAGame.Car_TA:ModdedBoost", "Int")
+///     .unwrap()
+///     .class_attribute("TAGame.Car_TA", 60, "TAGame.Car_TA:ModdedPaint", "TeamPaint")
+///     .unwrap();
+///
+/// # let data = include_bytes!("../../assets/replays/good/rumble.replay");
+/// let replay = boxcars::ParserBuilder::new(&data[..])
+///     .must_parse_network_data()
+///     .with_overrides(overrides)
+///     .parse()
+///     .unwrap();
+/// assert!(replay.network_frames.is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ClassOverrides {
+    /// The kind of each attribute keyed by the attribute's object name
+    attributes: HashMap<String, AttributeTag>,
+
+    /// The class of each object keyed by the object's name
+    classes: HashMap<String, String>,
+
+    /// The attributes that each class has in addition to those in the replay's net cache
+    class_attributes: HashMap<String, Vec<(StreamId, String)>>,
+}
+
+impl ClassOverrides {
+    pub fn new() -> Self {
+        ClassOverrides::default()
+    }
+
+    /// Decodes the attribute with the given object name (eg: `TAGame.Car_TA:TeamPaint`) as the
+    /// given kind, instead of how boxcars would decode it
+    pub fn attribute(mut self, name: &str, kind: &str) -> Result<Self, OverrideError> {
+        let tag = tag(kind)?;
+        self.attributes
+            .insert(String::from(normalize_object(name)), tag);
+        Ok(self)
+    }
+
+    /// Resolves the object (eg: `Archetypes.Car.Car_Default`) to the class (eg:
+    /// `TAGame.Car_TA`), so that actors of the object have the attributes of the class. Unless
+    /// boxcars knows how the object spawns, actors of the object spawn like the class's actors.
+    pub fn object_class(mut self, object: &str, class: &str) -> Self {
+        self.classes
+            .insert(String::from(normalize_object(object)), String::from(class));
+        self
+    }
+
+    /// Adds the attribute with the given object name and kind to the class at the stream id, for
+    /// replays whose net cache leaves out an attribute of the class. Objects of the class and of
+    /// its subclasses have the attribute. The attribute is ignored in replays that don't contain
+    /// an object of the attribute's name.
+    pub fn class_attribute(
+        mut self,
+        class: &str,
+        stream_id: i32,
+        name: &str,
+        kind: &str,
+    ) -> Result<Self, OverrideError> {
+        self = self.attribute(name, kind)?;
+        self.class_attributes
+            .entry(String::from(class))
+            .or_default()
+            .push((StreamId(stream_id), String::from(name)));
+        Ok(self)
+    }
+
+    /// The attribute tag of the (normalized) object name
+    pub(crate) fn attribute_tag(&self, name: &str) -> Option<AttributeTag> {
+        self.attributes.get(name).cloned()
+    }
+
+    /// The class that the overrides resolve the (normalized) object name to
+    pub(crate) fn class_of(&self, name: &str) -> Option<&str> {
+        self.classes.get(name).map(|x| x.as_str())
+    }
+
+    /// The additional attributes of the class
+    pub(crate) fn class_attributes(&self, class: &str) -> &[(StreamId, String)] {
+        self.class_attributes
+            .get(class)
+            .map(|x| x.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// The classes that have additional attributes
+    pub(crate) fn classes_with_attributes(&self) -> impl Iterator<Item = &str> {
+        self.class_attributes.keys().map(|x| x.as_str())
+    }
+}
+
+fn tag(kind: &str) -> Result<AttributeTag, OverrideError> {
+    AttributeTag::from_name(kind).ok_or_else(|| OverrideError::UnknownKind(String::from(kind)))
+}
+
+/// The attribute tag of the (normalized) object name
+pub(crate) fn object_tag(overrides: Option<&ClassOverrides>, name: &str) -> AttributeTag {
+    overrides
+        .and_then(|x| x.attribute_tag(name))
+        .or_else(|| ATTRIBUTES.get(name).cloned())
+        .unwrap_or(AttributeTag::NotImplemented)
+}
+
+/// How actors of the object spawn, falling back to how actors of the object's class spawn
+pub(crate) fn spawn_trajectory(overrides: Option<&ClassOverrides>, name: &str) -> SpawnTrajectory {
+    SPAWN_STATS
+        .get(name)
+        .or_else(|| {
+            let class = overrides?.class_of(normalize_object(name))?;
+            SPAWN_STATS.get(class)
+        })
+        .cloned()
+        .unwrap_or(SpawnTrajectory::None)
+}
+
+/// Returns true if the (normalized) object name resolves to a class
+pub(crate) fn has_class(overrides: Option<&ClassOverrides>, name: &str) -> bool {
+    OBJECT_CLASSES.contains_key(name) || overrides.is_some_and(|x| x.classes.contains_key(name))
+}
+
+/// The classes that objects resolve to, where the overrides take precedence over the built-in
+/// table
+pub(crate) fn object_classes(overrides: Option<&ClassOverrides>) -> Vec<(&str, &str)> {
+    let overridden = |obj: &str| overrides.is_some_and(|x| x.classes.contains_key(obj));
+    OBJECT_CLASSES
+        .entries()
+        .filter(|(obj, _)| !overridden(obj))
+        .map(|(obj, class)| (*obj, *class))
+        .chain(
+            overrides
+                .into_iter()
+                .flat_map(|x| x.classes.iter())
+                .map(|(obj, class)| (obj.as_str(), class.as_str())),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::Header;
+    use crate::models::Replay;
+    use crate::network::{self, DecodeOptions, NetworkFrames, ObjectId};
+    use crate::parser::ReplayBody;
+    use crate::ParserBuilder;
+    use failure::Error;
+    use std::borrow::Cow;
+
+    fn decode(replay: &Replay<'_>, overrides: ClassOverrides) -> Result<NetworkFrames, Error> {
+        let header = Header {
+            major_version: replay.major_version,
+            minor_version: replay.minor_version,
+            net_version: replay.net_version,
+            game_type: Cow::Borrowed(""),
+            properties: replay.properties.clone(),
+        };
+
+        let body = ReplayBody {
+            objects: replay.objects.clone(),
+            net_cache: replay.net_cache.clone(),
+            network_data: replay.network_data,
+            ..ReplayBody::default()
+        };
+
+        let options = DecodeOptions {
+            overrides: Some(overrides),
+            ..DecodeOptions::default()
+        };
+        network::parse(&header, &body, &options)
+    }
+
+    fn rename(replay: &mut Replay<'_>, from: &str, to: &'static str) -> ObjectId {
+        let id = replay.object_id(from).unwrap();
+        replay.objects[usize::from(id)] = Cow::Borrowed(to);
+        id
+    }
+
+    #[test]
+    fn test_overrides() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let expected = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap()
+            .network_frames;
+
+        let mut replay = ParserBuilder::new(&data[..])
+            .never_parse_network_data()
+            .parse()
+            .unwrap();
+        rename(
+            &mut replay,
+            "Archetypes.Car.Car_Default",
+            "Archetypes.Car.Car_Modded",
+        );
+        rename(
+            &mut replay,
+            "TAGame.Car_TA:TeamPaint",
+            "TAGame.Car_TA:ModdedPaint",
+        );
+
+        // Leave the boost amount out of the net cache
+        let boost = replay
+            .object_id("TAGame.CarComponent_Boost_TA:ReplicatedBoostAmount")
+            .unwrap();
+        let class = replay.object_id("TAGame.CarComponent_Boost_TA").unwrap();
+        let cache = replay
+            .net_cache
+            .iter_mut()
+            .find(|x| ObjectId(x.object_ind) == class)
+            .unwrap();
+        let at = cache
+            .properties
+            .iter()
+            .position(|x| ObjectId(x.object_ind) == boost)
+            .unwrap();
+        let stream_id = cache.properties.remove(at).stream_id;
+
+        assert!(decode(&replay, ClassOverrides::new()).is_err());
+
+        let overrides = ClassOverrides::new()
+            .object_class("Archetypes.Car.Car_Modded", "TAGame.Car_TA")
+            .attribute("TAGame.Car_TA:ModdedPaint", "TeamPaint")
+            .unwrap()
+            .class_attribute(
+                "TAGame.CarComponent_Boost_TA",
+                stream_id,
+                "TAGame.CarComponent_Boost_TA:ReplicatedBoostAmount",
+                "Byte",
+            )
+            .unwrap();
+        assert_eq!(decode(&replay, overrides).ok(), expected);
+    }
+
+    #[test]
+    fn test_unknown_kind() {
+        let err = ClassOverrides::new()
+            .attribute("TAGame.Car_TA:TeamPaint", "Paint")
+            .unwrap_err();
+        assert_eq!(err, OverrideError::UnknownKind(String::from("Paint")));
+    }
+}
@@ -0,0 +1,228 @@
+use crate::models::Replay;
+use crate::network::frame_decoder::{FrameBuffers, FrameSink};
+use crate::network::models::{ActorId, Frame, ObjectId, SpawnTrajectory};
+use failure::Error;
+use fnv::{FnvHashMap, FnvHashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Reuses the allocations of decoding the network data across parses, for services that decode
+/// a large number of replays and would otherwise spend much of their time in the allocator.
+///
+/// The pool keeps the decoder's scratch space (the buffers of the frame being decoded and the
+/// map of live actors) once a parse finishes, and hands it to the next parse. The frames of a
+/// replay that is no longer needed can be given back with `recycle`, so that the next replay's
+/// frames are decoded into their vectors. The pool is a cheap handle that can be cloned and
+/// shared between threads, where each parse in flight takes its own scratch space.
+///
+/// ```
+/// # let data = include_bytes!("../../assets/replays/good/rumble.replay");
+/// let pool = boxcars::ParserPool::new();
+/// for _ in 0..2 {
+///     let replay = boxcars::ParserBuilder::new(&data[..])
+///         .must_parse_network_data()
+///         .with_pool(&pool)
+///         .parse()
+///         .unwrap();
+///     assert!(replay.network_frames.is_some());
+///     pool.recycle(replay);
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct ParserPool {
+    inner: Arc<Mutex<PoolInner>>,
+}
+
+#[derive(Debug, Default)]
+struct PoolInner {
+    scratch: Vec<DecodeScratch>,
+
+    /// Frames with cleared vectors that keep their capacity
+    frames: Vec<Frame>,
+}
+
+/// The allocations of decoding that aren't handed to the caller
+#[derive(Debug, Default)]
+pub(crate) struct DecodeScratch {
+    pub buffers: FrameBuffers,
+    pub actors: FnvHashMap<ActorId, ObjectId>,
+    pub skipped: FnvHashSet<ActorId>,
+    pub spawns: Vec<SpawnTrajectory>,
+}
+
+impl DecodeScratch {
+    fn clear(&mut self) {
+        self.buffers.clear();
+        self.actors.clear();
+        self.skipped.clear();
+        self.spawns.clear();
+    }
+}
+
+impl ParserPool {
+    pub fn new() -> Self {
+        ParserPool::default()
+    }
+
+    /// Gives the replay's frames back to the pool to decode another replay's frames into. The
+    /// attributes of the frames are dropped, while the frames' vectors are kept.
+    pub fn recycle(&self, replay: Replay<'_>) {
+        if let Some(network) = replay.network_frames {
+            self.recycle_frames(network.frames);
+        }
+    }
+
+    /// Gives the frames back to the pool, like `recycle`
+    pub fn recycle_frames(&self, frames: Vec<Frame>) {
+        let mut inner = self.lock();
+        inner.frames.extend(frames.into_iter().map(|mut frame| {
+            frame.new_actors.clear();
+            frame.deleted_actors.clear();
+            frame.updated_actors.clear();
+            frame
+        }));
+    }
+
+    /// The number of recycled frames that the pool holds
+    pub fn frames_available(&self) -> usize {
+        self.lock().frames.len()
+    }
+
+    /// Drops the allocations that the pool holds, eg: after decoding an unusually large replay
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.scratch.clear();
+        inner.frames.clear();
+    }
+
+    /// A poisoned pool only holds cleared allocations, so it's safe to keep using
+    fn lock(&self) -> MutexGuard<'_, PoolInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn take_scratch(&self) -> DecodeScratch {
+        self.lock().scratch.pop().unwrap_or_default()
+    }
+
+    pub(crate) fn give_scratch(&self, mut scratch: DecodeScratch) {
+        scratch.clear();
+        self.lock().scratch.push(scratch);
+    }
+
+    /// Takes up to the given number of recycled frames
+    fn take_frames(&self, frames: usize) -> Vec<Frame> {
+        let mut inner = self.lock();
+        let at = inner.frames.len().saturating_sub(frames);
+        inner.frames.split_off(at)
+    }
+
+    /// A sink that decodes frames into the pool's recycled frames, expecting the given number
+    /// of frames
+    pub(crate) fn frames(&self, frames: usize) -> PooledFrames {
+        PooledFrames {
+            frames: Vec::with_capacity(frames),
+            spare: self.take_frames(frames),
+        }
+    }
+
+    /// Returns the recycled frames that went unused back to the pool
+    pub(crate) fn finish(&self, sink: PooledFrames) -> Vec<Frame> {
+        if !sink.spare.is_empty() {
+            self.lock().frames.extend(sink.spare);
+        }
+        sink.frames
+    }
+}
+
+impl fmt::Debug for ParserPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.lock();
+        f.debug_struct("ParserPool")
+            .field("scratch", &inner.scratch.len())
+            .field("frames", &inner.frames.len())
+            .finish()
+    }
+}
+
+/// Pools are equal when they share the same allocations
+impl PartialEq for ParserPool {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+/// Collects the decoded frames into recycled frames, falling back to new frames once the
+/// recycled frames run out
+pub(crate) struct PooledFrames {
+    frames: Vec<Frame>,
+    spare: Vec<Frame>,
+}
+
+impl FrameSink for PooledFrames {
+    fn frame(&mut self, time: f32, delta: f32, buffers: &mut FrameBuffers) -> Result<(), Error> {
+        let frame = match self.spare.pop() {
+            Some(mut frame) => {
+                frame.time = time;
+                frame.delta = delta;
+                frame.new_actors.extend(buffers.new_actors.drain(..));
+                frame
+                    .deleted_actors
+                    .extend(buffers.deleted_actors.drain(..));
+                frame
+                    .updated_actors
+                    .extend(buffers.updated_actors.drain(..));
+                frame
+            }
+            None => Frame {
+                time,
+                delta,
+                new_actors: buffers.new_actors.drain(..).collect(),
+                deleted_actors: buffers.deleted_actors.drain(..).collect(),
+                updated_actors: buffers.updated_actors.drain(..).collect(),
+            },
+        };
+
+        self.frames.push(frame);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParserBuilder;
+
+    #[test]
+    fn test_pool() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let expected = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let pool = ParserPool::new();
+        let parse = || {
+            ParserBuilder::new(&data[..])
+                .must_parse_network_data()
+                .with_pool(&pool)
+                .parse()
+                .unwrap()
+        };
+
+        let replay = parse();
+        assert_eq!(replay, expected);
+        assert_eq!(pool.lock().scratch.len(), 1);
+
+        let frames = replay.network_frames.as_ref().unwrap().frames.len();
+        pool.recycle(replay);
+        assert_eq!(pool.frames_available(), frames);
+
+        // The recycled frames are decoded into and the decoded frames are the same
+        let replay = parse();
+        assert_eq!(pool.frames_available(), 0);
+        assert_eq!(replay, expected);
+
+        pool.clear();
+        assert_eq!(pool.lock().scratch.len(), 0);
+    }
+}
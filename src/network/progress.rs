@@ -0,0 +1,122 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// How far decoding the network data has progressed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// The number of frames decoded so far
+    pub frames_decoded: usize,
+
+    /// The number of frames in the replay according to the header
+    pub frames_len: usize,
+
+    /// The number of bytes of network data consumed so far
+    pub bytes_read: usize,
+
+    /// The number of bytes of network data
+    pub bytes_len: usize,
+}
+
+impl Progress {
+    /// The percent of the network data that has been decoded, from 0 to 100
+    pub fn percent(&self) -> f32 {
+        if self.bytes_len == 0 {
+            100.0
+        } else {
+            self.bytes_read as f32 / self.bytes_len as f32 * 100.0
+        }
+    }
+}
+
+/// A callback invoked after every `every` decoded frames
+#[derive(Clone)]
+pub(crate) struct ProgressHook {
+    pub every: usize,
+    pub callback: Arc<dyn Fn(Progress) + Send + Sync>,
+}
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressHook")
+            .field("every", &self.every)
+            .finish()
+    }
+}
+
+/// Hooks are equal when they share the same callback
+impl PartialEq for ProgressHook {
+    fn eq(&self, other: &Self) -> bool {
+        self.every == other.every
+            && Arc::as_ptr(&self.callback) as *const u8 == Arc::as_ptr(&other.callback) as *const u8
+    }
+}
+
+/// A flag that another thread sets to cancel decoding
+#[derive(Debug, Clone)]
+pub(crate) struct Cancellation(pub Arc<AtomicBool>);
+
+impl Cancellation {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl PartialEq for Cancellation {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ParserBuilder;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_progress() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let progress = Arc::clone(&seen);
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .on_progress(1000, move |x| progress.lock().unwrap().push(x))
+            .parse()
+            .unwrap();
+
+        let frames = replay.network_frames.unwrap().frames.len();
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), frames / 1000 + 1);
+        assert_eq!(seen[0].frames_decoded, 1000);
+        assert!(seen.windows(2).all(|x| x[0].bytes_read < x[1].bytes_read));
+
+        let last = seen.last().unwrap();
+        assert_eq!(last.frames_decoded, frames);
+        assert_eq!(last.frames_len, frames);
+        assert!(last.percent() > 99.0);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancel);
+
+        // Cancelling isn't a network data error, so it isn't ignored
+        let err = ParserBuilder::new(&data[..])
+            .ignore_network_data_on_error()
+            .cancel_on(Arc::clone(&cancel))
+            .on_progress(100, move |_| flag.store(true, Ordering::Relaxed))
+            .parse()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Parsing was cancelled");
+
+        // The flag is only checked while decoding frames
+        let replay = ParserBuilder::new(&data[..])
+            .never_parse_network_data()
+            .cancel_on(cancel)
+            .parse();
+        assert!(replay.is_ok());
+    }
+}
@@ -0,0 +1,218 @@
+use crate::network::{ActorId, ActorSeries, InstanceId};
+
+/// The number of quantized units in an unreal unit. Since net version 5 locations are sent with
+/// two decimal places, so a quantized location is the integer that was sent over the network
+/// (less the bias). Earlier locations are whole units, which quantize without loss as well.
+const LOCATION_SCALE: f32 = 100.0;
+
+impl ActorSeries {
+    /// Packs the series into fixed-point integers, which take a fraction of the memory and
+    /// serialized size of the floats for exporting large datasets. Locations quantize at the
+    /// precision that they were replicated with and boost amounts are bytes on the network, so
+    /// nothing is lost: `QuantizedSeries::dequantize` returns the series as it was.
+    pub fn quantize(&self) -> QuantizedSeries {
+        let location = |column: &[f32]| {
+            column
+                .iter()
+                .map(|x| {
+                    if x.is_nan() {
+                        0
+                    } else {
+                        (x * LOCATION_SCALE).round() as i32
+                    }
+                })
+                .collect()
+        };
+
+        QuantizedSeries {
+            instance_id: self.instance_id,
+            actor_id: self.actor_id,
+            first_frame: self.frame.first().copied().unwrap_or(0),
+            time: self.time.clone(),
+            located: self.x.iter().map(|x| !x.is_nan()).collect(),
+            x: location(&self.x),
+            y: location(&self.y),
+            z: location(&self.z),
+            boosted: self.boost.iter().map(|x| !x.is_nan()).collect(),
+            boost: self
+                .boost
+                .iter()
+                .map(|x| if x.is_nan() { 0 } else { *x as u8 })
+                .collect(),
+        }
+    }
+}
+
+/// An `ActorSeries` packed into fixed-point integers (see `ActorSeries::quantize`). Values that
+/// weren't replicated yet are zero and are told apart by the bitsets.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct QuantizedSeries {
+    pub instance_id: InstanceId,
+    pub actor_id: ActorId,
+
+    /// The index of the first frame, as the frames of a series are consecutive
+    pub first_frame: usize,
+    pub time: Vec<f32>,
+
+    /// The components of the location in hundredths of an unreal unit
+    pub x: Vec<i32>,
+    pub y: Vec<i32>,
+    pub z: Vec<i32>,
+
+    /// Whether the location of each frame was replicated
+    pub located: Bitset,
+
+    /// The boost amount from 0 to 255
+    pub boost: Vec<u8>,
+
+    /// Whether the boost amount of each frame was replicated
+    pub boosted: Bitset,
+}
+
+impl QuantizedSeries {
+    /// Unpacks the series, where values that weren't replicated are NaN again
+    pub fn dequantize(&self) -> ActorSeries {
+        let location = |column: &[i32]| {
+            column
+                .iter()
+                .zip(self.located.iter())
+                .map(|(x, known)| {
+                    if known {
+                        *x as f32 / LOCATION_SCALE
+                    } else {
+                        f32::NAN
+                    }
+                })
+                .collect()
+        };
+
+        ActorSeries {
+            instance_id: self.instance_id,
+            actor_id: self.actor_id,
+            frame: (self.first_frame..self.first_frame + self.time.len()).collect(),
+            time: self.time.clone(),
+            x: location(&self.x),
+            y: location(&self.y),
+            z: location(&self.z),
+            boost: self
+                .boost
+                .iter()
+                .zip(self.boosted.iter())
+                .map(|(x, known)| if known { f32::from(*x) } else { f32::NAN })
+                .collect(),
+        }
+    }
+}
+
+/// A sequence of booleans packed as bits into words, where the first boolean is the least
+/// significant bit of the first word
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Bitset {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bitset {
+    pub fn new() -> Self {
+        Bitset::default()
+    }
+
+    /// The number of booleans in the set
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, value: bool) {
+        if self.len % 64 == 0 {
+            self.words.push(0);
+        }
+
+        if value {
+            self.words[self.len / 64] |= 1 << (self.len % 64);
+        }
+        self.len += 1;
+    }
+
+    /// Returns the boolean at the index, or `None` if the index is out of range
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+
+        Some(self.words[index / 64] & (1 << (index % 64)) != 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(move |i| self.words[i / 64] & (1 << (i % 64)) != 0)
+    }
+
+    /// The packed words
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+}
+
+impl std::iter::FromIterator<bool> for Bitset {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut set = Bitset::new();
+        for value in iter {
+            set.push(value);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::FrameIndex;
+    use crate::ParserBuilder;
+
+    #[test]
+    fn test_bitset() {
+        let values: Vec<bool> = (0..130).map(|x| x % 3 == 0).collect();
+        let set: Bitset = values.iter().copied().collect();
+        assert_eq!(set.len(), 130);
+        assert_eq!(set.words().len(), 3);
+        assert_eq!(set.iter().collect::<Vec<_>>(), values);
+        assert_eq!(set.get(129), Some(true));
+        assert_eq!(set.get(130), None);
+        assert!(Bitset::new().is_empty());
+    }
+
+    #[test]
+    fn test_quantize() {
+        let data = include_bytes!("../../assets/replays/good/fc427.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let index = FrameIndex::new(&replay).unwrap();
+        let player = &index.players()[0];
+        let input = index.inputs(player.actor_id)[0];
+        let car = index.instance_at(input.car, input.frame).unwrap();
+        let series = index.series(car).unwrap();
+        let quantized = series.quantize();
+        assert_eq!(quantized.located.len(), series.frame.len());
+        assert!(quantized.boosted.iter().any(|x| x));
+
+        // NaN never equals itself, so compare the bits of the floats
+        let bits = |x: &ActorSeries| {
+            let columns = [&x.time, &x.x, &x.y, &x.z, &x.boost];
+            let bits: Vec<Vec<u32>> = columns
+                .iter()
+                .map(|c| c.iter().map(|v| v.to_bits()).collect())
+                .collect();
+            (x.frame.clone(), bits)
+        };
+        assert_eq!(bits(&quantized.dequantize()), bits(&series));
+    }
+}
@@ -0,0 +1,134 @@
+use crate::network::frame_decoder::{FrameBuffers, FrameSink};
+use crate::network::models::{ActorId, Frame, NewActor};
+use failure::Error;
+use fnv::FnvHashMap;
+
+/// Where decoding starts and ends when only a range of frames is decoded. Decoding starts at a
+/// keyframe as the game spawns every live actor again in a keyframe, so the actors that the
+/// following frames update are known without decoding the frames before the keyframe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Seek {
+    /// The index of the keyframe's frame
+    pub frame: usize,
+
+    /// The bit offset of the keyframe's frame in the network data
+    pub bit: usize,
+
+    /// The index of the frame to stop decoding at
+    pub end: usize,
+}
+
+/// The frames of a range, decoded from the keyframe before the range (see
+/// `Replay::decode_frames`)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct FrameRange {
+    /// The index of the first frame in the network frames
+    pub start: usize,
+
+    /// The actors alive before the first frame, as they were spawned by the keyframe or the
+    /// frames after it, ordered by actor id. Their attributes that were updated before the first
+    /// frame aren't kept.
+    pub actors: Vec<NewActor>,
+    pub frames: Vec<Frame>,
+}
+
+/// Keeps the frames of the range and tracks the live actors of the frames before it
+#[derive(Debug)]
+pub(crate) struct RangeFrames {
+    frame: usize,
+    start: usize,
+    actors: FnvHashMap<ActorId, NewActor>,
+    frames: Vec<Frame>,
+}
+
+impl RangeFrames {
+    pub fn new(keyframe: usize, start: usize) -> Self {
+        RangeFrames {
+            frame: keyframe,
+            start,
+            actors: FnvHashMap::default(),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn finish(self) -> FrameRange {
+        let mut actors: Vec<_> = self.actors.into_values().collect();
+        actors.sort_by_key(|x| x.actor_id);
+        FrameRange {
+            start: self.start,
+            actors,
+            frames: self.frames,
+        }
+    }
+}
+
+impl FrameSink for RangeFrames {
+    fn frame(&mut self, time: f32, delta: f32, buffers: &mut FrameBuffers) -> Result<(), Error> {
+        if self.frame >= self.start {
+            return self.frames.frame(time, delta, buffers);
+        }
+
+        for actor in &buffers.deleted_actors {
+            self.actors.remove(actor);
+        }
+
+        for actor in &buffers.new_actors {
+            self.actors.insert(actor.actor_id, *actor);
+        }
+
+        self.frame += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ParserBuilder;
+    use fnv::FnvHashMap;
+
+    #[test]
+    fn test_decode_frames() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+        let frames = &replay.network_frames.as_ref().unwrap().frames;
+        let keyframe = replay.keyframes[3].frame as usize;
+
+        for range in &[0..10, keyframe..keyframe + 5, keyframe + 40..keyframe + 90] {
+            let decoded = replay.decode_frames(range.clone()).unwrap();
+            assert_eq!(decoded.start, range.start);
+            assert_eq!(decoded.frames[..], frames[range.clone()]);
+
+            // The actors spawned since the keyframe are the actors alive before the range
+            let mut alive = FnvHashMap::default();
+            for frame in &frames[..range.start] {
+                for actor in &frame.deleted_actors {
+                    alive.remove(actor);
+                }
+                for actor in &frame.new_actors {
+                    alive.insert(actor.actor_id, *actor);
+                }
+            }
+
+            let mut alive: Vec<_> = alive.into_values().collect();
+            alive.sort_by_key(|x| x.actor_id);
+            if range.start == keyframe {
+                assert!(decoded.actors.is_empty());
+            } else {
+                assert_eq!(decoded.actors, alive);
+            }
+        }
+
+        // Ranges are clamped to the frames
+        let end = frames.len();
+        let tail = replay.decode_frames(end - 3..end + 100).unwrap();
+        assert_eq!(tail.frames[..], frames[end - 3..]);
+        let past = replay.decode_frames(end..end + 1).unwrap();
+        assert!(past.frames.is_empty());
+        assert!(replay.decode_frames(5..5).unwrap().frames.is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+use crate::network::spans::BitSpan;
+use std::fmt;
+use std::sync::Arc;
+
+/// The actor data of a frame that was skipped because an actor couldn't be decoded. The network
+/// data doesn't record the size of an actor's data, so everything from the undecodable actor to
+/// the start of the next frame is skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedActors {
+    /// The index of the frame with the undecodable actor
+    pub frame: usize,
+
+    /// The time of the frame with the undecodable actor
+    pub time: f32,
+
+    /// The skipped bits of the network data, starting where decoding failed
+    pub span: BitSpan,
+
+    /// Why the actor couldn't be decoded
+    pub error: String,
+}
+
+/// A callback invoked each time actor data is skipped
+#[derive(Clone)]
+pub(crate) struct SkipHook(pub Arc<dyn Fn(SkippedActors) + Send + Sync>);
+
+impl fmt::Debug for SkipHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SkipHook")
+    }
+}
+
+/// Hooks are equal when they share the same callback
+impl PartialEq for SkipHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0) as *const u8 == Arc::as_ptr(&other.0) as *const u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ParserBuilder;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_skip_undecodable_actors() {
+        // Renaming an attribute leaves it without a known decoding
+        let mut data = include_bytes!("../../assets/replays/good/rumble.replay").to_vec();
+        let name = b"TAGame.Ball_TA:HitTeamNum\0";
+        let ind = data.windows(name.len()).position(|x| x == name).unwrap();
+        data[ind + name.len() - 2] = b'X';
+
+        let strict = ParserBuilder::new(&data[..])
+            .never_check_crc()
+            .must_parse_network_data()
+            .parse();
+        assert!(strict.is_err());
+
+        let skipped = Arc::new(Mutex::new(Vec::new()));
+        let diagnostics = Arc::clone(&skipped);
+        let replay = ParserBuilder::new(&data[..])
+            .never_check_crc()
+            .must_parse_network_data()
+            .skip_undecodable_actors(move |x| diagnostics.lock().unwrap().push(x))
+            .parse()
+            .unwrap();
+
+        let original =
+            ParserBuilder::new(include_bytes!("../../assets/replays/good/rumble.replay"))
+                .must_parse_network_data()
+                .parse()
+                .unwrap();
+        let frames = replay.network_frames.unwrap().frames;
+        let original_frames = original.network_frames.unwrap().frames;
+        assert_eq!(frames.len(), original_frames.len());
+
+        let skipped = skipped.lock().unwrap();
+        assert!(!skipped.is_empty());
+        for x in skipped.iter() {
+            assert!(x.error.contains("HitTeamNuX"), "{}", x.error);
+            assert!(x.span.len > 0);
+            assert_eq!(frames[x.frame].time, x.time);
+        }
+
+        // Frames without skipped actor data are decoded as usual
+        let first = skipped[0].frame;
+        assert_eq!(frames[..first], original_frames[..first]);
+    }
+}
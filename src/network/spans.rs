@@ -0,0 +1,85 @@
+use crate::network::frame_decoder::{FrameBuffers, FrameSink};
+use crate::network::models::Frame;
+use failure::Error;
+use std::ops::Range;
+
+/// A contiguous range of bits in the network data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct BitSpan {
+    /// The bit offset from the start of the network data
+    pub start: usize,
+
+    /// The number of bits
+    pub len: usize,
+}
+
+impl BitSpan {
+    /// The range of bytes in the network data that the span touches. The first and last bytes
+    /// may be shared with neighboring data as spans are rarely byte aligned.
+    pub fn bytes(&self) -> Range<usize> {
+        let end = self.start + self.len;
+        self.start / 8..(end + 7) / 8
+    }
+}
+
+/// Where a decoded frame is located in the network data
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct FrameSpan {
+    /// The frame from its time to the end of its actor data
+    pub span: BitSpan,
+
+    /// Parallel to the frame's `new_actors`. Each span starts at the actor id.
+    pub new_actors: Vec<BitSpan>,
+
+    /// Parallel to the frame's `updated_actors`. Each span starts at the stream id, as the
+    /// actor id is shared by all attributes updated on the actor in a row.
+    pub updated_actors: Vec<BitSpan>,
+}
+
+/// The location of each decoded frame in the network data, parallel to `NetworkFrames::frames`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct FrameSpans {
+    /// The byte offset of the network data from the start of the replay. Add this to a span's
+    /// bytes to locate it within the replay file.
+    pub network_offset: usize,
+    pub frames: Vec<FrameSpan>,
+}
+
+/// Decodes frames alongside their spans
+#[derive(Debug, Default)]
+pub(crate) struct SpannedFrames {
+    pub frames: Vec<Frame>,
+    pub spans: FrameSpans,
+}
+
+impl FrameSink for SpannedFrames {
+    const RECORD_SPANS: bool = true;
+
+    fn frame(&mut self, time: f32, delta: f32, buffers: &mut FrameBuffers) -> Result<(), Error> {
+        self.spans.frames.push(FrameSpan {
+            span: buffers.frame_span,
+            new_actors: buffers.new_actor_spans.drain(..).collect(),
+            updated_actors: buffers.updated_actor_spans.drain(..).collect(),
+        });
+        self.frames.frame(time, delta, buffers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_bytes() {
+        assert_eq!(BitSpan { start: 0, len: 8 }.bytes(), 0..1);
+        assert_eq!(BitSpan { start: 3, len: 8 }.bytes(), 0..2);
+        assert_eq!(BitSpan { start: 16, len: 1 }.bytes(), 2..3);
+        assert_eq!(BitSpan { start: 16, len: 0 }.bytes(), 2..2);
+    }
+}
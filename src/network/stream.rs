@@ -0,0 +1,97 @@
+use crate::network::arena::FrameRef;
+use crate::network::frame_decoder::{FrameBuffers, FrameSink};
+use failure::Error;
+use serde::ser::{SerializeSeq, Serializer};
+
+/// Serializes each frame as soon as it is decoded so that the frames are never held in memory
+/// all at once.
+pub(crate) struct SerializeFrames<S: SerializeSeq> {
+    pub seq: S,
+
+    /// The first serialization error, which aborts decoding
+    pub error: Option<S::Error>,
+}
+
+impl<S: SerializeSeq> SerializeFrames<S> {
+    pub fn new<T>(serializer: T) -> Result<Self, T::Error>
+    where
+        T: Serializer<SerializeSeq = S, Error = S::Error>,
+    {
+        Ok(SerializeFrames {
+            seq: serializer.serialize_seq(None)?,
+            error: None,
+        })
+    }
+}
+
+impl<S: SerializeSeq> FrameSink for SerializeFrames<S> {
+    fn frame(&mut self, time: f32, delta: f32, buffers: &mut FrameBuffers) -> Result<(), Error> {
+        let frame = FrameRef {
+            time,
+            delta,
+            new_actors: &buffers.new_actors,
+            deleted_actors: &buffers.deleted_actors,
+            updated_actors: &buffers.updated_actors,
+        };
+
+        self.seq.serialize_element(&frame).map_err(|e| {
+            self.error = Some(e);
+            format_err!("Unable to serialize frame")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ParserBuilder;
+    use std::io::{self, Write};
+
+    #[test]
+    fn test_serialize_frames_matches_frames() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let mut out = Vec::new();
+        let (streamed_replay, res) = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .serialize_frames_to(&mut serde_json::Serializer::new(&mut out))
+            .unwrap();
+
+        assert!(res.is_some());
+        assert!(streamed_replay.network_frames.is_none());
+        let frames = &replay.network_frames.as_ref().unwrap().frames;
+        assert_eq!(out, serde_json::to_vec(frames).unwrap());
+    }
+
+    struct FailingWriter(usize);
+
+    impl Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.0 < buf.len() {
+                return Err(io::Error::other("disk full"));
+            }
+
+            self.0 -= buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_serialize_frames_writer_error() {
+        // Serialization errors are not network data errors so they aren't ignored
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let mut writer = FailingWriter(10_000);
+        let err = ParserBuilder::new(&data[..])
+            .ignore_network_data_on_error()
+            .serialize_frames_to(&mut serde_json::Serializer::new(&mut writer))
+            .unwrap_err();
+        assert!(format!("{}", err).contains("disk full"));
+    }
+}
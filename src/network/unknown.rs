@@ -0,0 +1,135 @@
+use crate::network::models::NewActor;
+use std::fmt;
+use std::sync::Arc;
+
+/// Determines what happens when a new actor spawns as an object that boxcars doesn't know. Each
+/// Rocket League patch tends to introduce a few objects, so it's common for a replay from a new
+/// patch to contain unknown objects.
+///
+/// How an unknown object's trajectory is encoded on spawn is unknown, so it's assumed to spawn
+/// without one, like a static actor. An actor that spawns with a trajectory will cause the rest
+/// of the frame to be decoded incorrectly. Attribute updates to an unknown object can only be
+/// decoded when the replay's net cache describes the object's attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownObjects {
+    /// Keep the actor as if it were a static actor
+    Static,
+
+    /// Leave the actor, its attribute updates, and its deletion out of the decoded frames
+    Skip,
+
+    /// Fail decoding the network data
+    Fail,
+}
+
+/// A new actor that spawned as an object that boxcars doesn't know
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownActor {
+    /// The index of the frame that the actor spawned in
+    pub frame: usize,
+
+    pub actor: NewActor,
+
+    /// The name of the actor's object
+    pub object: String,
+}
+
+/// A callback invoked each time an actor spawns as an unknown object
+#[derive(Clone)]
+pub(crate) struct UnknownHook(pub Arc<dyn Fn(UnknownActor) + Send + Sync>);
+
+impl fmt::Debug for UnknownHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("UnknownHook")
+    }
+}
+
+/// Hooks are equal when they share the same callback
+impl PartialEq for UnknownHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0) as *const u8 == Arc::as_ptr(&other.0) as *const u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnknownObjects;
+    use crate::{ActorId, Attribute, FrameBuilder, ParserBuilder, ReplayBuilder, Trajectory};
+    use std::sync::{Arc, Mutex};
+
+    fn replay() -> Vec<u8> {
+        let trajectory = Trajectory {
+            location: None,
+            rotation: None,
+        };
+
+        ReplayBuilder::new()
+            .frame(
+                FrameBuilder::new(1.0, 0.03)
+                    .spawn(ActorId(0), "Archetypes.Ball.Ball_Default", trajectory)
+                    .spawn(ActorId(1), "Archetypes.Mystery.Mystery_Default", trajectory),
+            )
+            .frame(
+                FrameBuilder::new(1.03, 0.03)
+                    .update(ActorId(0), "Engine.Actor:bHidden", Attribute::Boolean(true))
+                    .update(ActorId(1), "Engine.Actor:bHidden", Attribute::Boolean(true)),
+            )
+            .frame(FrameBuilder::new(1.06, 0.03).delete(ActorId(1)))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_unknown_objects() {
+        let data = replay();
+        let unknown = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&unknown);
+        let kept = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .on_unknown_object(move |x| recorded.lock().unwrap().push(x))
+            .parse()
+            .unwrap();
+
+        let frames = kept.network_frames.unwrap().frames;
+        assert_eq!(frames[0].new_actors.len(), 2);
+        assert_eq!(frames[1].updated_actors.len(), 2);
+        assert_eq!(&frames[2].deleted_actors[..], &[ActorId(1)]);
+
+        let unknown = unknown.lock().unwrap();
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].frame, 0);
+        assert_eq!(unknown[0].actor, frames[0].new_actors[1]);
+        assert_eq!(unknown[0].object, "Archetypes.Mystery.Mystery_Default");
+
+        let skipped = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .unknown_objects(UnknownObjects::Skip)
+            .parse()
+            .unwrap();
+
+        let frames = skipped.network_frames.unwrap().frames;
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].new_actors.len(), 1);
+        assert_eq!(frames[1].updated_actors.len(), 1);
+        assert!(frames[2].deleted_actors.is_empty());
+        assert!(frames
+            .iter()
+            .flat_map(|x| x.updated_actors.iter())
+            .all(|x| x.actor_id == ActorId(0)));
+
+        let err = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .unknown_objects(UnknownObjects::Fail)
+            .parse()
+            .unwrap_err();
+        assert!(err.to_string().contains("Mystery"), "{}", err);
+
+        // Strict mode fails by default, but can be told otherwise
+        assert!(ParserBuilder::new(&data[..]).strict().parse().is_err());
+        let strict = ParserBuilder::new(&data[..])
+            .strict()
+            .unknown_objects(UnknownObjects::Static)
+            .parse();
+        assert!(strict.is_ok());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::network::frame_decoder::{FrameBuffers, FrameSink};
+use crate::network::models::{ActorId, NewActor, UpdatedAttribute};
+use failure::Error;
+
+/// Visits the network data as it is decoded, so that a consumer can aggregate frames on the fly
+/// without the frames being collected. Each frame is visited as its new actors, then its
+/// updated attributes, and then its deleted actors, which is the order that they are applied to
+/// the state of the game, before the frame ends. Every method does nothing by default.
+///
+/// ```
+/// use boxcars::{Attribute, FrameVisitor, UpdatedAttribute};
+///
+/// #[derive(Default)]
+/// struct Demolitions(usize);
+///
+/// impl FrameVisitor for Demolitions {
+///     fn on_attribute(&mut self, update: &UpdatedAttribute) {
+///         if let Attribute::Demolish(_) = update.attribute {
+///             self.0 += 1;
+///         }
+///     }
+/// }
+///
+/// # let data = include_bytes!("../../assets/replays/good/rumble.replay");
+/// let mut demolitions = Demolitions::default();
+/// let (replay, decoded) = boxcars::ParserBuilder::new(&data[..])
+///     .must_parse_network_data()
+///     .visit_frames(&mut demolitions)
+///     .unwrap();
+/// assert!(decoded);
+/// assert!(replay.network_frames.is_none());
+/// ```
+pub trait FrameVisitor {
+    fn on_new_actor(&mut self, _actor: &NewActor) {}
+
+    fn on_attribute(&mut self, _update: &UpdatedAttribute) {}
+
+    fn on_deleted_actor(&mut self, _actor: ActorId) {}
+
+    /// Called after each frame has been visited with the frame's time and delta
+    fn on_frame_end(&mut self, _time: f32, _delta: f32) {}
+}
+
+/// Drives a visitor with the decoded frames
+pub(crate) struct VisitFrames<'a, V: ?Sized>(pub &'a mut V);
+
+impl<'a, V: FrameVisitor + ?Sized> FrameSink for VisitFrames<'a, V> {
+    fn frame(&mut self, time: f32, delta: f32, buffers: &mut FrameBuffers) -> Result<(), Error> {
+        for actor in &buffers.new_actors {
+            self.0.on_new_actor(actor);
+        }
+
+        for update in &buffers.updated_actors {
+            self.0.on_attribute(update);
+        }
+
+        for actor in &buffers.deleted_actors {
+            self.0.on_deleted_actor(*actor);
+        }
+
+        self.0.on_frame_end(time, delta);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParserBuilder;
+
+    #[derive(Default)]
+    struct Counts {
+        new_actors: usize,
+        attributes: usize,
+        deleted_actors: usize,
+        times: Vec<f32>,
+    }
+
+    impl FrameVisitor for Counts {
+        fn on_new_actor(&mut self, _actor: &NewActor) {
+            self.new_actors += 1;
+        }
+
+        fn on_attribute(&mut self, _update: &UpdatedAttribute) {
+            self.attributes += 1;
+        }
+
+        fn on_deleted_actor(&mut self, _actor: ActorId) {
+            self.deleted_actors += 1;
+        }
+
+        fn on_frame_end(&mut self, time: f32, _delta: f32) {
+            self.times.push(time);
+        }
+    }
+
+    #[test]
+    fn test_visit_frames() {
+        let data = include_bytes!("../../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let mut counts = Counts::default();
+        let (visited, decoded) = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .visit_frames(&mut counts)
+            .unwrap();
+        assert!(decoded);
+        assert!(visited.network_frames.is_none());
+
+        let frames = replay.network_frames.unwrap().frames;
+        let times: Vec<_> = frames.iter().map(|x| x.time).collect();
+        assert_eq!(counts.times, times);
+
+        let sum = |f: fn(&crate::Frame) -> usize| frames.iter().map(f).sum::<usize>();
+        assert_eq!(counts.new_actors, sum(|x| x.new_actors.len()));
+        assert_eq!(counts.attributes, sum(|x| x.updated_actors.len()));
+        assert_eq!(counts.deleted_actors, sum(|x| x.deleted_actors.len()));
+    }
+}
@@ -56,13 +56,26 @@
 
 use crate::core_parser::CoreParser;
 use crate::crc::calc_crc;
-use crate::errors::ParseError;
-use crate::header::{self, Header};
+use crate::errors::{NetworkError, ParseError};
+use crate::events::ReplayEvents;
+use crate::header::{self, Header, ROCKET_LEAGUE_GAME_TYPE};
+use crate::limits::Limits;
 use crate::models::*;
-use crate::network;
+use crate::network::{
+    self, Cancellation, ClassOverrides, DecodeOptions, FrameArena, FrameSpans, FrameVisitor,
+    ParserPool, Progress, ProgressHook, SkipHook, SkippedActors, UnknownActor, UnknownHook,
+    UnknownObjects,
+};
 use crate::parsing_utils::{le_f32, le_i32};
+use crate::sections::ReplaySections;
+use crate::stats::{ParseStats, Stopwatch};
+use crate::version::{ReplayVersion, UnknownVersions, VersionHook};
 use failure::{Error, ResultExt};
 use std::borrow::Cow;
+use std::panic;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, OnceLock};
+use std::thread;
 
 /// Determines under what circumstances the parser should perform the crc check for replay
 /// corruption. Since the crc check is the most time consuming check for parsing (causing
@@ -72,7 +85,8 @@ use std::borrow::Cow;
 pub enum CrcCheck {
     /// Always perform the crc check. Useful when the replay has had its contents modified. This
     /// will catch a user that increased the number of goals they scored (easy) but only if they
-    /// didn't update the crc as well (not as easy).
+    /// didn't update the crc as well (not as easy). On machines with more than one core, the crc
+    /// of the body is computed on a second thread while the body is parsed.
     Always,
 
     /// Never perform the crc check. Useful only when it doesn't matter to know if a replay is
@@ -90,14 +104,16 @@ pub enum CrcCheck {
 /// intensive and volatile section of the replay.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NetworkParse {
-    /// If the network data fails parse return an error
+    /// If the network data fails parse return an error. Replays of other games than Rocket
+    /// League fail with `NetworkError::UnsupportedGame`.
     Always,
 
     /// Skip parsing the network data
     Never,
 
     /// Attempt to parse the network data, but if unsuccessful ignore the error
-    /// and continue parsing
+    /// and continue parsing. The network data of other games than Rocket League (eg: Rocket
+    /// League Sideswipe) is not decoded, so those replays have no network frames.
     IgnoreOnError,
 }
 
@@ -108,6 +124,8 @@ pub struct ParserBuilder<'a> {
     data: &'a [u8],
     crc_check: Option<CrcCheck>,
     network_parse: Option<NetworkParse>,
+    decode_options: DecodeOptions,
+    raw_strings: bool,
 }
 
 impl<'a> ParserBuilder<'a> {
@@ -116,6 +134,8 @@ impl<'a> ParserBuilder<'a> {
             data,
             crc_check: None,
             network_parse: None,
+            decode_options: DecodeOptions::default(),
+            raw_strings: false,
         }
     }
 
@@ -159,18 +179,743 @@ impl<'a> ParserBuilder<'a> {
         self
     }
 
+    /// Downsamples the network frames to the given rate in hertz (eg: 10 frames a second
+    /// instead of the usual 30). Every frame is still decoded, and the frames between samples
+    /// are merged into the next sample, so that applying the sampled frames reconstructs the
+    /// same actor state as applying all of them. Each sampled frame's delta is the time since the
+    /// previous sample, and the last frame is always kept. Only the latest value of an attribute
+    /// is kept, so attributes that signal an event (eg: a demolition) are lost when they are
+    /// replaced before the next sample. A rate of zero keeps every frame.
+    ///
+    /// Downsampling applies to `parse`, `parse_arena`, and `serialize_frames_to`, but not to
+    /// `parse_spans` as spans locate the original frames in the network data.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .must_parse_network_data()
+    ///     .sample_rate(10)
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// // The replay lasts about seven minutes
+    /// assert!(replay.network_frames.unwrap().frames.len() <= 4200);
+    /// ```
+    pub fn sample_rate(mut self, hz: u32) -> ParserBuilder<'a> {
+        self.decode_options.sample_rate = Some(hz);
+        self
+    }
+
+    /// Only keeps the attribute updates that change an actor's value. Replays replicate many
+    /// attributes that haven't changed (eg: when an actor becomes relevant again), so dropping
+    /// them greatly reduces the size of the frames of long replays. Attributes that signal an
+    /// event, like a demolition or stat event, are always kept. The frames reconstruct the same
+    /// actor state as the full frames.
+    ///
+    /// Like downsampling, this applies to `parse`, `parse_arena`, and `serialize_frames_to`,
+    /// but not to `parse_spans`. Combined with a sample rate, the attributes are compared
+    /// between samples.
+    pub fn only_changed_attributes(mut self) -> ParserBuilder<'a> {
+        self.decode_options.only_changed_attributes = true;
+        self
+    }
+
+    /// Invokes the callback after every `every` frames are decoded and once more when decoding
+    /// finishes, so that a progress bar can be shown while a large replay's network data is
+    /// decoded. The callback is invoked on the thread that is parsing.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .must_parse_network_data()
+    ///     .on_progress(500, |progress| println!("{:.0}%", progress.percent()))
+    ///     .parse()
+    ///     .unwrap();
+    /// ```
+    pub fn on_progress<F>(mut self, every: usize, callback: F) -> ParserBuilder<'a>
+    where
+        F: Fn(Progress) + Send + Sync + 'static,
+    {
+        self.decode_options.progress = Some(ProgressHook {
+            every: every.max(1),
+            callback: Arc::new(callback),
+        });
+        self
+    }
+
+    /// Checks the flag before each frame is decoded and stops parsing with an error once the
+    /// flag is set, so that a server can abandon a parse that is no longer needed. The error
+    /// is returned even when network data errors are ignored.
+    ///
+    /// ```
+    /// use std::sync::atomic::AtomicBool;
+    /// use std::sync::Arc;
+    ///
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let cancel = Arc::new(AtomicBool::new(true));
+    /// let res = boxcars::ParserBuilder::new(&data[..])
+    ///     .cancel_on(Arc::clone(&cancel))
+    ///     .parse();
+    /// assert!(res.is_err());
+    /// ```
+    pub fn cancel_on(mut self, cancel: Arc<AtomicBool>) -> ParserBuilder<'a> {
+        self.decode_options.cancel = Some(Cancellation(cancel));
+        self
+    }
+
+    /// Fails decoding the network data with a `LimitExceeded` error when the header lists more
+    /// than the given number of frames. Without a limit, the number of frames is only bounded by
+    /// the size of the network data. Like other network data errors, the error is ignored unless
+    /// the network data must be parsed.
+    pub fn max_frames(mut self, frames: usize) -> ParserBuilder<'a> {
+        self.decode_options.limits.max_frames = Some(frames);
+        self
+    }
+
+    /// Fails decoding the network data with a `LimitExceeded` error when more than the given
+    /// number of actors are alive at the same time. Without a limit, the number of actors is
+    /// only bounded by the number of channels in the replay.
+    pub fn max_actors(mut self, actors: usize) -> ParserBuilder<'a> {
+        self.decode_options.limits.max_actors = Some(actors);
+        self
+    }
+
+    /// Fails parsing with a `LimitExceeded` error when a string in the header or body has more
+    /// than the given number of characters. Defaults to 10,000.
+    pub fn max_string_len(mut self, characters: usize) -> ParserBuilder<'a> {
+        self.decode_options.limits.max_string_len = characters;
+        self
+    }
+
+    /// Fails parsing with a `LimitExceeded` error when a list in the header or body has more than
+    /// the given number of elements. Defaults to 25,000.
+    ///
+    /// ```
+    /// use boxcars::{LimitExceeded, LimitKind};
+    ///
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let err = boxcars::ParserBuilder::new(&data[..])
+    ///     .max_list_len(10)
+    ///     .parse()
+    ///     .unwrap_err();
+    ///
+    /// let limit = err
+    ///     .iter_chain()
+    ///     .find_map(|x| x.downcast_ref::<LimitExceeded>())
+    ///     .unwrap();
+    /// assert_eq!(limit.kind, LimitKind::ListLength);
+    /// assert_eq!(limit.limit, 10);
+    /// ```
+    pub fn max_list_len(mut self, elements: usize) -> ParserBuilder<'a> {
+        self.decode_options.limits.max_list_len = elements;
+        self
+    }
+
+    /// Replaces all of the limits at once. See `Limits`.
+    pub fn with_limits(mut self, limits: Limits) -> ParserBuilder<'a> {
+        self.decode_options.limits = limits;
+        self
+    }
+
+    /// Fails decoding the network data when a new actor spawns as an object that boxcars doesn't
+    /// know, when fewer frames are decoded than the header lists, or when there is data left
+    /// over after the frames. These usually mean that a patch of Rocket League changed the
+    /// network data in a way that boxcars decodes incorrectly instead of failing, so strict mode
+    /// is meant for testing boxcars against replays from new patches. Attributes that boxcars
+    /// can't decode always fail decoding. Unknown objects can be allowed with `unknown_objects`.
+    ///
+    /// Unless configured otherwise, strict mode implies that the network data must be parsed so
+    /// that the errors are not ignored.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .strict()
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(replay.network_frames.is_some());
+    /// ```
+    pub fn strict(mut self) -> ParserBuilder<'a> {
+        self.decode_options.strict = true;
+        self
+    }
+
+    /// Instead of failing to decode the network data when an actor can't be decoded, skips to
+    /// the next frame and invokes the callback with what was skipped. The network data doesn't
+    /// record the size of an actor's data, so the actors decoded before the undecodable actor
+    /// are kept and the rest of the frame is lost. Later updates to actors spawned in the lost
+    /// data can't be decoded either and are skipped in turn. Decoding still fails when the next
+    /// frame can't be found, which happens when the undecodable actor precedes a pause in the
+    /// replay (eg: after a goal).
+    ///
+    /// Useful for those who prefer partial data to none, as frames are kept that would
+    /// otherwise be lost by a single attribute that changed in a new Rocket League patch.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let skipped = Arc::new(Mutex::new(Vec::new()));
+    /// let diagnostics = Arc::clone(&skipped);
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .must_parse_network_data()
+    ///     .skip_undecodable_actors(move |x| diagnostics.lock().unwrap().push(x))
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(skipped.lock().unwrap().is_empty());
+    /// ```
+    pub fn skip_undecodable_actors<F>(mut self, callback: F) -> ParserBuilder<'a>
+    where
+        F: Fn(SkippedActors) + Send + Sync + 'static,
+    {
+        self.decode_options.skip = Some(SkipHook(Arc::new(callback)));
+        self
+    }
+
+    /// Determines what happens when a new actor spawns as an object that boxcars doesn't know.
+    /// Defaults to failing in strict mode and keeping the actor as a static actor otherwise.
+    pub fn unknown_objects(mut self, unknown: UnknownObjects) -> ParserBuilder<'a> {
+        self.decode_options.unknown_objects = Some(unknown);
+        self
+    }
+
+    /// Invokes the callback each time a new actor spawns as an object that boxcars doesn't
+    /// know, so that unknown objects can be recorded for any of the `UnknownObjects` fallbacks.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let unknown = Arc::new(Mutex::new(Vec::new()));
+    /// let recorded = Arc::clone(&unknown);
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .must_parse_network_data()
+    ///     .unknown_objects(boxcars::UnknownObjects::Skip)
+    ///     .on_unknown_object(move |x| recorded.lock().unwrap().push(x.object))
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(unknown.lock().unwrap().is_empty());
+    /// ```
+    pub fn on_unknown_object<F>(mut self, callback: F) -> ParserBuilder<'a>
+    where
+        F: Fn(UnknownActor) + Send + Sync + 'static,
+    {
+        self.decode_options.on_unknown = Some(UnknownHook(Arc::new(callback)));
+        self
+    }
+
+    /// Determines what happens to a replay from a newer version of Rocket League than boxcars
+    /// knows (see `ReplayVersion::LATEST_KNOWN`). Defaults to decoding the network data as is.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .must_parse_network_data()
+    ///     .unknown_versions(boxcars::UnknownVersions::Fail)
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(replay.version().is_known());
+    /// ```
+    pub fn unknown_versions(mut self, unknown: UnknownVersions) -> ParserBuilder<'a> {
+        self.decode_options.unknown_versions = Some(unknown);
+        self
+    }
+
+    /// Invokes the callback with the version of a replay that is newer than boxcars knows, for
+    /// logging a warning alongside any of the `UnknownVersions` behaviors. The callback is
+    /// invoked before the network data would be decoded, even when it isn't parsed.
+    pub fn on_unknown_version<F>(mut self, callback: F) -> ParserBuilder<'a>
+    where
+        F: Fn(ReplayVersion) + Send + Sync + 'static,
+    {
+        self.decode_options.on_unknown_version = Some(VersionHook(Arc::new(callback)));
+        self
+    }
+
+    /// Patches up net caches that don't resolve as is, so that replays whose class hierarchy is
+    /// broken decode instead of failing on missing attributes. A parent id refers to the closest
+    /// preceding class with that cache id, parents that are listed after their children are
+    /// inherited all the same, and objects whose class isn't in the replay are left without
+    /// attributes. Replays with net caches that resolve as is decode the same either way.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .must_parse_network_data()
+    ///     .recover_net_cache()
+    ///     .parse()
+    ///     .unwrap();
+    /// assert!(replay.network_frames.is_some());
+    /// ```
+    pub fn recover_net_cache(mut self) -> ParserBuilder<'a> {
+        self.decode_options.recover_net_cache = true;
+        self
+    }
+
+    /// Merges the overrides over the tables that boxcars decodes the network data with, for this
+    /// parse only. See `ClassOverrides`.
+    pub fn with_overrides(mut self, overrides: ClassOverrides) -> ParserBuilder<'a> {
+        self.decode_options.overrides = Some(overrides);
+        self
+    }
+
+    /// Decodes the network data with the allocations that the pool kept from previous parses,
+    /// and leaves this parse's allocations in the pool once done. See `ParserPool`.
+    pub fn with_pool(mut self, pool: &ParserPool) -> ParserBuilder<'a> {
+        self.decode_options.pool = Some(pool.clone());
+        self
+    }
+
+    /// Retains the original bytes of every string in the header and body in the replay's
+    /// `raw_strings`, alongside the decoded text. Decoding is lossy for some byte sequences, so
+    /// the bytes are needed to reproduce a replay byte for byte. Strings within the network data
+    /// aren't retained.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .never_parse_network_data()
+    ///     .retain_raw_strings()
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let game_type = &replay.raw_strings[0];
+    /// assert_eq!(game_type.text(), replay.game_type);
+    /// assert_eq!(game_type.bytes, b"TAGame.Replay_Soccar_TA\0");
+    /// assert!(replay.raw_strings.iter().all(|x| x.is_lossless()));
+    /// ```
+    pub fn retain_raw_strings(mut self) -> ParserBuilder<'a> {
+        self.raw_strings = true;
+        self
+    }
+
     pub fn parse(self) -> Result<Replay<'a>, Error> {
+        self.parser().parse()
+    }
+
+    /// Parses a replay that may be truncated (eg: partially downloaded) or have a corrupt footer
+    /// instead of failing on the first section that can't be parsed. The header must be whole,
+    /// but the body and footer are parsed one section at a time until a section fails to parse,
+    /// and the returned `PartialReplay` marks the section that failed and lists the sections
+    /// that were unavailable. The network frames are decoded only when the whole replay is
+    /// available.
+    ///
+    /// ```
+    /// use boxcars::BodySection;
+    ///
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let partial = boxcars::ParserBuilder::new(&data[..data.len() / 2])
+    ///     .parse_partial()
+    ///     .unwrap();
+    /// assert!(partial.has(BodySection::KeyFrames));
+    /// assert!(!partial.has(BodySection::NetCache));
+    /// assert!(partial.replay.get_property("MapName").is_some());
+    /// ```
+    pub fn parse_partial(self) -> Result<PartialReplay<'a>, Error> {
+        self.parser().parse_partial()
+    }
+
+    fn parser(&self) -> Parser<'a> {
+        let network_parse = if self.decode_options.strict {
+            NetworkParse::Always
+        } else {
+            NetworkParse::IgnoreOnError
+        };
+
         let mut parser = Parser::new(
             self.data,
             self.crc_check.unwrap_or(CrcCheck::OnError),
-            self.network_parse.unwrap_or(NetworkParse::IgnoreOnError),
+            self.network_parse.unwrap_or(network_parse),
         );
-        parser.parse()
+        parser.core.limits = self.decode_options.limits;
+        if self.raw_strings {
+            parser.core.raw_strings = Some(Vec::new());
+        }
+        parser.decode_options = self.decode_options.clone();
+        parser
+    }
+
+    /// Parses the replay and additionally records the location of each decoded frame, new
+    /// actor, and attribute in the network data. The returned spans are parallel to the frames
+    /// in the replay's `network_frames`. Useful for debugging and hex editor tooling, but
+    /// recording the spans slows down decoding.
+    pub fn parse_spans(self) -> Result<(Replay<'a>, Option<FrameSpans>), Error> {
+        let mut parser = self.parser();
+        let options = self.decode_options;
+        let (mut replay, network) =
+            parser.parse_with(|header, body| network::parse_spans(header, body, &options))?;
+        Ok(match network {
+            Some((frames, spans)) => {
+                replay.network_frames = Some(frames);
+                (replay, Some(spans))
+            }
+            None => (replay, None),
+        })
+    }
+
+    /// Parses the replay and measures the time spent on each section of the replay, as well as
+    /// counting what was decoded from the network data. The counts are of the decoded network
+    /// data, so downsampling or only keeping changed attributes doesn't affect them. The counts
+    /// are zero when the network data isn't decoded.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let (replay, stats) = boxcars::ParserBuilder::new(&data[..])
+    ///     .must_parse_network_data()
+    ///     .parse_with_stats()
+    ///     .unwrap();
+    /// assert_eq!(stats.frames, replay.network_frames.unwrap().frames.len());
+    /// assert_eq!(stats.bytes, data.len());
+    /// ```
+    pub fn parse_with_stats(self) -> Result<(Replay<'a>, ParseStats), Error> {
+        let mut parser = self.parser();
+        parser.stats = Some(ParseStats {
+            bytes: self.data.len(),
+            ..ParseStats::default()
+        });
+
+        let options = self.decode_options;
+        let (mut replay, network) =
+            parser.parse_with(|header, body| network::parse_stats(header, body, &options))?;
+        let mut stats = parser.stats.unwrap_or_default();
+        if let Some((frames, decoded)) = network {
+            replay.network_frames = Some(frames);
+            stats.frames = decoded.frames;
+            stats.actors_created = decoded.actors_created;
+            stats.attributes_decoded = decoded.attributes_decoded;
+            stats.bits_unread = decoded.bits_unread;
+        }
+
+        Ok((replay, stats))
+    }
+
+    /// Parses the replay and records where each section of the replay begins and ends, for
+    /// forensic tooling and for patching a section of a replay in place (see `ReplaySections`)
+    pub fn parse_sections(self) -> Result<(Replay<'a>, ReplaySections), Error> {
+        let mut parser = self.parser();
+        parser.sections = Some(ReplaySections::default());
+        let replay = parser.parse()?;
+        Ok((replay, parser.sections.unwrap_or_default()))
+    }
+
+    /// Parses the replay but instead of collecting the decoded frames into the replay's
+    /// `network_frames` (which will be left empty), each frame is serialized as soon as it is
+    /// decoded. The frames are serialized as a sequence with the same shape as
+    /// `NetworkFrames::frames`, so exporting a large replay doesn't require holding all of its
+    /// frames in memory.
+    ///
+    /// The serializer's output is returned when the network data was decoded. If decoding
+    /// fails part way through, the serializer will have written only some of the frames.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let mut out = Vec::new();
+    /// let (replay, _) = boxcars::ParserBuilder::new(&data[..])
+    ///     .must_parse_network_data()
+    ///     .serialize_frames_to(&mut serde_json::Serializer::new(&mut out))
+    ///     .unwrap();
+    /// assert!(replay.network_frames.is_none());
+    /// assert!(out.starts_with(b"[{"));
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn serialize_frames_to<S>(self, serializer: S) -> Result<(Replay<'a>, Option<S::Ok>), Error>
+    where
+        S: serde::Serializer,
+        S::Error: Send + Sync + 'static,
+    {
+        let mut parser = self.parser();
+        let options = self.decode_options;
+        let (replay, network) = parser.parse_with(|header, body| {
+            network::serialize_frames(header, body, &options, serializer)
+        })?;
+        Ok((replay, network.transpose()?))
+    }
+
+    /// Parses the replay but instead of collecting the decoded frames into the replay's
+    /// `network_frames` (which will be left empty), each frame is given to the visitor as soon
+    /// as it is decoded. The frames are decoded into buffers that are reused between frames, so
+    /// a visitor that aggregates the frames requires almost no allocations. Returns whether the
+    /// network data was decoded, as the visitor may have seen some of the frames before decoding
+    /// failed when network data errors are ignored.
+    pub fn visit_frames<V>(self, visitor: &mut V) -> Result<(Replay<'a>, bool), Error>
+    where
+        V: FrameVisitor + ?Sized,
+    {
+        let mut parser = self.parser();
+        let options = self.decode_options;
+        let (replay, network) =
+            parser.parse_with(|header, body| network::visit(header, body, &options, visitor))?;
+        Ok((replay, network.is_some()))
+    }
+
+    /// Returns a pull parser that yields the replay as a sequence of events instead of parsing
+    /// the replay all at once. See `ReplayEvents` for the order of the events.
+    pub fn events(self) -> ReplayEvents<'a> {
+        ReplayEvents::new(self.parser(), self.decode_options)
+    }
+
+    /// Parses the replay but decodes the network data into a `FrameArena` instead of the
+    /// replay's `network_frames`, which will be left empty. The arena stores all frames
+    /// contiguously and should be preferred when decoding a large number of replays as it
+    /// dramatically reduces the number of allocations.
+    pub fn parse_arena(self) -> Result<(Replay<'a>, Option<FrameArena>), Error> {
+        let mut parser = self.parser();
+        let options = self.decode_options;
+        parser.parse_with(|header, body| network::parse_arena(header, body, &options))
+    }
+
+    /// Parses a replay that may be preceded by junk, such as metadata that a tool prepended or
+    /// the rest of a container that the replay is embedded in. The first `within` bytes are
+    /// scanned for a header whose size fits the data, that starts with the versions and game
+    /// type of a replay, and whose crc validates, and the replay is parsed from there. Offsets
+    /// within the replay (eg: `ReplayBody::network_offset`) are relative to the discovered
+    /// offset.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let mut junk = b"some metadata".to_vec();
+    /// junk.extend_from_slice(&data[..]);
+    ///
+    /// let salvaged = boxcars::ParserBuilder::new(&junk)
+    ///     .parse_salvaged(16 * 1024)
+    ///     .unwrap();
+    /// assert_eq!(salvaged.offset, 13);
+    /// assert_eq!(salvaged.replay.game_type, "TAGame.Replay_Soccar_TA");
+    /// ```
+    pub fn parse_salvaged(self, within: usize) -> Result<SalvagedReplay<'a>, Error> {
+        let offset = find_header(self.data, within).ok_or(ParseError::HeaderNotFound(within))?;
+        let replay = ParserBuilder {
+            data: &self.data[offset..],
+            ..self
+        }
+        .parse()?;
+        Ok(SalvagedReplay { replay, offset })
+    }
+
+    /// Parses data that holds multiple replays back to back, as some archiving tools produce,
+    /// with the options of the builder. Each replay is yielded with its byte offset in the data.
+    /// A replay that fails to parse is yielded as an error and the next replay is parsed, as
+    /// long as the replay's header and content sizes fit the data. Otherwise the end of the
+    /// replay isn't known, so the error is the last item.
+    ///
+    /// ```
+    /// let rumble = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let small = include_bytes!("../assets/replays/good/small-frames.replay");
+    /// let data = [&rumble[..], &small[..]].concat();
+    ///
+    /// let replays: Vec<_> = boxcars::ParserBuilder::new(&data)
+    ///     .never_parse_network_data()
+    ///     .parse_concatenated()
+    ///     .collect();
+    /// assert_eq!(replays.len(), 2);
+    /// assert_eq!(replays[1].0, rumble.len());
+    /// assert!(replays.iter().all(|(_, x)| x.is_ok()));
+    /// ```
+    pub fn parse_concatenated(self) -> ConcatenatedReplays<'a> {
+        ConcatenatedReplays {
+            builder: self,
+            offset: 0,
+        }
+    }
+}
+
+/// The replays of data that holds multiple replays back to back (see
+/// `ParserBuilder::parse_concatenated`)
+#[derive(Debug, Clone)]
+pub struct ConcatenatedReplays<'a> {
+    builder: ParserBuilder<'a>,
+    offset: usize,
+}
+
+impl<'a> Iterator for ConcatenatedReplays<'a> {
+    type Item = (usize, Result<Replay<'a>, Error>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = self
+            .builder
+            .data
+            .get(self.offset..)
+            .filter(|x| !x.is_empty())?;
+        let offset = self.offset;
+        let (data, next) = match replay_len(data) {
+            Some(len) => (&data[..len], offset + len),
+            None => (data, self.builder.data.len()),
+        };
+
+        self.offset = next;
+        let replay = ParserBuilder {
+            data,
+            ..self.builder.clone()
+        }
+        .parse();
+        Some((offset, replay))
+    }
+}
+
+/// The length of the replay at the start of the data from the sizes of its header and content,
+/// if they fit the data
+fn replay_len(data: &[u8]) -> Option<usize> {
+    let read_u32 = |at: usize| {
+        data.get(at..at.checked_add(4)?)
+            .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]) as usize)
+    };
+
+    let content = read_u32(0)?.checked_add(8)?;
+    let len = read_u32(content)?.checked_add(content.checked_add(8)?)?;
+    Some(len).filter(|x| *x <= data.len())
+}
+
+/// The offset of the first header within the first `within` bytes of the data whose size fits
+/// the data and whose crc validates
+fn find_header(data: &[u8], within: usize) -> Option<usize> {
+    let read_u32 = |at: usize| {
+        data.get(at..at + 4)
+            .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+    };
+
+    (0..=within.min(data.len())).find(|&offset| {
+        let (size, crc) = match (read_u32(offset), read_u32(offset + 4)) {
+            (Some(size), Some(crc)) => (size as usize, crc),
+            _ => return false,
+        };
+
+        // A real header has at least room for the versions. The crc is only computed for
+        // headers that start like a replay, as nearly every offset will have a size that doesn't
+        // fit or versions that aren't plausible.
+        let start = offset + 8;
+        if size < 8 || size > data.len() - start {
+            return false;
+        }
+
+        let header = &data[start..start + size];
+        is_plausible_header(header) && calc_crc(header) == crc
+    })
+}
+
+/// Whether the header data starts with the versions of a replay followed by the game type (eg:
+/// `TAGame.Replay_Soccar_TA`)
+fn is_plausible_header(header: &[u8]) -> bool {
+    let read_i32 = |at: usize| header.get(at..at + 4).map(le_i32);
+    let (major, minor) = match (read_i32(0), read_i32(4)) {
+        (Some(major), Some(minor)) => (major, minor),
+        _ => return false,
+    };
+
+    // Some replays record versions of zero
+    if !(0..1000).contains(&major) || !(0..1000).contains(&minor) {
+        return false;
+    }
+
+    // The net version only follows the versions of newer replays (see `header::parse_header`)
+    let game_type = if major > 865 && minor > 17 { 12 } else { 8 };
+
+    // Skip the length prefix of the game type
+    header
+        .get(game_type + 4..)
+        .is_some_and(|x| x.starts_with(ROCKET_LEAGUE_GAME_TYPE.as_bytes()))
+}
+
+/// A replay found after the junk that preceded it, as returned by
+/// `ParserBuilder::parse_salvaged`
+#[derive(Debug)]
+pub struct SalvagedReplay<'a> {
+    pub replay: Replay<'a>,
+
+    /// The byte offset of the replay's header in the data
+    pub offset: usize,
+}
+
+fn is_cancelled(e: &Error) -> bool {
+    matches!(e.downcast_ref(), Some(NetworkError::Cancelled))
+}
+
+/// Sections at least this large have their crc computed on a second thread when the crc is
+/// always checked. Smaller sections (eg: the header) are checked faster than a thread spawns.
+const THREADED_CRC_BYTES: usize = 64 * 1024;
+
+fn multi_core() -> bool {
+    static MULTI_CORE: OnceLock<bool> = OnceLock::new();
+    *MULTI_CORE.get_or_init(|| thread::available_parallelism().is_ok_and(|x| x.get() > 1))
+}
+
+/// Computes the crc of the data and the microseconds it took when timed
+fn timed_crc(data: &[u8], timed: bool) -> (u32, u64) {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("crc", bytes = data.len()).entered();
+
+    let mut watch = Stopwatch::new(timed);
+    let crc = calc_crc(data);
+    (crc, watch.lap())
+}
+
+/// A section of the body or footer of a replay, in the order of the replay data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum BodySection {
+    Levels,
+    KeyFrames,
+    NetworkData,
+    DebugInfo,
+    TickMarks,
+    Packages,
+    Objects,
+    Names,
+    ClassIndices,
+    NetCache,
+}
+
+impl BodySection {
+    /// Every section, in the order of the replay data
+    pub const ALL: [BodySection; 10] = [
+        BodySection::Levels,
+        BodySection::KeyFrames,
+        BodySection::NetworkData,
+        BodySection::DebugInfo,
+        BodySection::TickMarks,
+        BodySection::Packages,
+        BodySection::Objects,
+        BodySection::Names,
+        BodySection::ClassIndices,
+        BodySection::NetCache,
+    ];
+}
+
+/// A replay parsed from data that may be truncated or corrupt, as returned by
+/// `ParserBuilder::parse_partial`. The sections that couldn't be parsed are left empty in the
+/// replay.
+#[derive(Debug)]
+pub struct PartialReplay<'a> {
+    pub replay: Replay<'a>,
+
+    /// The sections that couldn't be parsed, in the order of the replay data. Once a section
+    /// fails to parse, the sections that follow it are missing too. The network frames can only
+    /// be decoded when no section is missing.
+    pub missing: Vec<BodySection>,
+
+    /// The section that failed to parse and why, which is the first of the missing sections.
+    /// The error can differ from `error` when the body failed for another reason (eg: the crc
+    /// check).
+    pub failed: Option<(BodySection, Error)>,
+
+    /// Why the body couldn't be parsed as a whole, which is set even when every section could
+    /// be parsed on its own (eg: the crc check failed)
+    pub error: Option<Error>,
+}
+
+impl<'a> PartialReplay<'a> {
+    /// Returns true if the section was parsed
+    pub fn has(&self, section: BodySection) -> bool {
+        !self.missing.contains(&section)
+    }
+
+    /// Returns true if the whole replay was parsed
+    pub fn is_complete(&self) -> bool {
+        self.error.is_none()
     }
 }
 
 /// Intermediate parsing structure for the body / footer
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Default)]
 pub struct ReplayBody<'a> {
     pub levels: Vec<Cow<'a, str>>,
     pub keyframes: Vec<KeyFrame>,
@@ -182,6 +927,9 @@ pub struct ReplayBody<'a> {
     pub class_indices: Vec<ClassIndex<'a>>,
     pub net_cache: Vec<ClassNetCache>,
     pub network_data: &'a [u8],
+
+    /// The byte offset of the network data from the start of the replay
+    pub network_offset: usize,
 }
 
 /// Holds the current state of parsing a replay
@@ -190,6 +938,13 @@ pub struct Parser<'a> {
     core: CoreParser<'a>,
     crc_check: CrcCheck,
     network_parse: NetworkParse,
+    decode_options: DecodeOptions,
+
+    /// Collects the stats of parsing when set
+    stats: Option<ParseStats>,
+
+    /// Records where each section of the replay is when set
+    sections: Option<ReplaySections>,
 }
 
 impl<'a> Parser<'a> {
@@ -198,6 +953,9 @@ impl<'a> Parser<'a> {
             core: CoreParser::new(data),
             crc_check,
             network_parse,
+            decode_options: DecodeOptions::default(),
+            stats: None,
+            sections: None,
         }
     }
 
@@ -211,6 +969,140 @@ impl<'a> Parser<'a> {
     }
 
     fn parse(&mut self) -> Result<Replay<'a>, Error> {
+        let options = self.decode_options.clone();
+        let (mut replay, network) =
+            self.parse_with(|header, body| network::parse(header, body, &options))?;
+        replay.network_frames = network;
+        Ok(replay)
+    }
+
+    /// Parses the replay sections and decodes the network data with the given function, which
+    /// will be called according to the network parse setting. The returned replay won't have its
+    /// `network_frames` set, as the decoded network data is returned alongside.
+    fn parse_with<T, F>(&mut self, network: F) -> Result<(Replay<'a>, Option<T>), Error>
+    where
+        F: FnOnce(&Header<'_>, &ReplayBody<'_>) -> Result<T, Error>,
+    {
+        let mut watch = Stopwatch::new(self.stats.is_some());
+        let (header_size, header_crc, header) = self.header_section()?;
+        let header_us = watch.lap();
+
+        let (content_size, content_crc, body) = self.body_section()?;
+        let body_us = watch.lap();
+
+        let network = self.network(&header, &body, network)?;
+
+        if let Some(stats) = self.stats.as_mut() {
+            stats.header_us = header_us;
+            stats.body_us = body_us;
+            stats.network_us = watch.lap();
+            stats.bits_unread = body.network_data.len() * 8;
+        }
+
+        let replay = self.replay(
+            header,
+            (header_size, header_crc),
+            (content_size, content_crc),
+            body,
+        );
+        Ok((replay, network))
+    }
+
+    /// Parses the replay from data that may be truncated. The header must be whole, while the
+    /// body is parsed one section at a time until a section fails to parse.
+    fn parse_partial(&mut self) -> Result<PartialReplay<'a>, Error> {
+        let (header_size, header_crc, header) = self.header_section()?;
+        let body_start = self.core.clone();
+        let error = match self.body_section() {
+            Ok((content_size, content_crc, body)) => {
+                let options = self.decode_options.clone();
+                let network =
+                    self.network(&header, &body, |h, b| network::parse(h, b, &options))?;
+                let header_section = (header_size, header_crc);
+                let content_section = (content_size, content_crc);
+                let mut replay = self.replay(header, header_section, content_section, body);
+                replay.network_frames = network;
+                return Ok(PartialReplay {
+                    replay,
+                    missing: Vec::new(),
+                    failed: None,
+                    error: None,
+                });
+            }
+            Err(e) => e,
+        };
+
+        // The body's crc is of data that is likely missing, so the sections are parsed without
+        // the crc check
+        self.core = body_start;
+        let mut body = ReplayBody::default();
+        let mut section = BodySection::Levels;
+        let mut content_section = (0, 0);
+        let res = self.content_header().and_then(|x| {
+            content_section = x;
+            self.parse_body_sections(&mut body, &mut section)
+        });
+
+        let (missing, failed) = match res {
+            Ok(()) => (Vec::new(), None),
+            Err(e) => {
+                let missing = BodySection::ALL
+                    .iter()
+                    .skip_while(|x| **x != section)
+                    .cloned()
+                    .collect();
+                (missing, Some((section, e)))
+            }
+        };
+
+        let replay = self.replay(header, (header_size, header_crc), content_section, body);
+        Ok(PartialReplay {
+            replay,
+            missing,
+            failed,
+            error: Some(error),
+        })
+    }
+
+    /// Assembles the replay out of its sections and the raw strings collected, without the
+    /// network frames
+    fn replay(
+        &mut self,
+        header: Header<'a>,
+        (header_size, header_crc): (i32, u32),
+        (content_size, content_crc): (i32, u32),
+        body: ReplayBody<'a>,
+    ) -> Replay<'a> {
+        Replay {
+            header_size,
+            header_crc,
+            major_version: header.major_version,
+            minor_version: header.minor_version,
+            net_version: header.net_version,
+            game_type: header.game_type,
+            properties: header.properties,
+            content_size,
+            content_crc,
+            network_frames: None,
+            network_data: body.network_data,
+            levels: body.levels,
+            keyframes: body.keyframes,
+            debug_info: body.debug_info,
+            tick_marks: body.tick_marks,
+            packages: body.packages,
+            objects: body.objects,
+            names: body.names,
+            class_indices: body.class_indices,
+            net_cache: body.net_cache,
+            raw_strings: self.core.raw_strings.take().unwrap_or_default(),
+        }
+    }
+
+    /// Parses the header's size, crc, and data
+    pub(crate) fn header_section(&mut self) -> Result<(i32, u32, Header<'a>), Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("header").entered();
+
         let header_size = self
             .core
             .take(4, le_i32)
@@ -227,9 +1119,41 @@ impl<'a> Parser<'a> {
             .view_data(header_size as usize)
             .with_context(|e| self.err_str("header data", e))?;
 
-        let header =
-            self.crc_section(header_data, header_crc as u32, "header", Self::parse_header)?;
+        if let Some(sections) = self.sections.as_mut() {
+            let start = self.core.bytes_read() as usize;
+            sections.header = start..start + header_data.len();
+        }
+
+        let header = self.crc_section(header_data, header_crc, "header", Self::parse_header)?;
+        Ok((header_size, header_crc, header))
+    }
+
+    /// Parses the content's size, crc, and the body and footer
+    pub(crate) fn body_section(&mut self) -> Result<(i32, u32, ReplayBody<'a>), Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("body").entered();
 
+        let (content_size, content_crc) = self.content_header()?;
+        let content_data = self
+            .core
+            .view_data(content_size as usize)
+            .with_context(|e| self.err_str("content data", e))?;
+
+        if let Some(sections) = self.sections.as_mut() {
+            let start = self.core.bytes_read() as usize;
+            sections.content = start..start + content_data.len();
+        }
+
+        let body = self.crc_section(content_data, content_crc, "body", Self::parse_body)?;
+        if let Some(sections) = self.sections.as_mut() {
+            sections.trailing = self.core.bytes_read() as usize..sections.content.end;
+        }
+
+        Ok((content_size, content_crc, body))
+    }
+
+    /// Parses the content's size and crc
+    fn content_header(&mut self) -> Result<(i32, u32), Error> {
         let content_size = self
             .core
             .take(4, le_i32)
@@ -241,59 +1165,74 @@ impl<'a> Parser<'a> {
             .map(|x| x as u32)
             .with_context(|e| self.err_str("content crc", e))?;
 
-        let content_data = self
-            .core
-            .view_data(content_size as usize)
-            .with_context(|e| self.err_str("content data", e))?;
+        Ok((content_size, content_crc))
+    }
 
-        let body = self.crc_section(content_data, content_crc as u32, "body", Self::parse_body)?;
+    /// Decodes the network data with the given function according to the network parse setting
+    pub(crate) fn network<T, F>(
+        &self,
+        header: &Header<'_>,
+        body: &ReplayBody<'_>,
+        network: F,
+    ) -> Result<Option<T>, Error>
+    where
+        F: FnOnce(&Header<'_>, &ReplayBody<'_>) -> Result<T, Error>,
+    {
+        // Other games have their own network classes, so their frames would decode as garbage
+        if !header.is_rocket_league() {
+            return match self.network_parse {
+                NetworkParse::Always => {
+                    let game_type = header.game_type.to_string();
+                    Err(NetworkError::UnsupportedGame(game_type).into())
+                }
+                NetworkParse::IgnoreOnError | NetworkParse::Never => Ok(None),
+            };
+        }
 
-        let mut network: Option<NetworkFrames> = None;
-        match self.network_parse {
-            NetworkParse::Always => {
-                network = Some(self.parse_network(&header, &body)?);
+        let version = header.version();
+        if !version.is_known() {
+            if let Some(hook) = &self.decode_options.on_unknown_version {
+                (hook.0)(version);
             }
-            NetworkParse::IgnoreOnError => {
-                if let Ok(v) = self.parse_network(&header, &body) {
-                    network = Some(v);
+
+            match self.decode_options.unknown_versions {
+                Some(UnknownVersions::SkipNetwork) => return Ok(None),
+                Some(UnknownVersions::Fail) if self.network_parse != NetworkParse::Never => {
+                    let latest = ReplayVersion::LATEST_KNOWN;
+                    return Err(NetworkError::UnknownVersion(version, latest).into());
                 }
+                _ => {}
             }
-            NetworkParse::Never => network = None,
         }
 
-        Ok(Replay {
-            header_size,
-            header_crc,
-            major_version: header.major_version,
-            minor_version: header.minor_version,
-            net_version: header.net_version,
-            game_type: header.game_type,
-            properties: header.properties,
-            content_size,
-            content_crc,
-            network_frames: network,
-            levels: body.levels,
-            keyframes: body.keyframes,
-            debug_info: body.debug_info,
-            tick_marks: body.tick_marks,
-            packages: body.packages,
-            objects: body.objects,
-            names: body.names,
-            class_indices: body.class_indices,
-            net_cache: body.net_cache,
-        })
-    }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("network", bytes = body.network_data.len()).entered();
 
-    fn parse_network(
-        &mut self,
-        header: &Header<'_>,
-        body: &ReplayBody<'_>,
-    ) -> Result<NetworkFrames, Error> {
-        network::parse(header, body)
+        match self.network_parse {
+            NetworkParse::Always => Ok(Some(network(header, body)?)),
+            NetworkParse::IgnoreOnError => match network(header, body) {
+                Err(e) if is_cancelled(&e) => Err(e),
+                res => Ok(res.ok()),
+            },
+            NetworkParse::Never => Ok(None),
+        }
     }
 
     fn parse_header(&mut self) -> Result<Header<'a>, Error> {
-        header::parse_header(&mut self.core)
+        let (header, properties_offset) = header::parse_header(&mut self.core)?;
+        if let Some(sections) = self.sections.as_mut() {
+            sections.properties = properties_offset..self.core.bytes_read() as usize;
+        }
+        Ok(header)
+    }
+
+    /// Moves on to parsing the section of the body, recording where it starts when asked to
+    fn start_section(&mut self, section: &mut BodySection, next: BodySection) {
+        *section = next;
+        let offset = self.core.bytes_read() as usize;
+        if let Some(sections) = self.sections.as_mut() {
+            sections.start(next, offset);
+        }
     }
 
     /// Parses a section and performs a crc check as configured
@@ -307,94 +1246,166 @@ impl<'a> Parser<'a> {
     where
         F: FnMut(&mut Self) -> Result<T, Error>,
     {
-        match (self.crc_check, f(self)) {
-            (CrcCheck::Always, res) => {
-                let actual = calc_crc(data);
-                if actual != crc as u32 {
+        match self.crc_check {
+            CrcCheck::Always => {
+                let threaded = data.len() >= THREADED_CRC_BYTES && multi_core();
+                let (res, (actual, crc_us)) = self.parse_and_crc(data, threaded, f);
+                self.add_crc_time(crc_us);
+                if actual != crc {
                     Err(Error::from(ParseError::CrcMismatch(crc, actual)))
                 } else {
                     res
                 }
             }
-            (CrcCheck::OnError, Err(e)) => {
-                let actual = calc_crc(data);
-                if actual != crc as u32 {
-                    Err(e
-                        .context(format!(
-                            "Failed to parse {} and crc check failed. Replay is corrupt",
-                            section
-                        ))
-                        .into())
-                } else {
-                    Err(e)
+            CrcCheck::OnError => match f(self) {
+                Err(e) => {
+                    let (actual, crc_us) = timed_crc(data, self.stats.is_some());
+                    self.add_crc_time(crc_us);
+                    if actual != crc {
+                        Err(e
+                            .context(format!(
+                                "Failed to parse {} and crc check failed. Replay is corrupt",
+                                section
+                            ))
+                            .into())
+                    } else {
+                        Err(e)
+                    }
                 }
+                Ok(s) => Ok(s),
+            },
+            CrcCheck::Never => f(self),
+        }
+    }
+
+    fn add_crc_time(&mut self, crc_us: u64) {
+        if let Some(stats) = self.stats.as_mut() {
+            stats.crc_us += crc_us;
+        }
+    }
+
+    /// Parses a section and computes the crc of its data (and how long it took when collecting
+    /// stats). When threaded, the crc is computed on a second thread while the section is parsed,
+    /// so that checking the crc doesn't add to the time it takes to parse.
+    fn parse_and_crc<T, F>(
+        &mut self,
+        data: &[u8],
+        threaded: bool,
+        mut f: F,
+    ) -> (Result<T, Error>, (u32, u64))
+    where
+        F: FnMut(&mut Self) -> Result<T, Error>,
+    {
+        let timed = self.stats.is_some();
+        if threaded {
+            #[cfg(feature = "tracing")]
+            let span = tracing::Span::current();
+
+            let res = thread::scope(|s| {
+                // Fall back to computing the crc after parsing if a thread can't be spawned
+                let crc = thread::Builder::new()
+                    .spawn_scoped(s, || {
+                        // Attribute the crc to the section being parsed on this thread
+                        #[cfg(feature = "tracing")]
+                        let _entered = span.enter();
+                        timed_crc(data, timed)
+                    })
+                    .ok()?;
+                let res = f(self);
+                let actual = crc.join().unwrap_or_else(|e| panic::resume_unwind(e));
+                Some((res, actual))
+            });
+
+            if let Some(res) = res {
+                return res;
             }
-            (CrcCheck::OnError, Ok(s)) => Ok(s),
-            (CrcCheck::Never, res) => res,
         }
+
+        let res = f(self);
+        (res, timed_crc(data, timed))
     }
 
     fn parse_body(&mut self) -> Result<ReplayBody<'a>, Error> {
-        let levels = self
+        let mut body = ReplayBody::default();
+        self.parse_body_sections(&mut body, &mut BodySection::Levels)?;
+        Ok(body)
+    }
+
+    /// Parses the body and footer sections into the body one at a time, recording the section
+    /// being parsed so that a failure can be attributed to it
+    fn parse_body_sections(
+        &mut self,
+        body: &mut ReplayBody<'a>,
+        section: &mut BodySection,
+    ) -> Result<(), Error> {
+        self.start_section(section, BodySection::Levels);
+        body.levels = self
             .core
             .text_list()
             .with_context(|e| self.err_str("levels", e))?;
 
-        let keyframes = self
+        self.start_section(section, BodySection::KeyFrames);
+        body.keyframes = self
             .parse_keyframe()
             .with_context(|e| self.err_str("keyframes", e))?;
 
+        self.start_section(section, BodySection::NetworkData);
         let network_size = self
             .core
             .take(4, le_i32)
             .with_context(|e| self.err_str("network size", e))?;
 
-        let network_data = self
+        let network_offset = self.core.bytes_read() as usize;
+        body.network_data = self
             .core
             .take(network_size as usize, |d| d)
             .with_context(|e| self.err_str("network data", e))?;
+        body.network_offset = network_offset;
 
-        let debug_infos = self
+        self.start_section(section, BodySection::DebugInfo);
+        body.debug_info = self
             .parse_debuginfo()
             .with_context(|e| self.err_str("debug info", e))?;
 
-        let tickmarks = self
+        self.start_section(section, BodySection::TickMarks);
+        body.tick_marks = self
             .parse_tickmarks()
             .with_context(|e| self.err_str("tickmarks", e))?;
 
-        let packages = self
+        self.start_section(section, BodySection::Packages);
+        body.packages = self
             .core
             .text_list()
             .with_context(|e| self.err_str("packages", e))?;
-        let objects = self
+
+        self.start_section(section, BodySection::Objects);
+        body.objects = self
             .core
             .text_list()
             .with_context(|e| self.err_str("objects", e))?;
-        let names = self
+
+        self.start_section(section, BodySection::Names);
+        body.names = self
             .core
             .text_list()
             .with_context(|e| self.err_str("names", e))?;
 
-        let class_index = self
+        self.start_section(section, BodySection::ClassIndices);
+        body.class_indices = self
             .parse_classindex()
             .with_context(|e| self.err_str("class index", e))?;
 
-        let net_cache = self
+        self.start_section(section, BodySection::NetCache);
+        body.net_cache = self
             .parse_classcache()
             .with_context(|e| self.err_str("net cache", e))?;
 
-        Ok(ReplayBody {
-            levels,
-            keyframes,
-            debug_info: debug_infos,
-            tick_marks: tickmarks,
-            packages,
-            objects,
-            names,
-            class_indices: class_index,
-            net_cache,
-            network_data,
-        })
+        let offset = self.core.bytes_read() as usize;
+        if let Some(sections) = self.sections.as_mut() {
+            sections.end(offset);
+        }
+
+        Ok(())
     }
 
     fn parse_tickmarks(&mut self) -> Result<Vec<TickMark<'a>>, ParseError> {
@@ -567,6 +1578,81 @@ mod tests {
         assert!(err.as_fail().cause().is_none());
     }
 
+    #[test]
+    fn test_parse_salvaged() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let expected = ParserBuilder::new(&data[..]).parse().unwrap();
+
+        // Junk of small sizes is checked against the crc and rejected
+        let mut junk: Vec<u8> = [16, 0, 0, 0].iter().cycle().take(3000).cloned().collect();
+        junk.extend_from_slice(&data[..]);
+        let salvaged = ParserBuilder::new(&junk).parse_salvaged(4096).unwrap();
+        assert_eq!(salvaged.offset, 3000);
+        assert_eq!(salvaged.replay, expected);
+
+        let salvaged = ParserBuilder::new(&data[..]).parse_salvaged(0).unwrap();
+        assert_eq!(salvaged.offset, 0);
+
+        let err = ParserBuilder::new(&junk).parse_salvaged(2048).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ParseError>(),
+            Some(&ParseError::HeaderNotFound(2048))
+        );
+        assert!(ParserBuilder::new(&[]).parse_salvaged(1024).is_err());
+    }
+
+    #[test]
+    fn test_parse_salvaged_skips_implausible_headers() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+
+        // A block of junk whose size and crc match, but that doesn't start like a header
+        let block = [0xffu8; 32];
+        let mut junk = Vec::new();
+        junk.extend_from_slice(&(block.len() as u32).to_le_bytes());
+        junk.extend_from_slice(&calc_crc(&block).to_le_bytes());
+        junk.extend_from_slice(&block);
+        junk.extend_from_slice(&data[..]);
+
+        let salvaged = ParserBuilder::new(&junk).parse_salvaged(1024).unwrap();
+        assert_eq!(salvaged.offset, 40);
+    }
+
+    #[test]
+    fn test_parse_concatenated() {
+        let rumble = include_bytes!("../assets/replays/good/rumble.replay");
+        let small = include_bytes!("../assets/replays/good/small-frames.replay");
+
+        // The second replay is corrupt but its sizes are intact, and the third is truncated
+        let mut corrupt = small.to_vec();
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 0xff;
+        let data = [&rumble[..], &corrupt, &rumble[..], &small[..100]].concat();
+
+        let replays: Vec<_> = ParserBuilder::new(&data)
+            .always_check_crc()
+            .never_parse_network_data()
+            .parse_concatenated()
+            .collect();
+        let offsets: Vec<_> = replays.iter().map(|x| x.0).collect();
+        let second = rumble.len() + small.len();
+        assert_eq!(
+            offsets,
+            vec![0, rumble.len(), second, second + rumble.len()]
+        );
+
+        let parsed: Vec<_> = replays.iter().map(|x| x.1.is_ok()).collect();
+        assert_eq!(parsed, vec![true, false, true, false]);
+        assert_eq!(
+            replays[2].1.as_ref().unwrap(),
+            &ParserBuilder::new(&rumble[..])
+                .never_parse_network_data()
+                .parse()
+                .unwrap()
+        );
+
+        assert_eq!(ParserBuilder::new(&[]).parse_concatenated().count(), 0);
+    }
+
     #[test]
     fn test_the_fuzz_object_id_too_large() {
         let data = include_bytes!("../assets/replays/bad/fuzz-large-object-id.replay");
@@ -608,4 +1694,280 @@ mod tests {
         parser = Parser::new(&data[..], CrcCheck::OnError, NetworkParse::Never);
         assert!(parser.parse().is_ok());
     }
+
+    #[test]
+    fn test_threaded_crc() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let mut parser = Parser::new(&data[..], CrcCheck::Always, NetworkParse::Never);
+        let (res, (crc, _)) = parser.parse_and_crc(&data[..], true, Parser::header_section);
+
+        let mut parser = Parser::new(&data[..], CrcCheck::Always, NetworkParse::Never);
+        let (expected, (expected_crc, _)) =
+            parser.parse_and_crc(&data[..], false, Parser::header_section);
+        assert_eq!(res.unwrap(), expected.unwrap());
+        assert_eq!(crc, expected_crc);
+        assert_eq!(crc, calc_crc(&data[..]));
+    }
+
+    fn limit_exceeded(err: &Error) -> Option<crate::LimitExceeded> {
+        err.iter_chain()
+            .find_map(|x| x.downcast_ref::<crate::LimitExceeded>())
+            .copied()
+    }
+
+    #[test]
+    fn test_limits() {
+        use crate::LimitKind;
+
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let frames = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .max_frames(100)
+            .parse()
+            .unwrap_err();
+        let limit = limit_exceeded(&frames).unwrap();
+        assert_eq!(limit.kind, LimitKind::Frames);
+        assert_eq!(limit.limit, 100);
+        assert!(limit.requested > 100);
+
+        let actors = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .max_actors(5)
+            .parse()
+            .unwrap_err();
+        let limit = limit_exceeded(&actors).unwrap();
+        assert_eq!(limit.kind, LimitKind::Actors);
+        assert_eq!(limit.requested, 6);
+
+        let text = ParserBuilder::new(&data[..])
+            .never_parse_network_data()
+            .max_string_len(4)
+            .parse()
+            .unwrap_err();
+        assert_eq!(limit_exceeded(&text).unwrap().kind, LimitKind::StringLength);
+
+        // Relaxed limits still parse the replay
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .max_frames(100_000)
+            .max_actors(1_000)
+            .max_string_len(100_000)
+            .max_list_len(100_000)
+            .parse();
+        assert!(replay.is_ok());
+
+        let limits = Limits {
+            max_actors: Some(5),
+            ..Limits::default()
+        };
+        let actors = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .with_limits(limits)
+            .parse()
+            .unwrap_err();
+        assert_eq!(limit_exceeded(&actors).unwrap().kind, LimitKind::Actors);
+    }
+
+    #[test]
+    fn test_relaxed_list_limit() {
+        let data = include_bytes!("../assets/replays/bad/fuzz-list-too-large.replay");
+        let err = ParserBuilder::new(&data[..])
+            .never_check_crc()
+            .max_list_len(usize::MAX)
+            .parse()
+            .unwrap_err();
+
+        // The list is decoded until it reaches a corrupt string
+        let limit = limit_exceeded(&err).unwrap();
+        assert_eq!(limit.kind, crate::LimitKind::StringLength);
+    }
+
+    #[test]
+    fn test_parse_partial() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let full = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse_partial()
+            .unwrap();
+        assert!(full.is_complete());
+        assert!(full.missing.is_empty());
+        assert!(full.replay.network_frames.is_some());
+
+        // Truncated in the middle of the network data
+        let partial = ParserBuilder::new(&data[..data.len() / 2])
+            .must_parse_network_data()
+            .parse_partial()
+            .unwrap();
+        assert!(!partial.is_complete());
+        assert_eq!(&partial.missing[..], &BodySection::ALL[2..]);
+        assert_eq!(partial.replay.properties, full.replay.properties);
+        assert_eq!(partial.replay.keyframes, full.replay.keyframes);
+        assert_eq!(partial.replay.content_crc, full.replay.content_crc);
+        assert!(partial.replay.network_frames.is_none());
+        assert!(partial.replay.net_cache.is_empty());
+
+        // Only the net cache is missing
+        let partial = ParserBuilder::new(&data[..data.len() - 4])
+            .parse_partial()
+            .unwrap();
+        assert_eq!(&partial.missing[..], &[BodySection::NetCache]);
+        assert_eq!(partial.replay.class_indices, full.replay.class_indices);
+
+        // The header is required
+        assert!(ParserBuilder::new(&data[..100]).parse_partial().is_err());
+    }
+
+    #[test]
+    fn test_parse_partial_corrupt_footer() {
+        let data = include_bytes!("../assets/replays/bad/fuzz-list-too-large.replay");
+        let partial = ParserBuilder::new(&data[..])
+            .always_check_crc()
+            .parse_partial()
+            .unwrap();
+        assert!(!partial.is_complete());
+        assert_eq!(&partial.missing[..], &BodySection::ALL[3..]);
+        assert!(!partial.replay.levels.is_empty());
+        assert!(!partial.replay.keyframes.is_empty());
+        assert!(!partial.replay.network_data.is_empty());
+
+        // The body failed the crc check, while the debug info failed to parse
+        let (section, err) = partial.failed.unwrap();
+        assert_eq!(section, BodySection::DebugInfo);
+        assert!(format!("{}", err).starts_with("Could not decode replay debug info"));
+        assert_eq!(
+            "Crc mismatch. Expected 3765941959 but received 1314727725",
+            format!("{}", partial.error.unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_network_later() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let full = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let mut replay = ParserBuilder::new(&data[..])
+            .never_parse_network_data()
+            .parse()
+            .unwrap();
+        assert!(replay.network_frames.is_none());
+        assert_eq!(replay.network_data, full.network_data);
+
+        let frames = replay.parse_network().unwrap().clone();
+        assert_eq!(Some(frames), full.network_frames);
+        assert_eq!(replay, full);
+    }
+
+    #[test]
+    fn test_recover_net_cache() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let full = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let mut parser = Parser::new(&data[..], CrcCheck::Never, NetworkParse::Never);
+        let (_, _, header) = parser.header_section().unwrap();
+        let (_, _, mut body) = parser.body_section().unwrap();
+
+        // Children are listed before their parents
+        body.net_cache.reverse();
+        let mut options = DecodeOptions::default();
+        assert!(network::parse(&header, &body, &options).is_err());
+
+        options.recover_net_cache = true;
+        let frames = network::parse(&header, &body, &options).unwrap();
+        assert_eq!(Some(frames), full.network_frames);
+    }
+
+    #[test]
+    fn test_unknown_versions() {
+        use crate::{ActorId, FrameBuilder, ReplayBuilder, Trajectory};
+        use std::sync::Mutex;
+
+        let trajectory = Trajectory {
+            location: None,
+            rotation: None,
+        };
+        let data = ReplayBuilder::new()
+            .version(868, 99, 10)
+            .frame(FrameBuilder::new(1.0, 0.03).spawn(
+                ActorId(0),
+                "Archetypes.Ball.Ball_Default",
+                trajectory,
+            ))
+            .build()
+            .unwrap();
+
+        // Newer replays are decoded by default
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let hook = Arc::clone(&reported);
+        let replay = ParserBuilder::new(&data)
+            .must_parse_network_data()
+            .on_unknown_version(move |x| hook.lock().unwrap().push(x))
+            .parse()
+            .unwrap();
+        assert!(!replay.version().is_known());
+        assert_eq!(replay.network_frames.unwrap().frames.len(), 1);
+        assert_eq!(
+            *reported.lock().unwrap(),
+            vec![ReplayVersion::new(868, 99, 10)]
+        );
+
+        let replay = ParserBuilder::new(&data)
+            .must_parse_network_data()
+            .unknown_versions(UnknownVersions::SkipNetwork)
+            .parse()
+            .unwrap();
+        assert!(replay.network_frames.is_none());
+
+        let err = ParserBuilder::new(&data)
+            .ignore_network_data_on_error()
+            .unknown_versions(UnknownVersions::Fail)
+            .parse()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Replay version 868.99.10 is newer than the latest known version 868.26.10"
+        );
+
+        // Only the network data depends on the version
+        let replay = ParserBuilder::new(&data)
+            .never_parse_network_data()
+            .unknown_versions(UnknownVersions::Fail)
+            .parse();
+        assert!(replay.is_ok());
+    }
+
+    #[test]
+    fn test_unsupported_game() {
+        use crate::ReplayBuilder;
+
+        let data = ReplayBuilder::new()
+            .game_type("Sideswipe.Replay_Soccar")
+            .build()
+            .unwrap();
+
+        let err = ParserBuilder::new(&data)
+            .must_parse_network_data()
+            .parse()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Game type of Sideswipe.Replay_Soccar is not from Rocket League (eg: Rocket League Sideswipe), so its network data can't be decoded"
+        );
+
+        let replay = ParserBuilder::new(&data)
+            .ignore_network_data_on_error()
+            .parse()
+            .unwrap();
+        assert!(!replay.is_rocket_league());
+        assert!(replay.network_frames.is_none());
+
+        let data = ReplayBuilder::new().build().unwrap();
+        let replay = ParserBuilder::new(&data).parse().unwrap();
+        assert!(replay.is_rocket_league());
+    }
 }
@@ -41,13 +41,22 @@ pub fn decode_str(input: &[u8]) -> Result<&str, ParseError> {
     }
 }
 
+/// Decodes a UTF-16 string, like player names with CJK characters or emoji, that should end
+/// with a null character. Surrogate pairs are decoded into their character, while unpaired
+/// surrogates and a dangling byte of an odd length are replaced with the replacement character.
+/// A string that is missing its null terminator keeps its last character.
 pub fn decode_utf16(input: &[u8]) -> Result<Cow<'_, str>, ParseError> {
     if input.len() < 2 {
-        Err(ParseError::ZeroSize)
-    } else {
-        let (s, _) = UTF_16LE.decode_without_bom_handling(&input[..input.len() - 2]);
-        Ok(s)
+        return Err(ParseError::ZeroSize);
     }
+
+    let data = match input {
+        [data @ .., 0, 0] if input.len() % 2 == 0 => data,
+        _ => input,
+    };
+
+    let (s, _) = UTF_16LE.decode_without_bom_handling(data);
+    Ok(s)
 }
 
 pub fn decode_windows1252(input: &[u8]) -> Result<Cow<'_, str>, ParseError> {
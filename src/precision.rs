@@ -0,0 +1,501 @@
+//! Controls how floats are serialized. The positions, rotations, and velocities in the network
+//! data are serialized with the full noise of an `f32`, which bloats the output and causes
+//! otherwise identical output to differ. Wrapping a serializer in `RoundFloats` rounds every
+//! float that passes through it.
+
+use serde::ser::{
+    Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+
+/// How floats are written by `RoundFloats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatPrecision {
+    /// Round to the given number of decimal places
+    Decimals(u8),
+
+    /// Scale by ten to the power of the given number of decimal places and write the rounded
+    /// result as an integer (eg: 1.2345 with 2 places is written as 123)
+    FixedPoint(u8),
+}
+
+/// A serializer that rounds floats according to a precision before passing them along to the
+/// wrapped serializer. Floats that aren't finite are passed along as is.
+///
+/// ```
+/// use boxcars::{FloatPrecision, RoundFloats};
+/// use serde::Serialize;
+///
+/// let mut out = Vec::new();
+/// let mut json = serde_json::Serializer::new(&mut out);
+/// vec![1.23456f32, -0.5]
+///     .serialize(RoundFloats::new(&mut json, FloatPrecision::Decimals(2)))
+///     .unwrap();
+/// assert_eq!(out, b"[1.23,-0.5]");
+/// ```
+///
+/// The wrapped serializer can be given to `ParserBuilder::serialize_frames_to` to round the
+/// frames as they are decoded.
+#[derive(Debug, Clone, Copy)]
+pub struct RoundFloats<S> {
+    inner: S,
+    precision: FloatPrecision,
+}
+
+impl<S> RoundFloats<S> {
+    pub fn new(inner: S, precision: FloatPrecision) -> Self {
+        RoundFloats { inner, precision }
+    }
+}
+
+impl FloatPrecision {
+    fn scale(self) -> f64 {
+        match self {
+            FloatPrecision::Decimals(x) | FloatPrecision::FixedPoint(x) => 10f64.powi(i32::from(x)),
+        }
+    }
+}
+
+/// A value that is serialized with its floats rounded
+struct Rounded<'b, T: ?Sized> {
+    value: &'b T,
+    precision: FloatPrecision,
+}
+
+impl<'b, T: ?Sized + Serialize> Serialize for Rounded<'b, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value
+            .serialize(RoundFloats::new(serializer, self.precision))
+    }
+}
+
+/// Wraps the compound serializers of the wrapped serializer so that their elements are rounded
+pub struct Compound<C> {
+    inner: C,
+    precision: FloatPrecision,
+}
+
+impl<C> Compound<C> {
+    fn rounded<'b, T: ?Sized>(&self, value: &'b T) -> Rounded<'b, T> {
+        Rounded {
+            value,
+            precision: self.precision,
+        }
+    }
+}
+
+impl<S: Serializer> RoundFloats<S> {
+    fn rounded<'b, T: ?Sized>(&self, value: &'b T) -> Rounded<'b, T> {
+        Rounded {
+            value,
+            precision: self.precision,
+        }
+    }
+}
+
+impl<S: Serializer> Serializer for RoundFloats<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Compound<S::SerializeSeq>;
+    type SerializeTuple = Compound<S::SerializeTuple>;
+    type SerializeTupleStruct = Compound<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Compound<S::SerializeTupleVariant>;
+    type SerializeMap = Compound<S::SerializeMap>;
+    type SerializeStruct = Compound<S::SerializeStruct>;
+    type SerializeStructVariant = Compound<S::SerializeStructVariant>;
+
+    fn serialize_f32(self, v: f32) -> Result<S::Ok, S::Error> {
+        if !v.is_finite() {
+            return self.inner.serialize_f32(v);
+        }
+
+        let scaled = (f64::from(v) * self.precision.scale()).round();
+        match self.precision {
+            FloatPrecision::Decimals(_) => {
+                // Rounding in f64 and narrowing keeps the shortest representation of the
+                // rounded f32 (eg: 1.23 instead of 1.2300000190734863)
+                let rounded = (scaled / self.precision.scale()) as f32;
+                self.inner.serialize_f32(rounded)
+            }
+            FloatPrecision::FixedPoint(_) => self.inner.serialize_i64(scaled as i64),
+        }
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<S::Ok, S::Error> {
+        if !v.is_finite() {
+            return self.inner.serialize_f64(v);
+        }
+
+        let scaled = (v * self.precision.scale()).round();
+        match self.precision {
+            FloatPrecision::Decimals(_) => {
+                self.inner.serialize_f64(scaled / self.precision.scale())
+            }
+            FloatPrecision::FixedPoint(_) => self.inner.serialize_i64(scaled as i64),
+        }
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bool(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i8(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i16(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i32(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i64(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i128(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u8(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u16(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u32(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u64(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u128(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_char(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_str(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_none()
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<S::Ok, S::Error> {
+        let value = self.rounded(value);
+        self.inner.serialize_some(&value)
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        let value = self.rounded(value);
+        self.inner.serialize_newtype_struct(name, &value)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        let value = self.rounded(value);
+        self.inner
+            .serialize_newtype_variant(name, variant_index, variant, &value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        Ok(Compound {
+            inner: self.inner.serialize_seq(len)?,
+            precision: self.precision,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        Ok(Compound {
+            inner: self.inner.serialize_tuple(len)?,
+            precision: self.precision,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        Ok(Compound {
+            inner: self.inner.serialize_tuple_struct(name, len)?,
+            precision: self.precision,
+        })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        Ok(Compound {
+            inner: self
+                .inner
+                .serialize_tuple_variant(name, variant_index, variant, len)?,
+            precision: self.precision,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        Ok(Compound {
+            inner: self.inner.serialize_map(len)?,
+            precision: self.precision,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        Ok(Compound {
+            inner: self.inner.serialize_struct(name, len)?,
+            precision: self.precision,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        Ok(Compound {
+            inner: self
+                .inner
+                .serialize_struct_variant(name, variant_index, variant, len)?,
+            precision: self.precision,
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+impl<C: SerializeSeq> SerializeSeq for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.rounded(value);
+        self.inner.serialize_element(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeTuple> SerializeTuple for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.rounded(value);
+        self.inner.serialize_element(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeTupleStruct> SerializeTupleStruct for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.rounded(value);
+        self.inner.serialize_field(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeTupleVariant> SerializeTupleVariant for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.rounded(value);
+        self.inner.serialize_field(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeMap> SerializeMap for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), C::Error> {
+        let key = self.rounded(key);
+        self.inner.serialize_key(&key)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.rounded(value);
+        self.inner.serialize_value(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeStruct> SerializeStruct for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), C::Error> {
+        let value = self.rounded(value);
+        self.inner.serialize_field(key, &value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), C::Error> {
+        self.inner.skip_field(key)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeStructVariant> SerializeStructVariant for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), C::Error> {
+        let value = self.rounded(value);
+        self.inner.serialize_field(key, &value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), C::Error> {
+        self.inner.skip_field(key)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParserBuilder;
+
+    fn to_json<T: Serialize>(value: &T, precision: FloatPrecision) -> String {
+        let mut out = Vec::new();
+        let mut json = serde_json::Serializer::new(&mut out);
+        value
+            .serialize(RoundFloats::new(&mut json, precision))
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_round_floats() {
+        let values = (1.23456f32, Some(-2.5f64), f32::NAN, vec![100.006f32]);
+        assert_eq!(
+            to_json(&values, FloatPrecision::Decimals(1)),
+            "[1.2,-2.5,null,[100.0]]"
+        );
+        assert_eq!(
+            to_json(&values, FloatPrecision::FixedPoint(2)),
+            "[123,-250,null,[10001]]"
+        );
+        assert_eq!(
+            to_json(&values, FloatPrecision::Decimals(0)),
+            "[1.0,-3.0,null,[100.0]]"
+        );
+    }
+
+    #[test]
+    fn test_round_replay() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let full = serde_json::to_string(&replay).unwrap();
+        let rounded = to_json(&replay, FloatPrecision::Decimals(2));
+        assert!(rounded.len() < full.len());
+
+        // Every number has at most two decimal places
+        let value: serde_json::Value = serde_json::from_str(&rounded).unwrap();
+        let mut stack = vec![&value];
+        while let Some(x) = stack.pop() {
+            match x {
+                serde_json::Value::Number(n) => {
+                    let s = n.to_string();
+                    let decimals = s.split('.').nth(1).map_or(0, |x| x.len());
+                    assert!(decimals <= 2, "{}", s);
+                }
+                serde_json::Value::Array(arr) => stack.extend(arr.iter()),
+                serde_json::Value::Object(obj) => stack.extend(obj.values()),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,816 @@
+//! Encodes a replay with the protobuf schema in `proto/boxcars.proto`. The encoding is done by
+//! hand so that the schema doesn't require code generation at build time.
+
+use crate::models::*;
+use crate::network::*;
+
+impl<'a> Replay<'a> {
+    /// Encodes the replay as a `boxcars.Replay` protobuf message. The schema can be found in
+    /// `proto/boxcars.proto` and used to generate decoders for other languages.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..]).parse().unwrap();
+    /// let encoded = replay.to_proto();
+    /// assert!(!encoded.is_empty());
+    /// ```
+    pub fn to_proto(&self) -> Vec<u8> {
+        let mut message = Message::default();
+        self.encode(&mut message);
+        message.buf
+    }
+}
+
+const VARINT: u32 = 0;
+const LENGTH_DELIMITED: u32 = 2;
+const FIXED32: u32 = 5;
+
+/// A protobuf message being encoded. Scalar fields follow proto3 semantics and are omitted when
+/// they hold their default value, while the `some_*` and `oneof_*` methods always write the
+/// field as its presence is meaningful.
+#[derive(Debug, Default)]
+struct Message {
+    buf: Vec<u8>,
+}
+
+impl Message {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u32) {
+        self.varint(u64::from(field << 3 | wire_type));
+    }
+
+    fn oneof_varint(&mut self, field: u32, value: u64) {
+        self.key(field, VARINT);
+        self.varint(value);
+    }
+
+    fn oneof_float(&mut self, field: u32, value: f32) {
+        self.key(field, FIXED32);
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn oneof_bytes(&mut self, field: u32, value: &[u8]) {
+        self.key(field, LENGTH_DELIMITED);
+        self.varint(value.len() as u64);
+        self.buf.extend_from_slice(value);
+    }
+
+    fn uint32(&mut self, field: u32, value: u32) {
+        self.uint64(field, u64::from(value));
+    }
+
+    fn uint64(&mut self, field: u32, value: u64) {
+        if value != 0 {
+            self.oneof_varint(field, value);
+        }
+    }
+
+    fn int32(&mut self, field: u32, value: i32) {
+        // Negative int32 values are sign extended to 64 bits
+        self.uint64(field, i64::from(value) as u64);
+    }
+
+    fn bool(&mut self, field: u32, value: bool) {
+        self.uint64(field, u64::from(value));
+    }
+
+    fn float(&mut self, field: u32, value: f32) {
+        if value.to_bits() != 0 {
+            self.oneof_float(field, value);
+        }
+    }
+
+    fn string(&mut self, field: u32, value: &str) {
+        self.bytes(field, value.as_bytes());
+    }
+
+    fn bytes(&mut self, field: u32, value: &[u8]) {
+        if !value.is_empty() {
+            self.oneof_bytes(field, value);
+        }
+    }
+
+    fn some_uint32(&mut self, field: u32, value: Option<u32>) {
+        if let Some(x) = value {
+            self.oneof_varint(field, u64::from(x));
+        }
+    }
+
+    fn some_int32(&mut self, field: u32, value: Option<i32>) {
+        if let Some(x) = value {
+            self.oneof_varint(field, i64::from(x) as u64);
+        }
+    }
+
+    fn some_float(&mut self, field: u32, value: Option<f32>) {
+        if let Some(x) = value {
+            self.oneof_float(field, x);
+        }
+    }
+
+    fn some_string(&mut self, field: u32, value: Option<&str>) {
+        if let Some(x) = value {
+            self.oneof_bytes(field, x.as_bytes());
+        }
+    }
+
+    fn message<T: Proto + ?Sized>(&mut self, field: u32, value: &T) {
+        let mut inner = Message::default();
+        value.encode(&mut inner);
+        self.oneof_bytes(field, &inner.buf);
+    }
+
+    fn some_message<T: Proto>(&mut self, field: u32, value: Option<&T>) {
+        if let Some(x) = value {
+            self.message(field, x);
+        }
+    }
+
+    fn messages<'b, T, I>(&mut self, field: u32, values: I)
+    where
+        T: Proto + 'b,
+        I: IntoIterator<Item = &'b T>,
+    {
+        for value in values {
+            self.message(field, value);
+        }
+    }
+
+    fn strings<'b, I, S>(&mut self, field: u32, values: I)
+    where
+        I: IntoIterator<Item = &'b S>,
+        S: AsRef<str> + ?Sized + 'b,
+    {
+        for value in values {
+            self.oneof_bytes(field, value.as_ref().as_bytes());
+        }
+    }
+
+    fn packed_int32<I: IntoIterator<Item = i32>>(&mut self, field: u32, values: I) {
+        let mut inner = Message::default();
+        for value in values {
+            inner.varint(i64::from(value) as u64);
+        }
+        self.bytes(field, &inner.buf);
+    }
+}
+
+/// A type that is encoded as a protobuf message
+trait Proto {
+    fn encode(&self, m: &mut Message);
+}
+
+impl<'a> Proto for Replay<'a> {
+    fn encode(&self, m: &mut Message) {
+        m.int32(1, self.header_size);
+        m.uint32(2, self.header_crc);
+        m.int32(3, self.major_version);
+        m.int32(4, self.minor_version);
+        m.some_int32(5, self.net_version);
+        m.string(6, &self.game_type);
+        for (key, value) in &self.properties {
+            m.message(7, &(*key, value));
+        }
+        m.int32(8, self.content_size);
+        m.uint32(9, self.content_crc);
+        m.some_message(10, self.network_frames.as_ref());
+        m.strings(11, &self.levels);
+        m.messages(12, &self.keyframes);
+        m.messages(13, &self.debug_info);
+        m.messages(14, &self.tick_marks);
+        m.strings(15, &self.packages);
+        m.strings(16, &self.objects);
+        m.strings(17, &self.names);
+        m.messages(18, &self.class_indices);
+        m.messages(19, &self.net_cache);
+    }
+}
+
+impl<'a> Proto for (&'a str, &HeaderProp<'a>) {
+    fn encode(&self, m: &mut Message) {
+        m.string(1, self.0);
+        m.message(2, self.1);
+    }
+}
+
+/// The elements of a header array property
+struct HeaderArray<'b, 'a>(&'b [Vec<(&'a str, HeaderProp<'a>)>]);
+
+/// The properties of a header array element
+struct HeaderDict<'b, 'a>(&'b [(&'a str, HeaderProp<'a>)]);
+
+impl<'b, 'a> Proto for HeaderArray<'b, 'a> {
+    fn encode(&self, m: &mut Message) {
+        for element in self.0 {
+            m.message(1, &HeaderDict(element));
+        }
+    }
+}
+
+impl<'b, 'a> Proto for HeaderDict<'b, 'a> {
+    fn encode(&self, m: &mut Message) {
+        for (key, value) in self.0 {
+            m.message(1, &(*key, value));
+        }
+    }
+}
+
+impl<'a> Proto for HeaderProp<'a> {
+    fn encode(&self, m: &mut Message) {
+        match self {
+            HeaderProp::Array(x) => m.message(1, &HeaderArray(x)),
+            HeaderProp::Bool(x) => m.oneof_varint(2, u64::from(*x)),
+            HeaderProp::Byte { kind, value } => m.message(3, &(*kind, *value)),
+            HeaderProp::Float(x) => m.oneof_float(4, *x),
+            HeaderProp::Int(x) => m.oneof_varint(5, i64::from(*x) as u64),
+            HeaderProp::Name(x) => m.oneof_bytes(6, x.as_bytes()),
+            HeaderProp::QWord(x) => m.oneof_varint(7, *x),
+            HeaderProp::Str(x) => m.oneof_bytes(8, x.as_bytes()),
+        }
+    }
+}
+
+impl<'a> Proto for (&'a str, Option<&'a str>) {
+    fn encode(&self, m: &mut Message) {
+        m.string(1, self.0);
+        m.some_string(2, self.1);
+    }
+}
+
+impl Proto for NetworkFrames {
+    fn encode(&self, m: &mut Message) {
+        m.messages(1, &self.frames);
+    }
+}
+
+impl Proto for KeyFrame {
+    fn encode(&self, m: &mut Message) {
+        m.float(1, self.time);
+        m.int32(2, self.frame);
+        m.int32(3, self.position);
+    }
+}
+
+impl<'a> Proto for DebugInfo<'a> {
+    fn encode(&self, m: &mut Message) {
+        m.int32(1, self.frame);
+        m.string(2, &self.user);
+        m.string(3, &self.text);
+    }
+}
+
+impl<'a> Proto for TickMark<'a> {
+    fn encode(&self, m: &mut Message) {
+        m.string(1, &self.description);
+        m.int32(2, self.frame);
+    }
+}
+
+impl<'a> Proto for ClassIndex<'a> {
+    fn encode(&self, m: &mut Message) {
+        m.string(1, self.class);
+        m.int32(2, self.index);
+    }
+}
+
+impl Proto for CacheProp {
+    fn encode(&self, m: &mut Message) {
+        m.int32(1, self.object_ind);
+        m.int32(2, self.stream_id);
+    }
+}
+
+impl Proto for ClassNetCache {
+    fn encode(&self, m: &mut Message) {
+        m.int32(1, self.object_ind);
+        m.int32(2, self.parent_id);
+        m.int32(3, self.cache_id);
+        m.messages(4, &self.properties);
+    }
+}
+
+impl Proto for Frame {
+    fn encode(&self, m: &mut Message) {
+        m.float(1, self.time);
+        m.float(2, self.delta);
+        m.messages(3, &self.new_actors);
+        m.packed_int32(4, self.deleted_actors.iter().map(|x| x.0));
+        m.messages(5, &self.updated_actors);
+    }
+}
+
+impl Proto for NewActor {
+    fn encode(&self, m: &mut Message) {
+        m.int32(1, self.actor_id.0);
+        m.some_int32(2, self.name_id);
+        m.int32(3, self.object_id.0);
+        m.message(4, &self.initial_trajectory);
+    }
+}
+
+impl Proto for Trajectory {
+    fn encode(&self, m: &mut Message) {
+        m.some_message(1, self.location.as_ref());
+        m.some_message(2, self.rotation.as_ref());
+    }
+}
+
+impl Proto for UpdatedAttribute {
+    fn encode(&self, m: &mut Message) {
+        m.int32(1, self.actor_id.0);
+        m.int32(2, self.stream_id.0);
+        m.message(3, &self.attribute);
+    }
+}
+
+impl Proto for Vector {
+    fn encode(&self, m: &mut Message) {
+        m.int32(1, self.bias);
+        m.int32(2, self.dx);
+        m.int32(3, self.dy);
+        m.int32(4, self.dz);
+    }
+}
+
+impl Proto for Rotation {
+    fn encode(&self, m: &mut Message) {
+        m.some_int32(1, self.yaw.map(i32::from));
+        m.some_int32(2, self.pitch.map(i32::from));
+        m.some_int32(3, self.roll.map(i32::from));
+    }
+}
+
+impl Proto for CompressedRotation {
+    fn encode(&self, m: &mut Message) {
+        match *self {
+            CompressedRotation::Rotator { pitch, yaw, roll } => {
+                m.message(1, &[pitch, yaw, roll].map(u32::from)[..])
+            }
+            CompressedRotation::Quaternion { largest, a, b, c } => {
+                m.message(2, &[u32::from(largest), a, b, c][..])
+            }
+        }
+    }
+}
+
+/// Messages that only contain consecutive uint32 fields starting at field 1
+impl Proto for [u32] {
+    fn encode(&self, m: &mut Message) {
+        for (i, value) in self.iter().enumerate() {
+            m.uint32(i as u32 + 1, *value);
+        }
+    }
+}
+
+impl Proto for Attribute {
+    fn encode(&self, m: &mut Message) {
+        match self {
+            Attribute::Boolean(x) => m.oneof_varint(1, u64::from(*x)),
+            Attribute::Byte(x) => m.oneof_varint(2, u64::from(*x)),
+            Attribute::AppliedDamage(a, b, c, d) => m.message(3, &(*a, *b, *c, *d)),
+            Attribute::DamageState(a, b, c, d, e, f) => m.message(4, &(*a, *b, *c, *d, *e, *f)),
+            Attribute::CamSettings(x) => m.message(5, x),
+            Attribute::ClubColors(x) => m.message(6, x),
+            Attribute::Demolish(x) => m.message(7, x),
+            Attribute::DemolishExtended(x) => m.message(36, x),
+            Attribute::Enum(x) => m.oneof_varint(8, u64::from(*x)),
+            Attribute::Explosion(x) => m.message(9, x),
+            Attribute::ExtendedExplosion(a, b, c) => m.message(10, &(*a, *b, *c)),
+            Attribute::FlaggedByte(a, b) => m.message(11, &(*a, u32::from(*b))),
+            Attribute::Flagged(a, b) => m.message(12, &(*a, *b)),
+            Attribute::Float(x) => m.oneof_float(13, *x),
+            Attribute::GameMode(a, b) => m.message(14, &[u32::from(*a), u32::from(*b)][..]),
+            Attribute::Int(x) => m.oneof_varint(15, i64::from(*x) as u64),
+            Attribute::Int64(x) => m.oneof_varint(16, *x as u64),
+            Attribute::Loadout(x) => m.message(17, x),
+            Attribute::TeamLoadout(x) => m.message(18, x),
+            Attribute::Location(x) => m.message(19, x),
+            Attribute::MusicStinger(x) => m.message(20, x),
+            Attribute::PlayerHistoryKey(x) => m.oneof_varint(21, u64::from(*x)),
+            Attribute::Pickup(x) => m.message(22, x),
+            Attribute::PickupNew(x) => m.message(37, x),
+            Attribute::QWord(x) => m.oneof_varint(23, *x),
+            Attribute::Welded(x) => m.message(24, x),
+            Attribute::Title(a, b, c, d, e, f, g, h) => {
+                m.message(25, &Title(*a, *b, [*c, *d, *e, *f, *g], *h))
+            }
+            Attribute::TeamPaint(x) => m.message(26, x),
+            Attribute::RigidBody(x) => m.message(27, x),
+            Attribute::String(x) => m.oneof_bytes(28, x.as_bytes()),
+            Attribute::UniqueId(x) => m.message(29, x),
+            Attribute::Reservation(x) => m.message(30, x),
+            Attribute::PartyLeader(x) => m.message(31, &PartyLeader(x.as_ref())),
+            Attribute::PrivateMatch(x) => m.message(32, x),
+            Attribute::LoadoutOnline(x) => m.message(33, &Products(x)),
+            Attribute::LoadoutsOnline(x) => m.message(34, x),
+            Attribute::StatEvent(a, b) => m.message(35, &(*a, *b)),
+            Attribute::ReplicatedBoost(x) => m.message(38, x),
+        }
+    }
+}
+
+impl Proto for (u8, Vector, u32, u32) {
+    fn encode(&self, m: &mut Message) {
+        m.uint32(1, u32::from(self.0));
+        m.message(2, &self.1);
+        m.uint32(3, self.2);
+        m.uint32(4, self.3);
+    }
+}
+
+impl Proto for (u8, bool, u32, Vector, bool, bool) {
+    fn encode(&self, m: &mut Message) {
+        m.uint32(1, u32::from(self.0));
+        m.bool(2, self.1);
+        m.uint32(3, self.2);
+        m.message(4, &self.3);
+        m.bool(5, self.4);
+        m.bool(6, self.5);
+    }
+}
+
+impl Proto for (Explosion, bool, u32) {
+    fn encode(&self, m: &mut Message) {
+        m.message(1, &self.0);
+        m.bool(2, self.1);
+        m.uint32(3, self.2);
+    }
+}
+
+/// Messages of a flag followed by a value: `FlaggedByte`, `Flagged`, and `StatEvent`
+impl Proto for (bool, u32) {
+    fn encode(&self, m: &mut Message) {
+        m.bool(1, self.0);
+        m.uint32(2, self.1);
+    }
+}
+
+impl Proto for CamSettings {
+    fn encode(&self, m: &mut Message) {
+        m.float(1, self.fov);
+        m.float(2, self.height);
+        m.float(3, self.angle);
+        m.float(4, self.distance);
+        m.float(5, self.swiftness);
+        m.float(6, self.swivel);
+        m.some_float(7, self.transition);
+    }
+}
+
+impl Proto for ClubColors {
+    fn encode(&self, m: &mut Message) {
+        m.bool(1, self.blue_flag);
+        m.uint32(2, u32::from(self.blue_color));
+        m.bool(3, self.orange_flag);
+        m.uint32(4, u32::from(self.orange_color));
+    }
+}
+
+impl Proto for Demolish {
+    fn encode(&self, m: &mut Message) {
+        m.bool(1, self.attacker_flag);
+        m.uint32(2, self.attacker_actor_id);
+        m.bool(3, self.victim_flag);
+        m.uint32(4, self.victim_actor_id);
+        m.message(5, &self.attack_velocity);
+        m.message(6, &self.victim_velocity);
+    }
+}
+
+impl Proto for DemolishExtended {
+    fn encode(&self, m: &mut Message) {
+        m.bool(1, self.attacker_pri_flag);
+        m.uint32(2, self.attacker_pri_actor_id);
+        m.bool(3, self.self_demo_flag);
+        m.uint32(4, self.self_demo_actor_id);
+        m.bool(5, self.self_demolish);
+        m.bool(6, self.goal_explosion_owner_flag);
+        m.uint32(7, self.goal_explosion_owner_actor_id);
+        m.bool(8, self.attacker_flag);
+        m.uint32(9, self.attacker_actor_id);
+        m.bool(10, self.victim_flag);
+        m.uint32(11, self.victim_actor_id);
+        m.message(12, &self.attack_velocity);
+        m.message(13, &self.victim_velocity);
+    }
+}
+
+impl Proto for Explosion {
+    fn encode(&self, m: &mut Message) {
+        m.bool(1, self.flag);
+        m.uint32(2, self.actor_id);
+        m.message(3, &self.location);
+    }
+}
+
+impl Proto for Loadout {
+    fn encode(&self, m: &mut Message) {
+        m.uint32(1, u32::from(self.version));
+        m.uint32(2, self.body);
+        m.uint32(3, self.decal);
+        m.uint32(4, self.wheels);
+        m.uint32(5, self.rocket_trail);
+        m.uint32(6, self.antenna);
+        m.uint32(7, self.topper);
+        m.uint32(8, self.unknown1);
+        m.some_uint32(9, self.unknown2);
+        m.some_uint32(10, self.engine_audio);
+        m.some_uint32(11, self.trail);
+        m.some_uint32(12, self.goal_explosion);
+        m.some_uint32(13, self.banner);
+        m.some_uint32(14, self.unknown3);
+    }
+}
+
+impl Proto for TeamLoadout {
+    fn encode(&self, m: &mut Message) {
+        m.message(1, &self.blue);
+        m.message(2, &self.orange);
+    }
+}
+
+impl Proto for MusicStinger {
+    fn encode(&self, m: &mut Message) {
+        m.bool(1, self.flag);
+        m.uint32(2, self.cue);
+        m.uint32(3, u32::from(self.trigger));
+    }
+}
+
+impl Proto for Pickup {
+    fn encode(&self, m: &mut Message) {
+        m.some_uint32(1, self.instigator_id);
+        m.bool(2, self.picked_up);
+    }
+}
+
+impl Proto for PickupNew {
+    fn encode(&self, m: &mut Message) {
+        m.some_uint32(1, self.instigator_id);
+        m.uint32(2, u32::from(self.picked_up));
+    }
+}
+
+impl Proto for ReplicatedBoost {
+    fn encode(&self, m: &mut Message) {
+        m.uint32(1, u32::from(self.grant_count));
+        m.uint32(2, u32::from(self.boost_amount));
+        m.uint32(3, u32::from(self.component_state[0]));
+        m.uint32(4, u32::from(self.component_state[1]));
+    }
+}
+
+impl Proto for Welded {
+    fn encode(&self, m: &mut Message) {
+        m.bool(1, self.active);
+        m.uint32(2, self.actor_id);
+        m.message(3, &self.offset);
+        m.float(4, self.mass);
+        m.message(5, &self.rotation);
+    }
+}
+
+struct Title(bool, bool, [u32; 5], bool);
+
+impl Proto for Title {
+    fn encode(&self, m: &mut Message) {
+        m.bool(1, self.0);
+        m.bool(2, self.1);
+        for (i, value) in self.2.iter().enumerate() {
+            m.uint32(i as u32 + 3, *value);
+        }
+        m.bool(8, self.3);
+    }
+}
+
+impl Proto for TeamPaint {
+    fn encode(&self, m: &mut Message) {
+        m.uint32(1, u32::from(self.team));
+        m.uint32(2, u32::from(self.primary_color));
+        m.uint32(3, u32::from(self.accent_color));
+        m.uint32(4, self.primary_finish);
+        m.uint32(5, self.accent_finish);
+    }
+}
+
+impl Proto for RigidBody {
+    fn encode(&self, m: &mut Message) {
+        m.bool(1, self.sleeping);
+        m.message(2, &self.location);
+        m.message(3, &self.rotation);
+        m.some_message(4, self.linear_velocity.as_ref());
+        m.some_message(5, self.angular_velocity.as_ref());
+    }
+}
+
+impl Proto for UniqueId {
+    fn encode(&self, m: &mut Message) {
+        m.uint32(1, u32::from(self.system_id));
+        m.message(2, &self.remote_id);
+        m.uint32(3, u32::from(self.local_id));
+    }
+}
+
+impl Proto for RemoteId {
+    fn encode(&self, m: &mut Message) {
+        match self {
+            RemoteId::PlayStation(x) => m.message(1, x),
+            RemoteId::PsyNet(x) => m.message(2, &(x.online_id, &x.unknown1[..])),
+            RemoteId::SplitScreen(x) => m.oneof_varint(3, u64::from(*x)),
+            RemoteId::Steam(x) => m.oneof_varint(4, *x),
+            RemoteId::Switch(x) => m.message(5, &(x.online_id, &x.unknown1[..])),
+            RemoteId::Xbox(x) => m.oneof_varint(6, *x),
+        }
+    }
+}
+
+impl Proto for Ps4Id {
+    fn encode(&self, m: &mut Message) {
+        m.uint64(1, self.online_id);
+        m.string(2, &self.name);
+        m.bytes(3, &self.unknown1);
+    }
+}
+
+/// The `PsyNetId` and `SwitchId` messages
+impl Proto for (u64, &[u8]) {
+    fn encode(&self, m: &mut Message) {
+        m.uint64(1, self.0);
+        m.bytes(2, self.1);
+    }
+}
+
+impl Proto for Reservation {
+    fn encode(&self, m: &mut Message) {
+        m.uint32(1, self.number);
+        m.message(2, &self.unique_id);
+        m.some_string(3, self.name.as_deref());
+        m.bool(4, self.unknown1);
+        m.bool(5, self.unknown2);
+        m.some_uint32(6, self.unknown3.map(u32::from));
+    }
+}
+
+struct PartyLeader<'a>(Option<&'a UniqueId>);
+
+impl<'a> Proto for PartyLeader<'a> {
+    fn encode(&self, m: &mut Message) {
+        m.some_message(1, self.0);
+    }
+}
+
+impl Proto for PrivateMatchSettings {
+    fn encode(&self, m: &mut Message) {
+        m.string(1, &self.mutators);
+        m.uint32(2, self.joinable_by);
+        m.uint32(3, self.max_players);
+        m.string(4, &self.game_name);
+        m.string(5, &self.password);
+        m.bool(6, self.flag);
+    }
+}
+
+/// Each team's products, which are encoded as a `ProductList` message
+impl Proto for Vec<Product> {
+    fn encode(&self, m: &mut Message) {
+        m.messages(1, self);
+    }
+}
+
+struct Products<'a>(&'a [Vec<Product>]);
+
+impl<'a> Proto for Products<'a> {
+    fn encode(&self, m: &mut Message) {
+        m.messages(1, self.0);
+    }
+}
+
+impl Proto for Product {
+    fn encode(&self, m: &mut Message) {
+        m.bool(1, self.unknown);
+        m.uint32(2, self.object_ind);
+        m.message(3, &self.value);
+    }
+}
+
+impl Proto for ProductValue {
+    fn encode(&self, m: &mut Message) {
+        match self {
+            ProductValue::NoColor => m.oneof_bytes(1, &[]),
+            ProductValue::Absent => m.oneof_bytes(2, &[]),
+            ProductValue::OldColor(x) => m.oneof_varint(3, u64::from(*x)),
+            ProductValue::NewColor(x) => m.oneof_varint(4, u64::from(*x)),
+            ProductValue::OldPaint(x) => m.oneof_varint(5, u64::from(*x)),
+            ProductValue::NewPaint(x) => m.oneof_varint(6, u64::from(*x)),
+            ProductValue::Title(x) => m.oneof_bytes(7, x.as_bytes()),
+            ProductValue::SpecialEdition(x) => m.oneof_varint(8, u64::from(*x)),
+            ProductValue::OldTeamEdition(x) => m.oneof_varint(9, u64::from(*x)),
+            ProductValue::NewTeamEdition(x) => m.oneof_varint(10, u64::from(*x)),
+        }
+    }
+}
+
+impl Proto for LoadoutsOnline {
+    fn encode(&self, m: &mut Message) {
+        m.messages(1, &self.blue);
+        m.messages(2, &self.orange);
+        m.bool(3, self.unknown1);
+        m.bool(4, self.unknown2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParserBuilder;
+
+    /// The fields of an encoded message as their field number and varint or bytes
+    fn decode(mut buf: &[u8]) -> Vec<(u32, u64, &[u8])> {
+        fn varint(buf: &mut &[u8]) -> u64 {
+            let mut result = 0;
+            for i in 0.. {
+                let byte = buf[0];
+                *buf = &buf[1..];
+                result |= u64::from(byte & 0x7f) << (i * 7);
+                if byte < 0x80 {
+                    break;
+                }
+            }
+            result
+        }
+
+        let mut fields = Vec::new();
+        while !buf.is_empty() {
+            let key = varint(&mut buf);
+            let (value, data) = match (key & 7) as u32 {
+                VARINT => (varint(&mut buf), &buf[..0]),
+                FIXED32 => {
+                    let (data, rest) = buf.split_at(4);
+                    buf = rest;
+                    (0, data)
+                }
+                LENGTH_DELIMITED => {
+                    let len = varint(&mut buf) as usize;
+                    let (data, rest) = buf.split_at(len);
+                    buf = rest;
+                    (len as u64, data)
+                }
+                x => panic!("unexpected wire type {}", x),
+            };
+            fields.push(((key >> 3) as u32, value, data));
+        }
+        fields
+    }
+
+    #[test]
+    fn test_varints() {
+        let mut m = Message::default();
+        m.uint32(1, 0);
+        assert!(m.buf.is_empty());
+
+        m.uint32(1, 300);
+        assert_eq!(m.buf, vec![0x08, 0xac, 0x02]);
+
+        let mut m = Message::default();
+        m.int32(2, -1);
+        assert_eq!(decode(&m.buf), vec![(2, u64::MAX, &[][..])]);
+    }
+
+    #[test]
+    fn test_replay_to_proto() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let encoded = replay.to_proto();
+        let fields = decode(&encoded);
+        let field = |n: u32| fields.iter().filter(move |x| x.0 == n);
+
+        assert_eq!(field(1).next().unwrap().1, replay.header_size as u64);
+        assert_eq!(field(6).next().unwrap().2, replay.game_type.as_bytes());
+        assert_eq!(field(7).count(), replay.properties.len());
+        assert_eq!(field(16).count(), replay.objects.len());
+
+        let network = decode(field(10).next().unwrap().2);
+        let frames = replay.network_frames.as_ref().unwrap();
+        assert_eq!(network.len(), frames.frames.len());
+
+        // The first frame's time is encoded as a float
+        let frame = decode(network[0].2);
+        let time = f32::from_le_bytes([frame[0].2[0], frame[0].2[1], frame[0].2[2], frame[0].2[3]]);
+        assert_eq!(frame[0].0, 1);
+        assert_eq!(time, frames.frames[0].time);
+
+        let updates = frame.iter().filter(|x| x.0 == 5).count();
+        assert_eq!(updates, frames.frames[0].updated_actors.len());
+    }
+}
@@ -0,0 +1,905 @@
+//! Controls how rotations are serialized. Rigid body rotations are serialized as they are
+//! compressed in the network data, which is a rotator prior to net version 7 and a quaternion
+//! afterwards, while spawn rotations are serialized as fractions of a turn. Wrapping a serializer
+//! in `FormatRotations` converts every rotation that passes through it to a single
+//! representation, so consumers don't have to handle each representation and net version.
+
+use crate::network::{CompressedRotation, Quaternion, Rotation};
+use serde::ser::{
+    Error, Impossible, Serialize, SerializeMap, SerializeSeq, SerializeStruct,
+    SerializeStructVariant, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    Serializer,
+};
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+
+/// How rotations are written by `FormatRotations`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationFormat {
+    /// As the rotations are compressed in the network data, which is how rotations are
+    /// serialized without `FormatRotations`
+    Compressed,
+
+    /// As a unit quaternion of `x`, `y`, `z`, and `w`
+    Quaternion,
+
+    /// As the `yaw`, `pitch`, and `roll` of an Unreal rotator in degrees
+    Degrees,
+}
+
+/// A serializer that writes the rotations of rigid bodies (`CompressedRotation`) and of spawned
+/// actors (`Rotation`) in the given format before passing them along to the wrapped serializer.
+/// Everything else is passed along as is.
+///
+/// ```
+/// use boxcars::{FormatRotations, RotationFormat};
+/// use serde::Serialize;
+///
+/// let rotation = boxcars::CompressedRotation::Rotator {
+///     pitch: 0,
+///     yaw: 16384,
+///     roll: 0,
+/// };
+///
+/// let mut out = Vec::new();
+/// let mut json = serde_json::Serializer::new(&mut out);
+/// rotation
+///     .serialize(FormatRotations::new(&mut json, RotationFormat::Degrees))
+///     .unwrap();
+/// assert_eq!(out, br#"{"yaw":90.0,"pitch":0.0,"roll":0.0}"#);
+/// ```
+///
+/// Like `RoundFloats`, the wrapped serializer can be given to `ParserBuilder::serialize_frames_to`
+/// and the two can wrap each other.
+#[derive(Debug, Clone, Copy)]
+pub struct FormatRotations<S> {
+    inner: S,
+    format: RotationFormat,
+}
+
+impl<S> FormatRotations<S> {
+    pub fn new(inner: S, format: RotationFormat) -> Self {
+        FormatRotations { inner, format }
+    }
+}
+
+/// A rotation as an Unreal rotator in degrees
+#[derive(Serialize)]
+struct Degrees {
+    yaw: f32,
+    pitch: f32,
+    roll: f32,
+}
+
+impl From<Quaternion> for Degrees {
+    fn from(quaternion: Quaternion) -> Self {
+        let (pitch, yaw, roll) = quaternion.to_rotator();
+        Degrees {
+            yaw: yaw.to_degrees(),
+            pitch: pitch.to_degrees(),
+            roll: roll.to_degrees(),
+        }
+    }
+}
+
+/// The components of a rotation that has been captured from its serialized fields
+enum Captured {
+    Compressed(CompressedRotation),
+    Rotation(Rotation),
+}
+
+impl Captured {
+    fn serialize<S: Serializer>(
+        self,
+        serializer: S,
+        format: RotationFormat,
+    ) -> Result<S::Ok, S::Error> {
+        match (format, self) {
+            (RotationFormat::Compressed, Captured::Compressed(x)) => x.serialize(serializer),
+            (RotationFormat::Compressed, Captured::Rotation(x)) => x.serialize(serializer),
+            (RotationFormat::Quaternion, Captured::Compressed(x)) => {
+                x.to_quaternion().serialize(serializer)
+            }
+            (RotationFormat::Quaternion, Captured::Rotation(x)) => {
+                x.to_quaternion().serialize(serializer)
+            }
+
+            // Rotators are converted directly, which keeps whole angles whole
+            (
+                RotationFormat::Degrees,
+                Captured::Compressed(CompressedRotation::Rotator { pitch, yaw, roll }),
+            ) => {
+                let angle = |x: u16| f32::from(x as i16) / 32768.0 * 180.0;
+                Degrees {
+                    yaw: angle(yaw),
+                    pitch: angle(pitch),
+                    roll: angle(roll),
+                }
+                .serialize(serializer)
+            }
+            (RotationFormat::Degrees, Captured::Compressed(x)) => {
+                Degrees::from(x.to_quaternion()).serialize(serializer)
+            }
+            (RotationFormat::Degrees, Captured::Rotation(x)) => {
+                let angle = |x: Option<i8>| f32::from(x.unwrap_or(0)) / 128.0 * 180.0;
+                Degrees {
+                    yaw: angle(x.yaw),
+                    pitch: angle(x.pitch),
+                    roll: angle(x.roll),
+                }
+                .serialize(serializer)
+            }
+        }
+    }
+}
+
+/// A rotation whose fields are captured as they are serialized, and that is written in the
+/// format once all of its fields are seen
+pub struct Pending<S> {
+    inner: S,
+    format: RotationFormat,
+    variant: &'static str,
+    fields: Vec<(&'static str, Option<i64>)>,
+}
+
+impl<S: Serializer> Pending<S> {
+    fn new(inner: S, format: RotationFormat, variant: &'static str) -> Self {
+        Pending {
+            inner,
+            format,
+            variant,
+            fields: Vec::with_capacity(4),
+        }
+    }
+
+    fn capture<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error> {
+        let value = value.serialize(Capture(PhantomData))?;
+        self.fields.push((key, value));
+        Ok(())
+    }
+
+    fn field<T: TryFrom<i64>>(&self, key: &str) -> Result<Option<T>, S::Error> {
+        match self.fields.iter().find(|(k, _)| *k == key) {
+            Some((_, Some(x))) => T::try_from(*x)
+                .map(Some)
+                .map_err(|_| S::Error::custom(format!("rotation {} is out of range", key))),
+            _ => Ok(None),
+        }
+    }
+
+    fn required<T: TryFrom<i64>>(&self, key: &str) -> Result<T, S::Error> {
+        self.field(key)?
+            .ok_or_else(|| S::Error::custom(format!("rotation is missing {}", key)))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        let captured = match self.variant {
+            "Rotator" => Captured::Compressed(CompressedRotation::Rotator {
+                pitch: self.required("pitch")?,
+                yaw: self.required("yaw")?,
+                roll: self.required("roll")?,
+            }),
+            "Quaternion" => Captured::Compressed(CompressedRotation::Quaternion {
+                largest: self.required("largest")?,
+                a: self.required("a")?,
+                b: self.required("b")?,
+                c: self.required("c")?,
+            }),
+            _ => Captured::Rotation(Rotation {
+                yaw: self.field("yaw")?,
+                pitch: self.field("pitch")?,
+                roll: self.field("roll")?,
+            }),
+        };
+        captured.serialize(self.inner, self.format)
+    }
+}
+
+/// A value that is serialized with its rotations formatted
+struct Formatted<'b, T: ?Sized> {
+    value: &'b T,
+    format: RotationFormat,
+}
+
+impl<'b, T: ?Sized + Serialize> Serialize for Formatted<'b, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value
+            .serialize(FormatRotations::new(serializer, self.format))
+    }
+}
+
+/// Wraps the compound serializers of the wrapped serializer so that their elements have their
+/// rotations formatted
+pub struct Compound<C> {
+    inner: C,
+    format: RotationFormat,
+}
+
+impl<C> Compound<C> {
+    fn formatted<'b, T: ?Sized>(&self, value: &'b T) -> Formatted<'b, T> {
+        Formatted {
+            value,
+            format: self.format,
+        }
+    }
+}
+
+/// A struct that is either passed along or is a rotation that is being captured
+pub enum StructCompound<S: Serializer, C> {
+    Passed(Compound<C>),
+    Rotation(Pending<S>),
+}
+
+impl<S: Serializer> FormatRotations<S> {
+    fn formatted<'b, T: ?Sized>(&self, value: &'b T) -> Formatted<'b, T> {
+        Formatted {
+            value,
+            format: self.format,
+        }
+    }
+}
+
+impl<S: Serializer> Serializer for FormatRotations<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Compound<S::SerializeSeq>;
+    type SerializeTuple = Compound<S::SerializeTuple>;
+    type SerializeTupleStruct = Compound<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Compound<S::SerializeTupleVariant>;
+    type SerializeMap = Compound<S::SerializeMap>;
+    type SerializeStruct = StructCompound<S, S::SerializeStruct>;
+    type SerializeStructVariant = StructCompound<S, S::SerializeStructVariant>;
+
+    fn serialize_bool(self, v: bool) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bool(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i8(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i16(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i32(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i64(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i128(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u8(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u16(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u32(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u64(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u128(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_f32(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_f64(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_char(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_str(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_none()
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<S::Ok, S::Error> {
+        let value = self.formatted(value);
+        self.inner.serialize_some(&value)
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        let value = self.formatted(value);
+        self.inner.serialize_newtype_struct(name, &value)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        let value = self.formatted(value);
+        self.inner
+            .serialize_newtype_variant(name, variant_index, variant, &value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        let inner = self.inner.serialize_seq(len)?;
+        Ok(Compound {
+            inner,
+            format: self.format,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        let inner = self.inner.serialize_tuple(len)?;
+        Ok(Compound {
+            inner,
+            format: self.format,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        let inner = self.inner.serialize_tuple_struct(name, len)?;
+        Ok(Compound {
+            inner,
+            format: self.format,
+        })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        let inner = self
+            .inner
+            .serialize_tuple_variant(name, variant_index, variant, len)?;
+        Ok(Compound {
+            inner,
+            format: self.format,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        let inner = self.inner.serialize_map(len)?;
+        Ok(Compound {
+            inner,
+            format: self.format,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        if name == "Rotation" && self.format != RotationFormat::Compressed {
+            return Ok(StructCompound::Rotation(Pending::new(
+                self.inner,
+                self.format,
+                name,
+            )));
+        }
+
+        let inner = self.inner.serialize_struct(name, len)?;
+        Ok(StructCompound::Passed(Compound {
+            inner,
+            format: self.format,
+        }))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        if name == "CompressedRotation" && self.format != RotationFormat::Compressed {
+            return Ok(StructCompound::Rotation(Pending::new(
+                self.inner,
+                self.format,
+                variant,
+            )));
+        }
+
+        let inner = self
+            .inner
+            .serialize_struct_variant(name, variant_index, variant, len)?;
+        Ok(StructCompound::Passed(Compound {
+            inner,
+            format: self.format,
+        }))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+impl<C: SerializeSeq> SerializeSeq for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.formatted(value);
+        self.inner.serialize_element(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeTuple> SerializeTuple for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.formatted(value);
+        self.inner.serialize_element(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeTupleStruct> SerializeTupleStruct for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.formatted(value);
+        self.inner.serialize_field(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeTupleVariant> SerializeTupleVariant for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.formatted(value);
+        self.inner.serialize_field(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeMap> SerializeMap for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), C::Error> {
+        let key = self.formatted(key);
+        self.inner.serialize_key(&key)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.formatted(value);
+        self.inner.serialize_value(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S, C> SerializeStruct for StructCompound<S, C>
+where
+    S: Serializer,
+    C: SerializeStruct<Ok = S::Ok, Error = S::Error>,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error> {
+        match self {
+            StructCompound::Passed(x) => {
+                let value = x.formatted(value);
+                x.inner.serialize_field(key, &value)
+            }
+            StructCompound::Rotation(x) => x.capture(key, value),
+        }
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+        match self {
+            StructCompound::Passed(x) => x.inner.skip_field(key),
+            StructCompound::Rotation(_) => Ok(()),
+        }
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        match self {
+            StructCompound::Passed(x) => x.inner.end(),
+            StructCompound::Rotation(x) => x.end(),
+        }
+    }
+}
+
+impl<S, C> SerializeStructVariant for StructCompound<S, C>
+where
+    S: Serializer,
+    C: SerializeStructVariant<Ok = S::Ok, Error = S::Error>,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error> {
+        match self {
+            StructCompound::Passed(x) => {
+                let value = x.formatted(value);
+                x.inner.serialize_field(key, &value)
+            }
+            StructCompound::Rotation(x) => x.capture(key, value),
+        }
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+        match self {
+            StructCompound::Passed(x) => x.inner.skip_field(key),
+            StructCompound::Rotation(_) => Ok(()),
+        }
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        match self {
+            StructCompound::Passed(x) => x.inner.end(),
+            StructCompound::Rotation(x) => x.end(),
+        }
+    }
+}
+
+/// Captures the value of a compressed field (eg: of a rotation or a `Vector`), which is an
+/// (optional) integer
+pub(crate) struct Capture<E>(pub PhantomData<E>);
+
+impl<E: Error> Capture<E> {
+    fn integer<T>(v: T) -> Result<Option<i64>, E>
+    where
+        i64: TryFrom<T>,
+    {
+        i64::try_from(v)
+            .map(Some)
+            .map_err(|_| E::custom("compressed component is out of range"))
+    }
+}
+
+fn unexpected<E: Error>() -> E {
+    E::custom("expected a compressed component to be an integer")
+}
+
+impl<E: Error> Serializer for Capture<E> {
+    type Ok = Option<i64>;
+    type Error = E;
+    type SerializeSeq = Impossible<Option<i64>, E>;
+    type SerializeTuple = Impossible<Option<i64>, E>;
+    type SerializeTupleStruct = Impossible<Option<i64>, E>;
+    type SerializeTupleVariant = Impossible<Option<i64>, E>;
+    type SerializeMap = Impossible<Option<i64>, E>;
+    type SerializeStruct = Impossible<Option<i64>, E>;
+    type SerializeStructVariant = Impossible<Option<i64>, E>;
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, E> {
+        Self::integer(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, E> {
+        Self::integer(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, E> {
+        Self::integer(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, E> {
+        Self::integer(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, E> {
+        Self::integer(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, E> {
+        Self::integer(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, E> {
+        Self::integer(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, E> {
+        Self::integer(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, E> {
+        Self::integer(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, E> {
+        Self::integer(v)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, E> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, E> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, E> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, E> {
+        Err(unexpected())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, E> {
+        Err(unexpected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParserBuilder;
+    use serde_json::Value;
+
+    fn to_json<T: Serialize>(value: &T, format: RotationFormat) -> String {
+        let mut out = Vec::new();
+        let mut json = serde_json::Serializer::new(&mut out);
+        value
+            .serialize(FormatRotations::new(&mut json, format))
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn to_value<T: Serialize>(value: &T, format: RotationFormat) -> Value {
+        serde_json::from_str(&to_json(value, format)).unwrap()
+    }
+
+    #[test]
+    fn test_format_rotations() {
+        let rotator = CompressedRotation::Rotator {
+            pitch: 0,
+            yaw: 16384,
+            roll: 0,
+        };
+        let mid = 1 << 17;
+        let quaternion = CompressedRotation::Quaternion {
+            largest: 3,
+            a: mid,
+            b: mid,
+            c: (1 << 18) - 1,
+        };
+        let spawn = Rotation {
+            yaw: Some(64),
+            pitch: None,
+            roll: None,
+        };
+
+        // The same quarter turn of yaw in each compressed form is formatted the same
+        for format in [RotationFormat::Quaternion, RotationFormat::Degrees] {
+            let expected = to_value(&rotator, format);
+            for value in [to_value(&quaternion, format), to_value(&spawn, format)] {
+                let fields = value.as_object().unwrap();
+                assert_eq!(fields.len(), expected.as_object().unwrap().len());
+                for (key, x) in fields {
+                    let x = x.as_f64().unwrap();
+                    assert!(
+                        (x - expected[key].as_f64().unwrap()).abs() < 1e-3,
+                        "{}",
+                        key
+                    );
+                }
+            }
+        }
+
+        assert_eq!(
+            to_value(&spawn, RotationFormat::Degrees),
+            serde_json::json!({"yaw": 90.0, "pitch": 0.0, "roll": 0.0})
+        );
+        assert_eq!(
+            to_value(&quaternion, RotationFormat::Compressed),
+            serde_json::to_value(quaternion).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_format_replay() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let expected = serde_json::to_string(&replay).unwrap();
+        assert_eq!(to_json(&replay, RotationFormat::Compressed), expected);
+
+        // Every rigid body's rotation is a quaternion
+        let value = to_value(&replay, RotationFormat::Quaternion);
+        let mut stack = vec![&value];
+        let mut rotations = 0;
+        while let Some(x) = stack.pop() {
+            match x {
+                Value::Array(arr) => stack.extend(arr.iter()),
+                Value::Object(obj) => {
+                    if let Some(Value::Object(body)) = obj.get("RigidBody") {
+                        let rotation = body["rotation"].as_object().unwrap();
+                        assert!(rotation.contains_key("w"));
+                        rotations += 1;
+                    }
+                    stack.extend(obj.values())
+                }
+                _ => {}
+            }
+        }
+        assert!(rotations > 0);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::parser::BodySection;
+use std::ops::Range;
+
+/// Where each section of a replay begins and ends, for forensic tooling and for patching a
+/// section of a replay in place. The ranges are of bytes from the start of the replay. The
+/// sections of the body start at their length prefix, so the network data's range includes its
+/// size while `ReplayBody::network_offset` is after it.
+///
+/// ```
+/// use boxcars::BodySection;
+///
+/// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+/// let (replay, sections) = boxcars::ParserBuilder::new(&data[..])
+///     .never_parse_network_data()
+///     .parse_sections()
+///     .unwrap();
+///
+/// let names = sections.section(BodySection::Names).unwrap();
+/// // The names are preceded by their count and the first name by its length
+/// assert_eq!(&data[names.start + 8..names.start + 17], b"Countdown");
+/// assert_eq!(sections.content.end, data.len());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct ReplaySections {
+    /// The header data that the header crc is of, which follows the header's size and crc
+    pub header: Range<usize>,
+
+    /// The header properties, up to and including the "None" that ends them
+    pub properties: Range<usize>,
+
+    /// The body and footer data that the content crc is of, which follows the content's size
+    /// and crc
+    pub content: Range<usize>,
+
+    /// The sections of the body and footer in the order of the replay data
+    pub body: Vec<(BodySection, Range<usize>)>,
+
+    /// The content after the net cache that isn't parsed, which is four zero bytes in newer
+    /// replays
+    pub trailing: Range<usize>,
+}
+
+impl ReplaySections {
+    /// The range of the section of the body or footer
+    pub fn section(&self, section: BodySection) -> Option<Range<usize>> {
+        self.body
+            .iter()
+            .find(|(x, _)| *x == section)
+            .map(|(_, range)| range.clone())
+    }
+
+    /// Records that the section of the body starts where the previous section ended. The body
+    /// is parsed again from its first section (eg: when salvaging a partial replay), so it then
+    /// starts over.
+    pub(crate) fn start(&mut self, section: BodySection, offset: usize) {
+        if section == BodySection::Levels {
+            self.body.clear();
+        }
+
+        self.end(offset);
+        self.body.push((section, offset..offset));
+    }
+
+    /// Records that the last section of the body ended at the offset
+    pub(crate) fn end(&mut self, offset: usize) {
+        if let Some((_, range)) = self.body.last_mut() {
+            range.end = offset;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BodySection, ParserBuilder};
+
+    #[test]
+    fn test_parse_sections() {
+        for entry in std::fs::read_dir("assets/replays/good").unwrap() {
+            let path = entry.unwrap().path();
+            let data = std::fs::read(&path).unwrap();
+            let (replay, sections) = ParserBuilder::new(&data)
+                .never_parse_network_data()
+                .parse_sections()
+                .unwrap();
+
+            assert_eq!(sections.header, 8..8 + replay.header_size as usize);
+            assert!(sections.header.start < sections.properties.start);
+            assert_eq!(sections.properties.end, sections.header.end);
+            assert_eq!(sections.content.start, sections.header.end + 8);
+            assert_eq!(sections.content.end, data.len());
+
+            // The sections of the body are back to back and fill the content up to the trailing
+            // data
+            let body: Vec<_> = sections.body.iter().map(|(x, _)| *x).collect();
+            assert_eq!(body, BodySection::ALL);
+            let mut offset = sections.content.start;
+            for (_, range) in &sections.body {
+                assert_eq!(range.start, offset);
+                offset = range.end;
+            }
+            assert_eq!(sections.trailing, offset..sections.content.end);
+            assert!(data[sections.trailing.clone()].iter().all(|x| *x == 0));
+
+            let network = sections.section(BodySection::NetworkData).unwrap();
+            let network_data = &data[network.start + 4..network.end];
+            assert_eq!(network_data, replay.network_data);
+        }
+    }
+}
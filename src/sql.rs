@@ -0,0 +1,279 @@
+//! Writes a replay as a SQL script of normalized tables. The script targets SQLite and is loaded
+//! with `sqlite3 replays.db < replay.sql`, which keeps the feature free of a SQLite dependency.
+
+use crate::models::Replay;
+use crate::network::{Attribute, Vector3f};
+use std::fmt::Display;
+use std::io::{self, Write};
+
+const SCHEMA: &str = "\
+CREATE TABLE IF NOT EXISTS replays (
+  id TEXT PRIMARY KEY,
+  match_guid TEXT,
+  game_type TEXT NOT NULL,
+  major_version INTEGER NOT NULL,
+  minor_version INTEGER NOT NULL,
+  net_version INTEGER,
+  map_name TEXT
+);
+CREATE TABLE IF NOT EXISTS players (
+  replay_id TEXT NOT NULL REFERENCES replays(id),
+  name TEXT,
+  platform TEXT,
+  online_id TEXT,
+  split_screen_index INTEGER NOT NULL,
+  team INTEGER,
+  score INTEGER,
+  goals INTEGER,
+  assists INTEGER,
+  saves INTEGER,
+  shots INTEGER,
+  bot INTEGER
+);
+CREATE TABLE IF NOT EXISTS frames (
+  replay_id TEXT NOT NULL REFERENCES replays(id),
+  frame INTEGER NOT NULL,
+  time REAL NOT NULL,
+  delta REAL NOT NULL,
+  PRIMARY KEY (replay_id, frame)
+);
+CREATE TABLE IF NOT EXISTS rigid_bodies (
+  replay_id TEXT NOT NULL REFERENCES replays(id),
+  frame INTEGER NOT NULL,
+  actor_id INTEGER NOT NULL,
+  sleeping INTEGER NOT NULL,
+  x REAL, y REAL, z REAL,
+  qx REAL, qy REAL, qz REAL, qw REAL,
+  vx REAL, vy REAL, vz REAL,
+  avx REAL, avy REAL, avz REAL
+);
+CREATE TABLE IF NOT EXISTS events (
+  replay_id TEXT NOT NULL REFERENCES replays(id),
+  frame INTEGER,
+  kind TEXT NOT NULL,
+  player_name TEXT,
+  team INTEGER
+);
+";
+
+impl<'a> Replay<'a> {
+    /// Writes the replay as a SQLite script that creates (if necessary) and fills the `replays`,
+    /// `players`, `frames`, `rigid_bodies`, and `events` tables. Rows are keyed by the replay's
+    /// "Id" header property so that the scripts of many replays can be loaded into the same
+    /// database. Events are the replay's tick marks and goals, and rigid bodies are the
+    /// decompressed rigid body updates of each frame.
+    ///
+    /// ```
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..]).parse().unwrap();
+    /// let mut script = Vec::new();
+    /// replay.write_sql(&mut script).unwrap();
+    /// assert!(String::from_utf8(script).unwrap().starts_with("BEGIN;"));
+    /// ```
+    pub fn write_sql<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut w = io::BufWriter::new(writer);
+        let id = self.sql_id();
+        let id = Text(&id);
+        let str_prop = |key: &str| self.get_property(key).and_then(|x| x.as_str());
+
+        writeln!(w, "BEGIN;")?;
+        w.write_all(SCHEMA.as_bytes())?;
+        writeln!(
+            w,
+            "INSERT INTO replays VALUES ({}, {}, {}, {}, {}, {}, {});",
+            id,
+            Nullable(self.match_guid().map(Text)),
+            Text(&self.game_type),
+            self.major_version,
+            self.minor_version,
+            Nullable(self.net_version),
+            Nullable(str_prop("MapName").map(Text)),
+        )?;
+
+        for player in self.player_stats() {
+            writeln!(
+                w,
+                "INSERT INTO players VALUES ({}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {});",
+                id,
+                Nullable(player.name.map(Text)),
+                Nullable(player.platform.map(Text)),
+                Nullable(player.online_id.map(|x| Text(x.to_string()))),
+                player.split_screen_index,
+                Nullable(player.team),
+                Nullable(player.score),
+                Nullable(player.goals),
+                Nullable(player.assists),
+                Nullable(player.saves),
+                Nullable(player.shots),
+                Nullable(player.bot.map(u8::from)),
+            )?;
+        }
+
+        for tick_mark in &self.tick_marks {
+            writeln!(
+                w,
+                "INSERT INTO events VALUES ({}, {}, {}, NULL, NULL);",
+                id,
+                tick_mark.frame,
+                Text(&tick_mark.description),
+            )?;
+        }
+
+        for goal in self.goals() {
+            writeln!(
+                w,
+                "INSERT INTO events VALUES ({}, {}, 'Goal', {}, {});",
+                id,
+                Nullable(goal.frame),
+                Nullable(goal.player_name.map(Text)),
+                Nullable(goal.player_team),
+            )?;
+        }
+
+        let net_version = self.net_version.unwrap_or(0);
+        let frames = self
+            .network_frames
+            .as_ref()
+            .map(|x| &x.frames[..])
+            .unwrap_or(&[]);
+        for (i, frame) in frames.iter().enumerate() {
+            writeln!(
+                w,
+                "INSERT INTO frames VALUES ({}, {}, {}, {});",
+                id,
+                i,
+                Real(frame.time),
+                Real(frame.delta),
+            )?;
+
+            for update in frame.updated_actors.iter() {
+                let body = match update.attribute {
+                    Attribute::RigidBody(body) => body.decompress(net_version),
+                    _ => continue,
+                };
+
+                let rotation = body.rotation;
+                writeln!(
+                    w,
+                    "INSERT INTO rigid_bodies VALUES ({}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {});",
+                    id,
+                    i,
+                    update.actor_id.0,
+                    u8::from(body.sleeping),
+                    Reals(Some(body.location)),
+                    Real(rotation.x),
+                    Real(rotation.y),
+                    Real(rotation.z),
+                    Real(rotation.w),
+                    Reals(body.linear_velocity),
+                    Reals(body.angular_velocity),
+                )?;
+            }
+        }
+
+        writeln!(w, "COMMIT;")?;
+        w.flush()
+    }
+
+    /// The key of the replay's rows, which falls back to the content crc for replays without an
+    /// "Id" header property
+    fn sql_id(&self) -> String {
+        self.get_property("Id")
+            .and_then(|x| x.as_str())
+            .map(String::from)
+            .unwrap_or_else(|| format!("{:08X}", self.content_crc))
+    }
+}
+
+/// A string literal, where single quotes are escaped by doubling them
+struct Text<T: AsRef<str>>(T);
+
+impl<T: AsRef<str>> Display for Text<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}'", self.0.as_ref().replace('\'', "''"))
+    }
+}
+
+struct Nullable<T>(Option<T>);
+
+impl<T: Display> Display for Nullable<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(ref x) => x.fmt(f),
+            None => f.write_str("NULL"),
+        }
+    }
+}
+
+/// A float, which is NULL when it isn't finite as SQL has no literal for NaN or infinity
+struct Real(f32);
+
+impl Display for Real {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_finite() {
+            write!(f, "{:?}", self.0)
+        } else {
+            f.write_str("NULL")
+        }
+    }
+}
+
+/// The three columns of a vector
+struct Reals(Option<Vector3f>);
+
+impl Display for Reals {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(v) => write!(f, "{}, {}, {}", Real(v.x), Real(v.y), Real(v.z)),
+            None => f.write_str("NULL, NULL, NULL"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParserBuilder;
+
+    #[test]
+    fn test_sql_literals() {
+        assert_eq!(Text("it's").to_string(), "'it''s'");
+        assert_eq!(Nullable::<i32>(None).to_string(), "NULL");
+        assert_eq!(Real(1.5).to_string(), "1.5");
+        assert_eq!(Real(f32::NAN).to_string(), "NULL");
+    }
+
+    #[test]
+    fn test_write_sql() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let mut script = Vec::new();
+        replay.write_sql(&mut script).unwrap();
+        let script = String::from_utf8(script).unwrap();
+        let rows = |table: &str| {
+            let prefix = format!("INSERT INTO {} VALUES ('", table);
+            script.lines().filter(|x| x.starts_with(&prefix)).count()
+        };
+
+        let frames = &replay.network_frames.as_ref().unwrap().frames;
+        let bodies = frames
+            .iter()
+            .flat_map(|x| x.updated_actors.iter())
+            .filter(|x| matches!(x.attribute, Attribute::RigidBody(_)))
+            .count();
+
+        assert_eq!(rows("replays"), 1);
+        assert_eq!(rows("players"), replay.player_stats().len());
+        assert_eq!(rows("frames"), frames.len());
+        assert_eq!(rows("rigid_bodies"), bodies);
+        assert_eq!(
+            rows("events"),
+            replay.tick_marks.len() + replay.goals().len()
+        );
+        assert!(script.ends_with("COMMIT;\n"));
+    }
+}
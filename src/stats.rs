@@ -0,0 +1,115 @@
+use std::time::Instant;
+
+/// Measurements of parsing a replay, for monitoring the performance of parsing and finding
+/// anomalous replays (eg: a replay that spawns an unusual number of actors)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct ParseStats {
+    /// Microseconds spent parsing the header, including its crc check
+    pub header_us: u64,
+
+    /// Microseconds spent parsing the body, including its crc check
+    pub body_us: u64,
+
+    /// Microseconds spent computing the crcs of the header and body, which is part of their
+    /// times unless the crc of a section was computed on another thread while the section was
+    /// parsed (see `CrcCheck::Always`)
+    pub crc_us: u64,
+
+    /// Microseconds spent decoding the network data
+    pub network_us: u64,
+
+    /// The number of frames decoded
+    pub frames: usize,
+
+    /// The number of new actors decoded
+    pub actors_created: usize,
+
+    /// The number of attribute updates decoded
+    pub attributes_decoded: usize,
+
+    /// The size of the replay
+    pub bytes: usize,
+
+    /// The number of bits left in the network data after decoding the frames, which is all
+    /// of the network data when it isn't decoded
+    pub bits_unread: usize,
+}
+
+/// Measures the time between laps when enabled. Parsing only reads the clock when asked for
+/// stats, as not every platform has a clock (eg: wasm).
+#[derive(Debug)]
+pub(crate) struct Stopwatch(Option<Instant>);
+
+impl Stopwatch {
+    pub fn new(enabled: bool) -> Self {
+        Stopwatch(if enabled { Some(Instant::now()) } else { None })
+    }
+
+    /// Microseconds since the previous lap
+    pub fn lap(&mut self) -> u64 {
+        match self.0.as_mut() {
+            Some(last) => {
+                let now = Instant::now();
+                let elapsed = now.duration_since(*last).as_micros() as u64;
+                *last = now;
+                elapsed
+            }
+            None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ParserBuilder;
+
+    #[test]
+    fn test_parse_with_stats() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let (replay, stats) = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse_with_stats()
+            .unwrap();
+
+        let frames = replay.network_frames.unwrap().frames;
+        assert_eq!(stats.frames, frames.len());
+        assert_eq!(
+            stats.actors_created,
+            frames.iter().map(|x| x.new_actors.len()).sum::<usize>()
+        );
+        assert_eq!(
+            stats.attributes_decoded,
+            frames.iter().map(|x| x.updated_actors.len()).sum::<usize>()
+        );
+        assert_eq!(stats.bytes, data.len());
+        assert_eq!(stats.bits_unread, 736);
+
+        // The counts are of the decoded network data
+        let (_, sampled) = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .sample_rate(10)
+            .parse_with_stats()
+            .unwrap();
+        assert_eq!(sampled.frames, stats.frames);
+        assert_eq!(sampled.attributes_decoded, stats.attributes_decoded);
+
+        let (replay, skipped) = ParserBuilder::new(&data[..])
+            .never_parse_network_data()
+            .parse_with_stats()
+            .unwrap();
+        assert!(replay.network_frames.is_none());
+        assert_eq!(skipped.frames, 0);
+        assert!(skipped.bits_unread > stats.bits_unread);
+
+        // Crcs are only timed when they are computed
+        let (_, checked) = ParserBuilder::new(&data[..])
+            .always_check_crc()
+            .never_parse_network_data()
+            .parse_with_stats()
+            .unwrap();
+        assert!(checked.crc_us > 0);
+        assert_eq!(skipped.crc_us, 0);
+    }
+}
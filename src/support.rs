@@ -0,0 +1,140 @@
+use crate::hashes::{ATTRIBUTES, OBJECT_CLASSES, SPAWN_STATS};
+use crate::models::Replay;
+use crate::network::{normalize_object, AttributeTag};
+use crate::version::ReplayVersion;
+use std::ops::Deref;
+
+/// An attribute that boxcars can decode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct SupportedAttribute {
+    /// The name of the attribute's object (eg: `TAGame.RBActor_TA:ReplicatedRBState`)
+    pub name: &'static str,
+
+    /// The `Attribute` variant that the attribute decodes into (eg: `RigidBody`)
+    pub kind: &'static str,
+}
+
+/// The attributes and classes that boxcars can decode for a replay version
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct SupportedAttributes {
+    pub version: ReplayVersion,
+
+    /// Sorted by name
+    pub attributes: Vec<SupportedAttribute>,
+
+    /// The classes that objects are resolved to so that their attributes can be decoded, sorted
+    /// by name
+    pub classes: Vec<&'static str>,
+}
+
+impl SupportedAttributes {
+    /// Returns true if the attribute with the given object name can be decoded
+    pub fn supports_attribute(&self, name: &str) -> bool {
+        self.kind_of(name).is_some()
+    }
+
+    /// The `Attribute` variant that the attribute with the given object name decodes into
+    pub fn kind_of(&self, name: &str) -> Option<&'static str> {
+        let name = normalize_object(name);
+        self.attributes
+            .binary_search_by(|x| x.name.cmp(name))
+            .ok()
+            .map(|i| self.attributes[i].kind)
+    }
+
+    /// Returns true if actors of the given object are known to spawn correctly. An unknown object
+    /// is assumed to spawn without a trajectory, which may be wrong.
+    pub fn supports_object(&self, name: &str) -> bool {
+        SPAWN_STATS.contains_key(name) || OBJECT_CLASSES.contains_key(normalize_object(name))
+    }
+
+    /// The attributes in the replay's net cache that can't be decoded, which only cause an error
+    /// when they are replicated in the network data. It's common for a replay to contain a few
+    /// unsupported attributes that are never replicated, so a replay with unsupported attributes
+    /// may still parse. Determining the attributes only requires the replay's header and body,
+    /// so the network data needn't be decoded.
+    pub fn unsupported_attributes<'b>(&self, replay: &'b Replay<'_>) -> Vec<&'b str> {
+        let mut unsupported: Vec<&str> = replay
+            .net_cache
+            .iter()
+            .flat_map(|x| x.properties.iter())
+            .filter_map(|x| replay.objects.get(x.object_ind as usize))
+            .map(Deref::deref)
+            .filter(|x| !self.supports_attribute(x))
+            .collect();
+        unsupported.sort_unstable();
+        unsupported.dedup();
+        unsupported
+    }
+}
+
+/// Returns the attributes and classes that boxcars can decode for replays of the given version.
+/// Every version that boxcars parses decodes the same attributes today, though how an attribute
+/// is decoded depends on the version (see `Feature`).
+///
+/// ```
+/// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+/// let replay = boxcars::ParserBuilder::new(&data[..])
+///     .never_parse_network_data()
+///     .parse()
+///     .unwrap();
+///
+/// let supported = boxcars::supported_attributes(replay.version());
+/// assert_eq!(supported.kind_of("TAGame.RBActor_TA:ReplicatedRBState"), Some("RigidBody"));
+/// assert!(supported.supports_object("Archetypes.Ball.Ball_Default"));
+/// println!("{:?}", supported.unsupported_attributes(&replay));
+/// ```
+pub fn supported_attributes(version: ReplayVersion) -> SupportedAttributes {
+    let mut attributes: Vec<_> = ATTRIBUTES
+        .entries()
+        .filter(|(_, tag)| **tag != AttributeTag::NotImplemented)
+        .map(|(name, tag)| SupportedAttribute {
+            name,
+            kind: tag.name(),
+        })
+        .collect();
+    attributes.sort_unstable();
+
+    let mut classes: Vec<_> = OBJECT_CLASSES.values().cloned().collect();
+    classes.sort_unstable();
+    classes.dedup();
+
+    SupportedAttributes {
+        version,
+        attributes,
+        classes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParserBuilder;
+
+    #[test]
+    fn test_unsupported_attributes() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .never_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let supported = supported_attributes(replay.version());
+        assert!(supported
+            .attributes
+            .windows(2)
+            .all(|x| x[0].name < x[1].name));
+        assert!(supported.supports_attribute("TAGame.Ball_TA:HitTeamNum"));
+        assert!(supported.supports_object("TAGame.Default__PRI_TA"));
+        assert!(!supported.supports_object("TAGame.Ball_Unknown_TA"));
+
+        let unsupported = supported.unsupported_attributes(&replay);
+        assert!(!unsupported.is_empty());
+        assert!(unsupported.iter().all(|x| !supported.supports_attribute(x)));
+        assert!(!unsupported.contains(&"TAGame.RBActor_TA:ReplicatedRBState"));
+    }
+}
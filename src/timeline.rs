@@ -0,0 +1,178 @@
+//! Derives a single time-ordered timeline of the notable moments of a match (kickoffs, goals,
+//! saves, demolitions, boost pickups, and the start of overtime) from the header's goals, the
+//! tick marks, and the attributes of the network frames. Not to be confused with `events`,
+//! which yields the replay's data as it is parsed.
+
+use crate::models::{GoalEvent, Replay};
+use crate::network::attributes::Attribute;
+use crate::network::{ActorId, FrameIndex};
+use std::convert::TryFrom;
+
+/// A moment of the match on the timeline (see `Replay::timeline`)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct TimelineEvent<'a> {
+    pub frame: Option<i32>,
+
+    /// The time of the event's frame in seconds, from the network frames when they are decoded,
+    /// else estimated from the keyframes
+    pub time: Option<f32>,
+    pub kind: TimelineKind<'a>,
+}
+
+/// What happened at a moment of the match
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub enum TimelineKind<'a> {
+    /// The kickoff countdown ended and the cars were released
+    Kickoff,
+    Goal(GoalEvent<'a>),
+
+    /// A save tick mark of the team (0 for blue and 1 for orange)
+    Save {
+        team: u8,
+    },
+
+    /// A car demolished another car (or itself). The actors are the cars.
+    Demolition {
+        attacker: Option<ActorId>,
+        victim: Option<ActorId>,
+    },
+
+    /// A car picked up the boost pad (or boost pickup) actor
+    BoostPickup {
+        pickup: ActorId,
+        car: Option<ActorId>,
+    },
+    OvertimeStart,
+}
+
+impl<'a> Replay<'a> {
+    /// The timeline of the match in the order of the frames. Goals and saves are derived from the
+    /// header and the tick marks, so they are available without the network frames. Kickoffs,
+    /// demolitions, boost pickups, and the start of overtime are derived from the network
+    /// frames, so they are only included when the frames are decoded and can be indexed.
+    ///
+    /// ```
+    /// use boxcars::TimelineKind;
+    ///
+    /// # let data = include_bytes!("../assets/replays/good/rumble.replay");
+    /// let replay = boxcars::ParserBuilder::new(&data[..])
+    ///     .must_parse_network_data()
+    ///     .parse()
+    ///     .unwrap();
+    ///
+    /// let timeline = replay.timeline();
+    /// let goals = timeline
+    ///     .iter()
+    ///     .filter(|x| matches!(x.kind, TimelineKind::Goal(_)))
+    ///     .count();
+    /// assert_eq!(goals, 7);
+    /// ```
+    pub fn timeline(&self) -> Vec<TimelineEvent<'_>> {
+        let mut events: Vec<TimelineEvent<'_>> = self
+            .goal_events()
+            .into_iter()
+            .map(|goal| TimelineEvent {
+                frame: goal.frame,
+                time: goal.time,
+                kind: TimelineKind::Goal(goal),
+            })
+            .collect();
+
+        for mark in &self.tick_marks {
+            let team = match mark.description.as_ref() {
+                "Team0Save" => 0,
+                "Team1Save" => 1,
+                _ => continue,
+            };
+
+            events.push(TimelineEvent {
+                frame: Some(mark.frame),
+                time: self.frame_time(mark.frame),
+                kind: TimelineKind::Save { team },
+            });
+        }
+
+        let index = self
+            .network_frames
+            .as_ref()
+            .and_then(|_| FrameIndex::new(self).ok());
+        if let Some(index) = index {
+            self.network_events(&index, &mut events);
+        }
+
+        // Events without a frame are placed last
+        events.sort_by_key(|x| (x.frame.is_none(), x.frame));
+        events
+    }
+
+    fn network_events(&self, index: &FrameIndex<'_>, events: &mut Vec<TimelineEvent<'_>>) {
+        let mut push = |frame: usize, time: f32, kind| {
+            events.push(TimelineEvent {
+                frame: i32::try_from(frame).ok(),
+                time: Some(time),
+                kind,
+            })
+        };
+
+        let flagged = |flag: bool, id: u32| Some(ActorId(id as i32)).filter(|_| flag);
+
+        // The game state's countdown is replicated in all versions, unlike the round countdown
+        let countdown = self.object_id("TAGame.GameEvent_TA:ReplicatedGameStateTimeRemaining");
+        for (frame, time, _, x) in countdown.into_iter().flat_map(|x| index.updates_of(x)) {
+            if *x == Attribute::Int(0) {
+                push(frame, time, TimelineKind::Kickoff);
+            }
+        }
+
+        let demolitions = [
+            "TAGame.Car_TA:ReplicatedDemolish",
+            "TAGame.Car_TA:ReplicatedDemolishExtended",
+            "TAGame.Car_TA:ReplicatedDemolishGoalExplosion",
+        ];
+        let demolitions = demolitions.iter().filter_map(|x| self.object_id(x));
+        for (frame, time, _, x) in demolitions.flat_map(|x| index.updates_of(x)) {
+            let (attacker, victim) = match x {
+                Attribute::Demolish(x) => (
+                    flagged(x.attacker_flag, x.attacker_actor_id),
+                    flagged(x.victim_flag, x.victim_actor_id),
+                ),
+                Attribute::DemolishExtended(x) => (
+                    flagged(x.attacker_flag, x.attacker_actor_id),
+                    flagged(x.victim_flag, x.victim_actor_id),
+                ),
+                _ => continue,
+            };
+            push(frame, time, TimelineKind::Demolition { attacker, victim });
+        }
+
+        // Older pickups are replicated as picked up (and later as not picked up), while newer
+        // pickups replicate a byte that changes with each pickup
+        let pickups = [
+            "TAGame.VehiclePickup_TA:ReplicatedPickupData",
+            "TAGame.VehiclePickup_TA:NewReplicatedPickupData",
+        ];
+        let pickups = pickups.iter().filter_map(|x| self.object_id(x));
+        for (frame, time, pickup, x) in pickups.flat_map(|x| index.updates_of(x)) {
+            let car = match x {
+                Attribute::Pickup(x) if x.picked_up => x.instigator_id,
+                Attribute::PickupNew(x) if x.instigator_id.is_some() => x.instigator_id,
+                _ => continue,
+            };
+
+            let car = car.map(|x| ActorId(x as i32));
+            push(frame, time, TimelineKind::BoostPickup { pickup, car });
+        }
+
+        let overtime = self.object_id("TAGame.GameEvent_Soccar_TA:bOverTime");
+        let start = overtime
+            .into_iter()
+            .flat_map(|x| index.updates_of(x))
+            .find(|(_, _, _, x)| **x == Attribute::Boolean(true));
+        if let Some((frame, time, _, _)) = start {
+            push(frame, time, TimelineKind::OvertimeStart);
+        }
+    }
+}
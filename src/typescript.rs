@@ -0,0 +1,125 @@
+//! TypeScript definitions of the JSON output so that web consumers can type check their use of
+//! a serialized replay.
+
+const DEFINITIONS: &str = include_str!("boxcars.d.ts");
+
+/// Returns the contents of a `.d.ts` file that describes a replay serialized to JSON. The field
+/// names follow the casing of the serialized output, so the definitions are in camel case when
+/// the `camel-case` feature is enabled.
+///
+/// ```
+/// let definitions = boxcars::typescript::definitions();
+/// assert!(definitions.contains("export interface Replay {"));
+/// ```
+pub fn definitions() -> String {
+    if cfg!(feature = "camel-case") {
+        DEFINITIONS
+            .lines()
+            .map(|line| camel_case_property(line) + "\n")
+            .collect()
+    } else {
+        String::from(DEFINITIONS)
+    }
+}
+
+/// Rewrites the property name of an interface's line (eg: `  header_size: number;`)
+fn camel_case_property(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    let name = match trimmed.find(':') {
+        Some(i) if !indent.is_empty() => &trimmed[..i],
+        _ => return String::from(line),
+    };
+
+    // Union members (eg: `| { Boolean: boolean }`) are enum variants, which keep their name
+    if !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return String::from(line);
+    }
+
+    let mut result = String::from(indent);
+    for (i, part) in name.split('_').enumerate() {
+        let mut chars = part.chars();
+        match chars.next() {
+            Some(c) if i > 0 => {
+                result.extend(c.to_uppercase());
+                result.push_str(chars.as_str());
+            }
+            _ => result.push_str(part),
+        }
+    }
+    result.push_str(&trimmed[name.len()..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParserBuilder;
+    use serde_json::Value;
+    use std::collections::BTreeSet;
+
+    fn collect_keys(value: &Value, keys: &mut BTreeSet<String>) {
+        match value {
+            Value::Array(x) => x.iter().for_each(|x| collect_keys(x, keys)),
+            Value::Object(x) => {
+                for (key, value) in x {
+                    // Header properties are keyed by their name rather than a field
+                    if key != "properties" {
+                        keys.insert(key.clone());
+                        collect_keys(value, keys);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_definitions_cover_output() {
+        let definitions = definitions();
+        let replays: [&[u8]; 3] = [
+            include_bytes!("../assets/replays/good/rumble.replay"),
+            include_bytes!("../assets/replays/good/fc427.replay"),
+            include_bytes!("../assets/replays/partial/windows_1252.replay"),
+        ];
+
+        let mut keys = BTreeSet::new();
+        for data in replays.iter() {
+            let replay = ParserBuilder::new(data)
+                .must_parse_network_data()
+                .parse()
+                .unwrap();
+            collect_keys(&serde_json::to_value(&replay).unwrap(), &mut keys);
+        }
+
+        // Every field and enum variant in the output must be defined
+        let missing: Vec<_> = keys
+            .iter()
+            .filter(|key| {
+                !definitions.contains(&format!(" {}:", key))
+                    && !definitions.contains(&format!(" {}?:", key))
+            })
+            .collect();
+        assert!(missing.is_empty(), "undefined keys: {:?}", missing);
+    }
+
+    #[test]
+    fn test_camel_case_property() {
+        assert_eq!(
+            camel_case_property("  header_size: number;"),
+            "  headerSize: number;"
+        );
+        assert_eq!(
+            camel_case_property("  frames: Frame[];"),
+            "  frames: Frame[];"
+        );
+        assert_eq!(
+            camel_case_property("  | { Boolean: boolean }"),
+            "  | { Boolean: boolean }"
+        );
+        assert_eq!(
+            camel_case_property("export interface Replay {"),
+            "export interface Replay {"
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! The parser of the little endian, length prefixed data that makes up the header and body of a
+//! replay, for reading other files serialized by Unreal Engine 3 (eg: the properties of a save
+//! file).
+//!
+//! Strings are prefixed with their length (including the null terminator), where a negative
+//! length denotes a UTF-16 string. Lists are prefixed with their number of elements. Properties
+//! are a sequence of a name, a type, and a value, ending with a property named "None".
+//!
+//! ```
+//! use boxcars::unreal::UnrealParser;
+//!
+//! # let data = include_bytes!("../assets/replays/good/rumble.replay");
+//! let mut parser = UnrealParser::new(&data[..]);
+//!
+//! // The header's size and crc
+//! parser.skip(8).unwrap();
+//! let major_version = parser.read_i32().unwrap();
+//! let minor_version = parser.read_i32().unwrap();
+//! assert_eq!(parser.read_text().unwrap(), "TAGame.Replay_Soccar_TA");
+//!
+//! let properties = parser.read_properties().unwrap();
+//! assert!(properties.iter().any(|(key, _)| *key == "MapName"));
+//! ```
+
+use crate::core_parser::CoreParser;
+use crate::header::parse_rdict;
+use crate::models::HeaderProp;
+use crate::parsing_utils::{le_f32, le_i32, le_u64};
+use std::borrow::Cow;
+
+pub use crate::errors::UnrealError;
+
+/// Reads values from the front of a slice of data
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnrealParser<'a> {
+    core: CoreParser<'a>,
+}
+
+impl<'a> UnrealParser<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        UnrealParser {
+            core: CoreParser::new(data),
+        }
+    }
+
+    /// The number of bytes read
+    pub fn position(&self) -> usize {
+        self.core.bytes_read() as usize
+    }
+
+    /// The data left to read
+    pub fn remaining(&self) -> &'a [u8] {
+        self.core.remaining()
+    }
+
+    pub fn skip(&mut self, bytes: usize) -> Result<(), UnrealError> {
+        Ok(self.core.take(bytes, |_| ())?)
+    }
+
+    pub fn read_bytes(&mut self, bytes: usize) -> Result<&'a [u8], UnrealError> {
+        Ok(self.core.take(bytes, |d| d)?)
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, UnrealError> {
+        Ok(self.core.take(4, le_i32)?)
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, UnrealError> {
+        Ok(self.core.take(4, |d| le_i32(d) as u32)?)
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, UnrealError> {
+        Ok(self.core.take(8, le_u64)?)
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, UnrealError> {
+        Ok(self.core.take(4, le_f32)?)
+    }
+
+    /// Reads a length prefixed UTF-8 string, which Unreal uses for names (eg: property names)
+    pub fn read_str(&mut self) -> Result<&'a str, UnrealError> {
+        Ok(self.core.parse_str()?)
+    }
+
+    /// Reads a length prefixed string that is either UTF-16 or Windows-1252 encoded
+    pub fn read_text(&mut self) -> Result<Cow<'a, str>, UnrealError> {
+        Ok(self.core.parse_text()?)
+    }
+
+    /// Reads a list of elements prefixed with the number of elements, reading each element with
+    /// the given function
+    pub fn read_list<T, F>(&mut self, mut f: F) -> Result<Vec<T>, UnrealError>
+    where
+        F: FnMut(&mut Self) -> Result<T, UnrealError>,
+    {
+        let size = self.read_i32()?;
+        let limits = self.core.limits;
+        CoreParser::repeat(size as usize, &limits, || f(self))
+    }
+
+    /// Reads a list of strings (see `read_text`)
+    pub fn read_text_list(&mut self) -> Result<Vec<Cow<'a, str>>, UnrealError> {
+        Ok(self.core.text_list()?)
+    }
+
+    /// Reads properties until the property named "None"
+    pub fn read_properties(&mut self) -> Result<Vec<(&'a str, HeaderProp<'a>)>, UnrealError> {
+        Ok(parse_rdict(&mut self.core)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParserBuilder;
+
+    #[test]
+    fn test_read_properties() {
+        let data = include_bytes!("../assets/replays/good/rumble.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .never_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let mut parser = UnrealParser::new(&data[..]);
+        parser.skip(8).unwrap();
+        assert_eq!(parser.read_i32().unwrap(), replay.major_version);
+        assert_eq!(parser.read_i32().unwrap(), replay.minor_version);
+        assert_eq!(parser.read_text().unwrap(), replay.game_type);
+        assert_eq!(parser.read_properties().unwrap(), replay.properties);
+        assert_eq!(parser.position() + parser.remaining().len(), data.len());
+    }
+
+    #[test]
+    fn test_read_list() {
+        let data = [2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3];
+        let mut parser = UnrealParser::new(&data[..]);
+        let list = parser.read_list(UnrealParser::read_u32).unwrap();
+        assert_eq!(list, vec![1, 2]);
+        assert_eq!(
+            parser.read_u32().unwrap_err(),
+            UnrealError::InsufficientData(4, 1)
+        );
+
+        // Errors of an element are returned as is
+        let mut parser = UnrealParser::new(&data[..9]);
+        let err = parser.read_list(UnrealParser::read_u32).unwrap_err();
+        assert_eq!(err, UnrealError::InsufficientData(4, 1));
+
+        let mut parser = UnrealParser::new(&[0xff, 0xff, 0xff, 0x7f]);
+        let err = parser.read_list(UnrealParser::read_u32).unwrap_err();
+        assert!(matches!(err, UnrealError::LimitExceeded(_)));
+    }
+}
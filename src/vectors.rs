@@ -0,0 +1,595 @@
+//! Controls how vectors are serialized. Vectors (eg: locations and velocities) are serialized as
+//! they are compressed in the network data: integer components offset by a bias, whose number
+//! of bits and scale depend on the replay's net version. Wrapping a serializer in
+//! `DecompressVectors` writes every vector that passes through it as a `Vector3f` in unreal
+//! units, so consumers of the output don't have to decompress vectors for each net version.
+
+use crate::models::Replay;
+use crate::network::{Vector, Vector3f};
+use crate::rotations::Capture;
+use serde::ser::{
+    Error, Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+
+/// A serializer that writes every `Vector` decompressed into a `Vector3f` before passing it
+/// along to the wrapped serializer. Velocities (the values of fields that end in `velocity`) are
+/// decompressed as velocities and every other vector as a location (see `Vector::to_velocity`
+/// and `Vector::to_vector3f`). The compressed vectors are still available from the models for
+/// callers that need the raw quantized values.
+///
+/// ```
+/// use boxcars::DecompressVectors;
+/// use serde::Serialize;
+///
+/// let vector = boxcars::Vector {
+///     bias: 1 << 17,
+///     dx: (1 << 17) + 150,
+///     dy: 1 << 17,
+///     dz: (1 << 17) - 25,
+/// };
+///
+/// let mut out = Vec::new();
+/// let mut json = serde_json::Serializer::new(&mut out);
+/// vector
+///     .serialize(DecompressVectors::new(&mut json, 7))
+///     .unwrap();
+/// assert_eq!(out, br#"{"x":1.5,"y":0.0,"z":-0.25}"#);
+/// ```
+///
+/// Like `RoundFloats`, the wrapped serializer can be given to `ParserBuilder::serialize_frames_to`
+/// and can wrap (or be wrapped by) the other serializers.
+#[derive(Debug, Clone, Copy)]
+pub struct DecompressVectors<S> {
+    inner: S,
+    net_version: i32,
+    kind: VectorKind,
+}
+
+/// What the vector that is serialized next is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VectorKind {
+    Location,
+    Velocity,
+}
+
+impl<S> DecompressVectors<S> {
+    /// Decompresses the vectors of a replay with the given net version
+    pub fn new(inner: S, net_version: i32) -> Self {
+        DecompressVectors {
+            inner,
+            net_version,
+            kind: VectorKind::Location,
+        }
+    }
+
+    /// Decompresses the vectors of the replay
+    pub fn for_replay(inner: S, replay: &Replay<'_>) -> Self {
+        DecompressVectors::new(inner, replay.net_version.unwrap_or(0))
+    }
+}
+
+/// A vector whose fields are captured as they are serialized, and that is written decompressed
+/// once all of its fields are seen
+pub struct Pending<S> {
+    inner: S,
+    net_version: i32,
+    kind: VectorKind,
+    fields: [Option<i32>; 4],
+}
+
+impl<S: Serializer> Pending<S> {
+    fn capture<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error> {
+        let index = match key {
+            "bias" => 0,
+            "dx" => 1,
+            "dy" => 2,
+            "dz" => 3,
+            _ => return Err(S::Error::custom(format!("unexpected vector field {}", key))),
+        };
+
+        let value = value.serialize(Capture(PhantomData))?;
+        let value = value.and_then(|x| i32::try_from(x).ok());
+        self.fields[index] = value;
+        Ok(())
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        let vector = match self.fields {
+            [Some(bias), Some(dx), Some(dy), Some(dz)] => Vector { bias, dx, dy, dz },
+            _ => return Err(S::Error::custom("vector is missing a component")),
+        };
+
+        let decompressed: Vector3f = match self.kind {
+            VectorKind::Location => vector.to_vector3f(self.net_version),
+            VectorKind::Velocity => vector.to_velocity(self.net_version),
+        };
+        decompressed.serialize(self.inner)
+    }
+}
+
+/// A value that is serialized with its vectors decompressed
+struct Decompressed<'b, T: ?Sized> {
+    value: &'b T,
+    net_version: i32,
+    kind: VectorKind,
+}
+
+impl<'b, T: ?Sized + Serialize> Serialize for Decompressed<'b, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(DecompressVectors {
+            inner: serializer,
+            net_version: self.net_version,
+            kind: self.kind,
+        })
+    }
+}
+
+/// Wraps the compound serializers of the wrapped serializer so that their elements have their
+/// vectors decompressed
+pub struct Compound<C> {
+    inner: C,
+    net_version: i32,
+}
+
+impl<C> Compound<C> {
+    fn decompressed<'b, T: ?Sized>(&self, value: &'b T) -> Decompressed<'b, T> {
+        Decompressed {
+            value,
+            net_version: self.net_version,
+            kind: VectorKind::Location,
+        }
+    }
+
+    /// Fields that are velocities are decompressed as velocities
+    fn field<'b, T: ?Sized>(&self, key: &str, value: &'b T) -> Decompressed<'b, T> {
+        let velocity = key.ends_with("velocity") || key.ends_with("Velocity");
+        Decompressed {
+            value,
+            net_version: self.net_version,
+            kind: if velocity {
+                VectorKind::Velocity
+            } else {
+                VectorKind::Location
+            },
+        }
+    }
+}
+
+/// A struct that is either passed along or is a vector that is being captured
+pub enum StructCompound<S: Serializer> {
+    Passed(Compound<S::SerializeStruct>),
+    Vector(Pending<S>),
+}
+
+impl<S: Serializer> DecompressVectors<S> {
+    fn decompressed<'b, T: ?Sized>(&self, value: &'b T) -> Decompressed<'b, T> {
+        Decompressed {
+            value,
+            net_version: self.net_version,
+            kind: self.kind,
+        }
+    }
+}
+
+impl<S: Serializer> Serializer for DecompressVectors<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Compound<S::SerializeSeq>;
+    type SerializeTuple = Compound<S::SerializeTuple>;
+    type SerializeTupleStruct = Compound<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Compound<S::SerializeTupleVariant>;
+    type SerializeMap = Compound<S::SerializeMap>;
+    type SerializeStruct = StructCompound<S>;
+    type SerializeStructVariant = Compound<S::SerializeStructVariant>;
+
+    fn serialize_bool(self, v: bool) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bool(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i8(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i16(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i32(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i64(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_i128(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u8(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u16(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u32(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u64(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_u128(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_f32(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_f64(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_char(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_str(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_none()
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<S::Ok, S::Error> {
+        let value = self.decompressed(value);
+        self.inner.serialize_some(&value)
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        let value = self.decompressed(value);
+        self.inner.serialize_newtype_struct(name, &value)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        let value = self.decompressed(value);
+        self.inner
+            .serialize_newtype_variant(name, variant_index, variant, &value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        let net_version = self.net_version;
+        let inner = self.inner.serialize_seq(len)?;
+        Ok(Compound { inner, net_version })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        let net_version = self.net_version;
+        let inner = self.inner.serialize_tuple(len)?;
+        Ok(Compound { inner, net_version })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        let net_version = self.net_version;
+        let inner = self.inner.serialize_tuple_struct(name, len)?;
+        Ok(Compound { inner, net_version })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        let net_version = self.net_version;
+        let inner = self
+            .inner
+            .serialize_tuple_variant(name, variant_index, variant, len)?;
+        Ok(Compound { inner, net_version })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        let net_version = self.net_version;
+        let inner = self.inner.serialize_map(len)?;
+        Ok(Compound { inner, net_version })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        if name == "Vector" {
+            return Ok(StructCompound::Vector(Pending {
+                inner: self.inner,
+                net_version: self.net_version,
+                kind: self.kind,
+                fields: [None; 4],
+            }));
+        }
+
+        let net_version = self.net_version;
+        let inner = self.inner.serialize_struct(name, len)?;
+        Ok(StructCompound::Passed(Compound { inner, net_version }))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        let net_version = self.net_version;
+        let inner = self
+            .inner
+            .serialize_struct_variant(name, variant_index, variant, len)?;
+        Ok(Compound { inner, net_version })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+impl<C: SerializeSeq> SerializeSeq for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.decompressed(value);
+        self.inner.serialize_element(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeTuple> SerializeTuple for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.decompressed(value);
+        self.inner.serialize_element(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeTupleStruct> SerializeTupleStruct for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.decompressed(value);
+        self.inner.serialize_field(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeTupleVariant> SerializeTupleVariant for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.decompressed(value);
+        self.inner.serialize_field(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<C: SerializeMap> SerializeMap for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), C::Error> {
+        let key = self.decompressed(key);
+        self.inner.serialize_key(&key)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), C::Error> {
+        let value = self.decompressed(value);
+        self.inner.serialize_value(&value)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: Serializer> SerializeStruct for StructCompound<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error> {
+        match self {
+            StructCompound::Passed(x) => {
+                let value = x.field(key, value);
+                x.inner.serialize_field(key, &value)
+            }
+            StructCompound::Vector(x) => x.capture(key, value),
+        }
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+        match self {
+            StructCompound::Passed(x) => x.inner.skip_field(key),
+            StructCompound::Vector(_) => Ok(()),
+        }
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        match self {
+            StructCompound::Passed(x) => x.inner.end(),
+            StructCompound::Vector(x) => x.end(),
+        }
+    }
+}
+
+impl<C: SerializeStructVariant> SerializeStructVariant for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), C::Error> {
+        let value = self.field(key, value);
+        self.inner.serialize_field(key, &value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), C::Error> {
+        self.inner.skip_field(key)
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.inner.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::Attribute;
+    use crate::ParserBuilder;
+    use serde_json::Value;
+
+    fn to_value<T: Serialize>(value: &T, net_version: i32) -> Value {
+        let mut out = Vec::new();
+        let mut json = serde_json::Serializer::new(&mut out);
+        value
+            .serialize(DecompressVectors::new(&mut json, net_version))
+            .unwrap();
+        serde_json::from_slice(&out).unwrap()
+    }
+
+    #[test]
+    fn test_decompress_vectors() {
+        let data = include_bytes!("../assets/replays/good/fc427.replay");
+        let replay = ParserBuilder::new(&data[..])
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let net_version = replay.net_version.unwrap();
+        let frames = &replay.network_frames.as_ref().unwrap().frames;
+        let (body, update) = frames
+            .iter()
+            .flat_map(|x| x.updated_actors.iter())
+            .find_map(|x| match &x.attribute {
+                Attribute::RigidBody(body) if body.linear_velocity.is_some() => Some((body, x)),
+                _ => None,
+            })
+            .unwrap();
+
+        // Rigid bodies decompress like `RigidBody::decompress`
+        let state = body.decompress(net_version);
+        let value = to_value(update, net_version);
+        let actual = &value["attribute"]["RigidBody"];
+        let expected = serde_json::to_value(state).unwrap();
+        for key in &["location", "linear_velocity", "angular_velocity"] {
+            let key = if cfg!(feature = "camel-case") {
+                match *key {
+                    "linear_velocity" => "linearVelocity",
+                    "angular_velocity" => "angularVelocity",
+                    x => x,
+                }
+            } else {
+                key
+            };
+
+            for axis in &["x", "y", "z"] {
+                let x = actual[key][axis].as_f64().unwrap();
+                assert!(
+                    (x - expected[key][axis].as_f64().unwrap()).abs() < 1e-3,
+                    "{}",
+                    key
+                );
+            }
+        }
+
+        // Every vector in the replay is decompressed
+        let value = to_value(&replay, net_version);
+        let mut stack = vec![&value];
+        let mut locations = 0;
+        while let Some(x) = stack.pop() {
+            match x {
+                Value::Array(arr) => stack.extend(arr.iter()),
+                Value::Object(obj) => {
+                    assert!(!obj.contains_key("bias"));
+                    locations += obj.get("location").map_or(0, |_| 1);
+                    stack.extend(obj.values())
+                }
+                _ => {}
+            }
+        }
+        assert!(locations > 0);
+    }
+}
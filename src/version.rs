@@ -0,0 +1,192 @@
+use std::fmt;
+use std::sync::Arc;
+
+/// The version of the game that recorded a replay. Versions are ordered by their major, minor,
+/// and then net version. Replays that predate the net version have a net version of 0.
+///
+/// The network decoders gate format changes on the version with `at_least`, so custom decoders
+/// can gate their formats the same way.
+///
+/// ```
+/// use boxcars::ReplayVersion;
+///
+/// let version = ReplayVersion::new(868, 22, 7);
+/// assert!(version.at_least(868, 20, 7));
+/// assert!(version < ReplayVersion::new(868, 23, 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "camel-case", serde(rename_all = "camelCase"))]
+pub struct ReplayVersion {
+    pub major: i32,
+    pub minor: i32,
+    pub net: i32,
+}
+
+/// A change to the replay format that is only present in replays from a certain version onward
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// The game mode attribute is encoded in 8 bits instead of 2
+    WideGameMode,
+
+    /// Party leader reservations contain additional flags
+    ReservationFlags,
+
+    /// New actors are recorded with an id into the replay's names
+    ActorNameIds,
+
+    /// Painted and team edition products are encoded in 31 bits
+    NewPaintedProducts,
+
+    /// Camera settings contain a transition speed
+    CameraTransition,
+
+    /// Product colors are encoded in 32 bits
+    NewProductColors,
+
+    /// The network data ends with a trailer
+    NetworkTrailer,
+
+    /// PS4 ids contain 16 bytes of additional data instead of 8
+    LongPs4Ids,
+
+    /// Locations are recorded with two decimal places of precision
+    PreciseVectors,
+
+    /// Rigid body rotations are compressed quaternions instead of compressed rotators, and
+    /// vector components can be encoded in more bits
+    QuaternionRotations,
+
+    /// PsyNet ids are only the online id, without the 24 bytes of additional data
+    CompactPsyNetIds,
+}
+
+impl ReplayVersion {
+    /// The newest version that boxcars is known to decode. Each Rocket League patch that changes
+    /// the replay format bumps the version, so newer replays may not decode correctly.
+    pub const LATEST_KNOWN: ReplayVersion = ReplayVersion {
+        major: 868,
+        minor: 26,
+        net: 10,
+    };
+
+    pub fn new(major: i32, minor: i32, net: i32) -> Self {
+        ReplayVersion { major, minor, net }
+    }
+
+    /// Returns true if the version is no newer than the latest version that boxcars knows
+    pub fn is_known(&self) -> bool {
+        *self <= ReplayVersion::LATEST_KNOWN
+    }
+
+    /// Returns true if the version is the same or newer than the given version
+    pub fn at_least(&self, major: i32, minor: i32, net: i32) -> bool {
+        *self >= ReplayVersion::new(major, minor, net)
+    }
+
+    /// Returns true if replays of this version contain the format change
+    pub fn supports(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::WideGameMode | Feature::ReservationFlags => self.at_least(868, 12, 0),
+            Feature::ActorNameIds => self.at_least(868, 14, 0),
+            Feature::NewPaintedProducts => self.at_least(868, 18, 0),
+            Feature::CameraTransition => self.at_least(868, 20, 0),
+            Feature::NewProductColors => self.at_least(868, 23, 8),
+            Feature::NetworkTrailer => self.at_least(868, 24, 10),
+
+            // The net version gates are independent of the major and minor version
+            Feature::LongPs4Ids => self.net >= 1,
+            Feature::PreciseVectors => self.net >= 5,
+            Feature::QuaternionRotations => self.net >= 7,
+            Feature::CompactPsyNetIds => self.net >= 10,
+        }
+    }
+}
+
+impl fmt::Display for ReplayVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.net)
+    }
+}
+
+/// Determines what happens to a replay from a newer version than boxcars knows (see
+/// `ReplayVersion::LATEST_KNOWN`). Most patches don't change the network data in ways that
+/// matter, so newer replays usually decode fine, but a patch that does can cause the network
+/// data to fail to decode or, worse, to decode incorrectly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownVersions {
+    /// Decode the network data as if it were of the latest known version
+    Decode,
+
+    /// Leave the network data undecoded, as if parsing with `NetworkParse::Never`
+    SkipNetwork,
+
+    /// Fail parsing, unless the network data isn't parsed
+    Fail,
+}
+
+/// A callback invoked with the version of a replay that is newer than boxcars knows
+#[derive(Clone)]
+pub(crate) struct VersionHook(pub Arc<dyn Fn(ReplayVersion) + Send + Sync>);
+
+impl fmt::Debug for VersionHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("VersionHook")
+    }
+}
+
+/// Hooks are equal when they share the same callback
+impl PartialEq for VersionHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0) as *const u8 == Arc::as_ptr(&other.0) as *const u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_ordering() {
+        let version = ReplayVersion::new(868, 22, 7);
+        assert!(version < ReplayVersion::new(868, 23, 0));
+        assert!(version > ReplayVersion::new(868, 20, 10));
+        assert!(version.at_least(868, 22, 7));
+        assert!(!version.at_least(868, 22, 8));
+        assert_eq!(version.to_string(), "868.22.7");
+
+        // Each part outranks the parts after it
+        let version = ReplayVersion::new(18, 27, 1);
+        assert!(version < ReplayVersion::new(19, 27, 1));
+        assert!(version < ReplayVersion::new(18, 28, 1));
+        assert!(version < ReplayVersion::new(18, 27, 2));
+        assert!(version > ReplayVersion::new(17, 27, 1));
+        assert!(version > ReplayVersion::new(18, 26, 1));
+        assert!(version > ReplayVersion::new(18, 27, 0));
+        assert!(version > ReplayVersion::new(17, 99, 99));
+    }
+
+    #[test]
+    fn test_is_known() {
+        assert!(ReplayVersion::new(868, 20, 7).is_known());
+        assert!(ReplayVersion::LATEST_KNOWN.is_known());
+        assert!(!ReplayVersion::new(868, 26, 11).is_known());
+        assert!(!ReplayVersion::new(869, 0, 0).is_known());
+    }
+
+    #[test]
+    fn test_supports() {
+        let version = ReplayVersion::new(868, 12, 0);
+        assert!(version.supports(Feature::WideGameMode));
+        assert!(!version.supports(Feature::ActorNameIds));
+        assert!(!version.supports(Feature::LongPs4Ids));
+
+        let version = ReplayVersion::new(868, 22, 7);
+        assert!(version.supports(Feature::QuaternionRotations));
+        assert!(version.supports(Feature::PreciseVectors));
+        assert!(!version.supports(Feature::NewProductColors));
+        assert!(!version.supports(Feature::NetworkTrailer));
+        assert!(!version.supports(Feature::CompactPsyNetIds));
+        assert!(ReplayVersion::LATEST_KNOWN.supports(Feature::CompactPsyNetIds));
+    }
+}
@@ -12,7 +12,7 @@ macro_rules! frame_len_test {
 
             match parsing {
                 Ok(replay) => assert_eq!(replay.network_frames.unwrap().frames.len(), $frame_len),
-                Err(ref e) => panic!(format!("{}", e)),
+                Err(ref e) => panic!("{}", e),
             }
         }
     };
@@ -59,3 +59,8 @@ frame_len_test!(test_74936_replay, "../assets/replays/good/74936.replay", 10609)
 frame_len_test!(test_1ec9_replay, "../assets/replays/good/1ec9.replay", 332);
 frame_len_test!(test_9a2cd_replay, "../assets/replays/good/9a2cd.replay", 2616);
 frame_len_test!(test_9e35b_replay, "../assets/replays/good/9e35b.replay", 12859);
+frame_len_test!(
+    test_utf16_867_replay,
+    "../assets/replays/partial/utf-16.replay",
+    8369
+);
@@ -1,5 +1,6 @@
 use boxcars::{self, ParserBuilder};
 use std::borrow::Cow;
+use std::convert::TryFrom;
 
 #[test]
 fn test_sample1() {
@@ -149,6 +150,68 @@ fn test_short_psynet_id() {
     }
 }
 
+#[test]
+fn test_ps4_ids() {
+    use boxcars::attributes::RemoteId;
+
+    let ps4_ids = |replay: &boxcars::Replay| {
+        let index = boxcars::FrameIndex::new(replay).unwrap();
+        let ids: Vec<_> = index
+            .players()
+            .iter()
+            .filter_map(|x| match &x.unique_id.remote_id {
+                RemoteId::PlayStation(ps4) => Some(ps4.clone()),
+                _ => None,
+            })
+            .collect();
+        assert!(!ids.is_empty());
+        ids
+    };
+
+    // Older replays only have the platform tag
+    let data = include_bytes!("../assets/replays/good/3d07e.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+    for id in ps4_ids(&replay) {
+        assert!(id.platform_tag().unwrap().ends_with("ps4"));
+        assert!(id.extra().is_empty());
+    }
+
+    let data = include_bytes!("../assets/replays/good/159a4.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+    for id in ps4_ids(&replay) {
+        assert_eq!(id.platform_tag().unwrap().len(), 7);
+        assert_eq!(id.extra(), &[1, 0, 0, 0, 0, 0, 0, 0]);
+    }
+}
+
+#[test]
+fn test_xbox_ids() {
+    let data = include_bytes!("../assets/replays/good/3381.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+    let index = boxcars::FrameIndex::new(&replay).unwrap();
+    let ids: Vec<_> = index
+        .players()
+        .iter()
+        .filter_map(|x| x.unique_id.remote_id.xbox_id())
+        .collect();
+    assert!(!ids.is_empty());
+
+    // Xbox user ids are the 0x0009 prefix followed by the user number
+    for id in ids {
+        assert!(id.hex().starts_with("0009"));
+        assert_eq!(id.to_string(), id.0.to_string());
+    }
+}
+
 #[test]
 fn test_switch_id() {
     let data = include_bytes!("../assets/replays/good/7083.replay");
@@ -166,6 +229,40 @@ fn test_switch_id() {
     } else {
         panic!("Needed switch remote_id");
     }
+
+    // Some ids of a player have zeros instead of the extra words
+    let ids: Vec<_> = replay
+        .network_frames
+        .as_ref()
+        .unwrap()
+        .frames
+        .iter()
+        .flat_map(|x| x.updated_actors.iter())
+        .filter_map(|x| match &x.attribute {
+            boxcars::Attribute::UniqueId(id) | boxcars::Attribute::PartyLeader(Some(id)) => {
+                Some(id)
+            }
+            boxcars::Attribute::Reservation(x) => Some(&x.unique_id),
+            _ => None,
+        })
+        .filter_map(|x| match &x.remote_id {
+            boxcars::attributes::RemoteId::Switch(x) if x.online_id == 8814846996756628943 => {
+                Some(x.clone())
+            }
+            _ => None,
+        })
+        .collect();
+    let zeroed = ids
+        .iter()
+        .find(|x| x.extra_words() == Some([0; 3]))
+        .unwrap();
+    let full = ids
+        .iter()
+        .find(|x| x.extra_words() != Some([0; 3]))
+        .unwrap();
+    assert_ne!(zeroed, full);
+    assert!(zeroed.is_same_player(full));
+    assert_eq!(zeroed.normalized(), full.normalized());
 }
 
 #[test]
@@ -225,12 +322,895 @@ fn test_preserve_endian() {
             boxcars::Attribute::LoadoutsOnline(x) => Some(x.blue.iter().flat_map(|pr| pr.iter())),
             _ => None,
         })
-        .flat_map(|x| x)
+        .flatten()
         .filter_map(|x| match x.value {
             boxcars::attributes::ProductValue::NewPaint(p) => Some(p),
             _ => None,
         })
         .collect::<Vec<_>>();
 
-    assert_eq!(*new_paints.get(0).unwrap(), 11);
+    assert_eq!(*new_paints.first().unwrap(), 11);
+}
+
+#[test]
+fn test_interned_strings() {
+    let data = include_bytes!("../assets/replays/good/60dfe.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+
+    let frames = &replay.network_frames.as_ref().unwrap().frames;
+    let names: Vec<&boxcars::SharedStr> = frames
+        .iter()
+        .flat_map(|x| x.updated_actors.iter())
+        .filter_map(|x| match &x.attribute {
+            boxcars::Attribute::Reservation(r) => r.name.as_ref().filter(|n| *n == "Shope"),
+            _ => None,
+        })
+        .collect();
+
+    assert!(names.len() > 1);
+    assert!(names
+        .iter()
+        .all(|x| boxcars::SharedStr::ptr_eq(x, names[0])));
+}
+
+#[test]
+fn test_header_property_accessors() {
+    let data = include_bytes!("../assets/replays/good/rumble.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+
+    let team_size = replay.get_property("TeamSize").unwrap();
+    assert_eq!(team_size.as_i32(), Some(3));
+    assert_eq!(team_size.as_str(), None);
+    assert_eq!(team_size.as_f32(), None);
+
+    let fps = replay.get_property("RecordFPS").and_then(|x| x.as_f32());
+    assert_eq!(fps, Some(30.0));
+
+    let map = replay.get_property("MapName").and_then(|x| x.as_str());
+    assert_eq!(map, Some("stadium_foggy_p"));
+
+    let goals = replay.get_property("Goals").and_then(|x| x.as_array());
+    assert!(!goals.unwrap().is_empty());
+
+    assert!(replay.get_property("NotAProperty").is_none());
+}
+
+#[test]
+fn test_player_stats() {
+    let data = include_bytes!("../assets/replays/good/07e9.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+
+    let stats = replay.player_stats();
+    assert_eq!(
+        stats,
+        vec![boxcars::PlayerStat {
+            name: Some("Nadir"),
+            platform: Some("OnlinePlatform_Steam"),
+            online_id: Some(76561197960464457),
+            team: Some(1),
+            score: Some(95),
+            goals: Some(1),
+            assists: Some(0),
+            saves: Some(0),
+            shots: Some(2),
+            bot: Some(false),
+            split_screen_index: 0,
+        }]
+    );
+}
+
+#[test]
+fn test_player_stats_old_platform() {
+    let data = include_bytes!("../assets/replays/good/6688.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+
+    let stats = replay.player_stats();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].name, Some("Gompers"));
+    assert_eq!(stats[0].platform, Some("OnlinePlatform_Steam"));
+}
+
+#[test]
+fn test_player_stats_missing() {
+    let data = boxcars::ReplayBuilder::new().build().unwrap();
+    let replay = ParserBuilder::new(&data[..]).parse().unwrap();
+    assert_eq!(replay.player_stats(), Vec::new());
+}
+
+#[test]
+fn test_goals() {
+    let data = include_bytes!("../assets/replays/good/fc427.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+
+    let goal = |frame, player_name, tick_mark_frame| boxcars::GoalMark {
+        frame: Some(frame),
+        player_name: Some(player_name),
+        player_team: Some(1),
+        tick_mark_frame: Some(tick_mark_frame),
+    };
+
+    // The save tick mark between the first two goals is ignored
+    assert_eq!(
+        replay.goals(),
+        vec![
+            goal(290, "MyNameIzMayhem", 245),
+            goal(1747, "Blake", 1702),
+            goal(4871, "Blake", 4826),
+            goal(8388, "MyNameIzMayhem", 8343),
+        ]
+    );
+}
+
+#[test]
+fn test_goals_without_tick_marks() {
+    let goal = vec![
+        ("frame", boxcars::HeaderProp::Int(10)),
+        ("PlayerName", boxcars::HeaderProp::Str("comagoosie".into())),
+        ("PlayerTeam", boxcars::HeaderProp::Int(0)),
+    ];
+
+    let data = boxcars::ReplayBuilder::new()
+        .property("Goals", boxcars::HeaderProp::Array(vec![goal]))
+        .build()
+        .unwrap();
+    let replay = ParserBuilder::new(&data[..]).parse().unwrap();
+    assert_eq!(
+        replay.goals(),
+        vec![boxcars::GoalMark {
+            frame: Some(10),
+            player_name: Some("comagoosie"),
+            player_team: Some(0),
+            tick_mark_frame: None,
+        }]
+    );
+}
+
+#[test]
+fn test_highlights() {
+    let data = include_bytes!("../assets/replays/good/fc427.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+
+    let highlights = replay.highlights();
+    assert_eq!(
+        &highlights[..2],
+        &[
+            boxcars::Highlight {
+                frame: Some(290),
+                car_name: Some("Car_TA_41"),
+                ball_name: None,
+            },
+            boxcars::Highlight {
+                frame: Some(1580),
+                car_name: Some("Car_TA_51"),
+                ball_name: Some("Ball_TA_8"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_rank_info() {
+    let data = include_bytes!("../assets/replays/good/3381.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+
+    assert_eq!(
+        replay.rank_info(),
+        boxcars::RankInfo {
+            match_type: Some("Online"),
+            ranked: Some(true),
+            team_size: Some(3),
+            unfair_team_size: None,
+        }
+    );
+
+    let data = include_bytes!("../assets/replays/good/db70.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    let rank = replay.rank_info();
+    assert_eq!(rank.match_type, Some("Tournament"));
+    assert_eq!(rank.ranked, Some(false));
+
+    // Some replays don't reference a match type class
+    let data = include_bytes!("../assets/replays/good/07e9.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    assert_eq!(replay.rank_info().ranked, None);
+}
+
+#[test]
+fn test_match_identity() {
+    let data = include_bytes!("../assets/replays/good/fc427.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+
+    assert_eq!(replay.match_guid(), None);
+    let identity = replay.match_identity();
+
+    // The fingerprint is hashed from bytes, so it's the same on every platform
+    assert_eq!(
+        identity,
+        boxcars::MatchIdentity::Fingerprint(5155776976532539061)
+    );
+
+    // Another player's recording of the match has a different id and date
+    let mut other = replay.clone();
+    for (key, prop) in other.properties.iter_mut() {
+        if *key == "Id" || *key == "Date" {
+            *prop = boxcars::HeaderProp::Str(Cow::Borrowed("other"));
+        }
+    }
+    assert_eq!(other.match_identity(), identity);
+
+    let data = include_bytes!("../assets/replays/good/07e9.replay");
+    let unrelated = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    assert_ne!(unrelated.match_identity(), identity);
+
+    other.properties.push((
+        "MatchGuid",
+        boxcars::HeaderProp::Str(Cow::Borrowed("B3B5E4E311E9767C5A9C7FB1EB4BD9B0")),
+    ));
+    assert_eq!(other.match_guid(), Some("B3B5E4E311E9767C5A9C7FB1EB4BD9B0"));
+    assert_eq!(
+        other.match_identity(),
+        boxcars::MatchIdentity::Guid("B3B5E4E311E9767C5A9C7FB1EB4BD9B0")
+    );
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_recorded_at() {
+    use chrono::NaiveDate;
+
+    let data = include_bytes!("../assets/replays/good/fc427.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    let expected = NaiveDate::from_ymd_opt(2019, 5, 14)
+        .and_then(|x| x.and_hms_opt(20, 20, 31))
+        .unwrap();
+    assert_eq!(replay.recorded_at(), Some(expected));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_recorded_at_minutes() {
+    use chrono::NaiveDate;
+
+    let data = include_bytes!("../assets/replays/good/6688.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    let expected = NaiveDate::from_ymd_opt(2015, 10, 27)
+        .and_then(|x| x.and_hms_opt(18, 29, 0))
+        .unwrap();
+    assert_eq!(replay.recorded_at(), Some(expected));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_recorded_at_invalid() {
+    let data = boxcars::ReplayBuilder::new()
+        .property("Date", boxcars::HeaderProp::Str("yesterday".into()))
+        .build()
+        .unwrap();
+    let replay = ParserBuilder::new(&data[..]).parse().unwrap();
+    assert_eq!(replay.recorded_at(), None);
+}
+
+#[test]
+fn test_id_lookups() {
+    let data = include_bytes!("../assets/replays/good/fc427.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+
+    let frames = &replay.network_frames.as_ref().unwrap().frames;
+    let actor = frames
+        .iter()
+        .flat_map(|x| x.new_actors.iter())
+        .find(|x| x.object_name(&replay) == Some("Archetypes.Car.Car_Default"))
+        .unwrap();
+    assert!(actor.name(&replay).unwrap().starts_with("Car_TA_"));
+
+    let object_id = boxcars::ObjectId::try_from(replay.objects.len()).unwrap();
+    assert_eq!(object_id.lookup(&replay), None);
+    assert_eq!(boxcars::ObjectId::from(-1).lookup(&replay), None);
+    assert_eq!(boxcars::ActorId::from(7).to_string(), "7");
+}
+
+#[test]
+fn test_frame_spans() {
+    let data = include_bytes!("../assets/replays/good/rumble.replay");
+    let (replay, spans) = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse_spans()
+        .unwrap();
+
+    let frames = replay.network_frames.unwrap().frames;
+    let spans = spans.unwrap();
+    assert_eq!(frames.len(), spans.frames.len());
+
+    // The network data is preceded by its size
+    let size = &data[spans.network_offset - 4..spans.network_offset];
+    let size = i32::from_le_bytes([size[0], size[1], size[2], size[3]]) as usize;
+
+    let mut next_frame = 0;
+    for (frame, span) in frames.iter().zip(spans.frames.iter()) {
+        assert_eq!(span.span.start, next_frame);
+        next_frame = span.span.start + span.span.len;
+
+        assert_eq!(frame.new_actors.len(), span.new_actors.len());
+        assert_eq!(frame.updated_actors.len(), span.updated_actors.len());
+        for attr in span.new_actors.iter().chain(span.updated_actors.iter()) {
+            assert!(attr.start > span.span.start);
+            assert!(attr.start + attr.len < next_frame);
+        }
+    }
+
+    assert!(next_frame <= size * 8);
+
+    // The first frame's time is at the start of the network data
+    let time = &data[spans.network_offset..spans.network_offset + 4];
+    let time = f32::from_le_bytes([time[0], time[1], time[2], time[3]]);
+    assert_eq!(time, frames[0].time);
+}
+
+#[test]
+fn test_keyframe_offsets() {
+    let data = include_bytes!("../assets/replays/good/rumble.replay");
+    let (mut replay, spans) = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse_spans()
+        .unwrap();
+
+    // Each keyframe starts at the span of its frame
+    let spans = spans.unwrap();
+    let offsets = replay.keyframe_offsets();
+    assert_eq!(offsets.len(), replay.keyframes.len());
+    for offset in &offsets {
+        let span = &spans.frames[offset.frame].span;
+        assert_eq!(offset.bit_offset, span.start);
+        assert_eq!(offset.byte_offset, span.bytes().start);
+    }
+
+    // Keyframes outside of the network data are skipped
+    let bits = replay.network_data.len() as i32 * 8;
+    replay.keyframes[0].position = -1;
+    replay.keyframes[1].frame = -1;
+    replay.keyframes[2].position = bits;
+    let resolved = replay.keyframe_offsets();
+    assert_eq!(resolved[..], offsets[3..]);
+}
+
+#[test]
+fn test_rigid_body_units() {
+    // Velocities decompress to the same units regardless of the net version
+    for data in &[
+        &include_bytes!("../assets/replays/good/rumble.replay")[..],
+        &include_bytes!("../assets/replays/good/3381.replay")[..],
+        &include_bytes!("../assets/replays/good/fc427.replay")[..],
+    ] {
+        let replay = ParserBuilder::new(data)
+            .must_parse_network_data()
+            .parse()
+            .unwrap();
+
+        let net_version = replay.net_version.unwrap_or(0);
+        let bodies: Vec<_> = replay
+            .network_frames
+            .unwrap()
+            .frames
+            .iter()
+            .flat_map(|x| x.updated_actors.iter())
+            .filter_map(|x| match x.attribute {
+                boxcars::Attribute::RigidBody(body) => Some(body.decompress(net_version)),
+                _ => None,
+            })
+            .collect();
+
+        let max = |f: &dyn Fn(&boxcars::attributes::RigidBodyState) -> Option<f32>| {
+            bodies.iter().filter_map(f).fold(0.0f32, f32::max)
+        };
+
+        // The ball has a max speed of 6000 uu/s and 6 rad/s
+        let length = |v: boxcars::Vector3f| (v.x * v.x + v.y * v.y + v.z * v.z).sqrt();
+        let speed = max(&|x| x.linear_velocity.map(length));
+        let spin = max(&|x| x.angular_velocity_radians().map(length));
+        let height = max(&|x| Some(x.location_meters().z));
+        assert!(speed > 2000.0 && speed < 6100.0, "{}", speed);
+        assert!(spin > 5.0 && spin < 6.1, "{}", spin);
+        assert!(height > 15.0 && height < 21.0, "{}", height);
+    }
+}
+
+#[test]
+fn test_replay_version() {
+    let data = include_bytes!("../assets/replays/good/rumble.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    let version = replay.version();
+    assert_eq!(version, boxcars::ReplayVersion::new(868, 12, 0));
+    assert!(version.supports(boxcars::Feature::WideGameMode));
+    assert!(!version.supports(boxcars::Feature::ActorNameIds));
+
+    let data = include_bytes!("../assets/replays/good/fc427.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    let version = replay.version();
+    assert_eq!(version.to_string(), "868.24.10");
+    assert!(version.supports(boxcars::Feature::NetworkTrailer));
+    assert!(version.supports(boxcars::Feature::QuaternionRotations));
+}
+
+#[test]
+fn test_split_screen_players() {
+    let data = include_bytes!("../assets/replays/partial/windows_1252.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+
+    let stats = replay.player_stats();
+    let split_screen: Vec<_> = stats
+        .iter()
+        .filter(|x| x.online_id == Some(76561198033459682))
+        .map(|x| (x.name.unwrap(), x.split_screen_index))
+        .collect();
+    assert_eq!(
+        split_screen,
+        vec![("caudillman6000\u{b3}(2)", 1), ("caudillman6000\u{b3}", 0)]
+    );
+
+    // Players that do not share an online id are never split-screen guests
+    assert!(stats
+        .iter()
+        .filter(|x| x.online_id != Some(76561198033459682))
+        .all(|x| x.split_screen_index == 0));
+
+    let index = boxcars::FrameIndex::new(&replay).unwrap();
+    let players = index.players();
+    let guest = players
+        .iter()
+        .find(|x| x.unique_id.is_split_screen_guest())
+        .unwrap();
+    assert_eq!(guest.name, Some("caudillman6000\u{b3}(2)"));
+
+    // Each player in the network data is matched to one player in the header
+    for player in players.iter().filter(|x| x.unique_id.system_id == 1) {
+        let matched: Vec<_> = stats
+            .iter()
+            .filter(|x| x.is_player(player.unique_id))
+            .collect();
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].name, player.name);
+    }
+}
+
+#[test]
+fn test_unique_id_display() {
+    use boxcars::attributes::Platform;
+
+    let data = include_bytes!("../assets/replays/partial/windows_1252.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+    let index = boxcars::FrameIndex::new(&replay).unwrap();
+    let mut ids: Vec<_> = index
+        .players()
+        .iter()
+        .filter(|x| x.unique_id.remote_id.online_id() == Some(76561198033459682))
+        .map(|x| (x.unique_id.platform(), x.unique_id.to_string()))
+        .collect();
+    ids.sort_by(|a, b| a.1.cmp(&b.1));
+    assert_eq!(
+        ids,
+        vec![
+            (Platform::Steam, String::from("76561198033459682")),
+            (Platform::Steam, String::from("76561198033459682:1")),
+        ]
+    );
+
+    let data = include_bytes!("../assets/replays/good/159a4.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+    let index = boxcars::FrameIndex::new(&replay).unwrap();
+    let players = index.players();
+    let player = players
+        .iter()
+        .find(|x| x.unique_id.platform() == Platform::PlayStation)
+        .unwrap();
+    assert_eq!(player.unique_id.to_string(), player.name.unwrap());
+
+    let data = include_bytes!("../assets/replays/good/netversion.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+    let index = boxcars::FrameIndex::new(&replay).unwrap();
+    let players = index.players();
+    let ids: Vec<_> = players
+        .iter()
+        .filter(|x| x.unique_id.platform() == Platform::Xbox)
+        .map(|x| x.unique_id.to_string())
+        .collect();
+    assert!(ids.contains(&String::from("2533274808104947")));
+    assert!(players
+        .iter()
+        .any(|x| x.unique_id.platform() == Platform::Switch));
+}
+
+#[test]
+fn test_steam_ids() {
+    let data = include_bytes!("../assets/replays/partial/windows_1252.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+    let index = boxcars::FrameIndex::new(&replay).unwrap();
+    let players = index.players();
+    let steam: Vec<_> = players
+        .iter()
+        .filter_map(|x| x.unique_id.remote_id.steam_id())
+        .collect();
+    assert!(!steam.is_empty());
+
+    // Players are individual accounts of the public universe on the desktop client
+    for id in steam {
+        assert_eq!((id.universe(), id.account_type(), id.instance()), (1, 1, 1));
+        assert_eq!(id.steam3(), format!("[U:1:{}]", id.account_id()));
+        assert_eq!(
+            id.to_string(),
+            (76561197960265728 + u64::from(id.account_id())).to_string()
+        );
+    }
+
+    assert!(players
+        .iter()
+        .filter(|x| x.unique_id.system_id != 1)
+        .all(|x| x.unique_id.remote_id.steam_id().is_none()));
+}
+
+#[test]
+fn test_strict() {
+    for entry in std::fs::read_dir("assets/replays/good").unwrap() {
+        let path = entry.unwrap().path();
+        let data = std::fs::read(&path).unwrap();
+        if let Err(e) = ParserBuilder::new(&data).strict().parse() {
+            panic!("{}: {}", path.display(), e);
+        }
+    }
+
+    // A set bit in the padding after the frames is left over data
+    let mut data = include_bytes!("../assets/replays/good/rumble.replay").to_vec();
+    let (_, spans) = ParserBuilder::new(&data[..]).parse_spans().unwrap();
+    let spans = spans.unwrap();
+    let last = &spans.frames.last().unwrap().span;
+    data[spans.network_offset + (last.start + last.len) / 8 + 1] |= 0x80;
+
+    let lenient = ParserBuilder::new(&data[..])
+        .never_check_crc()
+        .must_parse_network_data()
+        .parse();
+    assert!(lenient.is_ok());
+
+    let err = ParserBuilder::new(&data[..])
+        .never_check_crc()
+        .strict()
+        .parse()
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .ends_with("bits were left over after decoding the frames"));
+}
+
+#[test]
+fn test_content_hash() {
+    let data = include_bytes!("../assets/replays/good/rumble.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    let hash = replay.content_hash();
+
+    // The hash of a replay never changes
+    assert_eq!(hash, 0x3870_5d92_08d5_a980);
+
+    // The decoded network frames, crcs, and property order don't factor into the hash
+    let mut other = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+    other.header_crc = 0;
+    other.properties.reverse();
+    assert_eq!(other.content_hash(), hash);
+
+    other.properties.pop();
+    assert_ne!(other.content_hash(), hash);
+
+    let mut other = replay.clone();
+    other.network_data = &other.network_data[1..];
+    assert_ne!(other.content_hash(), hash);
+
+    let data = include_bytes!("../assets/replays/good/07e9.replay");
+    let unrelated = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    assert_ne!(unrelated.content_hash(), hash);
+}
+
+#[test]
+fn test_match_fingerprint() {
+    let data = include_bytes!("../assets/replays/good/fc427.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    let fingerprint = replay.match_fingerprint();
+    assert_eq!(fingerprint.guid, None);
+    assert_eq!(fingerprint.goal_frames, vec![290, 1747, 4871, 8388]);
+
+    // Another player's recording started later and its goals are a few frames off
+    let mut other = fingerprint.clone();
+    other.goal_frames = vec![1290, 2750, 5865, 9390];
+    assert_eq!(other.key(), fingerprint.key());
+    assert!(other.matches(&fingerprint));
+
+    other.goal_frames[2] += 500;
+    assert!(!other.matches(&fingerprint));
+
+    other.goal_frames.pop();
+    assert!(!other.matches(&fingerprint));
+
+    let data = include_bytes!("../assets/replays/good/07e9.replay");
+    let unrelated = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    assert_ne!(unrelated.match_fingerprint().key(), fingerprint.key());
+    assert!(!unrelated.match_fingerprint().matches(&fingerprint));
+
+    // The guid decides when both recordings have it
+    let mut guid = fingerprint.clone();
+    guid.guid = Some("B3B5E4E311E9767C5A9C7FB1EB4BD9B0");
+    guid.goal_frames.clear();
+    let mut other_guid = guid.clone();
+    assert!(guid.matches(&other_guid));
+    other_guid.guid = Some("49D9FB8E11E9767C5A9C7FB1EB4BD9B0");
+    assert!(!guid.matches(&other_guid));
+    assert_ne!(guid.key(), other_guid.key());
+}
+
+#[test]
+fn test_estimated_duration() {
+    for data in &[
+        &include_bytes!("../assets/replays/good/rumble.replay")[..],
+        &include_bytes!("../assets/replays/good/d52eb.replay")[..],
+        &include_bytes!("../assets/replays/good/01d3e5.replay")[..],
+    ] {
+        let mut replay = ParserBuilder::new(data)
+            .never_parse_network_data()
+            .parse()
+            .unwrap();
+        let estimate = replay.estimated_duration().unwrap();
+        let frame_count = replay.frame_count();
+
+        let network = replay.parse_network().unwrap();
+        let frames = &network.frames;
+        let duration = frames.last().unwrap().time - frames.first().unwrap().time;
+        assert_eq!(frame_count, Some(frames.len() as i32));
+        assert_eq!(replay.estimated_duration(), Some(duration));
+        assert!((estimate - duration).abs() / duration < 0.05);
+    }
+
+    // Without keyframes, the estimate falls back to the record rate
+    let data = include_bytes!("../assets/replays/good/rumble.replay");
+    let mut replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    replay.keyframes.clear();
+    assert_eq!(replay.estimated_duration(), Some(7744.0 / 30.0));
+
+    let data = include_bytes!("../assets/replays/good/no-frames.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    assert_eq!(replay.frame_count(), None);
+    assert_eq!(replay.estimated_duration(), None);
+}
+
+#[test]
+fn test_goal_events() {
+    let data = include_bytes!("../assets/replays/good/d52eb.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+    let goals = replay.goal_events();
+    assert_eq!(goals.len(), 7);
+
+    let frames = &replay.network_frames.as_ref().unwrap().frames;
+    for goal in &goals {
+        assert!(goal.score_frame.is_some());
+        assert_eq!(goal.time, Some(frames[goal.frame.unwrap() as usize].time));
+    }
+
+    // The team's score increased a frame before the header's goal frame
+    assert_eq!(goals[3].scorer, Some("FunFactJac"));
+    assert_eq!(goals[3].frame, Some(9990));
+    assert_eq!(goals[3].score_frame, Some(9989));
+    assert_eq!(goals[3].score, Some(2));
+    assert_eq!(goals[3].tick_mark_frame, Some(9900));
+
+    let scores: Vec<_> = goals.iter().map(|x| (x.team, x.score)).collect();
+    assert_eq!(
+        scores,
+        vec![
+            (Some(0), Some(1)),
+            (Some(1), Some(1)),
+            (Some(0), Some(2)),
+            (Some(1), Some(2)),
+            (Some(1), Some(3)),
+            (Some(0), Some(3)),
+            (Some(0), Some(4)),
+        ]
+    );
+
+    // Without the network frames, the times are estimated from the keyframes
+    let header = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    for (goal, estimate) in goals.iter().zip(header.goal_events()) {
+        assert_eq!(estimate.frame, goal.frame);
+        assert_eq!(estimate.score, None);
+        assert!((estimate.time.unwrap() - goal.time.unwrap()).abs() < 10.0);
+    }
+}
+
+#[test]
+fn test_frame_iterators() {
+    let data = include_bytes!("../assets/replays/good/rumble.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+
+    let network = replay.network_frames.as_ref().unwrap();
+    let updates: usize = network.frames.iter().map(|x| x.updated_actors.len()).sum();
+    assert_eq!(network.updates().count(), updates);
+
+    let (frame, time, actor_id, body) = network.rigid_bodies().next().unwrap();
+    assert_eq!(time, network.frames[frame].time);
+    assert!(network.frames[frame]
+        .updated_actors
+        .iter()
+        .any(|x| x.actor_id == actor_id && x.attribute == boxcars::Attribute::RigidBody(*body)));
+
+    let actor: Vec<_> = network.updates_for(actor_id).collect();
+    assert!(actor.iter().all(|x| x.2 == actor_id));
+    assert!(actor.windows(2).all(|x| x[0].0 <= x[1].0));
+    assert!(actor
+        .iter()
+        .any(|x| matches!(x.3, boxcars::Attribute::RigidBody(_))));
+}
+
+#[test]
+fn test_timeline() {
+    use boxcars::TimelineKind;
+
+    let data = include_bytes!("../assets/replays/good/d52eb.replay");
+    let replay = ParserBuilder::new(&data[..])
+        .must_parse_network_data()
+        .parse()
+        .unwrap();
+
+    let timeline = replay.timeline();
+    let count = |f: fn(&TimelineKind<'_>) -> bool| timeline.iter().filter(|x| f(&x.kind)).count();
+    let saves = replay
+        .tick_marks
+        .iter()
+        .filter(|x| x.description.ends_with("Save"))
+        .count();
+    assert_eq!(count(|x| matches!(x, TimelineKind::Goal(_))), 7);
+    assert_eq!(count(|x| matches!(x, TimelineKind::Save { .. })), saves);
+    assert_eq!(count(|x| matches!(x, TimelineKind::Kickoff)), 8);
+    assert_eq!(count(|x| matches!(x, TimelineKind::OvertimeStart)), 1);
+    assert!(count(|x| matches!(x, TimelineKind::BoostPickup { .. })) > 0);
+    assert!(timeline.windows(2).all(|x| x[0].frame <= x[1].frame));
+
+    let frames = &replay.network_frames.as_ref().unwrap().frames;
+    for event in timeline
+        .iter()
+        .filter(|x| matches!(x.kind, TimelineKind::Kickoff))
+    {
+        let frame = event.frame.unwrap() as usize;
+        assert_eq!(event.time, Some(frames[frame].time));
+    }
+
+    // Without the network frames only the goals and saves remain
+    let replay = ParserBuilder::new(&data[..])
+        .never_parse_network_data()
+        .parse()
+        .unwrap();
+    assert_eq!(replay.timeline().len(), 7 + saves);
+}
+
+#[test]
+fn test_867_replay_version_gates() {
+    use boxcars::{CompressedRotation, Feature, ReplayVersion};
+
+    // The oldest replay in the assets predates the net version and every versioned format change
+    let data = include_bytes!("../assets/replays/partial/utf-16.replay");
+    let replay = ParserBuilder::new(&data[..]).strict().parse().unwrap();
+    assert_eq!(replay.net_version, None);
+    assert_eq!(replay.version(), ReplayVersion::new(867, 9, 0));
+
+    let features = [
+        Feature::WideGameMode,
+        Feature::ReservationFlags,
+        Feature::ActorNameIds,
+        Feature::NewPaintedProducts,
+        Feature::CameraTransition,
+        Feature::NewProductColors,
+        Feature::NetworkTrailer,
+        Feature::LongPs4Ids,
+        Feature::PreciseVectors,
+        Feature::QuaternionRotations,
+        Feature::CompactPsyNetIds,
+    ];
+    assert!(features.iter().all(|&x| !replay.version().supports(x)));
+
+    let network = replay.network_frames.as_ref().unwrap();
+    assert_eq!(network.frames.len(), 8369);
+    assert!(network
+        .frames
+        .iter()
+        .all(|x| x.new_actors.iter().all(|actor| actor.name_id.is_none())));
+    assert!(network.rigid_bodies().count() > 0);
+    assert!(network
+        .rigid_bodies()
+        .all(|(_, _, _, x)| matches!(x.rotation, CompressedRotation::Rotator { .. })));
 }